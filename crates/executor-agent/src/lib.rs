@@ -0,0 +1,5 @@
+//! The remote agent an SSH executor can push to its host (`agent_binary`).
+//! The `openclaw-remote-agent` binary serves [`protocol`] requests on its
+//! stdin and stdout for as long as the executor keeps the channel open.
+
+pub mod protocol;
@@ -0,0 +1,31 @@
+//! openclaw-remote-agent: run with no arguments, it serves requests on
+//! stdin/stdout until stdin closes. `supervise` is how it launches a task:
+//! a detached copy of itself that runs the task and records its exit code.
+
+mod serve;
+mod supervise;
+
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = match args.as_slice() {
+        [] => serve::serve(),
+        [mode, start] if mode == "supervise" => supervise::run(start),
+        [flag] if flag == "--version" => {
+            println!("openclaw-remote-agent {}", env!("CARGO_PKG_VERSION"));
+            Ok(())
+        }
+        _ => {
+            eprintln!("usage: openclaw-remote-agent [--version]");
+            return ExitCode::from(2);
+        }
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("openclaw-remote-agent: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,113 @@
+//! Requests and responses between the SSH executor and the remote agent.
+//! Each message is a frame: a 4-byte big-endian length, then that many bytes
+//! of JSON. Every request gets exactly one response, in order.
+
+use serde::{Deserialize, Serialize};
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+
+/// Bumped whenever a request or response changes shape.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Largest frame either side accepts.
+const MAX_FRAME: usize = 16 * 1024 * 1024;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum Request {
+    /// First request on a channel.
+    Hello,
+    /// Whether the channel is still alive.
+    Heartbeat,
+    Start(StartRequest),
+    /// Check several tasks at once.
+    Status { tasks: Vec<TaskQuery> },
+    /// The last `lines` lines of `file` (`~` is the agent's home).
+    Tail { file: String, lines: usize },
+    /// SIGTERM `pid`'s process group, then SIGKILL after `grace_secs`.
+    Kill { pid: u32, grace_secs: u64 },
+}
+
+/// Launch a task detached from the channel. Its output goes to
+/// `<task_dir>/claude.log`, its PID to `claude.pid` and its exit code to
+/// `claude.exitcode`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StartRequest {
+    /// The task's dir; `~` is the agent's home.
+    pub task_dir: String,
+    /// Working directory; `~` is the agent's home.
+    pub workspace: String,
+    /// Run with `sh -c`.
+    pub command: String,
+    /// Prefix each log line with the UTC time it was written.
+    pub timestamps: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TaskQuery {
+    /// The task's dir; `~` is the agent's home.
+    pub task_dir: String,
+    /// The recorded PID, if any; otherwise the one in `claude.pid` is used.
+    pub pid: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "reply", rename_all = "snake_case")]
+pub enum Response {
+    Hello { protocol: u32, version: String },
+    Heartbeat,
+    Started { pid: u32 },
+    /// One state per queried task, in the order asked.
+    Status { tasks: Vec<TaskState> },
+    Lines { lines: Vec<String> },
+    /// `SIGTERM` or `SIGKILL`, whichever ended the task; `None` if it was
+    /// already gone.
+    Killed { signal: Option<String> },
+    Error { message: String },
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TaskState {
+    pub pid: Option<u32>,
+    pub alive: bool,
+    /// Set once the task has exited.
+    pub exit_code: Option<i32>,
+}
+
+/// Write `message` as one frame.
+pub fn write_frame(w: &mut impl Write, message: &impl Serialize) -> io::Result<()> {
+    let body = serde_json::to_vec(message)?;
+    w.write_all(&(body.len() as u32).to_be_bytes())?;
+    w.write_all(&body)?;
+    w.flush()
+}
+
+/// Read one frame's JSON, or `None` if the stream ended between frames.
+pub fn read_frame(r: &mut impl Read) -> io::Result<Option<Vec<u8>>> {
+    let mut len = [0u8; 4];
+    match r.read_exact(&mut len) {
+        Ok(()) => {}
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+    let len = u32::from_be_bytes(len) as usize;
+    if len > MAX_FRAME {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("frame of {} bytes is over the {} byte limit", len, MAX_FRAME),
+        ));
+    }
+    let mut body = vec![0u8; len];
+    r.read_exact(&mut body)?;
+    Ok(Some(body))
+}
+
+/// `path` with a leading `~` expanded to the agent's home.
+pub fn expand_home(path: &str) -> PathBuf {
+    let home = std::env::var_os("HOME").map(PathBuf::from).unwrap_or_default();
+    match path.strip_prefix('~') {
+        Some("") => home,
+        Some(rest) if rest.starts_with('/') => home.join(&rest[1..]),
+        _ => PathBuf::from(path),
+    }
+}
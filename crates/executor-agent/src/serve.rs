@@ -0,0 +1,165 @@
+//! The request loop: one response per request, until stdin closes.
+
+use executor_agent::protocol::{
+    self, expand_home, Request, Response, StartRequest, TaskQuery, TaskState, PROTOCOL_VERSION,
+};
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::os::unix::process::CommandExt;
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// How much of a log `Tail` reads back at a time.
+const TAIL_CHUNK: u64 = 64 * 1024;
+
+/// How often `Kill` checks whether the task has exited.
+const KILL_POLL: Duration = Duration::from_millis(100);
+
+pub fn serve() -> io::Result<()> {
+    let mut input = io::stdin().lock();
+    let mut output = io::stdout().lock();
+    let mut agent = Agent::default();
+    while let Some(frame) = protocol::read_frame(&mut input)? {
+        let response = match serde_json::from_slice(&frame) {
+            Ok(request) => agent.handle(request),
+            Err(e) => Response::Error {
+                message: format!("Unreadable request: {}", e),
+            },
+        };
+        protocol::write_frame(&mut output, &response)?;
+    }
+    Ok(())
+}
+
+#[derive(Default)]
+struct Agent {
+    /// Supervisors started on this channel, reaped as they exit so they
+    /// don't linger as zombies that still look alive.
+    supervisors: Vec<Child>,
+}
+
+impl Agent {
+    fn handle(&mut self, request: Request) -> Response {
+        self.supervisors.retain_mut(|child| matches!(child.try_wait(), Ok(None)));
+        let result = match request {
+            Request::Hello => Ok(Response::Hello {
+                protocol: PROTOCOL_VERSION,
+                version: env!("CARGO_PKG_VERSION").to_string(),
+            }),
+            Request::Heartbeat => Ok(Response::Heartbeat),
+            Request::Start(start) => self.start(&start),
+            Request::Status { tasks } => Ok(Response::Status {
+                tasks: tasks.iter().map(task_state).collect(),
+            }),
+            Request::Tail { file, lines } => {
+                tail(&expand_home(&file), lines).map(|lines| Response::Lines { lines })
+            }
+            Request::Kill { pid, grace_secs } => Ok(Response::Killed {
+                signal: self.kill(pid, Duration::from_secs(grace_secs)),
+            }),
+        };
+        result.unwrap_or_else(|e| Response::Error {
+            message: e.to_string(),
+        })
+    }
+
+    /// Launch a supervisor for the task in a session of its own, so its PID
+    /// leads a process group holding everything the task spawns.
+    fn start(&mut self, start: &StartRequest) -> io::Result<Response> {
+        let dir = expand_home(&start.task_dir);
+        std::fs::create_dir_all(&dir)?;
+        let mut command = Command::new(std::env::current_exe()?);
+        command
+            .arg("supervise")
+            .arg(serde_json::to_string(start)?)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        // SAFETY: setsid is async-signal-safe and touches no parent state
+        unsafe {
+            command.pre_exec(|| {
+                if libc::setsid() == -1 {
+                    return Err(io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+        let child = command.spawn()?;
+        let pid = child.id();
+        std::fs::write(dir.join("claude.pid"), format!("{}\n", pid))?;
+        self.supervisors.push(child);
+        Ok(Response::Started { pid })
+    }
+
+    /// SIGTERM `pid`'s process group (or just `pid` if it leads none), then
+    /// SIGKILL if it outlives `grace`. The signal that ended it, if any.
+    fn kill(&mut self, pid: u32, grace: Duration) -> Option<String> {
+        let pid = pid as libc::pid_t;
+        let target = if signal(-pid, 0) { -pid } else { pid };
+        if !signal(target, libc::SIGTERM) {
+            return None;
+        }
+        let deadline = Instant::now() + grace;
+        loop {
+            self.supervisors.retain_mut(|child| matches!(child.try_wait(), Ok(None)));
+            if !signal(target, 0) {
+                return Some("SIGTERM".to_string());
+            }
+            if Instant::now() >= deadline {
+                signal(target, libc::SIGKILL);
+                return Some("SIGKILL".to_string());
+            }
+            std::thread::sleep(KILL_POLL);
+        }
+    }
+}
+
+/// Send `sig` to `target` (a process group if negative); whether it was there.
+fn signal(target: libc::pid_t, sig: libc::c_int) -> bool {
+    // SAFETY: kill has no memory-safety preconditions
+    unsafe { libc::kill(target, sig) == 0 }
+}
+
+fn task_state(query: &TaskQuery) -> TaskState {
+    let dir = &expand_home(&query.task_dir);
+    let pid = query.pid.or_else(|| read_number(&dir.join("claude.pid")));
+    TaskState {
+        pid,
+        alive: pid.is_some_and(|pid| signal(pid as libc::pid_t, 0)),
+        exit_code: read_number(&dir.join("claude.exitcode")),
+    }
+}
+
+fn read_number<T: std::str::FromStr>(path: &Path) -> Option<T> {
+    std::fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+/// The last `lines` lines of `path`, read back from the end so a long log
+/// isn't read whole. Nothing if there is no such file yet.
+fn tail(path: &Path, lines: usize) -> io::Result<Vec<String>> {
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut start = file.metadata()?.len();
+    let mut buf = Vec::new();
+    // One newline more than `lines` means the (possibly partial) first line
+    // read isn't among the ones returned
+    while start > 0 && buf.iter().filter(|&&b| b == b'\n').count() <= lines {
+        let step = TAIL_CHUNK.min(start);
+        start -= step;
+        let mut chunk = vec![0u8; step as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(&mut chunk)?;
+        chunk.extend_from_slice(&buf);
+        buf = chunk;
+    }
+    let text = String::from_utf8_lossy(&buf);
+    let all: Vec<&str> = text.lines().collect();
+    Ok(all[all.len().saturating_sub(lines)..]
+        .iter()
+        .map(|line| line.to_string())
+        .collect())
+}
@@ -0,0 +1,70 @@
+//! `openclaw-remote-agent supervise <start request>`: runs one task, writing
+//! its output to claude.log and, once it exits, its exit code to
+//! claude.exitcode. Started detached by `serve`, so it outlives the channel.
+
+use executor_agent::protocol::{expand_home, StartRequest};
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::process::ExitStatusExt;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Exit code recorded for a task that couldn't be run at all, as a shell
+/// reports a command it can't find.
+const NOT_RUN: i32 = 127;
+
+pub fn run(start: &str) -> io::Result<()> {
+    let start: StartRequest = serde_json::from_str(start)?;
+    let dir = &expand_home(&start.task_dir);
+    // A task that never ran still gets an exit code, or it would look like
+    // one that finished cleanly
+    let (code, result) = match supervise(&start, dir) {
+        Ok(code) => (code, Ok(())),
+        Err(e) => {
+            if let Ok(mut log) = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(dir.join("claude.log"))
+            {
+                let _ = writeln!(log, "openclaw-remote-agent: {}", e);
+            }
+            (NOT_RUN, Err(e))
+        }
+    };
+    std::fs::write(dir.join("claude.exitcode"), format!("{}\n", code))?;
+    result
+}
+
+/// Run the task, returning its exit code.
+fn supervise(start: &StartRequest, dir: &Path) -> io::Result<i32> {
+    let mut log = File::create(dir.join("claude.log"))?;
+
+    let mut command = Command::new("sh");
+    command
+        .arg("-c")
+        .arg(&start.command)
+        .current_dir(expand_home(&start.workspace))
+        .stdin(Stdio::null());
+    let status = if start.timestamps {
+        // One pipe for both, so lines are stamped in the order written
+        let (reader, writer) = io::pipe()?;
+        command.stdout(writer.try_clone()?).stderr(writer);
+        let mut child = command.spawn()?;
+        drop(command);
+        for line in BufReader::new(reader).split(b'\n') {
+            let stamp = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ");
+            write!(log, "{} ", stamp)?;
+            log.write_all(&line?)?;
+            log.write_all(b"\n")?;
+        }
+        child.wait()?
+    } else {
+        command.stdout(log.try_clone()?).stderr(log);
+        command.status()?
+    };
+
+    // Killed by a signal: the code a shell would report
+    Ok(status
+        .code()
+        .unwrap_or_else(|| 128 + status.signal().unwrap_or(0)))
+}
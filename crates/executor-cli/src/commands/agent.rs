@@ -0,0 +1,43 @@
+use crate::{dispatch, shutdown};
+use executor_core::config::Config;
+
+/// Push each executor's `agent_binary` to its host again and restart the
+/// agent there: `executor`, or every executor that has one.
+pub async fn upgrade(config: &Config, executor: Option<&str>) -> anyhow::Result<()> {
+    let names: Vec<&str> = match executor {
+        Some(name) => vec![name],
+        None => config
+            .executors
+            .iter()
+            .filter(|e| e.agent_binary.is_some())
+            .map(|e| e.name.as_str())
+            .collect(),
+    };
+    if names.is_empty() {
+        anyhow::bail!("No executor has an agent_binary to push");
+    }
+
+    let mut failed = 0;
+    for name in names {
+        let executor = dispatch::create_executor(config, name)?;
+        match executor.upgrade_agent(shutdown::token()).await {
+            Ok(agent) => {
+                println!(
+                    "{}: agent {} (protocol {}) at {}",
+                    name, agent.version, agent.protocol, agent.path
+                );
+                if agent.removed > 0 {
+                    println!("  Removed {} older build(s)", agent.removed);
+                }
+            }
+            Err(e) => {
+                eprintln!("{}: {}", name, e);
+                failed += 1;
+            }
+        }
+    }
+    if failed > 0 {
+        anyhow::bail!("{} agent upgrade(s) failed", failed);
+    }
+    Ok(())
+}
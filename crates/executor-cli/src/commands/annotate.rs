@@ -0,0 +1,24 @@
+use crate::dispatch;
+use executor_core::audit::{self, AuditAction, AuditEntry};
+
+pub fn run(task_id_str: &str, note: &str) -> anyhow::Result<()> {
+    let note = note.trim();
+    if note.is_empty() {
+        anyhow::bail!("The note is empty");
+    }
+
+    let task_id = dispatch::resolve_task(task_id_str)?;
+    let store = dispatch::metadata_store();
+    let mut meta = store.load(&task_id)?;
+    meta.annotate(audit::current_user(), note.to_string());
+    store.put(&meta)?;
+
+    dispatch::audit(AuditEntry {
+        executor: Some(meta.executor_name.clone()),
+        task_id: Some(task_id.clone()),
+        detail: Some(note.to_string()),
+        ..AuditEntry::new(AuditAction::Annotate)
+    });
+    println!("Task {} annotated.", task_id);
+    Ok(())
+}
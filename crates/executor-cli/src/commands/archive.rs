@@ -0,0 +1,204 @@
+use crate::{dispatch, shutdown};
+use executor_core::audit::{AuditAction, AuditEntry};
+use executor_core::completion::completions_dir;
+use executor_core::config::Config;
+use executor_core::error::ExecutorError;
+use executor_core::log_cache;
+use executor_core::metadata::TaskMetadata;
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+/// Bundle all task metadata, completion records and logs into a tarball.
+/// Compression follows the output extension (`.tar.zst`, `.tar.gz`, ...).
+pub async fn export(config: &Config, output: &Path, log_lines: usize) -> anyhow::Result<()> {
+    let staging = staging_dir();
+    let result = export_into(config, &staging, output, log_lines).await;
+    let _ = std::fs::remove_dir_all(&staging);
+    result
+}
+
+async fn export_into(
+    config: &Config,
+    staging: &Path,
+    output: &Path,
+    log_lines: usize,
+) -> anyhow::Result<()> {
+    let tasks = dispatch::metadata_store().list()?;
+    let (meta_dir, completion_dir, log_dir) = layout(staging);
+    for dir in [&meta_dir, &completion_dir, &log_dir] {
+        std::fs::create_dir_all(dir)?;
+    }
+
+    let mut logs = 0;
+    for meta in &tasks {
+        meta.write_to_dir(&meta_dir)?;
+
+        let completion = completions_dir().join(format!("{}.json", meta.task_id));
+        if completion.exists() {
+            std::fs::copy(
+                &completion,
+                completion_dir.join(format!("{}.json", meta.task_id)),
+            )?;
+        }
+
+        if log_lines == 0 {
+            continue;
+        }
+        match task_log(config, meta, log_lines).await {
+            Ok(lines) => {
+                let mut data = lines.join("\n");
+                data.push('\n');
+                std::fs::write(log_dir.join(format!("{}.log", meta.task_id)), data)?;
+                logs += 1;
+            }
+            Err(ExecutorError::Cancelled) => return Err(ExecutorError::Cancelled.into()),
+            Err(e) => eprintln!("No logs for {}: {}", meta.task_id, e),
+        }
+    }
+
+    tar(&[
+        "-a",
+        "-cf",
+        &output.to_string_lossy(),
+        "-C",
+        &staging.to_string_lossy(),
+        ".",
+    ])
+    .await?;
+
+    println!(
+        "Exported {} task(s) and {} log(s) to {}",
+        tasks.len(),
+        logs,
+        output.display()
+    );
+    Ok(())
+}
+
+/// Restore an archive written by `export`. Existing tasks are kept unless `force` is set.
+pub async fn import(input: &Path, force: bool) -> anyhow::Result<()> {
+    if !input.exists() {
+        anyhow::bail!("Archive not found: {}", input.display());
+    }
+
+    let staging = staging_dir();
+    std::fs::create_dir_all(&staging)?;
+    let result = import_from(&staging, input, force).await;
+    let _ = std::fs::remove_dir_all(&staging);
+    result
+}
+
+async fn import_from(staging: &Path, input: &Path, force: bool) -> anyhow::Result<()> {
+    tar(&[
+        "-xf",
+        &input.to_string_lossy(),
+        "-C",
+        &staging.to_string_lossy(),
+    ])
+    .await?;
+
+    let store = dispatch::metadata_store();
+    let (meta_dir, completion_dir, log_dir) = layout(staging);
+    let (mut imported, mut skipped) = (0, 0);
+
+    for entry in std::fs::read_dir(&meta_dir)? {
+        let path = entry?.path();
+        let meta = match TaskMetadata::read_from_file(&path) {
+            Ok(meta) => meta,
+            Err(e) => {
+                eprintln!("Skipping {}: {}", path.display(), e);
+                continue;
+            }
+        };
+        // The ID names the files written below; one from a crafted archive
+        // could point outside the data directory
+        if !meta.task_id.is_file_safe() {
+            eprintln!(
+                "Skipping {}: task ID {:?} is not a valid file name",
+                path.display(),
+                meta.task_id.0
+            );
+            continue;
+        }
+        if !force && store.get(&meta.task_id)?.is_some() {
+            skipped += 1;
+            continue;
+        }
+        store.put(&meta)?;
+
+        let completion = completion_dir.join(format!("{}.json", meta.task_id));
+        if completion.exists() {
+            std::fs::create_dir_all(completions_dir())?;
+            std::fs::copy(
+                &completion,
+                completions_dir().join(format!("{}.json", meta.task_id)),
+            )?;
+        }
+
+        let log = log_dir.join(format!("{}.log", meta.task_id));
+        if let Ok(data) = std::fs::read_to_string(&log) {
+            let lines: Vec<String> = data.lines().map(String::from).collect();
+            log_cache::write_cached_log(&meta.task_id, &lines)?;
+        }
+
+        imported += 1;
+    }
+
+    println!(
+        "Imported {} task(s), skipped {} already present",
+        imported, skipped
+    );
+    dispatch::audit(AuditEntry {
+        detail: Some(format!(
+            "{}: {} imported, {} skipped",
+            input.display(),
+            imported,
+            skipped
+        )),
+        ..AuditEntry::new(AuditAction::Import)
+    });
+    Ok(())
+}
+
+/// A task's log: the local cache if we have one, otherwise fetched from its executor.
+async fn task_log(
+    config: &Config,
+    meta: &TaskMetadata,
+    lines: usize,
+) -> Result<Vec<String>, ExecutorError> {
+    if let Some(cached) = log_cache::read_cached_log(&meta.task_id) {
+        return Ok(log_cache::tail(cached, lines));
+    }
+    let executor = dispatch::create_executor(config, &meta.executor_name)?;
+    executor.logs(&meta.task_id, lines, shutdown::token()).await
+}
+
+/// Archive layout: metadata/, completions/, logs/.
+fn layout(root: &Path) -> (PathBuf, PathBuf, PathBuf) {
+    (
+        root.join("metadata"),
+        root.join("completions"),
+        root.join("logs"),
+    )
+}
+
+fn staging_dir() -> PathBuf {
+    std::env::temp_dir().join(format!("openclaw-archive-{}", uuid::Uuid::new_v4()))
+}
+
+async fn tar(args: &[&str]) -> anyhow::Result<()> {
+    let output = Command::new("tar")
+        .args(args)
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to run tar: {}", e))?;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "tar failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
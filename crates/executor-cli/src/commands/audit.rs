@@ -0,0 +1,83 @@
+use crate::dispatch;
+use crate::output::OutputFormat;
+use executor_core::audit::read_entries;
+use executor_core::stats::parse_window;
+
+pub struct AuditFilter {
+    pub action: Option<String>,
+    pub executor: Option<String>,
+    pub task_id: Option<String>,
+    pub since: Option<String>,
+    pub limit: usize,
+}
+
+pub async fn run(filter: AuditFilter, format: OutputFormat) -> anyhow::Result<()> {
+    let task_id = match filter.task_id {
+        Some(ref reference) => Some(dispatch::resolve_task_or_id(reference)?),
+        None => None,
+    };
+    let cutoff = match filter.since.as_deref() {
+        Some(since) => Some(chrono::Utc::now() - parse_window(since).map_err(anyhow::Error::msg)?),
+        None => None,
+    };
+
+    let mut entries: Vec<_> = read_entries()?
+        .into_iter()
+        .filter(|e| cutoff.is_none_or(|c| e.timestamp >= c))
+        .filter(|e| {
+            filter
+                .action
+                .as_ref()
+                .is_none_or(|a| e.action.to_string() == *a)
+        })
+        .filter(|e| {
+            filter
+                .executor
+                .as_ref()
+                .is_none_or(|x| e.executor.as_ref() == Some(x))
+        })
+        .filter(|e| {
+            task_id
+                .as_ref()
+                .is_none_or(|t| e.task_id.as_ref() == Some(t))
+        })
+        .collect();
+
+    // Keep the most recent `limit` entries, still printed oldest first
+    if entries.len() > filter.limit {
+        entries.drain(..entries.len() - filter.limit);
+    }
+
+    if !format.is_table() {
+        return format.print_list(&entries);
+    }
+
+    if entries.is_empty() {
+        println!("No audit entries found.");
+        return Ok(());
+    }
+
+    println!(
+        "{:<20} {:<10} {:<12} {:<12} {:<38} RESULT",
+        "TIME", "USER", "ACTION", "EXECUTOR", "TASK ID"
+    );
+    println!("{}", "-".repeat(105));
+    for e in &entries {
+        let result = match (&e.error, &e.detail) {
+            (Some(err), _) => format!("error: {}", err),
+            (None, Some(detail)) => detail.clone(),
+            (None, None) => "ok".into(),
+        };
+        println!(
+            "{:<20} {:<10} {:<12} {:<12} {:<38} {}",
+            e.timestamp.format("%Y-%m-%d %H:%M:%S"),
+            e.user,
+            e.action.to_string(),
+            e.executor.as_deref().unwrap_or("-"),
+            e.task_id.as_ref().map(|t| t.0.as_str()).unwrap_or("-"),
+            result
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,52 @@
+use crate::{deps, dispatch, shutdown};
+use executor_core::batch::load_batch;
+use executor_core::config::Config;
+
+/// Launch every task in a batch file and print a summary of assigned task IDs.
+/// A failed launch is reported in the summary and doesn't stop the rest of the batch.
+pub async fn run(config: &Config, file: &str) -> anyhow::Result<()> {
+    let tasks = load_batch(std::path::Path::new(file))?;
+
+    let mut rows: Vec<(String, String, String)> = Vec::new();
+    let mut failures = 0;
+
+    for task in &tasks {
+        let result = async {
+            let executor = dispatch::create_executor(config, &task.spec.executor)?;
+            let mut request = task.spec.to_request(Vec::new())?;
+            dispatch::apply_defaults(config, &task.spec.executor, &mut request);
+            let meta = executor.start(request, shutdown::token()).await?;
+            if task.detach {
+                Ok::<_, anyhow::Error>(meta)
+            } else {
+                deps::wait_for_task(config, &meta.task_id).await
+            }
+        }
+        .await;
+
+        match result {
+            Ok(meta) => rows.push((
+                task.spec.executor.clone(),
+                meta.task_id.to_string(),
+                meta.status.to_string(),
+            )),
+            Err(e) => {
+                failures += 1;
+                eprintln!("Failed to launch on {}: {}", task.spec.executor, e);
+                rows.push((task.spec.executor.clone(), "-".into(), "error".into()));
+            }
+        }
+    }
+
+    println!("{:<4} {:<15} {:<38} {:<10}", "#", "EXECUTOR", "TASK ID", "STATUS");
+    println!("{}", "-".repeat(70));
+    for (i, (executor, task_id, status)) in rows.iter().enumerate() {
+        println!("{:<4} {:<15} {:<38} {:<10}", i + 1, executor, task_id, status);
+    }
+
+    if failures > 0 {
+        anyhow::bail!("{} of {} tasks failed to launch", failures, tasks.len());
+    }
+
+    Ok(())
+}
@@ -1,25 +1,226 @@
-use crate::dispatch;
+use crate::{dispatch, shutdown};
 use executor_core::config::Config;
-use executor_core::metadata::{metadata_dir, TaskMetadata};
-use executor_core::task::TaskId;
+use executor_core::host::kib;
+use executor_core::log_archive::{self, ArchiveEntry};
+use executor_core::metadata::TaskMetadata;
+use executor_core::stats::parse_window;
+use executor_core::store::MetadataQuery;
+use executor_core::task::TaskStatus;
+use executor_core::ExecutorError;
+use std::collections::BTreeMap;
+use std::io::{BufRead, IsTerminal, Write};
 
 pub async fn run(config: &Config, task_id_str: &str) -> anyhow::Result<()> {
-    let task_id = TaskId::from_string(task_id_str.to_string());
-    let meta = load_local_meta(&task_id)?;
-    let executor = dispatch::create_executor(config, &meta.executor_name)?;
+    let task_id = dispatch::resolve_task(task_id_str)?;
+    let meta = dispatch::metadata_store().load(&task_id)?;
 
-    executor.cleanup(&task_id).await?;
+    let archived = cleanup_task(config, &meta).await?;
     println!("Task {} cleaned up.", task_id);
+    if let Some(entry) = archived {
+        println!(
+            "Log archived: {} -> {}",
+            kib(entry.original_bytes / 1024),
+            kib(entry.compressed_bytes / 1024)
+        );
+    }
 
     Ok(())
 }
 
-fn load_local_meta(task_id: &TaskId) -> anyhow::Result<TaskMetadata> {
-    let dir = metadata_dir();
-    let path = dir.join(format!("{}.meta.json", task_id));
-    if path.exists() {
-        Ok(TaskMetadata::read_from_file(&path)?)
-    } else {
-        anyhow::bail!("No local metadata for task {}", task_id)
+/// Clean up every finished task its executor's `cleanup` policy says is
+/// due, or with `dry_run` only list them.
+pub async fn run_expired(config: &Config, dry_run: bool) -> anyhow::Result<()> {
+    let tasks = expired(config)?;
+    if tasks.is_empty() {
+        println!("No finished tasks are due for cleanup.");
+        return Ok(());
+    }
+
+    print_tasks(&tasks);
+    if dry_run {
+        return Ok(());
+    }
+    cleanup_all(config, &tasks).await
+}
+
+/// Clean up every completed, failed or killed task, narrowed to tasks that
+/// finished more than `older_than` (e.g. `7d`) ago and, with `keep_failed`,
+/// to those that didn't fail. With `dry_run` only list them. The tasks are
+/// listed and confirmed first unless `yes`.
+pub async fn run_terminal(
+    config: &Config,
+    older_than: Option<&str>,
+    keep_failed: bool,
+    dry_run: bool,
+    yes: bool,
+) -> anyhow::Result<()> {
+    let now = chrono::Utc::now();
+    let cutoff = match older_than {
+        Some(window) => Some(now - parse_window(window).map_err(anyhow::Error::msg)?),
+        None => None,
+    };
+    let tasks: Vec<TaskMetadata> = dispatch::metadata_store()
+        .query(&MetadataQuery::default())?
+        .into_iter()
+        .filter(|meta| meta.status.is_terminal())
+        .filter(|meta| !(keep_failed && meta.status == TaskStatus::Failed))
+        .filter(|meta| {
+            cutoff.is_none_or(|cutoff| meta.finished_at.is_some_and(|at| at <= cutoff))
+        })
+        .collect();
+    if tasks.is_empty() {
+        println!("No finished tasks match.");
+        return Ok(());
+    }
+
+    print_tasks(&tasks);
+    if dry_run {
+        return Ok(());
+    }
+    if !yes && !confirm(tasks.len())? {
+        println!("Aborted.");
+        return Ok(());
+    }
+    cleanup_all(config, &tasks).await
+}
+
+/// Finished tasks due for cleanup under their executor's `cleanup` policy.
+/// Tasks on executors no longer in the config are left alone.
+pub fn expired(config: &Config) -> anyhow::Result<Vec<TaskMetadata>> {
+    let now = chrono::Utc::now();
+    Ok(dispatch::metadata_store()
+        .query(&MetadataQuery::default())?
+        .into_iter()
+        .filter(|meta| {
+            config
+                .find_executor(&meta.executor_name)
+                .is_some_and(|executor| executor.cleanup.is_due(meta, now))
+        })
+        .collect())
+}
+
+/// Clean up `meta`'s task on its executor, then archive its log.
+pub async fn cleanup_task(
+    config: &Config,
+    meta: &TaskMetadata,
+) -> anyhow::Result<Option<ArchiveEntry>> {
+    let executor = dispatch::create_executor(config, &meta.executor_name)?;
+    executor.cleanup(&meta.task_id, shutdown::token()).await?;
+    Ok(archive(meta))
+}
+
+/// Clean up `tasks`, each executor's in one go (over one connection on an
+/// SSH host), then print the space freed per executor and in all. A task
+/// that fails to be cleaned up is reported and the rest are still cleaned
+/// up.
+async fn cleanup_all(config: &Config, tasks: &[TaskMetadata]) -> anyhow::Result<()> {
+    let mut by_executor: BTreeMap<&str, Vec<&TaskMetadata>> = BTreeMap::new();
+    for meta in tasks {
+        by_executor.entry(&meta.executor_name).or_default().push(meta);
+    }
+
+    let mut summary = Vec::new();
+    let mut failures = 0;
+    let (mut total_kb, mut unmeasured) = (0, 0);
+    for (executor_name, metas) in by_executor {
+        let task_ids: Vec<_> = metas.iter().map(|meta| meta.task_id.clone()).collect();
+        let outcomes = match dispatch::create_executor(config, executor_name) {
+            Ok(executor) => executor.cleanup_many(&task_ids, shutdown::token()).await,
+            Err(e) => Err(e),
+        };
+        let outcomes = match outcomes {
+            Ok(outcomes) => outcomes,
+            // Ctrl-C stops the remaining cleanups too
+            Err(ExecutorError::Cancelled) => return Err(ExecutorError::Cancelled.into()),
+            Err(e) => {
+                eprintln!(
+                    "Could not clean up {} task(s) on {}: {}",
+                    metas.len(),
+                    executor_name,
+                    e
+                );
+                failures += metas.len();
+                continue;
+            }
+        };
+
+        let (mut cleaned, mut freed_kb) = (0, 0);
+        for (outcome, meta) in outcomes.iter().zip(&metas) {
+            if let Some(ref e) = outcome.error {
+                failures += 1;
+                eprintln!("Failed to clean up {}: {}", meta.task_id, e);
+                continue;
+            }
+            cleaned += 1;
+            match outcome.freed_kb {
+                Some(kb) => freed_kb += kb,
+                None => unmeasured += 1,
+            }
+            archive(meta);
+            println!("Task {} cleaned up.", meta.task_id);
+        }
+        total_kb += freed_kb;
+        summary.push((executor_name, cleaned, freed_kb));
+    }
+
+    println!();
+    for (executor_name, cleaned, freed_kb) in &summary {
+        println!("{:<15} {:>4} task(s)  {:>10} freed", executor_name, cleaned, kib(*freed_kb));
+    }
+    let cleaned: usize = summary.iter().map(|(_, cleaned, _)| cleaned).sum();
+    println!("{:<15} {:>4} task(s)  {:>10} freed", "total", cleaned, kib(total_kb));
+    if unmeasured > 0 {
+        println!(
+            "({} task(s) on executors that can't measure what they free are not counted)",
+            unmeasured
+        );
+    }
+
+    if failures > 0 {
+        anyhow::bail!("{} of {} task(s) could not be cleaned up", failures, tasks.len());
+    }
+    Ok(())
+}
+
+/// The backend's copy of a finished task's log is gone once it is cleaned
+/// up, so keep the local one compressed. Failing that is only warned about.
+fn archive(meta: &TaskMetadata) -> Option<ArchiveEntry> {
+    if !meta.status.is_terminal() {
+        return None;
+    }
+    match log_archive::archive_log(&meta.task_id) {
+        Ok(entry) => entry,
+        Err(e) => {
+            eprintln!("Warning: failed to archive log: {}", e);
+            None
+        }
+    }
+}
+
+fn print_tasks(tasks: &[TaskMetadata]) {
+    println!("{:<38} {:<15} {:<10} {:<17}", "TASK ID", "EXECUTOR", "STATUS", "FINISHED");
+    println!("{}", "-".repeat(82));
+    for task in tasks {
+        println!(
+            "{:<38} {:<15} {:<10} {:<17}",
+            task.task_id.to_string(),
+            task.executor_name,
+            task.status.to_string(),
+            task.finished_at
+                .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_default(),
+        );
+    }
+}
+
+/// Ask on the terminal before cleaning up `count` tasks.
+fn confirm(count: usize) -> anyhow::Result<bool> {
+    if !std::io::stdin().is_terminal() {
+        anyhow::bail!("Not cleaning up {} task(s) without confirmation; pass --yes", count);
     }
+    print!("Clean up {} task(s)? [y/N] ", count);
+    std::io::stdout().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
 }
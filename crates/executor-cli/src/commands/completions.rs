@@ -0,0 +1,28 @@
+use clap::Command;
+use clap_complete::env::Shells;
+use clap_complete::Shell;
+use std::path::Path;
+
+/// Environment variable the shell sets when it calls back for completions.
+pub const COMPLETE_VAR: &str = "COMPLETE";
+
+/// Print the script that registers completion for `shell`. The script asks
+/// the binary itself for candidates, so task IDs and executor names complete
+/// from what exists when Tab is pressed.
+pub fn run(cmd: Command, shell: Shell) -> anyhow::Result<()> {
+    let shells = Shells::builtins();
+    let completer = shells
+        .completer(&shell.to_string())
+        .ok_or_else(|| anyhow::anyhow!("No completion support for {}", shell))?;
+    let name = cmd.get_name();
+    completer.write_registration(COMPLETE_VAR, name, name, name, &mut std::io::stdout())?;
+    Ok(())
+}
+
+/// Write man pages for the CLI and each of its subcommands into `dir`.
+pub fn generate_man(cmd: Command, dir: &Path) -> anyhow::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    clap_mangen::generate_to(cmd, dir)?;
+    println!("Man pages written to {}", dir.display());
+    Ok(())
+}
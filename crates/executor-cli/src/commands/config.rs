@@ -1,13 +1,22 @@
+use crate::{dispatch, wizard};
+use crate::output::OutputFormat;
+use executor_core::audit::{AuditAction, AuditEntry};
+use executor_core::resolve::{Overrides, ResolvedSettings};
 use executor_core::Config;
+use std::io::IsTerminal;
+use std::path::Path;
 
+/// Written by `config --init` without a terminal to ask on (or with
+/// `--sample`): one executor of each kind, with placeholder hosts and paths.
 const SAMPLE_CONFIG: &str = r#"# OpenClaw Coding Agent Configuration
 # See: https://github.com/openclaw/coding-agent
+# Replace the example host, user and paths below with your own.
 
 executors:
-  - name: crib
+  - name: build-box
     type: ssh
-    host: 192.168.0.152
-    user: jimmy
+    host: build-box.example.com
+    user: you
     claude_path: ~/.npm-global/bin/claude
     labels:
       - rust
@@ -18,7 +27,10 @@ executors:
     image: claude-code:latest
     runtime: docker
     volumes:
-      - /home/jimmy/repos:/work
+      - /home/you/repos:/work
+    # cpus: 2
+    # memory: 4g
+    # pids_limit: 512
     labels:
       - isolated
       - reproducible
@@ -35,33 +47,44 @@ defaults:
   claude_path: claude
 "#;
 
-pub async fn run(path: bool, init: bool) -> anyhow::Result<()> {
+pub async fn run(config: &Config, path: bool, init: bool, sample: bool) -> anyhow::Result<()> {
     if path {
         println!("{}", Config::default_path().display());
         return Ok(());
     }
 
     if init {
-        let config_path = Config::default_path();
-        if config_path.exists() {
-            println!("Config already exists at: {}", config_path.display());
-            println!("Remove it first if you want to reinitialize.");
-            return Ok(());
-        }
-
-        if let Some(parent) = config_path.parent() {
-            std::fs::create_dir_all(parent)?;
-        }
-        std::fs::write(&config_path, SAMPLE_CONFIG)?;
-        println!("Sample config written to: {}", config_path.display());
-        return Ok(());
+        return init_config(sample).await;
     }
 
-    // Default: show current config path and status
-    let config_path = Config::default_path();
-    println!("Config path: {}", config_path.display());
-    if config_path.exists() {
-        let config = Config::load_from(&config_path)?;
+    // Default: show the loaded config's path and status. The config file
+    // itself is the last source.
+    let config_path = config.sources.last().cloned();
+    println!(
+        "Config path: {}",
+        config_path
+            .clone()
+            .unwrap_or_else(Config::default_path)
+            .display()
+    );
+    if config_path.is_some() {
+        if let Some(ref profile) = config.profile {
+            println!("Profile:     {}", profile);
+        }
+        let mut profiles: Vec<String> = config.profiles.keys().cloned().collect();
+        profiles.extend(Config::profile_files());
+        profiles.sort();
+        profiles.dedup();
+        if !profiles.is_empty() {
+            println!("Profiles:    {}", profiles.join(", "));
+        }
+        let includes = &config.sources[..config.sources.len().saturating_sub(1)];
+        if !includes.is_empty() {
+            println!("Includes:");
+            for source in includes {
+                println!("  - {}", source.display());
+            }
+        }
         println!("Executors:   {}", config.executors.len());
         for e in &config.executors {
             println!("  - {} ({})", e.name, e.executor_type);
@@ -73,3 +96,103 @@ pub async fn run(path: bool, init: bool) -> anyhow::Result<()> {
 
     Ok(())
 }
+
+/// Write a new config file: from the wizard on a terminal, otherwise (or with
+/// `sample`) the sample config. It is checked like `config --validate` and
+/// removed again if it has problems.
+async fn init_config(sample: bool) -> anyhow::Result<()> {
+    let config_path = Config::default_path();
+    if config_path.exists() {
+        println!("Config already exists at: {}", config_path.display());
+        println!("Remove it first if you want to reinitialize.");
+        return Ok(());
+    }
+
+    let interactive =
+        !sample && std::io::stdin().is_terminal() && std::io::stderr().is_terminal();
+    let content = if interactive {
+        wizard::config_yaml().await?
+    } else {
+        SAMPLE_CONFIG.to_string()
+    };
+
+    if let Some(parent) = config_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&config_path, content)?;
+    let problems = executor_core::validate::validate(&config_path, None);
+    if !problems.is_empty() {
+        std::fs::remove_file(&config_path)?;
+        for problem in &problems {
+            eprintln!("{}", problem);
+        }
+        anyhow::bail!("The new config has problems; nothing was written");
+    }
+    dispatch::audit(AuditEntry {
+        detail: Some(config_path.display().to_string()),
+        ..AuditEntry::new(AuditAction::ConfigInit)
+    });
+    if interactive {
+        println!("Config written to: {}", config_path.display());
+    } else {
+        println!("Sample config written to: {}", config_path.display());
+        println!("Replace its example hosts and paths, or run `config --init` on a terminal.");
+    }
+    Ok(())
+}
+
+/// Print every problem `validate` finds in the config at `path` (with
+/// `profile` applied), failing if there are any.
+pub fn validate(path: &Path, profile: Option<&str>) -> anyhow::Result<()> {
+    let problems = executor_core::validate::validate(path, profile);
+    if problems.is_empty() {
+        match profile {
+            Some(profile) => println!("{} (profile {}): OK", path.display(), profile),
+            None => println!("{}: OK", path.display()),
+        }
+        return Ok(());
+    }
+    for problem in &problems {
+        eprintln!("{}", problem);
+    }
+    anyhow::bail!(
+        "{} problem{} found in {}",
+        problems.len(),
+        if problems.len() == 1 { "" } else { "s" },
+        path.display()
+    )
+}
+
+/// Print each resolved setting with the layer it came from.
+pub async fn resolve(
+    config: &Config,
+    executor_name: Option<&str>,
+    overrides: &Overrides,
+    format: OutputFormat,
+) -> anyhow::Result<()> {
+    let executor = match executor_name {
+        Some(name) => Some(
+            config
+                .find_executor(name)
+                .ok_or_else(|| anyhow::anyhow!("Executor not found: {}", name))?,
+        ),
+        None => None,
+    };
+
+    let resolved = ResolvedSettings::resolve(config, executor, overrides);
+
+    if !format.is_table() {
+        return format.print(&resolved);
+    }
+
+    if let Some(name) = executor_name {
+        println!("Executor: {}", name);
+    }
+    println!("{:<26} {:<40} SOURCE", "FIELD", "VALUE");
+    println!("{}", "-".repeat(82));
+    for (field, value, source) in resolved.rows() {
+        println!("{:<26} {:<40} {}", field, value, source);
+    }
+
+    Ok(())
+}
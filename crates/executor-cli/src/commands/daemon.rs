@@ -0,0 +1,467 @@
+use crate::commands::cleanup;
+use crate::{dispatch, shutdown};
+use chrono::{DateTime, Local, Timelike};
+use executor_core::config::{Config, Schedule, TelegramConfig};
+use executor_core::cron::CronExpr;
+use executor_core::metadata::TaskMetadata;
+use executor_core::notify::telegram::{self, Action};
+use executor_core::task::{TaskId, TaskPayload, TaskRequest};
+use executor_core::validate;
+use notify::{RecursiveMode, Watcher};
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, watch};
+use tracing::{info, warn};
+
+/// How long to let an editor finish writing before reloading the config.
+const RELOAD_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// How often finished tasks are checked against their executor's `cleanup`
+/// policy.
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// The daemon's config and the schedules compiled from it, replaced as a
+/// whole when the config is reloaded.
+struct State {
+    config: Config,
+    schedules: Vec<(Schedule, CronExpr)>,
+}
+
+impl State {
+    fn new(config: Config) -> anyhow::Result<Self> {
+        let mut schedules = Vec::new();
+        for schedule in &config.schedules {
+            let expr: CronExpr = schedule.cron.parse().map_err(|e| {
+                anyhow::anyhow!("Schedule '{}': invalid cron: {}", schedule.name, e)
+            })?;
+            schedule
+                .spec
+                .payload()
+                .map_err(|e| anyhow::anyhow!("Schedule '{}': {}", schedule.name, e))?;
+            if config.find_executor(&schedule.spec.executor).is_none() {
+                anyhow::bail!(
+                    "Schedule '{}': executor not found: {}",
+                    schedule.name,
+                    schedule.spec.executor
+                );
+            }
+            schedules.push((schedule.clone(), expr));
+        }
+        Ok(Self { config, schedules })
+    }
+}
+
+/// Long-running controller process. Launches configured schedules when
+/// their cron expression matches, cleans up finished tasks as executors'
+/// `cleanup` policies say, and handles Telegram button presses, until Ctrl-C.
+/// Edits to the config at `path` (or its includes) are picked up without a restart.
+pub async fn run(config: Config, path: PathBuf) -> anyhow::Result<()> {
+    let state = State::new(config)?;
+    info!("Daemon started with {} schedule(s)", state.schedules.len());
+
+    // The Telegram poller itself is only set up at startup
+    let telegram = state.config.notifications.telegram.clone();
+    let (tx, rx) = watch::channel(Arc::new(state));
+    match telegram {
+        Some(ref telegram) if telegram.actions => {
+            tokio::join!(
+                watch_config(path, tx),
+                run_schedules(rx.clone()),
+                sweep_expired(rx.clone()),
+                serve_telegram(rx, telegram)
+            );
+        }
+        _ => {
+            tokio::join!(watch_config(path, tx), run_schedules(rx.clone()), sweep_expired(rx));
+        }
+    }
+
+    Ok(())
+}
+
+async fn run_schedules(state: watch::Receiver<Arc<State>>) {
+    let mut last_minute: Option<DateTime<Local>> = None;
+    loop {
+        let now = Local::now();
+        let minute = now
+            .with_second(0)
+            .and_then(|t| t.with_nanosecond(0))
+            .unwrap_or(now);
+
+        if last_minute != Some(minute) {
+            last_minute = Some(minute);
+            let state = state.borrow().clone();
+            for (schedule, expr) in &state.schedules {
+                if !expr.matches(&minute) {
+                    continue;
+                }
+                match launch(&state.config, schedule).await {
+                    Ok(meta) => info!(
+                        "Schedule '{}' launched task {} on {}",
+                        schedule.name, meta.task_id, meta.executor_name
+                    ),
+                    Err(e) => warn!("Schedule '{}' failed to launch: {}", schedule.name, e),
+                }
+            }
+        }
+
+        // Wake up just after the next minute boundary
+        let wait = 60 - u64::from(Local::now().second()).min(59);
+        if shutdown::sleep(Duration::from_secs(wait)).await.is_err() {
+            info!("Daemon stopping");
+            break;
+        }
+    }
+}
+
+/// Clean up the finished tasks that executors' `cleanup` policies say are
+/// due, at startup and then every [`CLEANUP_INTERVAL`].
+async fn sweep_expired(state: watch::Receiver<Arc<State>>) {
+    loop {
+        let config = state.borrow().config.clone();
+        match cleanup::expired(&config) {
+            Ok(tasks) => {
+                for meta in tasks {
+                    match cleanup::cleanup_task(&config, &meta).await {
+                        Ok(_) => info!(
+                            "Cleaned up task {} on {} (cleanup policy)",
+                            meta.task_id, meta.executor_name
+                        ),
+                        Err(e) => warn!("Cleanup of {} failed: {}", meta.task_id, e),
+                    }
+                }
+            }
+            Err(e) => warn!("Could not list tasks to clean up: {}", e),
+        }
+
+        if shutdown::sleep(CLEANUP_INTERVAL).await.is_err() {
+            break;
+        }
+    }
+}
+
+/// Reload the config whenever it or one of its includes changes on disk.
+/// A reload that fails to load or validate is refused, and the daemon keeps
+/// running on the previous config.
+async fn watch_config(path: PathBuf, tx: watch::Sender<Arc<State>>) {
+    let (events_tx, mut events) = mpsc::unbounded_channel();
+    let mut watcher = match notify::recommended_watcher(move |event| {
+        let _ = events_tx.send(event);
+    }) {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            warn!("Config reload disabled: {}", e);
+            return;
+        }
+    };
+    let profile = tx.borrow().config.profile.clone();
+    let mut watched = HashSet::new();
+    let mut files = watch_sources(&mut watcher, &mut watched, &path, &tx.borrow().config);
+
+    loop {
+        let event = tokio::select! {
+            _ = shutdown::token().cancelled() => break,
+            event = events.recv() => event,
+        };
+        match event {
+            None => break,
+            Some(Err(e)) => {
+                warn!("Config watch error: {}", e);
+                continue;
+            }
+            Some(Ok(event)) if !touches(&event, &files) => continue,
+            Some(Ok(_)) => {}
+        }
+
+        // Editors often write a file in several steps
+        if shutdown::sleep(RELOAD_DEBOUNCE).await.is_err() {
+            break;
+        }
+        while events.try_recv().is_ok() {}
+
+        let state = match reload(&path, profile.as_deref()) {
+            Ok(state) => state,
+            Err(e) => {
+                warn!(
+                    "Config reload refused, keeping the previous config: {:#}",
+                    e
+                );
+                continue;
+            }
+        };
+        let changes = diff(&tx.borrow().config, &state.config);
+        if changes.is_empty() {
+            continue;
+        }
+        info!("Config reloaded: {}", changes.join("; "));
+        files = watch_sources(&mut watcher, &mut watched, &path, &state.config);
+        tx.send_replace(Arc::new(state));
+    }
+}
+
+/// Load the config at `path` with `profile` applied, provided it passes
+/// `config --validate`.
+fn reload(path: &Path, profile: Option<&str>) -> anyhow::Result<State> {
+    let problems = validate::validate(path, profile);
+    if !problems.is_empty() {
+        let problems: Vec<String> = problems.iter().map(ToString::to_string).collect();
+        anyhow::bail!("{}", problems.join("; "));
+    }
+    State::new(Config::load_profile(path, profile)?)
+}
+
+/// Watch the directories holding the config and its includes, since editors
+/// often replace a file rather than write to it, and return the files whose
+/// changes should trigger a reload.
+fn watch_sources(
+    watcher: &mut impl Watcher,
+    watched: &mut HashSet<PathBuf>,
+    path: &Path,
+    config: &Config,
+) -> HashSet<PathBuf> {
+    let mut files = HashSet::new();
+    for source in std::iter::once(path).chain(config.sources.iter().map(PathBuf::as_path)) {
+        let Some(file) = normalize(source) else {
+            continue;
+        };
+        let Some(dir) = file.parent() else {
+            continue;
+        };
+        if !watched.contains(dir) {
+            match watcher.watch(dir, RecursiveMode::NonRecursive) {
+                Ok(()) => {
+                    watched.insert(dir.to_path_buf());
+                }
+                Err(e) => warn!("Cannot watch {} for config changes: {}", dir.display(), e),
+            }
+        }
+        files.insert(file);
+    }
+    files
+}
+
+/// `path` with its directory canonicalized, so watched and event paths compare equal.
+fn normalize(path: &Path) -> Option<PathBuf> {
+    let name = path.file_name()?;
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    Some(dir.canonicalize().ok()?.join(name))
+}
+
+fn touches(event: &notify::Event, files: &HashSet<PathBuf>) -> bool {
+    !event.kind.is_access()
+        && event
+            .paths
+            .iter()
+            .filter_map(|path| normalize(path))
+            .any(|path| files.contains(&path))
+}
+
+/// What changed between two configs: sections and executor or schedule
+/// entries, with the names of the fields that differ. Values are left out,
+/// since they may hold secrets.
+fn diff(old: &Config, new: &Config) -> Vec<String> {
+    let (Ok(Value::Object(old)), Ok(Value::Object(new))) =
+        (serde_json::to_value(old), serde_json::to_value(new))
+    else {
+        return vec!["config changed".to_string()];
+    };
+    let mut changes = Vec::new();
+    for section in keys(&old, &new) {
+        let (before, after) = (old.get(section), new.get(section));
+        if before == after {
+            continue;
+        }
+        match (section, before, after) {
+            ("executors", Some(Value::Array(before)), Some(Value::Array(after))) => {
+                entry_changes("executor", before, after, &mut changes)
+            }
+            ("schedules", Some(Value::Array(before)), Some(Value::Array(after))) => {
+                entry_changes("schedule", before, after, &mut changes)
+            }
+            _ => changes.push(format!("{}: {}", section, field_changes(before, after))),
+        }
+    }
+    changes
+}
+
+/// Added, removed and changed entries of a list keyed by `name`.
+fn entry_changes(kind: &str, before: &[Value], after: &[Value], changes: &mut Vec<String>) {
+    let name = |entry: &Value| {
+        entry
+            .get("name")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+    };
+    let find = |list: &[Value], wanted: &str| {
+        list.iter()
+            .find(|entry| name(entry).as_deref() == Some(wanted))
+            .cloned()
+    };
+    for entry in before {
+        let Some(entry_name) = name(entry) else {
+            continue;
+        };
+        match find(after, &entry_name) {
+            None => changes.push(format!("{} '{}' removed", kind, entry_name)),
+            Some(ref updated) if updated != entry => changes.push(format!(
+                "{} '{}': {}",
+                kind,
+                entry_name,
+                field_changes(Some(entry), Some(updated))
+            )),
+            Some(_) => {}
+        }
+    }
+    for entry_name in after.iter().filter_map(name) {
+        if find(before, &entry_name).is_none() {
+            changes.push(format!("{} '{}' added", kind, entry_name));
+        }
+    }
+}
+
+/// The fields that differ between two mappings, or just "changed".
+fn field_changes(before: Option<&Value>, after: Option<&Value>) -> String {
+    let (Some(Value::Object(before)), Some(Value::Object(after))) = (before, after) else {
+        return "changed".to_string();
+    };
+    let fields: Vec<&str> = keys(before, after)
+        .into_iter()
+        .filter(|key| before.get(*key) != after.get(*key))
+        .collect();
+    format!("{} changed", fields.join(", "))
+}
+
+/// The keys of both objects, without duplicates.
+fn keys<'a>(a: &'a Map<String, Value>, b: &'a Map<String, Value>) -> Vec<&'a str> {
+    let mut keys: Vec<&str> = a.keys().map(String::as_str).collect();
+    keys.extend(
+        b.keys()
+            .map(String::as_str)
+            .filter(|key| !a.contains_key(*key)),
+    );
+    keys
+}
+
+/// Poll for Retry / Clean up presses on task notifications and act on them.
+async fn serve_telegram(state: watch::Receiver<Arc<State>>, telegram: &TelegramConfig) {
+    info!("Handling Telegram actions for chat {}", telegram.chat_id);
+    let mut offset = 0;
+    loop {
+        let polled = tokio::select! {
+            _ = shutdown::token().cancelled() => break,
+            polled = telegram::poll_callbacks(telegram, offset) => polled,
+        };
+        let callbacks = match polled {
+            Ok((callbacks, next)) => {
+                offset = next;
+                callbacks
+            }
+            Err(e) => {
+                warn!("Telegram poll failed: {}", e);
+                if shutdown::sleep(Duration::from_secs(30)).await.is_err() {
+                    break;
+                }
+                continue;
+            }
+        };
+
+        for callback in callbacks {
+            // Only the configured chat may drive actions
+            let reply = match Action::parse(&callback.data) {
+                _ if callback.chat_id != telegram.chat_id => {
+                    "Not allowed from this chat".to_string()
+                }
+                Some((action, task_id)) => {
+                    let state = state.borrow().clone();
+                    match handle_action(&state.config, action, &task_id).await {
+                        Ok(reply) => reply,
+                        Err(e) => {
+                            warn!("Telegram {:?} of {} failed: {}", action, task_id, e);
+                            format!("Failed: {}", e)
+                        }
+                    }
+                }
+                None => "Unknown action".to_string(),
+            };
+            if let Err(e) = telegram::answer_callback(telegram, &callback.id, &reply).await {
+                warn!("Telegram answer failed: {}", e);
+            }
+        }
+    }
+}
+
+async fn handle_action(
+    config: &Config,
+    action: Action,
+    task_id: &TaskId,
+) -> anyhow::Result<String> {
+    let meta = dispatch::metadata_store().load(task_id)?;
+    let executor = dispatch::create_executor(config, &meta.executor_name)?;
+
+    match action {
+        Action::Retry => {
+            let mut request = retry_request(&meta);
+            dispatch::apply_defaults(config, &meta.executor_name, &mut request);
+            let retried = executor.start(request, shutdown::token()).await?;
+            info!(
+                "Telegram retry of {} started task {}",
+                task_id, retried.task_id
+            );
+            Ok(format!("Retrying as {}", retried.task_id))
+        }
+        Action::Cleanup => {
+            executor.cleanup(task_id, shutdown::token()).await?;
+            info!("Telegram cleanup of {}", task_id);
+            Ok("Cleaned up".to_string())
+        }
+    }
+}
+
+/// A fresh request for the same prompt or command, name, workspace, repo,
+/// branch setting, webhook, tags, services, Nix shell, heartbeat interval,
+/// turn limit and allowed tools, as recorded in the task's metadata.
+fn retry_request(meta: &TaskMetadata) -> TaskRequest {
+    let payload = match meta.task_type.as_str() {
+        "shell_command" => TaskPayload::ShellCommand {
+            command: meta.prompt.clone(),
+        },
+        _ => TaskPayload::ClaudeCode {
+            prompt: meta.prompt.clone(),
+            max_turns: meta.max_turns,
+            allowed_tools: meta.allowed_tools.clone(),
+        },
+    };
+    // A repo task's workspace was its own clone, so clone afresh instead
+    let workspace = match meta.repo {
+        Some(_) => None,
+        None => meta.workspace.clone(),
+    };
+    TaskRequest {
+        name: meta.name.clone(),
+        webhook_url: meta.webhook_url.clone(),
+        repo: meta.repo.clone(),
+        git_branch: meta.git_branch.is_some(),
+        tags: meta.tags.clone(),
+        services: meta.services.clone(),
+        nix_shell: meta.nix_shell.clone(),
+        heartbeat_interval_secs: meta.heartbeat_interval_secs,
+        ..TaskRequest::new(payload, workspace)
+    }
+}
+
+/// Launch one run of a schedule as a normal task tagged with the schedule name.
+async fn launch(config: &Config, schedule: &Schedule) -> anyhow::Result<TaskMetadata> {
+    let executor = dispatch::create_executor(config, &schedule.spec.executor)?;
+    let mut request = TaskRequest {
+        schedule_name: Some(schedule.name.clone()),
+        ..schedule.spec.to_request(Vec::new())?
+    };
+    dispatch::apply_defaults(config, &schedule.spec.executor, &mut request);
+    Ok(executor.start(request, shutdown::token()).await?)
+}
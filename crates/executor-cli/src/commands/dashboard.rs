@@ -1,26 +1,67 @@
-use executor_core::metadata::list_all_metadata;
+use crate::{dispatch, shutdown};
+use anyhow::Context;
+use executor_core::metadata::Dashboard;
+use executor_core::prometheus;
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast::{self, error::RecvError};
+
+/// Comment sent to idle `/events` clients, so proxies keep the stream open.
+const KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+/// Requests with longer headers than this are dropped.
+const MAX_REQUEST_BYTES: usize = 16 * 1024;
 
 /// Dashboard integration command. Covers GitHub issue #4.
-/// Outputs structured JSON/JSONL for external dashboard consumption.
-pub async fn run(stream: bool, watch: Option<u64>) -> anyhow::Result<()> {
+/// Outputs structured JSON/JSONL for external dashboard consumption, or writes
+/// Prometheus gauges to `prom_textfile`.
+pub async fn run(
+    stream: bool,
+    watch: Option<u64>,
+    prom_textfile: Option<&Path>,
+) -> anyhow::Result<()> {
     match watch {
         Some(interval) => {
-            // Watch mode: continuously output status
+            // Watch mode: continuously output status until Ctrl-C
             loop {
-                output_dashboard(stream)?;
-                tokio::time::sleep(std::time::Duration::from_secs(interval)).await;
+                refresh(stream, prom_textfile)?;
+                if shutdown::sleep(std::time::Duration::from_secs(interval)).await.is_err() {
+                    break;
+                }
             }
         }
         None => {
-            output_dashboard(stream)?;
+            refresh(stream, prom_textfile)?;
         }
     }
 
     Ok(())
 }
 
+fn refresh(stream: bool, prom_textfile: Option<&Path>) -> anyhow::Result<()> {
+    match prom_textfile {
+        Some(path) => write_textfile(path),
+        None => output_dashboard(stream),
+    }
+}
+
+/// Write the Prometheus gauges to `path` through a temporary file renamed over
+/// it, so node_exporter never reads a half-written file.
+fn write_textfile(path: &Path) -> anyhow::Result<()> {
+    let tasks = dispatch::metadata_store().list()?;
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    std::fs::write(&tmp, prometheus::render(&tasks, chrono::Utc::now()))
+        .with_context(|| format!("Failed to write {}", Path::new(&tmp).display()))?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
 fn output_dashboard(stream: bool) -> anyhow::Result<()> {
-    let tasks = list_all_metadata()?;
+    let tasks = dispatch::metadata_store().list()?;
 
     if stream {
         // JSONL: one line per task
@@ -28,23 +69,185 @@ fn output_dashboard(stream: bool) -> anyhow::Result<()> {
             println!("{}", task.to_jsonl_line());
         }
     } else {
-        // Full JSON array
-        let dashboard: Vec<serde_json::Value> = tasks
-            .iter()
-            .map(|t| t.to_dashboard_json())
-            .collect();
-
-        let output = serde_json::json!({
-            "timestamp": chrono::Utc::now().to_rfc3339(),
-            "task_count": tasks.len(),
-            "running": tasks.iter().filter(|t| t.status == executor_core::task::TaskStatus::Running).count(),
-            "completed": tasks.iter().filter(|t| t.status == executor_core::task::TaskStatus::Completed).count(),
-            "failed": tasks.iter().filter(|t| t.status == executor_core::task::TaskStatus::Failed).count(),
-            "tasks": dashboard,
+        // Full JSON snapshot
+        let output = Dashboard::new(&tasks);
+        println!("{}", serde_json::to_string_pretty(&output)?);
+    }
+
+    Ok(())
+}
+
+/// Serve the dashboard over HTTP on `addr` until Ctrl-C:
+/// - `GET /events`: server-sent events; a `snapshot` (the `dashboard` JSON)
+///   on connect, then a `task` event (a `dashboard --stream` line) whenever a
+///   task's metadata changes, and `removed` when a task is cleaned up
+/// - `GET /dashboard`: the current snapshot
+///
+/// Changes are found by re-reading task metadata every `interval` seconds.
+/// Browsers let only pages from `cors_origin` read the responses, and none
+/// without it.
+pub async fn serve(addr: &str, interval: u64, cors_origin: Option<&str>) -> anyhow::Result<()> {
+    let cors = match cors_origin {
+        // It goes into a header line as is
+        Some(origin) if origin.is_empty() || origin.contains(|c: char| c.is_whitespace()) => {
+            anyhow::bail!("--cors-origin {:?} is not an origin, e.g. http://localhost:3000", origin)
+        }
+        Some(origin) => format!("Access-Control-Allow-Origin: {}\r\n", origin),
+        None => String::new(),
+    };
+    let listener = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Failed to listen on {}", addr))?;
+    println!("Serving dashboard events on http://{}/events", listener.local_addr()?);
+
+    let (changes, _) = broadcast::channel(256);
+    tokio::spawn(watch_changes(changes.clone(), interval.max(1)));
+
+    loop {
+        let (stream, _) = tokio::select! {
+            accepted = listener.accept() => accepted?,
+            _ = shutdown::token().cancelled() => break,
+        };
+        let changes = changes.subscribe();
+        let cors = cors.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle_client(stream, changes, &cors).await {
+                tracing::debug!("Dashboard client: {}", e);
+            }
         });
+    }
 
-        println!("{}", serde_json::to_string_pretty(&output)?);
+    Ok(())
+}
+
+/// Broadcast an SSE frame for every task added, changed or removed since the
+/// previous look.
+async fn watch_changes(changes: broadcast::Sender<String>, interval: u64) {
+    let mut known = current_tasks().unwrap_or_default();
+    while shutdown::sleep(Duration::from_secs(interval)).await.is_ok() {
+        let tasks = match current_tasks() {
+            Ok(tasks) => tasks,
+            Err(e) => {
+                tracing::warn!("Failed to read task metadata: {}", e);
+                continue;
+            }
+        };
+        // Sending fails only when no client is connected
+        for (id, line) in &tasks {
+            if known.get(id) != Some(line) {
+                let _ = changes.send(sse_frame("task", line));
+            }
+        }
+        for id in known.keys().filter(|id| !tasks.contains_key(*id)) {
+            let removed = serde_json::json!({ "task_id": id });
+            let _ = changes.send(sse_frame("removed", &removed.to_string()));
+        }
+        known = tasks;
     }
+}
+
+/// Every task's `dashboard --stream` line, by task ID.
+fn current_tasks() -> anyhow::Result<HashMap<String, String>> {
+    let tasks = dispatch::metadata_store().list()?;
+    Ok(tasks
+        .iter()
+        .map(|t| (t.task_id.to_string(), t.to_jsonl_line()))
+        .collect())
+}
+
+fn snapshot() -> anyhow::Result<String> {
+    let tasks = dispatch::metadata_store().list()?;
+    Ok(serde_json::to_string(&Dashboard::new(&tasks))?)
+}
+
+fn sse_frame(event: &str, data: &str) -> String {
+    format!("event: {}\ndata: {}\n\n", event, data)
+}
+
+/// Answer one client. `cors` is the CORS header line to send, if any.
+async fn handle_client(
+    mut stream: TcpStream,
+    mut changes: broadcast::Receiver<String>,
+    cors: &str,
+) -> anyhow::Result<()> {
+    let path = match read_request(&mut stream).await? {
+        Some(path) => path,
+        None => {
+            let status = "405 Method Not Allowed";
+            return respond(&mut stream, status, "text/plain", cors, "GET only\n").await;
+        }
+    };
+
+    match path.as_str() {
+        "/events" => {
+            let headers = format!(
+                "HTTP/1.1 200 OK\r\n\
+                 Content-Type: text/event-stream\r\n\
+                 Cache-Control: no-cache\r\n\
+                 {}Connection: keep-alive\r\n\r\n",
+                cors
+            );
+            stream.write_all(headers.as_bytes()).await?;
+            stream.write_all(sse_frame("snapshot", &snapshot()?).as_bytes()).await?;
+            loop {
+                let frame = match tokio::time::timeout(KEEP_ALIVE, changes.recv()).await {
+                    Ok(Ok(frame)) => frame,
+                    // Fell too far behind: start over from a fresh snapshot
+                    Ok(Err(RecvError::Lagged(_))) => sse_frame("snapshot", &snapshot()?),
+                    Ok(Err(RecvError::Closed)) => break,
+                    Err(_) => ": keep-alive\n\n".to_string(),
+                };
+                stream.write_all(frame.as_bytes()).await?;
+            }
+            Ok(())
+        }
+        "/dashboard" => {
+            respond(&mut stream, "200 OK", "application/json", cors, &snapshot()?).await
+        }
+        _ => respond(&mut stream, "404 Not Found", "text/plain", cors, "Not found\n").await,
+    }
+}
+
+/// Read a request's headers, returning the path of a GET (without its query
+/// string) or `None` for any other method.
+async fn read_request(stream: &mut TcpStream) -> anyhow::Result<Option<String>> {
+    let mut request = Vec::new();
+    let mut chunk = [0u8; 1024];
+    while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+        if request.len() > MAX_REQUEST_BYTES {
+            anyhow::bail!("request headers too large");
+        }
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            anyhow::bail!("connection closed mid-request");
+        }
+        request.extend_from_slice(&chunk[..read]);
+    }
+
+    let request = String::from_utf8_lossy(&request);
+    let mut request_line = request.lines().next().unwrap_or_default().split_whitespace();
+    match (request_line.next(), request_line.next()) {
+        (Some("GET"), Some(target)) => Ok(target.split('?').next().map(String::from)),
+        _ => Ok(None),
+    }
+}
 
+async fn respond(
+    stream: &mut TcpStream,
+    status: &str,
+    content_type: &str,
+    cors: &str,
+    body: &str,
+) -> anyhow::Result<()> {
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\n\
+         {}Connection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        cors,
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
     Ok(())
 }
@@ -0,0 +1,101 @@
+use crate::{dispatch, shutdown};
+use executor_core::config::Config;
+use executor_core::git::{self, FileStat};
+use executor_core::log_cache;
+
+/// Widest `+`/`-` bar printed by `--stat`.
+const STAT_BAR_WIDTH: usize = 50;
+
+pub async fn run(
+    config: &Config,
+    task_id_str: &str,
+    files_only: bool,
+    stat: bool,
+) -> anyhow::Result<()> {
+    let task_id = dispatch::resolve_task(task_id_str)?;
+    let meta = dispatch::metadata_store().load(&task_id)?;
+
+    // Finished branch tasks were captured locally; anything else is diffed live
+    let (files, diff) = match log_cache::read_cached_diff(&task_id) {
+        Some(diff) if meta.git_branch.is_some() => (meta.changed_files, diff),
+        _ => {
+            let executor = dispatch::create_executor(config, &meta.executor_name)?;
+            let cancel = shutdown::token();
+            if meta.git_branch.is_some() {
+                let changes = executor.changes(&task_id, cancel).await?;
+                (changes.files, changes.diff)
+            } else {
+                // Without a task branch, diff against the workspace's own HEAD
+                let (code, output) = executor
+                    .exec_output(&task_id, "git diff HEAD", cancel)
+                    .await?;
+                if code != 0 {
+                    anyhow::bail!(
+                        "git diff failed in task {} (exit {}): {}",
+                        task_id,
+                        code,
+                        output.trim()
+                    );
+                }
+                let files = git::diff_stat(&output)
+                    .into_iter()
+                    .map(|stat| stat.path)
+                    .collect();
+                (files, output)
+            }
+        }
+    };
+
+    if files_only {
+        for file in &files {
+            println!("{}", file);
+        }
+    } else if stat {
+        print_stat(&git::diff_stat(&diff));
+    } else {
+        print!("{}", diff);
+    }
+
+    Ok(())
+}
+
+fn print_stat(stats: &[FileStat]) {
+    let path_width = stats.iter().map(|s| s.path.len()).max().unwrap_or(0);
+    let max_changes = stats
+        .iter()
+        .map(|s| s.insertions + s.deletions)
+        .max()
+        .unwrap_or(0);
+    let count_width = max_changes.to_string().len();
+
+    for s in stats {
+        let changes = s.insertions + s.deletions;
+        let (plus, minus) = if max_changes > STAT_BAR_WIDTH {
+            let scale = |n: usize| (n * STAT_BAR_WIDTH).div_ceil(max_changes);
+            (scale(s.insertions), scale(s.deletions))
+        } else {
+            (s.insertions, s.deletions)
+        };
+        println!(
+            " {:<pw$} | {:>cw$} {}{}",
+            s.path,
+            changes,
+            "+".repeat(plus),
+            "-".repeat(minus),
+            pw = path_width,
+            cw = count_width
+        );
+    }
+
+    let insertions: usize = stats.iter().map(|s| s.insertions).sum();
+    let deletions: usize = stats.iter().map(|s| s.deletions).sum();
+    println!(
+        " {} file{} changed, {} insertion{}(+), {} deletion{}(-)",
+        stats.len(),
+        if stats.len() == 1 { "" } else { "s" },
+        insertions,
+        if insertions == 1 { "" } else { "s" },
+        deletions,
+        if deletions == 1 { "" } else { "s" }
+    );
+}
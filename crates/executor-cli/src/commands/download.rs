@@ -0,0 +1,40 @@
+use crate::{dispatch, shutdown};
+use anyhow::Context;
+use executor_core::config::Config;
+use executor_core::executor::FileRoot;
+use std::path::{Path, PathBuf};
+
+pub async fn run(
+    config: &Config,
+    task_id_str: &str,
+    path: &str,
+    dest: Option<&str>,
+    task_dir: bool,
+) -> anyhow::Result<()> {
+    let task_id = dispatch::resolve_task(task_id_str)?;
+    let meta = dispatch::metadata_store().load(&task_id)?;
+    let executor = dispatch::create_executor(config, &meta.executor_name)?;
+
+    // Like cp: keep the remote file name unless a file name was given
+    let file_name = Path::new(path)
+        .file_name()
+        .with_context(|| format!("{} does not name a file", path))?;
+    let dest = match dest {
+        None => PathBuf::from(file_name),
+        Some(dest) if Path::new(dest).is_dir() => Path::new(dest).join(file_name),
+        Some(dest) => PathBuf::from(dest),
+    };
+
+    let root = if task_dir {
+        FileRoot::TaskDir
+    } else {
+        FileRoot::Workspace
+    };
+    executor
+        .download(&task_id, path, root, &dest, shutdown::token())
+        .await?;
+
+    let size = std::fs::metadata(&dest).map(|m| m.len()).unwrap_or(0);
+    println!("Downloaded {} to {} ({} bytes)", path, dest.display(), size);
+    Ok(())
+}
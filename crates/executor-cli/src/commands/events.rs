@@ -0,0 +1,53 @@
+use crate::{dispatch, shutdown};
+use executor_core::events::{global_events_path, task_events_path};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::time::Duration;
+
+/// How often `--follow` checks the stream for new events.
+const FOLLOW_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Print the last `lines` events (raw JSONL), then optionally keep tailing.
+pub async fn run(task_id: Option<String>, lines: usize, follow: bool) -> anyhow::Result<()> {
+    let path = match task_id {
+        Some(id) => task_events_path(&dispatch::resolve_task_or_id(&id)?),
+        None => global_events_path(),
+    };
+
+    let existing = std::fs::read_to_string(&path).unwrap_or_default();
+    let all: Vec<&str> = existing.lines().collect();
+    for line in &all[all.len().saturating_sub(lines)..] {
+        println!("{}", line);
+    }
+
+    if !follow {
+        return Ok(());
+    }
+
+    // Tail from where the initial read stopped, until Ctrl-C
+    let mut offset = existing.len() as u64;
+    let mut pending = String::new();
+    while shutdown::sleep(FOLLOW_INTERVAL).await.is_ok() {
+        offset += read_new(&path, offset, &mut pending)?;
+        while let Some(end) = pending.find('\n') {
+            println!("{}", &pending[..end]);
+            pending.drain(..=end);
+        }
+    }
+
+    Ok(())
+}
+
+/// Append bytes written after `offset` to `buf`; returns how many were read.
+fn read_new(path: &Path, offset: u64, buf: &mut String) -> std::io::Result<u64> {
+    let mut file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e),
+    };
+    file.seek(SeekFrom::Start(offset))?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes)?;
+    buf.push_str(&String::from_utf8_lossy(&bytes));
+    Ok(bytes.len() as u64)
+}
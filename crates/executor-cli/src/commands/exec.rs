@@ -0,0 +1,35 @@
+use crate::errors::Exit;
+use crate::{dispatch, shutdown};
+use executor_core::config::Config;
+
+pub async fn run(config: &Config, task_id_str: &str, command: &[String]) -> anyhow::Result<()> {
+    let task_id = dispatch::resolve_task(task_id_str)?;
+    let meta = dispatch::metadata_store().load(&task_id)?;
+    let executor = dispatch::create_executor(config, &meta.executor_name)?;
+
+    // A single argument is taken as a shell line; several are quoted as argv
+    let command = match command {
+        [line] => line.clone(),
+        args => args
+            .iter()
+            .map(|a| shell_quote(a))
+            .collect::<Vec<_>>()
+            .join(" "),
+    };
+
+    match executor.exec(&task_id, &command, shutdown::token()).await? {
+        0 => Ok(()),
+        code => Err(Exit { code, error: None }.into()),
+    }
+}
+
+fn shell_quote(s: &str) -> String {
+    if !s.is_empty()
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./=:,+@%".contains(c))
+    {
+        s.to_string()
+    } else {
+        format!("'{}'", s.replace('\'', "'\\''"))
+    }
+}
@@ -1,35 +1,62 @@
+use crate::output::OutputFormat;
+use chrono::{DateTime, Utc};
 use executor_core::config::Config;
+use executor_core::provision;
+use serde::Serialize;
 
-pub async fn run(config: &Config, json: bool) -> anyhow::Result<()> {
-    if config.executors.is_empty() {
+/// An executor as `--output json|yaml|jsonl` lists it.
+#[derive(Serialize)]
+struct ExecutorEntry<'a> {
+    name: &'a str,
+    #[serde(rename = "type")]
+    executor_type: String,
+    host: Option<&'a str>,
+    labels: &'a [String],
+    /// Whether the last `provision` found the host ready; `None` if never run.
+    ready: Option<bool>,
+    provisioned_at: Option<DateTime<Utc>>,
+}
+
+pub async fn run(config: &Config, format: OutputFormat) -> anyhow::Result<()> {
+    if config.executors.is_empty() && format.is_table() {
         println!("No executors configured.");
         println!("Run `openclaw-agent config --init` to create a sample config.");
         return Ok(());
     }
 
-    if json {
-        let entries: Vec<serde_json::Value> = config
+    if !format.is_table() {
+        let entries: Vec<ExecutorEntry> = config
             .executors
             .iter()
             .map(|e| {
-                serde_json::json!({
-                    "name": e.name,
-                    "type": e.executor_type.to_string(),
-                    "host": e.host,
-                    "labels": e.labels,
-                })
+                let provisioned = provision::load(e);
+                ExecutorEntry {
+                    name: &e.name,
+                    executor_type: e.executor_type.to_string(),
+                    host: e.host.as_deref(),
+                    labels: &e.labels,
+                    ready: provisioned.as_ref().map(|p| p.report.is_ready()),
+                    provisioned_at: provisioned.map(|p| p.provisioned_at),
+                }
             })
             .collect();
-        println!("{}", serde_json::to_string_pretty(&entries)?);
+        format.print_list(&entries)?;
     } else {
-        println!("{:<15} {:<12} {:<20} {}", "NAME", "TYPE", "HOST", "LABELS");
-        println!("{}", "-".repeat(60));
+        println!("{:<15} {:<12} {:<20} {:<10} LABELS", "NAME", "TYPE", "HOST", "READY");
+        println!("{}", "-".repeat(71));
         for e in &config.executors {
+            // As of the last `provision`
+            let ready = match provision::load(e) {
+                Some(p) if p.report.is_ready() => p.provisioned_at.format("%Y-%m-%d").to_string(),
+                Some(_) => "no".to_string(),
+                None => "-".to_string(),
+            };
             println!(
-                "{:<15} {:<12} {:<20} {}",
+                "{:<15} {:<12} {:<20} {:<10} {}",
                 e.name,
                 e.executor_type,
                 e.host.as_deref().unwrap_or("-"),
+                ready,
                 e.labels.join(", "),
             );
         }
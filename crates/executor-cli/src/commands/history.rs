@@ -0,0 +1,88 @@
+use crate::dispatch;
+use crate::output::OutputFormat;
+use executor_core::stats::{format_duration, parse_window, HistoryReport};
+use executor_core::store::MetadataQuery;
+
+pub async fn run(
+    since: &str,
+    executor_filter: Option<String>,
+    failures: usize,
+    format: OutputFormat,
+) -> anyhow::Result<()> {
+    let window = parse_window(since).map_err(anyhow::Error::msg)?;
+    let query = MetadataQuery {
+        executor: executor_filter,
+        ..Default::default()
+    };
+    let tasks = dispatch::metadata_store().query(&query)?;
+    let report = HistoryReport::build(&tasks, chrono::Utc::now() - window, failures);
+
+    if !format.is_table() {
+        return format.print(&report);
+    }
+
+    if report.total == 0 {
+        println!("No tasks finished in the last {}.", since);
+        return Ok(());
+    }
+
+    println!("Finished tasks in the last {}: {}", since, report.total);
+    for (status, count) in &report.by_status {
+        println!("  {:<10} {}", status, count);
+    }
+
+    println!();
+    println!(
+        "{:<15} {:>6} {:>10} {:>10} {:>10}",
+        "EXECUTOR", "TASKS", "P50", "P90", "P99"
+    );
+    println!("{}", "-".repeat(55));
+    for d in &report.durations {
+        println!(
+            "{:<15} {:>6} {:>10} {:>10} {:>10}",
+            d.executor,
+            d.tasks,
+            format_duration(d.p50_secs),
+            format_duration(d.p90_secs),
+            format_duration(d.p99_secs),
+        );
+    }
+
+    println!();
+    println!(
+        "{:<15} {:>6} {:>8} {:>8}",
+        "TYPE", "TASKS", "FAILED", "RATE"
+    );
+    println!("{}", "-".repeat(40));
+    for f in &report.failure_rates {
+        println!(
+            "{:<15} {:>6} {:>8} {:>7.1}%",
+            f.task_type,
+            f.tasks,
+            f.failed,
+            f.rate * 100.0
+        );
+    }
+
+    if !report.recent_failures.is_empty() {
+        println!();
+        println!("Recent failures:");
+        for f in &report.recent_failures {
+            let when = f
+                .finished_at
+                .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_else(|| "-".into());
+            println!("  {}  {}  {:<12} {}", when, f.task_id, f.executor, f.error);
+        }
+    }
+
+    if !report.notes.is_empty() {
+        println!();
+        println!("Notes:");
+        for n in &report.notes {
+            println!("  {}  {}", n.task_id, n.annotation.line());
+        }
+    }
+
+    Ok(())
+}
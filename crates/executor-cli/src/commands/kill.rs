@@ -1,38 +1,108 @@
-use crate::dispatch;
-use executor_core::completion;
+use crate::{dispatch, finish, shutdown};
 use executor_core::config::Config;
-use executor_core::metadata::{metadata_dir, TaskMetadata};
-use executor_core::task::TaskId;
+use executor_core::metadata::TaskMetadata;
+use executor_core::stats::{format_duration, parse_window};
+use executor_core::store::MetadataQuery;
+use executor_core::task::{TaskId, TaskStatus};
+use executor_core::ExecutorError;
+use std::io::{BufRead, IsTerminal, Write};
 
 pub async fn run(config: &Config, task_id_str: &str) -> anyhow::Result<()> {
-    let task_id = TaskId::from_string(task_id_str.to_string());
-    let meta = load_local_meta(&task_id)?;
-    let executor = dispatch::create_executor(config, &meta.executor_name)?;
-
-    executor.kill(&task_id).await?;
+    let task_id = dispatch::resolve_task(task_id_str)?;
+    kill(config, &task_id).await?;
     println!("Task {} killed.", task_id);
+    Ok(())
+}
 
-    // Write completion record for the killed task
-    let updated_meta = load_local_meta(&task_id)?;
-    if updated_meta.status.is_terminal() {
-        if let Ok(true) = completion::write_completion_record(&updated_meta) {
-            if let Some(ref webhook_url) = config.defaults.webhook_url {
-                if let Err(e) = completion::post_webhook(&updated_meta, webhook_url).await {
-                    eprintln!("Warning: webhook POST failed: {}", e);
+/// Kill every running or starting task, narrowed to one executor and/or to
+/// tasks started more than `older_than` (e.g. `2h`) ago. The tasks are listed
+/// and confirmed first unless `yes`. A failed kill doesn't stop the others.
+pub async fn run_bulk(
+    config: &Config,
+    executor_filter: Option<String>,
+    older_than: Option<&str>,
+    yes: bool,
+) -> anyhow::Result<()> {
+    let now = chrono::Utc::now();
+    let cutoff = match older_than {
+        Some(window) => Some(now - parse_window(window).map_err(anyhow::Error::msg)?),
+        None => None,
+    };
+    let query = MetadataQuery {
+        executor: executor_filter,
+        ..Default::default()
+    };
+    let tasks: Vec<TaskMetadata> = dispatch::metadata_store()
+        .query(&query)?
+        .into_iter()
+        .filter(|t| matches!(t.status, TaskStatus::Running | TaskStatus::Starting))
+        .filter(|t| cutoff.is_none_or(|cutoff| t.started_at <= cutoff))
+        .collect();
+
+    if tasks.is_empty() {
+        println!("No running tasks match.");
+        return Ok(());
+    }
+
+    println!("{:<38} {:<15} {:<10} {:<10}", "TASK ID", "EXECUTOR", "STATUS", "ELAPSED");
+    println!("{}", "-".repeat(75));
+    for task in &tasks {
+        println!(
+            "{:<38} {:<15} {:<10} {:<10}",
+            task.task_id.to_string(),
+            task.executor_name,
+            task.status.to_string(),
+            format_duration((now - task.started_at).num_seconds()),
+        );
+    }
+    if !yes && !confirm(tasks.len())? {
+        println!("Aborted.");
+        return Ok(());
+    }
+
+    let mut failures = 0;
+    for task in &tasks {
+        match kill(config, &task.task_id).await {
+            Ok(()) => println!("Task {} killed.", task.task_id),
+            Err(e) => {
+                // Ctrl-C stops the remaining kills too
+                if let Some(ExecutorError::Cancelled) = e.downcast_ref::<ExecutorError>() {
+                    return Err(e);
                 }
+                failures += 1;
+                eprintln!("Failed to kill {}: {}", task.task_id, e);
             }
         }
     }
 
+    if failures > 0 {
+        anyhow::bail!("{} of {} task(s) could not be killed", failures, tasks.len());
+    }
+    Ok(())
+}
+
+/// Kill one task and write its completion record.
+async fn kill(config: &Config, task_id: &TaskId) -> anyhow::Result<()> {
+    let meta = dispatch::metadata_store().load(task_id)?;
+    let executor = dispatch::create_executor(config, &meta.executor_name)?;
+
+    executor.kill(task_id, shutdown::token()).await?;
+
+    // Write completion record for the killed task
+    let updated_meta = dispatch::metadata_store().load(task_id)?;
+    finish::handle_finished(config, &updated_meta, false).await;
+
     Ok(())
 }
 
-fn load_local_meta(task_id: &TaskId) -> anyhow::Result<TaskMetadata> {
-    let dir = metadata_dir();
-    let path = dir.join(format!("{}.meta.json", task_id));
-    if path.exists() {
-        Ok(TaskMetadata::read_from_file(&path)?)
-    } else {
-        anyhow::bail!("No local metadata for task {}", task_id)
+/// Ask on the terminal before killing `count` tasks.
+fn confirm(count: usize) -> anyhow::Result<bool> {
+    if !std::io::stdin().is_terminal() {
+        anyhow::bail!("Not killing {} task(s) without confirmation; pass --yes", count);
     }
+    print!("Kill {} task(s)? [y/N] ", count);
+    std::io::stdout().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
 }
@@ -1,56 +1,164 @@
-use executor_core::metadata::list_all_metadata;
+use crate::dispatch;
+use crate::output::OutputFormat;
+use executor_core::metadata::TaskMetadata;
+use executor_core::stats::{format_duration, parse_window};
+use executor_core::store::MetadataQuery;
 use executor_core::task::TaskStatus;
+use std::cmp::Reverse;
+use std::ops::Range;
 
-pub async fn run(
-    json: bool,
-    jsonl: bool,
-    status_filter: Option<String>,
-    executor_filter: Option<String>,
-) -> anyhow::Result<()> {
-    let mut tasks = list_all_metadata()?;
+/// Order of `list` output (`--sort`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum SortKey {
+    /// Newest first
+    #[default]
+    Started,
+    /// Longest running (or longest run) first
+    Duration,
+    /// Starting, running, then completed, failed, killed and unknown
+    Status,
+    /// By executor name
+    Executor,
+}
 
-    // Apply filters
-    if let Some(ref status_str) = status_filter {
-        let target = parse_status(status_str);
-        tasks.retain(|t| t.status == target);
-    }
-    if let Some(ref exec_name) = executor_filter {
-        tasks.retain(|t| t.executor_name == *exec_name);
-    }
+pub struct ListArgs {
+    pub format: OutputFormat,
+    pub status: Option<String>,
+    pub executor: Option<String>,
+    pub group: Option<String>,
+    pub tag: Option<String>,
+    /// Look-back window on start time, e.g. `24h`.
+    pub since: Option<String>,
+    pub sort: SortKey,
+    pub reverse: bool,
+    pub limit: Option<usize>,
+    /// Show the tasks listed before this one.
+    pub before: Option<String>,
+    /// Show the tasks listed after this one.
+    pub after: Option<String>,
+}
 
-    if jsonl {
-        for task in &tasks {
-            println!("{}", task.to_jsonl_line());
+pub async fn run(args: ListArgs) -> anyhow::Result<()> {
+    let started_since = match args.since {
+        Some(ref window) => {
+            Some(chrono::Utc::now() - parse_window(window).map_err(anyhow::Error::msg)?)
         }
-    } else if json {
-        let json_tasks: Vec<_> = tasks.iter().map(|t| t.to_dashboard_json()).collect();
-        println!("{}", serde_json::to_string_pretty(&json_tasks)?);
+        None => None,
+    };
+    let query = MetadataQuery {
+        status: args.status.as_deref().map(parse_status),
+        executor: args.executor.clone(),
+        group_id: args.group.clone(),
+        tag: args.tag.clone(),
+        started_since,
+        ..Default::default()
+    };
+    let mut tasks = dispatch::metadata_store().query(&query)?;
+    sort_tasks(&mut tasks, args.sort);
+    if args.reverse {
+        tasks.reverse();
+    }
+    let page = page(&tasks, &args)?;
+    let (earlier, later) = (page.start, tasks.len() - page.end);
+    let tasks = &tasks[page];
+
+    if !args.format.is_table() {
+        let rows: Vec<_> = tasks.iter().map(|t| t.to_dashboard()).collect();
+        args.format.print_list(&rows)?;
     } else {
         if tasks.is_empty() {
             println!("No tasks found.");
             return Ok(());
         }
         println!(
-            "{:<4} {:<38} {:<12} {:<12} {:<10} {:<8}",
-            "", "TASK ID", "EXECUTOR", "TYPE", "STATUS", "PID"
+            "{:<4} {:<38} {:<20} {:<12} {:<12} {:<10} {:<8} {:<8} {:<8}",
+            "", "TASK ID", "NAME", "EXECUTOR", "TYPE", "STATUS", "PID", "ELAPSED", "PROGRESS"
         );
-        println!("{}", "-".repeat(88));
-        for task in &tasks {
+        println!("{}", "-".repeat(127));
+        for task in tasks {
             println!(
-                "{:<4} {:<38} {:<12} {:<12} {:<10} {:<8}",
+                "{:<4} {:<38} {:<20} {:<12} {:<12} {:<10} {:<8} {:<8} {:<8}",
                 task.task_icon(),
                 task.task_id,
+                task.name.as_deref().unwrap_or("-"),
                 task.executor_name,
                 task.executor_type,
                 task.status,
                 task.pid.map(|p| p.to_string()).unwrap_or_else(|| "-".into()),
+                format_duration(task.elapsed().num_seconds()),
+                task.progress_ratio()
+                    .map(|ratio| format!("{:.0}%", ratio * 100.0))
+                    .unwrap_or_else(|| "-".into()),
             );
         }
+        if earlier > 0 || later > 0 {
+            println!();
+        }
+        if earlier > 0 {
+            println!("{} earlier: --before {}", earlier, tasks[0].task_id);
+        }
+        if later > 0 {
+            println!("{} more: --after {}", later, tasks[tasks.len() - 1].task_id);
+        }
     }
 
     Ok(())
 }
 
+/// The part of the sorted `tasks` that `--after`, `--before` and `--limit`
+/// select. `--limit` keeps the tasks nearest the cursor: the first ones, or
+/// with only `--before`, the last ones.
+fn page(tasks: &[TaskMetadata], args: &ListArgs) -> anyhow::Result<Range<usize>> {
+    let mut start = 0;
+    let mut end = tasks.len();
+    if let Some(ref after) = args.after {
+        start = position(tasks, after)? + 1;
+    }
+    if let Some(ref before) = args.before {
+        end = position(tasks, before)?.max(start);
+    }
+    if let Some(limit) = args.limit {
+        if args.before.is_some() && args.after.is_none() {
+            start = end.saturating_sub(limit);
+        } else {
+            end = end.min(start + limit);
+        }
+    }
+    Ok(start..end)
+}
+
+/// Index in the listing of the task `reference` points at.
+fn position(tasks: &[TaskMetadata], reference: &str) -> anyhow::Result<usize> {
+    let task_id = dispatch::resolve_task(reference)?;
+    tasks
+        .iter()
+        .position(|t| t.task_id == task_id)
+        .ok_or_else(|| anyhow::anyhow!("Task {} is not in this listing", task_id))
+}
+
+/// Sort by `key`, newest first among equals.
+fn sort_tasks(tasks: &mut [TaskMetadata], key: SortKey) {
+    tasks.sort_by_key(|t| Reverse(t.started_at));
+    match key {
+        SortKey::Started => {}
+        SortKey::Duration => tasks.sort_by_key(|t| Reverse(t.elapsed())),
+        SortKey::Status => tasks.sort_by_key(|t| status_rank(t.status)),
+        SortKey::Executor => tasks.sort_by(|a, b| a.executor_name.cmp(&b.executor_name)),
+    }
+}
+
+/// Position of a status in the task lifecycle.
+fn status_rank(status: TaskStatus) -> u8 {
+    match status {
+        TaskStatus::Starting => 0,
+        TaskStatus::Running => 1,
+        TaskStatus::Completed => 2,
+        TaskStatus::Failed => 3,
+        TaskStatus::Killed => 4,
+        TaskStatus::Unknown => 5,
+    }
+}
+
 fn parse_status(s: &str) -> TaskStatus {
     match s.to_lowercase().as_str() {
         "starting" => TaskStatus::Starting,
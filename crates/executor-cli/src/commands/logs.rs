@@ -1,48 +1,334 @@
-use crate::dispatch;
+use crate::{dispatch, finish, shutdown};
 use executor_core::config::Config;
-use executor_core::metadata::{metadata_dir, TaskMetadata};
-use executor_core::task::TaskId;
+use executor_core::error::ExecutorError;
+use executor_core::log_filter::LogFilter;
+use executor_core::log_timestamps;
+use executor_core::metadata::TaskMetadata;
+use executor_core::task::{TaskId, TaskStatus};
+use executor_core::{ansi, log_archive, log_cache, Executor};
+use std::borrow::Cow;
+use std::io::IsTerminal;
+use std::time::Duration;
+
+/// Log lines fetched for filtering here, from backends that can't filter
+/// where the log lives.
+const FILTER_SCAN_LINES: usize = 100_000;
+
+/// Lines fetched per task each round of `logs --all --follow`. A task logging
+/// more than this between rounds has the rest skipped.
+const FOLLOW_WINDOW: usize = 200;
+
+/// Colors cycled through for `logs --all` prefixes on a terminal.
+const PREFIX_COLORS: [&str; 6] = ["36", "33", "32", "35", "34", "31"];
+
+/// How lines are shown: the `--timestamps`, `--no-color` and `--raw` flags.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LogView {
+    pub timestamps: bool,
+    pub no_color: bool,
+    pub raw: bool,
+}
+
+impl LogView {
+    /// Escapes garble anything but a terminal; --raw leaves lines untouched.
+    fn strip_ansi(&self) -> bool {
+        !self.raw && (self.no_color || !std::io::stdout().is_terminal())
+    }
+
+    fn printer(&self, config: &Config, meta: &TaskMetadata) -> Printer {
+        // Stamped lines are shown without their timestamps unless asked for
+        Printer {
+            timestamps: !self.raw
+                && !self.timestamps
+                && config
+                    .find_executor(&meta.executor_name)
+                    .is_some_and(|e| e.log_timestamps),
+            ansi: self.strip_ansi(),
+        }
+    }
+}
+
+/// What's taken out of each line before printing.
+struct Printer {
+    timestamps: bool,
+    ansi: bool,
+}
+
+impl Printer {
+    fn render<'a>(&self, line: &'a str) -> Cow<'a, str> {
+        let line = if self.timestamps {
+            log_timestamps::strip(line)
+        } else {
+            line
+        };
+        if self.ansi {
+            ansi::strip(line)
+        } else {
+            Cow::Borrowed(line)
+        }
+    }
+
+    fn print(&self, lines: &[String]) {
+        for line in lines {
+            println!("{}", self.render(line));
+        }
+    }
+}
 
 pub async fn run(
     config: &Config,
     task_id_str: &str,
     lines: usize,
     follow: Option<u64>,
+    filter: LogFilter,
+    view: LogView,
 ) -> anyhow::Result<()> {
-    let task_id = TaskId::from_string(task_id_str.to_string());
-    let meta = load_local_meta(&task_id)?;
-    let executor = dispatch::create_executor(config, &meta.executor_name)?;
+    filter.validate().map_err(anyhow::Error::msg)?;
+    let task_id = dispatch::resolve_task_or_id(task_id_str)?;
+    let meta = match dispatch::metadata_store().load(&task_id) {
+        Ok(meta) => meta,
+        Err(e) => {
+            // A cleaned-up task leaves only its archived log behind
+            let archived = log_archive::read_archived_log(&task_id).ok_or(e)?;
+            let printer = Printer {
+                timestamps: false,
+                ansi: view.strip_ansi(),
+            };
+            printer.print(&filter.apply(archived, lines));
+            return Ok(());
+        }
+    };
+    let printer = view.printer(config, &meta);
+    let cached = log_cache::read_cached_log(&task_id);
+
+    // A finished task's log was mirrored locally when it finished
+    if meta.status.is_terminal() && follow.is_none() {
+        if let Some(cached) = cached {
+            printer.print(&filter.apply(cached, lines));
+            return Ok(());
+        }
+    }
+
+    let executor = match dispatch::create_executor(config, &meta.executor_name) {
+        Ok(executor) => executor,
+        Err(e) => {
+            // Imported tasks may come from executors this machine doesn't know
+            printer.print(&filter.apply(cached.ok_or(e)?, lines));
+            return Ok(());
+        }
+    };
 
     match follow {
         Some(interval) => {
-            // Poll mode
+            // Poll mode, until Ctrl-C
             loop {
-                let log_lines = executor.logs(&task_id, lines).await?;
+                let log_lines = match fetch(executor.as_ref(), &task_id, lines, &filter).await {
+                    Err(ExecutorError::Cancelled) => break,
+                    result => result?,
+                };
                 // Clear screen and print
                 print!("\x1B[2J\x1B[H");
-                for line in &log_lines {
-                    println!("{}", line);
+                printer.print(&log_lines);
+                if shutdown::sleep(Duration::from_secs(interval)).await.is_err() {
+                    break;
                 }
-                tokio::time::sleep(std::time::Duration::from_secs(interval)).await;
             }
         }
-        None => {
-            let log_lines = executor.logs(&task_id, lines).await?;
-            for line in &log_lines {
-                println!("{}", line);
+        None => match fetch(executor.as_ref(), &task_id, lines, &filter).await {
+            Ok(log_lines) => printer.print(&log_lines),
+            Err(ExecutorError::Cancelled) => return Err(ExecutorError::Cancelled.into()),
+            // The backend's copy may be gone (cleaned up, host offline)
+            Err(e) => printer.print(&filter.apply(cached.ok_or(e)?, lines)),
+        },
+    }
+
+    Ok(())
+}
+
+/// Interleave the logs of every running task, docker-compose style: each line
+/// prefixed with the task's short ID and executor. Once, or every `follow`
+/// seconds until every task has finished (or Ctrl-C), picking up tasks started
+/// meanwhile.
+pub async fn run_all(
+    config: &Config,
+    lines: usize,
+    follow: Option<u64>,
+    filter: LogFilter,
+    view: LogView,
+) -> anyhow::Result<()> {
+    filter.validate().map_err(anyhow::Error::msg)?;
+    let color = !view.raw && !view.no_color && std::io::stdout().is_terminal();
+    let mut followed: Vec<Followed> = Vec::new();
+    let mut added = 0;
+
+    loop {
+        for meta in active_tasks()? {
+            if followed.iter().any(|t| t.meta.task_id == meta.task_id) {
+                continue;
+            }
+            let executor = match dispatch::create_executor(config, &meta.executor_name) {
+                Ok(executor) => executor,
+                Err(e) => {
+                    eprintln!("Skipping {}: {}", meta.task_id, e);
+                    continue;
+                }
+            };
+            let short_id = meta.task_id.0.get(..8).unwrap_or(&meta.task_id.0);
+            let task = meta.name.as_deref().unwrap_or(short_id);
+            followed.push(Followed {
+                label: format!("{} {}", task, meta.executor_name),
+                color: PREFIX_COLORS[added % PREFIX_COLORS.len()],
+                printer: view.printer(config, &meta),
+                meta,
+                executor,
+                seen: None,
+                log_size: None,
+            });
+            added += 1;
+        }
+        if followed.is_empty() {
+            if added == 0 {
+                println!("No running tasks.");
+            } else {
+                println!("All tasks finished.");
+            }
+            break;
+        }
+
+        let width = followed.iter().map(|t| t.label.len()).max().unwrap_or_default();
+        for task in &mut followed {
+            if follow.is_some() {
+                task.refresh(config).await?;
+            }
+            let new = match task.new_lines(lines, &filter).await {
+                Ok(new) => new,
+                Err(ExecutorError::Cancelled) => return Ok(()),
+                Err(e) => {
+                    tracing::debug!("Fetching log of {} failed: {}", task.meta.task_id, e);
+                    Vec::new()
+                }
+            };
+            let prefix = if color {
+                format!("\x1b[{}m{:<width$} |\x1b[0m", task.color, task.label)
+            } else {
+                format!("{:<width$} |", task.label)
+            };
+            for line in &new {
+                println!("{} {}", prefix, task.printer.render(line));
+            }
+            if task.meta.status.is_terminal() {
+                println!("{} --- {}", prefix, task.meta.status);
             }
         }
+        followed.retain(|t| !t.meta.status.is_terminal());
+
+        let Some(interval) = follow else {
+            break;
+        };
+        if shutdown::sleep(Duration::from_secs(interval)).await.is_err() {
+            break;
+        }
     }
 
     Ok(())
 }
 
-fn load_local_meta(task_id: &TaskId) -> anyhow::Result<TaskMetadata> {
-    let dir = metadata_dir();
-    let path = dir.join(format!("{}.meta.json", task_id));
-    if path.exists() {
-        Ok(TaskMetadata::read_from_file(&path)?)
-    } else {
-        anyhow::bail!("No local metadata for task {}", task_id)
+/// Tasks `logs --all` follows.
+fn active_tasks() -> anyhow::Result<Vec<TaskMetadata>> {
+    let mut tasks: Vec<TaskMetadata> = dispatch::metadata_store()
+        .list()?
+        .into_iter()
+        .filter(|t| matches!(t.status, TaskStatus::Running | TaskStatus::Starting))
+        .collect();
+    tasks.sort_by_key(|t| t.started_at);
+    Ok(tasks)
+}
+
+/// A task followed by `logs --all`.
+struct Followed {
+    meta: TaskMetadata,
+    executor: Box<dyn Executor>,
+    label: String,
+    color: &'static str,
+    printer: Printer,
+    /// Lines of the previous fetch, `None` before the first.
+    seen: Option<Vec<String>>,
+    /// Log size at the previous fetch, to skip fetching an unchanged log.
+    log_size: Option<u64>,
+}
+
+impl Followed {
+    /// Check the task's status, with its completion side effects once done.
+    async fn refresh(&mut self, config: &Config) -> Result<(), ExecutorError> {
+        match self.executor.status(&self.meta.task_id, shutdown::token()).await {
+            Ok(meta) => self.meta = finish::handle_finished(config, &meta, false).await,
+            Err(ExecutorError::Cancelled) => return Err(ExecutorError::Cancelled),
+            Err(e) => tracing::debug!("Checking {} failed: {}", self.meta.task_id, e),
+        }
+        Ok(())
+    }
+
+    /// Lines logged since the previous fetch: the last `lines` the first time.
+    async fn new_lines(
+        &mut self,
+        lines: usize,
+        filter: &LogFilter,
+    ) -> Result<Vec<String>, ExecutorError> {
+        let size = self.executor.log_size(&self.meta.task_id, shutdown::token()).await;
+        if let Err(ExecutorError::Cancelled) = size {
+            return Err(ExecutorError::Cancelled);
+        }
+        let size = size.ok();
+        if self.seen.is_some() && size.is_some() && size == self.log_size {
+            return Ok(Vec::new());
+        }
+
+        let window = match self.seen {
+            Some(_) => FOLLOW_WINDOW,
+            None => lines,
+        };
+        let current = fetch(self.executor.as_ref(), &self.meta.task_id, window, filter).await?;
+        self.log_size = size;
+        let new = match self.seen {
+            Some(ref seen) => current[overlap(seen, &current)..].to_vec(),
+            None => current.clone(),
+        };
+        self.seen = Some(current);
+        Ok(new)
+    }
+}
+
+/// How many lines `current` starts with that end `seen`, both being tails of
+/// the same growing log. The longest match wins, so a repeated line may be
+/// taken for one already printed rather than printed twice.
+fn overlap(seen: &[String], current: &[String]) -> usize {
+    (0..=seen.len().min(current.len()))
+        .rev()
+        .find(|&n| seen[seen.len() - n..] == current[..n])
+        .unwrap_or_default()
+}
+
+/// The last `lines` lines passing `filter`, filtered by the backend where it
+/// can and here otherwise.
+async fn fetch(
+    executor: &dyn Executor,
+    task_id: &TaskId,
+    lines: usize,
+    filter: &LogFilter,
+) -> Result<Vec<String>, ExecutorError> {
+    if filter.is_empty() {
+        return executor.logs(task_id, lines, shutdown::token()).await;
+    }
+    match executor
+        .filtered_logs(task_id, lines, filter, shutdown::token())
+        .await
+    {
+        Err(ExecutorError::Unsupported(_)) => {
+            let log = executor
+                .logs(task_id, FILTER_SCAN_LINES, shutdown::token())
+                .await?;
+            Ok(filter.apply(log, lines))
+        }
+        result => result,
     }
 }
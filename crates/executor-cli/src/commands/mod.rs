@@ -1,10 +1,31 @@
+pub mod agent;
+pub mod annotate;
+pub mod archive;
+pub mod audit;
+pub mod batch;
 pub mod cleanup;
+pub mod completions;
 pub mod config;
+pub mod daemon;
 pub mod dashboard;
+pub mod diff;
+pub mod download;
+pub mod events;
+pub mod exec;
 pub mod executors;
+pub mod history;
 pub mod kill;
 pub mod list;
 pub mod logs;
+pub mod pipeline;
+pub mod provision;
+pub mod reconcile;
 pub mod run;
+pub mod schema;
+pub mod search;
 pub mod start;
 pub mod status;
+pub mod sync;
+pub mod top;
+pub mod upload;
+pub mod verify;
@@ -0,0 +1,140 @@
+use crate::{dispatch, finish, shutdown};
+use executor_core::config::Config;
+use executor_core::error::ExecutorError;
+use executor_core::pipeline::Pipeline;
+use executor_core::task::{TaskId, TaskStatus};
+use std::collections::{BTreeMap, HashMap};
+
+/// State of a single pipeline task while the pipeline runs.
+enum Progress {
+    Running(TaskId),
+    Finished(TaskId, TaskStatus),
+    Skipped,
+}
+
+/// Run a pipeline file: launch every task whose parents have all completed
+/// successfully, poll running tasks, and repeat until nothing is left.
+/// Tasks downstream of a failure are skipped.
+pub async fn run(config: &Config, file: &str) -> anyhow::Result<()> {
+    let pipeline = Pipeline::load_from(std::path::Path::new(file))?;
+    let order = pipeline.execution_order()?;
+
+    let mut progress: HashMap<String, Progress> = HashMap::new();
+
+    loop {
+        // Launch (or skip) every task whose dependencies are settled.
+        for name in &order {
+            if progress.contains_key(name) {
+                continue;
+            }
+            let Some(task) = pipeline.find_task(name) else {
+                continue;
+            };
+
+            let mut parent_ids = Vec::new();
+            let mut settled = true;
+            let mut blocked = false;
+            for dep in &task.after {
+                match progress.get(dep) {
+                    Some(Progress::Finished(id, TaskStatus::Completed)) => {
+                        parent_ids.push(id.clone())
+                    }
+                    Some(Progress::Finished(..)) | Some(Progress::Skipped) => blocked = true,
+                    _ => settled = false,
+                }
+            }
+
+            if blocked {
+                println!("Skipping '{}': a dependency did not complete successfully", name);
+                progress.insert(name.clone(), Progress::Skipped);
+                continue;
+            }
+            if !settled {
+                continue;
+            }
+
+            let executor = dispatch::create_executor(config, &task.spec.executor)?;
+            let mut request = task.spec.to_request(parent_ids)?;
+            dispatch::apply_defaults(config, &task.spec.executor, &mut request);
+            let meta = executor.start(request, shutdown::token()).await?;
+            println!(
+                "{} Started '{}' as {} on {}",
+                meta.task_icon(),
+                name,
+                meta.task_id,
+                meta.executor_name
+            );
+            progress.insert(name.clone(), Progress::Running(meta.task_id));
+        }
+
+        let running: Vec<(String, TaskId)> = progress
+            .iter()
+            .filter_map(|(name, p)| match p {
+                Progress::Running(id) => Some((name.clone(), id.clone())),
+                _ => None,
+            })
+            .collect();
+
+        if running.is_empty() {
+            break;
+        }
+
+        // Poll as often as the most frequently polled executor asks
+        let interval = running
+            .iter()
+            .filter_map(|(name, _)| pipeline.find_task(name))
+            .map(|task| dispatch::heartbeat_interval(config, &task.spec.executor))
+            .min()
+            .unwrap_or_default();
+        shutdown::sleep(interval).await?;
+
+        // One status check per executor, covering all of its running tasks
+        let mut by_executor: BTreeMap<&str, Vec<(String, TaskId)>> = BTreeMap::new();
+        for (name, task_id) in running {
+            let executor_name = pipeline
+                .find_task(&name)
+                .map(|t| t.spec.executor.as_str())
+                .unwrap_or_default();
+            by_executor.entry(executor_name).or_default().push((name, task_id));
+        }
+        for (executor_name, tasks) in by_executor {
+            let executor = dispatch::create_executor(config, executor_name)?;
+            let task_ids: Vec<TaskId> = tasks.iter().map(|(_, id)| id.clone()).collect();
+            let checked = executor.status_many(&task_ids, shutdown::token()).await?;
+            for (name, task_id) in tasks {
+                let meta = checked
+                    .iter()
+                    .find(|meta| meta.task_id == task_id)
+                    .ok_or_else(|| ExecutorError::TaskNotFound(task_id.to_string()))?;
+                if meta.status.is_terminal() {
+                    finish::handle_finished(config, meta, false).await;
+                    println!("   '{}' finished: {}", name, meta.status);
+                    progress.insert(name, Progress::Finished(task_id, meta.status));
+                }
+            }
+        }
+    }
+
+    println!();
+    println!("{:<20} {:<38} {:<10}", "NAME", "TASK ID", "STATUS");
+    println!("{}", "-".repeat(70));
+    let mut failed = false;
+    for name in &order {
+        match progress.get(name) {
+            Some(Progress::Finished(id, status)) => {
+                failed |= *status != TaskStatus::Completed;
+                println!("{:<20} {:<38} {:<10}", name, id, status);
+            }
+            _ => {
+                failed = true;
+                println!("{:<20} {:<38} {:<10}", name, "-", "skipped");
+            }
+        }
+    }
+
+    if failed {
+        anyhow::bail!("Pipeline did not complete successfully");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,70 @@
+use crate::{dispatch, shutdown};
+use executor_core::config::{Config, ExecutorType};
+use executor_core::provision::{self, ProvisionReport};
+
+/// Install what tasks need on an executor's host (or with `check`, only look)
+/// and record whether it is ready.
+pub async fn run(config: &Config, executor_name: &str, check: bool) -> anyhow::Result<()> {
+    let exec_config = config
+        .find_executor(executor_name)
+        .ok_or_else(|| dispatch::executor_not_found(config, executor_name))?;
+    if exec_config.executor_type == ExecutorType::Container {
+        anyhow::bail!(
+            "{} is a container executor; git, node and claude come from its image",
+            executor_name
+        );
+    }
+    let executor = dispatch::create_executor(config, executor_name)?;
+
+    let verb = if check { "Checking" } else { "Provisioning" };
+    eprintln!("{} {}...", verb, executor_name);
+    let report = executor.provision(!check, shutdown::token()).await?;
+    if let Err(e) = provision::record(exec_config, &report) {
+        eprintln!("Warning: failed to record the result: {}", e);
+    }
+    print_report(&report);
+
+    // Tasks run plain `claude` unless a claude_path says otherwise
+    let resolved = dispatch::resolve(config, executor_name);
+    if let Some(path) = &report.claude_path {
+        if !report.claude_on_path && resolved.claude_path.value == "claude" {
+            println!(
+                "Note: claude is not on {}'s PATH; add `claude_path: {}` to it in the config.",
+                executor_name, path
+            );
+        }
+    }
+
+    if report.is_ready() {
+        println!("{} is ready.", executor_name);
+        Ok(())
+    } else {
+        anyhow::bail!(
+            "{} is not ready ({} problem(s))",
+            executor_name,
+            report.problems.len()
+        )
+    }
+}
+
+fn print_report(report: &ProvisionReport) {
+    let installed = |name: &str| {
+        if report.installed.iter().any(|i| i == name) {
+            " (installed)"
+        } else {
+            ""
+        }
+    };
+    let claude = match (&report.claude, &report.claude_path) {
+        (Some(version), Some(path)) => format!("{} at {}", version, path),
+        (None, Some(path)) => path.clone(),
+        _ => "-".to_string(),
+    };
+    println!("  git     {}{}", report.git.as_deref().unwrap_or("-"), installed("git"));
+    println!("  node    {}{}", report.node.as_deref().unwrap_or("-"), installed("node"));
+    println!("  claude  {}{}", claude, installed("claude"));
+    println!("  tasks   {}", report.task_root);
+    for problem in &report.problems {
+        println!("  Problem: {}", problem);
+    }
+}
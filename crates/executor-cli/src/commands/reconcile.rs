@@ -0,0 +1,67 @@
+use crate::{dispatch, finish, shutdown};
+use executor_core::config::Config;
+use executor_core::error::ExecutorError;
+use executor_core::metadata::TaskMetadata;
+use executor_core::store::MetadataQuery;
+use executor_core::task::{TaskId, TaskStatus};
+use std::collections::BTreeMap;
+
+/// Check every task still recorded as starting with its executor, which
+/// promotes it to running if it launched or marks it failed if it never did.
+pub async fn run(config: &Config) -> anyhow::Result<()> {
+    let checked = reconcile(config).await?;
+    if checked.is_empty() {
+        println!("No tasks stuck in starting.");
+        return Ok(());
+    }
+
+    println!("{:<38} {:<15} {:<10}", "TASK ID", "EXECUTOR", "STATUS");
+    println!("{}", "-".repeat(65));
+    for meta in &checked {
+        println!(
+            "{:<38} {:<15} {:<10}",
+            meta.task_id.to_string(),
+            meta.executor_name,
+            meta.status.to_string()
+        );
+    }
+    Ok(())
+}
+
+/// Status-check every starting task, returning them as updated. Each
+/// executor's tasks are checked together (one round-trip per SSH host), and
+/// running ones aren't sampled on top, which would cost a round-trip per
+/// task. Tasks whose executor can't be reached are reported and left as
+/// they are.
+pub async fn reconcile(config: &Config) -> anyhow::Result<Vec<TaskMetadata>> {
+    let query = MetadataQuery {
+        status: Some(TaskStatus::Starting),
+        ..Default::default()
+    };
+    let mut by_executor: BTreeMap<String, Vec<TaskId>> = BTreeMap::new();
+    for meta in dispatch::metadata_store().query(&query)? {
+        by_executor.entry(meta.executor_name).or_default().push(meta.task_id);
+    }
+    let mut checked = Vec::new();
+
+    for (executor_name, task_ids) in by_executor {
+        let executor = match dispatch::create_executor(config, &executor_name) {
+            Ok(executor) => executor,
+            Err(e) => {
+                eprintln!("Skipping {} task(s) on {}: {}", task_ids.len(), executor_name, e);
+                continue;
+            }
+        };
+        match executor.status_many(&task_ids, shutdown::token()).await {
+            Ok(updated) => {
+                for meta in updated {
+                    checked.push(finish::handle_finished(config, &meta, false).await);
+                }
+            }
+            Err(ExecutorError::Cancelled) => return Err(ExecutorError::Cancelled.into()),
+            Err(e) => eprintln!("Could not check tasks on {}: {}", executor_name, e),
+        }
+    }
+
+    Ok(checked)
+}
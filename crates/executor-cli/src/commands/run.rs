@@ -1,24 +1,59 @@
-use crate::dispatch;
+use crate::output::OutputFormat;
+use crate::{deps, dispatch, preflight, shutdown};
 use executor_core::config::Config;
-use executor_core::task::{TaskPayload, TaskRequest};
-
-pub async fn run(
-    config: &Config,
-    executor_name: &str,
-    cmd: String,
-    workspace: Option<String>,
-) -> anyhow::Result<()> {
+use executor_core::task::{TaskId, TaskPayload, TaskRequest};
+
+pub struct RunArgs {
+    pub executor: String,
+    pub name: Option<String>,
+    pub cmd: String,
+    pub workspace: Option<String>,
+    pub nix_shell: Option<String>,
+    pub after: Vec<String>,
+    pub force: bool,
+    /// Print only the task ID.
+    pub quiet: bool,
+    pub output: OutputFormat,
+}
+
+pub async fn run(config: &Config, args: RunArgs) -> anyhow::Result<()> {
+    let executor_name = args.executor.as_str();
     let executor = dispatch::create_executor(config, executor_name)?;
 
+    if let Some(ref name) = args.name {
+        dispatch::check_task_name(name)?;
+    }
+    let parent_ids = args
+        .after
+        .iter()
+        .map(|reference| dispatch::resolve_task(reference))
+        .collect::<Result<Vec<TaskId>, _>>()?;
+    deps::wait_for_parents(config, &parent_ids).await?;
+
+    let payload = TaskPayload::ShellCommand { command: args.cmd };
     let request = TaskRequest {
-        payload: TaskPayload::ShellCommand { command: cmd },
-        workspace,
+        name: args.name,
+        parent_ids,
+        nix_shell: args.nix_shell,
+        ..TaskRequest::new(payload, args.workspace)
     };
 
-    let meta = executor.start(request).await?;
+    preflight::check(config, executor_name, executor.as_ref(), &request, args.force).await?;
+
+    let meta = executor.start(request, shutdown::token()).await?;
 
+    if args.quiet {
+        println!("{}", meta.task_id);
+        return Ok(());
+    }
+    if !args.output.is_table() {
+        return args.output.print(&meta.to_dashboard());
+    }
     println!("{} Command started:", meta.task_icon());
     println!("  ID:       {}", meta.task_id);
+    if let Some(ref name) = meta.name {
+        println!("  Name:     {}", name);
+    }
     println!("  Type:     {}", meta.task_type);
     println!("  Executor: {} ({})", meta.executor_name, meta.executor_type);
     println!("  PID:      {}", meta.pid.map(|p| p.to_string()).unwrap_or_else(|| "N/A".into()));
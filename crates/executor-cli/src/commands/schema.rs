@@ -0,0 +1,10 @@
+use executor_core::schema;
+
+/// Print the JSON Schema for `kind` (one of [`schema::KINDS`]).
+pub fn run(kind: &str) -> anyhow::Result<()> {
+    let schema = schema::schema(kind).ok_or_else(|| {
+        anyhow::anyhow!("Unknown schema '{}' (expected one of: {})", kind, schema::KINDS.join(", "))
+    })?;
+    println!("{}", serde_json::to_string_pretty(&schema)?);
+    Ok(())
+}
@@ -0,0 +1,37 @@
+use crate::dispatch;
+use crate::output::OutputFormat;
+use executor_core::search::search;
+use executor_core::store::MetadataQuery;
+
+pub async fn run(
+    term: &str,
+    executor_filter: Option<String>,
+    limit: usize,
+    format: OutputFormat,
+) -> anyhow::Result<()> {
+    let query = MetadataQuery {
+        executor: executor_filter,
+        ..Default::default()
+    };
+    let tasks = dispatch::metadata_store().query(&query)?;
+    let mut hits = search(&tasks, term);
+    hits.truncate(limit);
+
+    if !format.is_table() {
+        return format.print_list(&hits);
+    }
+
+    if hits.is_empty() {
+        println!("No matches for \"{}\".", term);
+        return Ok(());
+    }
+
+    for hit in &hits {
+        println!(
+            "{}  {:<12} {:<7} {}",
+            hit.task_id, hit.executor, hit.field, hit.snippet
+        );
+    }
+
+    Ok(())
+}
@@ -1,34 +1,334 @@
-use crate::dispatch;
+use crate::output::OutputFormat;
+use crate::{deps, dispatch, plugins, preflight, shutdown};
+use anyhow::Context;
 use executor_core::config::Config;
-use executor_core::task::{TaskPayload, TaskRequest};
+use executor_core::executor::StartPlan;
+use executor_core::spec::PromptFile;
+use executor_core::task::{RepoSpec, ServiceSpec, TaskId, TaskPayload, TaskRequest};
+use std::io::{IsTerminal, Read};
+use tokio::task::JoinSet;
 
-pub async fn run(
-    config: &Config,
-    executor_name: &str,
-    prompt: String,
-    workspace: Option<String>,
-    max_turns: Option<u32>,
-    allowed_tools: Vec<String>,
-) -> anyhow::Result<()> {
-    let executor = dispatch::create_executor(config, executor_name)?;
-
-    let request = TaskRequest {
-        payload: TaskPayload::ClaudeCode {
-            prompt,
-            max_turns,
-            allowed_tools,
-        },
-        workspace,
+pub struct StartArgs {
+    pub executor: Option<String>,
+    pub name: Option<String>,
+    pub prompt: String,
+    pub workspace: Option<String>,
+    pub repo: Option<RepoSpec>,
+    pub git_branch: bool,
+    pub max_turns: Option<u32>,
+    pub allowed_tools: Vec<String>,
+    pub after: Vec<String>,
+    pub labels: Vec<String>,
+    pub fan_out: bool,
+    pub webhook: Option<String>,
+    pub tags: Vec<String>,
+    pub heartbeat_interval: Option<u64>,
+    pub nix_shell: Option<String>,
+    pub force: bool,
+    /// Print only the task ID(s).
+    pub quiet: bool,
+    /// Print what would be launched instead of starting the task.
+    pub dry_run: bool,
+    pub output: OutputFormat,
+    /// Sidecar containers, set only from a prompt file's front-matter.
+    pub services: Vec<ServiceSpec>,
+}
+
+/// The `--prompt` argument, read from stdin when it is `-` or missing and
+/// stdin isn't a terminal.
+pub fn read_prompt(arg: Option<String>) -> anyhow::Result<String> {
+    let stdin = std::io::stdin();
+    match arg {
+        Some(prompt) if prompt != "-" => return Ok(prompt),
+        None if stdin.is_terminal() => {
+            anyhow::bail!("--prompt is required (or pipe the prompt in on stdin)")
+        }
+        _ => {}
+    }
+
+    let mut prompt = String::new();
+    stdin.lock().read_to_string(&mut prompt)?;
+    let prompt = prompt.trim_end().to_string();
+    if prompt.trim().is_empty() {
+        anyhow::bail!("Prompt read from stdin is empty");
+    }
+    Ok(prompt)
+}
+
+/// Take the prompt from a prompt file, and any options the command line left
+/// unset from its front-matter.
+pub fn apply_prompt_file(args: &mut StartArgs, path: &str) -> anyhow::Result<()> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read prompt file {}", path))?;
+    let file = PromptFile::parse(&text).with_context(|| format!("Invalid prompt file {}", path))?;
+    let options = file.options;
+
+    args.prompt = file.prompt;
+    if args.executor.is_none() && !args.fan_out {
+        args.executor = options.executor;
+    }
+    if args.workspace.is_none() && args.repo.is_none() {
+        args.workspace = options.workspace;
+    }
+    args.max_turns = args.max_turns.or(options.max_turns);
+    if args.allowed_tools.is_empty() {
+        args.allowed_tools = options.allowed_tools;
+    }
+    for tag in options.tags {
+        if !args.tags.contains(&tag) {
+            args.tags.push(tag);
+        }
+    }
+    args.services = options.services;
+    args.nix_shell = args.nix_shell.take().or(options.nix_shell);
+    Ok(())
+}
+
+pub async fn run(config: &Config, args: StartArgs) -> anyhow::Result<()> {
+    let payload = TaskPayload::ClaudeCode {
+        prompt: args.prompt,
+        max_turns: args.max_turns,
+        allowed_tools: args.allowed_tools,
+    };
+    let parent_ids = args
+        .after
+        .iter()
+        .map(|reference| dispatch::resolve_task(reference))
+        .collect::<Result<Vec<TaskId>, _>>()?;
+
+    if args.fan_out {
+        deps::wait_for_parents(config, &parent_ids).await?;
+        let launch = Launch {
+            payload,
+            workspace: args.workspace,
+            repo: args.repo,
+            git_branch: args.git_branch,
+            parent_ids,
+            webhook_url: args.webhook,
+            tags: args.tags,
+            heartbeat_interval_secs: args.heartbeat_interval,
+            force: args.force,
+            services: args.services,
+            nix_shell: args.nix_shell,
+            name: args.name,
+        };
+        return fan_out(config, &args.labels, launch, args.quiet, args.output).await;
+    }
+
+    if let Some(ref name) = args.name {
+        dispatch::check_task_name(name)?;
+    }
+    let mut request = TaskRequest {
+        name: args.name,
+        parent_ids,
+        webhook_url: args.webhook,
+        repo: args.repo,
+        git_branch: args.git_branch,
+        tags: args.tags,
+        heartbeat_interval_secs: args.heartbeat_interval,
+        services: args.services,
+        nix_shell: args.nix_shell,
+        ..TaskRequest::new(payload, args.workspace)
     };
 
-    let meta = executor.start(request).await?;
+    let executor_name = match args.executor {
+        Some(name) => name,
+        None => plugins::host(config)
+            .select_executor(&request, &config.executors)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "--executor is required unless --fan-out is given or a plugin selects one"
+                )
+            })?,
+    };
+    let executor = dispatch::create_executor(config, &executor_name)?;
+
+    if args.dry_run {
+        dispatch::apply_defaults(config, &executor_name, &mut request);
+        return print_plan(executor.plan_start(request)?);
+    }
+
+    deps::wait_for_parents(config, &request.parent_ids).await?;
+
+    dispatch::apply_defaults(config, &executor_name, &mut request);
+    preflight::check(
+        config,
+        &executor_name,
+        executor.as_ref(),
+        &request,
+        args.force,
+    )
+    .await?;
+
+    let meta = executor.start(request, shutdown::token()).await?;
 
+    if args.quiet {
+        println!("{}", meta.task_id);
+        return Ok(());
+    }
+    if !args.output.is_table() {
+        return args.output.print(&meta.to_dashboard());
+    }
     println!("{} Task started:", meta.task_icon());
     println!("  ID:       {}", meta.task_id);
+    if let Some(ref name) = meta.name {
+        println!("  Name:     {}", name);
+    }
     println!("  Type:     {}", meta.task_type);
-    println!("  Executor: {} ({})", meta.executor_name, meta.executor_type);
-    println!("  PID:      {}", meta.pid.map(|p| p.to_string()).unwrap_or_else(|| "N/A".into()));
+    println!(
+        "  Executor: {} ({})",
+        meta.executor_name, meta.executor_type
+    );
+    println!(
+        "  PID:      {}",
+        meta.pid
+            .map(|p| p.to_string())
+            .unwrap_or_else(|| "N/A".into())
+    );
     println!("  Status:   {}", meta.status);
+    if let Some(ref commit) = meta.commit {
+        println!("  Commit:   {}", commit);
+    }
+    if let Some(ref branch) = meta.git_branch {
+        println!("  Branch:   {}", branch);
+    }
+
+    Ok(())
+}
+
+/// Print what `start --dry-run` found the executor would do.
+fn print_plan(plan: StartPlan) -> anyhow::Result<()> {
+    let meta = &plan.metadata;
+    println!(
+        "Dry run on {} ({}); nothing was started.",
+        meta.executor_name, meta.executor_type
+    );
+    if !plan.steps.is_empty() {
+        println!();
+        println!("Before launching:");
+        for step in &plan.steps {
+            println!("  {}", step);
+        }
+    }
+    println!();
+    println!("Command:");
+    println!("{}", plan.command);
+    println!();
+    println!("Metadata:");
+    println!("{}", serde_json::to_string_pretty(meta)?);
+    Ok(())
+}
+
+/// What every fanned-out task shares.
+struct Launch {
+    payload: TaskPayload,
+    workspace: Option<String>,
+    repo: Option<RepoSpec>,
+    git_branch: bool,
+    parent_ids: Vec<TaskId>,
+    webhook_url: Option<String>,
+    tags: Vec<String>,
+    heartbeat_interval_secs: Option<u64>,
+    force: bool,
+    services: Vec<ServiceSpec>,
+    nix_shell: Option<String>,
+    /// Each task is named `<name>-<executor>`.
+    name: Option<String>,
+}
+
+/// Launch the same payload on every executor matching all labels, concurrently,
+/// tagging each task with a shared group ID. Executors that fail the pre-flight
+/// check are skipped and count as failures. `quiet` prints just the IDs of the
+/// tasks started; other formats than a table print the started tasks once all
+/// launches are done.
+async fn fan_out(
+    config: &Config,
+    labels: &[String],
+    launch: Launch,
+    quiet: bool,
+    format: OutputFormat,
+) -> anyhow::Result<()> {
+    let targets = config.find_by_labels(labels);
+    if targets.is_empty() {
+        anyhow::bail!("No executors match labels: {}", labels.join(", "));
+    }
+
+    let group_id = uuid::Uuid::new_v4().to_string();
+    let mut launches = JoinSet::new();
+    let mut failures = 0;
+
+    let names: Vec<Option<String>> = targets
+        .iter()
+        .map(|e| launch.name.as_ref().map(|name| format!("{}-{}", name, e.name)))
+        .collect();
+    for name in names.iter().flatten() {
+        dispatch::check_task_name(name)?;
+    }
+
+    for (exec_config, task_name) in targets.into_iter().zip(names) {
+        let executor = dispatch::create_executor(config, &exec_config.name)?;
+        let mut request = TaskRequest {
+            name: task_name,
+            parent_ids: launch.parent_ids.clone(),
+            group_id: Some(group_id.clone()),
+            webhook_url: launch.webhook_url.clone(),
+            repo: launch.repo.clone(),
+            git_branch: launch.git_branch,
+            tags: launch.tags.clone(),
+            heartbeat_interval_secs: launch.heartbeat_interval_secs,
+            services: launch.services.clone(),
+            nix_shell: launch.nix_shell.clone(),
+            ..TaskRequest::new(launch.payload.clone(), launch.workspace.clone())
+        };
+        dispatch::apply_defaults(config, &exec_config.name, &mut request);
+        let checked = preflight::check(
+            config,
+            &exec_config.name,
+            executor.as_ref(),
+            &request,
+            launch.force,
+        )
+        .await;
+        if let Err(e) = checked {
+            failures += 1;
+            eprintln!("{}", e);
+            continue;
+        }
+
+        let name = exec_config.name.clone();
+        launches.spawn(async move { (name, executor.start(request, shutdown::token()).await) });
+    }
+
+    let table = !quiet && format.is_table();
+    let mut started = Vec::new();
+    if table {
+        println!("Group: {}", group_id);
+        println!("{:<15} {:<38} {:<10}", "EXECUTOR", "TASK ID", "STATUS");
+        println!("{}", "-".repeat(65));
+    }
+
+    while let Some(joined) = launches.join_next().await {
+        let (name, result) = joined?;
+        match result {
+            Ok(meta) if quiet => println!("{}", meta.task_id),
+            Ok(meta) if !table => started.push(meta.to_dashboard()),
+            Ok(meta) => println!("{:<15} {:<38} {:<10}", name, meta.task_id, meta.status),
+            Err(e) => {
+                failures += 1;
+                if table {
+                    println!("{:<15} {:<38} {:<10}", name, "-", "error");
+                }
+                eprintln!("Failed to start on {}: {}", name, e);
+            }
+        }
+    }
+
+    if !quiet && !table {
+        format.print_list(&started)?;
+    }
+    if failures > 0 {
+        anyhow::bail!("{} executor(s) failed to start the task", failures);
+    }
 
     Ok(())
 }
@@ -1,65 +1,163 @@
-use crate::dispatch;
-use executor_core::completion;
+use crate::errors::Exit;
+use crate::output::OutputFormat;
+use crate::{dispatch, finish, shutdown};
 use executor_core::config::Config;
 use executor_core::metadata::TaskMetadata;
-use executor_core::task::TaskId;
+use executor_core::stats::format_duration;
+use executor_core::task::TaskStatus;
 
-pub async fn run(config: &Config, task_id_str: &str, json: bool) -> anyhow::Result<()> {
-    let task_id = TaskId::from_string(task_id_str.to_string());
+/// Exit status for `--exit-code` when the status couldn't be checked at all.
+const EXIT_ERROR: i32 = 6;
+
+pub async fn run(
+    config: &Config,
+    task_id_str: &str,
+    format: OutputFormat,
+    exit_code: bool,
+    max_age: Option<chrono::Duration>,
+) -> anyhow::Result<()> {
+    let updated_meta = match check(config, task_id_str, max_age).await {
+        Ok(meta) => meta,
+        Err(e) if exit_code => {
+            return Err(Exit {
+                code: EXIT_ERROR,
+                error: Some(e),
+            }
+            .into())
+        }
+        Err(e) => return Err(e),
+    };
+
+    if format.is_table() {
+        print_status(&updated_meta);
+    } else {
+        format.print(&updated_meta.to_dashboard())?;
+    }
+
+    match status_exit_code(updated_meta.status) {
+        code if exit_code && code != 0 => Err(Exit { code, error: None }.into()),
+        _ => Ok(()),
+    }
+}
+
+/// The task's state: from local metadata if it is no older than `max_age`
+/// (`--cached`), otherwise from its executor.
+async fn check(
+    config: &Config,
+    task_id_str: &str,
+    max_age: Option<chrono::Duration>,
+) -> anyhow::Result<TaskMetadata> {
+    let task_id = dispatch::resolve_task(task_id_str)?;
 
     // Read local metadata to find the executor
-    let meta = load_local_meta(&task_id)?;
+    let meta = dispatch::metadata_store().load(&task_id)?;
+    if max_age.is_some_and(|max_age| meta.is_fresh(max_age)) {
+        return Ok(meta);
+    }
     let executor_name = meta.executor_name.clone();
 
     let executor = dispatch::create_executor(config, &executor_name)?;
-    let updated_meta = executor.status(&task_id).await?;
+    let updated_meta = executor.status(&task_id, shutdown::token()).await?;
 
     // Write completion record if task reached a terminal state
-    if updated_meta.status.is_terminal() {
-        if let Ok(true) = completion::write_completion_record(&updated_meta) {
-            // Fire webhook if configured
-            if let Some(ref webhook_url) = config.defaults.webhook_url {
-                if let Err(e) = completion::post_webhook(&updated_meta, webhook_url).await {
-                    eprintln!("Warning: webhook POST failed: {}", e);
-                }
-            }
-        }
-    }
+    Ok(finish::handle_finished(config, &updated_meta, true).await)
+}
 
-    if json {
-        println!("{}", serde_json::to_string_pretty(&updated_meta.to_dashboard_json())?);
-    } else {
-        print_status(&updated_meta);
+/// The process exit status `--exit-code` reports for a task state.
+fn status_exit_code(status: TaskStatus) -> i32 {
+    match status {
+        TaskStatus::Completed => 0,
+        TaskStatus::Failed => 1,
+        TaskStatus::Running => 2,
+        TaskStatus::Killed => 3,
+        TaskStatus::Starting => 4,
+        TaskStatus::Unknown => 5,
     }
-
-    Ok(())
 }
 
 fn print_status(meta: &TaskMetadata) {
     println!("{}  Task:     {}", meta.task_icon(), meta.task_id);
+    if let Some(ref name) = meta.name {
+        println!("   Name:     {}", name);
+    }
     println!("   Type:     {}", meta.task_type);
     println!("   Executor: {} ({})", meta.executor_name, meta.executor_type);
     println!("   Status:   {}", meta.status);
     println!("   PID:      {}", meta.pid.map(|p| p.to_string()).unwrap_or_else(|| "N/A".into()));
     println!("   Started:  {}", meta.started_at);
     println!("   Updated:  {}", meta.updated_at);
+    if let Some(checked) = meta.checked_at {
+        println!("   Checked:  {}", checked);
+    }
     if let Some(finished) = meta.finished_at {
         println!("   Finished: {}", finished);
     }
+    println!("   Elapsed:  {}", format_duration(meta.elapsed().num_seconds()));
     if let Some(code) = meta.exit_code {
         println!("   Exit:     {}", code);
     }
     if let Some(ref err) = meta.error {
         println!("   Error:    {}", err);
     }
-}
-
-fn load_local_meta(task_id: &TaskId) -> anyhow::Result<TaskMetadata> {
-    let dir = executor_core::metadata::metadata_dir();
-    let path = dir.join(format!("{}.meta.json", task_id));
-    if path.exists() {
-        Ok(TaskMetadata::read_from_file(&path)?)
-    } else {
-        anyhow::bail!("No local metadata for task {}", task_id)
+    if let Some(signal) = meta.killed_by {
+        println!("   Killed:   by {}", signal);
+    }
+    if !meta.parent_ids.is_empty() {
+        let parents: Vec<String> = meta.parent_ids.iter().map(|p| p.to_string()).collect();
+        println!("   After:    {}", parents.join(", "));
+    }
+    if let Some(ref group) = meta.group_id {
+        println!("   Group:    {}", group);
+    }
+    if let Some(ref schedule) = meta.schedule_name {
+        println!("   Schedule: {}", schedule);
+    }
+    if let Some(ref repo) = meta.repo {
+        let branch = repo.branch.as_deref().unwrap_or("default branch");
+        println!("   Repo:     {} ({})", repo.url, branch);
+    }
+    if let Some(ref commit) = meta.commit {
+        println!("   Commit:   {}", commit);
+    }
+    if let Some(ref branch) = meta.git_branch {
+        println!("   Branch:   {}", branch);
+    }
+    if !meta.changed_files.is_empty() {
+        println!("   Changed:  {} file(s)", meta.changed_files.len());
+    }
+    if let Some(ref pr_url) = meta.pr_url {
+        println!("   PR:       {}", pr_url);
+    }
+    if !meta.tags.is_empty() {
+        println!("   Tags:     {}", meta.tags.join(", "));
+    }
+    for (i, annotation) in meta.annotations.iter().enumerate() {
+        let label = if i == 0 { "Notes:" } else { "" };
+        println!("   {:<9} {}", label, annotation.line());
+    }
+    if let Some(ref progress) = meta.progress {
+        println!("   Progress: {}", progress.summary(meta.max_turns));
+    }
+    if let (Some(eta), Some(max_turns)) = (meta.eta(), meta.max_turns) {
+        println!(
+            "   ETA:      ~{} (if it uses all {} turns)",
+            format_duration(eta.num_seconds()),
+            max_turns
+        );
+    }
+    if let Some(ref usage) = meta.resource_usage {
+        println!("   Usage:    {}", usage.summary());
+    }
+    if let Some(ref stats) = meta.host_stats {
+        println!("   Host:     sampled {}", stats.sampled_at);
+        if let Some(load) = stats.load_line() {
+            println!("   Load:     {}", load);
+        }
+        if let Some(memory) = stats.memory_line() {
+            println!("   Memory:   {}", memory);
+        }
+        if let Some(disk) = stats.disk_line() {
+            println!("   Disk:     {}", disk);
+        }
     }
 }
@@ -0,0 +1,112 @@
+use crate::{dispatch, shutdown};
+use executor_core::audit::{AuditAction, AuditEntry};
+use executor_core::config::Config;
+use executor_core::error::ExecutorError;
+use executor_core::store::MetadataQuery;
+use executor_core::task::TaskId;
+use std::collections::HashSet;
+
+/// Reconcile local metadata with what each executor actually holds.
+pub async fn run(
+    config: &Config,
+    executor_filter: Option<String>,
+    dry_run: bool,
+) -> anyhow::Result<()> {
+    let store = dispatch::metadata_store();
+    let targets: Vec<_> = config
+        .executors
+        .iter()
+        .filter(|e| executor_filter.as_ref().is_none_or(|name| e.name == *name))
+        .collect();
+    if targets.is_empty() {
+        anyhow::bail!("No matching executors configured");
+    }
+
+    let (mut adopted, mut vanished, mut orphaned) = (0, 0, 0);
+
+    for exec_config in targets {
+        let executor = dispatch::create_executor(config, &exec_config.name)?;
+        let remote = match executor.list_remote(shutdown::token()).await {
+            Ok(remote) => remote,
+            Err(ExecutorError::Cancelled) => return Err(ExecutorError::Cancelled.into()),
+            Err(e) => {
+                eprintln!("Skipping {}: {}", exec_config.name, e);
+                continue;
+            }
+        };
+        let remote_ids: HashSet<&TaskId> = remote.iter().map(|t| &t.task_id).collect();
+
+        // Remote tasks we have no record of
+        for task in &remote {
+            if store.get(&task.task_id)?.is_some() {
+                continue;
+            }
+            match &task.metadata {
+                // Only a plain file name can be stored, and only under the
+                // ID of the dir it was found in
+                Some(meta) if meta.task_id != task.task_id || !task.task_id.is_file_safe() => {
+                    orphaned += 1;
+                    println!(
+                        "{:<12} {} not adopted: its metadata is for task ID {:?}",
+                        exec_config.name, task.task_id, meta.task_id.0
+                    );
+                }
+                Some(meta) => {
+                    adopted += 1;
+                    println!(
+                        "{:<12} {} adopted ({})",
+                        exec_config.name, task.task_id, meta.task_type
+                    );
+                    if !dry_run {
+                        store.put(meta)?;
+                    }
+                }
+                None => {
+                    orphaned += 1;
+                    println!(
+                        "{:<12} {} has no metadata on either side",
+                        exec_config.name, task.task_id
+                    );
+                }
+            }
+        }
+
+        // Tasks we think are live but whose remote artifacts are gone
+        let query = MetadataQuery {
+            executor: Some(exec_config.name.clone()),
+            ..Default::default()
+        };
+        for mut meta in store.query(&query)? {
+            if meta.status.is_terminal() || remote_ids.contains(&meta.task_id) {
+                continue;
+            }
+            vanished += 1;
+            println!(
+                "{:<12} {} vanished remotely (was {})",
+                exec_config.name, meta.task_id, meta.status
+            );
+            if !dry_run {
+                meta.mark_failed("Remote task directory vanished".into());
+                store.put(&meta)?;
+            }
+        }
+    }
+
+    println!(
+        "\n{}{} adopted, {} marked failed, {} without metadata",
+        if dry_run { "(dry run) " } else { "" },
+        adopted,
+        vanished,
+        orphaned
+    );
+
+    if !dry_run {
+        dispatch::audit(AuditEntry {
+            executor: executor_filter,
+            detail: Some(format!("{} adopted, {} marked failed", adopted, vanished)),
+            ..AuditEntry::new(AuditAction::Sync)
+        });
+    }
+
+    Ok(())
+}
@@ -0,0 +1,185 @@
+use crate::{dispatch, shutdown};
+use chrono::{DateTime, Utc};
+use executor_core::config::Config;
+use executor_core::events::{self, EventKind};
+use executor_core::host::{kib, HostStats};
+use executor_core::metadata::TaskMetadata;
+use executor_core::stats::format_duration;
+use executor_core::task::{TaskId, TaskStatus};
+use executor_core::ExecutorError;
+use std::collections::{BTreeMap, HashMap};
+use std::io::IsTerminal;
+use std::time::Duration;
+
+/// Prompt characters shown per task.
+const PROMPT_CHARS: usize = 40;
+
+/// A task's log size as last seen, to tell how fast the log is growing.
+struct LogSample {
+    bytes: u64,
+    at: DateTime<Utc>,
+}
+
+/// Redraw an overview of running and starting tasks, and the load on the
+/// executors they run on, every `interval` seconds until Ctrl-C (or once).
+/// Task status is read as recorded, not polled: the heartbeat column shows
+/// how long ago something last checked on each task.
+pub async fn run(config: &Config, interval: u64, once: bool) -> anyhow::Result<()> {
+    let interval = interval.max(1);
+    let clear = !once && std::io::stdout().is_terminal();
+    let mut logs: HashMap<TaskId, LogSample> = HashMap::new();
+
+    loop {
+        let screen = render(config, interval, &mut logs).await?;
+        if clear {
+            print!("\x1b[2J\x1b[H");
+        }
+        print!("{}", screen);
+        if once
+            || shutdown::sleep(Duration::from_secs(interval))
+                .await
+                .is_err()
+        {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+async fn render(
+    config: &Config,
+    interval: u64,
+    logs: &mut HashMap<TaskId, LogSample>,
+) -> anyhow::Result<String> {
+    let now = Utc::now();
+    let tasks: Vec<TaskMetadata> = dispatch::metadata_store()
+        .list()?
+        .into_iter()
+        .filter(|t| matches!(t.status, TaskStatus::Running | TaskStatus::Starting))
+        .collect();
+
+    let mut lines = vec![format!(
+        "{} active task(s) at {} (every {}s, Ctrl-C to quit)",
+        tasks.len(),
+        now.format("%Y-%m-%d %H:%M:%S UTC"),
+        interval
+    )];
+    if tasks.is_empty() {
+        lines.push(String::new());
+        lines.push("No running tasks.".to_string());
+        return Ok(lines.join("\n") + "\n");
+    }
+
+    let mut per_executor: BTreeMap<&str, usize> = BTreeMap::new();
+    for task in &tasks {
+        *per_executor.entry(task.executor_name.as_str()).or_default() += 1;
+    }
+
+    lines.push(String::new());
+    lines.push(format!(
+        "{:<15} {:<6} {:<8} {:<12} {:<10}",
+        "EXECUTOR", "TASKS", "LOAD", "MEM FREE", "DISK USED"
+    ));
+    lines.push("-".repeat(55));
+    for (name, count) in &per_executor {
+        let stats = sample_host(config, name).await;
+        let stats = stats.as_ref();
+        lines.push(format!(
+            "{:<15} {:<6} {:<8} {:<12} {:<10}",
+            name,
+            count,
+            stats
+                .and_then(|s| s.load_avg)
+                .map(|[one, _, _]| format!("{:.2}", one))
+                .unwrap_or_else(|| "-".into()),
+            stats
+                .and_then(|s| s.mem_available_kb)
+                .map(kib)
+                .unwrap_or_else(|| "-".into()),
+            stats
+                .and_then(HostStats::disk_used_percent)
+                .map(|p| format!("{}%", p))
+                .unwrap_or_else(|| "-".into()),
+        ));
+    }
+
+    lines.push(String::new());
+    lines.push(format!(
+        "{:<38} {:<15} {:<9} {:<8} {:<10} {:<11} {}",
+        "TASK ID", "EXECUTOR", "STATUS", "ELAPSED", "HEARTBEAT", "LOG", "PROMPT"
+    ));
+    lines.push("-".repeat(100 + PROMPT_CHARS));
+    for task in &tasks {
+        let heartbeat = events::last_event_at(&task.task_id, EventKind::Heartbeat)
+            .map(|at| format!("{} ago", format_duration((now - at).num_seconds())))
+            .unwrap_or_else(|| "never".into());
+        lines.push(format!(
+            "{:<38} {:<15} {:<9} {:<8} {:<10} {:<11} {}",
+            task.task_id.to_string(),
+            task.executor_name,
+            task.status.to_string(),
+            format_duration((now - task.started_at).num_seconds()),
+            heartbeat,
+            log_rate(config, task, logs).await,
+            prompt_line(&task.prompt),
+        ));
+    }
+
+    // Forget tasks that are no longer active
+    logs.retain(|id, _| tasks.iter().any(|t| &t.task_id == id));
+    Ok(lines.join("\n") + "\n")
+}
+
+/// The executor's host resources right now, or `None` if it can't be sampled.
+async fn sample_host(config: &Config, executor_name: &str) -> Option<HostStats> {
+    let executor = dispatch::create_executor(config, executor_name).ok()?;
+    match executor.host_stats(None, shutdown::token()).await {
+        Ok(stats) => stats,
+        Err(ExecutorError::Unsupported(_) | ExecutorError::Cancelled) => None,
+        Err(e) => {
+            tracing::debug!("Sampling {} failed: {}", executor_name, e);
+            None
+        }
+    }
+}
+
+/// How fast the task's log grew since the previous refresh, e.g. `1.2 KiB/s`.
+/// `-` on the first refresh or if the executor can't tell.
+async fn log_rate(
+    config: &Config,
+    task: &TaskMetadata,
+    logs: &mut HashMap<TaskId, LogSample>,
+) -> String {
+    let Ok(executor) = dispatch::create_executor(config, &task.executor_name) else {
+        return "-".into();
+    };
+    let Ok(bytes) = executor.log_size(&task.task_id, shutdown::token()).await else {
+        return "-".into();
+    };
+    let sample = LogSample {
+        bytes,
+        at: Utc::now(),
+    };
+    let rate = logs.get(&task.task_id).and_then(|prev| {
+        let secs = (sample.at - prev.at).num_milliseconds() as f64 / 1000.0;
+        (secs > 0.0).then(|| sample.bytes.saturating_sub(prev.bytes) as f64 / secs)
+    });
+    logs.insert(task.task_id.clone(), sample);
+
+    match rate {
+        Some(rate) if rate < 1024.0 => format!("{:.0} B/s", rate),
+        Some(rate) => format!("{}/s", kib((rate / 1024.0) as u64)),
+        None => "-".into(),
+    }
+}
+
+/// The first line of a prompt, cut to fit the table.
+fn prompt_line(prompt: &str) -> String {
+    let line = prompt.lines().next().unwrap_or_default().trim();
+    if line.chars().count() <= PROMPT_CHARS {
+        return line.to_string();
+    }
+    let cut: String = line.chars().take(PROMPT_CHARS - 3).collect();
+    format!("{}...", cut.trim_end())
+}
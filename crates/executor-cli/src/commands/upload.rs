@@ -0,0 +1,49 @@
+use crate::{dispatch, shutdown};
+use anyhow::Context;
+use executor_core::config::Config;
+use executor_core::executor::FileRoot;
+use std::path::Path;
+
+pub async fn run(
+    config: &Config,
+    task_id_str: &str,
+    source: &str,
+    path: Option<&str>,
+    task_dir: bool,
+) -> anyhow::Result<()> {
+    let task_id = dispatch::resolve_task(task_id_str)?;
+    let meta = dispatch::metadata_store().load(&task_id)?;
+    let executor = dispatch::create_executor(config, &meta.executor_name)?;
+
+    let source = Path::new(source);
+    if !source.is_file() {
+        anyhow::bail!("{} is not a file", source.display());
+    }
+    // Like cp: keep the local file name unless a remote file name was given
+    let file_name = source
+        .file_name()
+        .with_context(|| format!("{} does not name a file", source.display()))?
+        .to_string_lossy();
+    let path = match path {
+        None => file_name.into_owned(),
+        Some(dir) if dir.ends_with('/') => format!("{}{}", dir, file_name),
+        Some(path) => path.to_string(),
+    };
+
+    let root = if task_dir {
+        FileRoot::TaskDir
+    } else {
+        FileRoot::Workspace
+    };
+    executor
+        .upload(&task_id, source, &path, root, shutdown::token())
+        .await?;
+
+    println!(
+        "Uploaded {} to {} on task {}",
+        source.display(),
+        path,
+        task_id
+    );
+    Ok(())
+}
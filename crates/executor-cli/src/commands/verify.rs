@@ -0,0 +1,146 @@
+use crate::{dispatch, shutdown};
+use executor_core::config::Config;
+use executor_core::task::{TaskId, TaskPayload, TaskRequest, TaskStatus};
+use executor_core::{Executor, ExecutorError};
+use std::time::{Duration, Instant};
+
+/// Log lines read back when looking for the task's output.
+const VERIFY_LOG_LINES: usize = 50;
+
+/// Run a trivial shell task on an executor through start, heartbeat, status,
+/// logs and cleanup, reporting each stage, as a quick check that the
+/// executor works. The task has `timeout` to finish. Fails naming the first
+/// stage that did not pass; the task is cleaned up whichever stage that was.
+pub async fn run(config: &Config, executor_name: &str, timeout: Duration) -> anyhow::Result<()> {
+    let executor = dispatch::create_executor(config, executor_name)?;
+    let marker = format!("openclaw-verify-{}", &TaskId::new().to_string()[..8]);
+    eprintln!(
+        "Verifying {} ({})...",
+        executor_name,
+        executor.executor_type()
+    );
+
+    let request = TaskRequest {
+        name: Some(marker.clone()),
+        ..TaskRequest::new(
+            TaskPayload::ShellCommand {
+                command: format!("echo {}", marker),
+            },
+            None,
+        )
+    };
+    let task_id = match executor.start(request, shutdown::token()).await {
+        Ok(meta) => {
+            report("start", Ok(format!("task {}", meta.task_id)));
+            meta.task_id
+        }
+        Err(e) => {
+            report("start", Err(e.to_string()));
+            anyhow::bail!("{} failed verification at start", executor_name);
+        }
+    };
+
+    let failed = check(executor.as_ref(), &task_id, &marker, timeout).await;
+    let cleaned = executor.cleanup(&task_id, shutdown::token()).await;
+    report(
+        "cleanup",
+        cleaned
+            .as_ref()
+            .map(|_| "removed".to_string())
+            .map_err(|e| e.to_string()),
+    );
+
+    match (failed, cleaned) {
+        (Some(stage), _) => anyhow::bail!("{} failed verification at {}", executor_name, stage),
+        (None, Err(_)) => anyhow::bail!("{} failed verification at cleanup", executor_name),
+        (None, Ok(())) => {
+            println!("{} passed verification.", executor_name);
+            Ok(())
+        }
+    }
+}
+
+/// The heartbeat, status and logs stages; the name of the first that
+/// failed, if any did.
+async fn check(
+    executor: &dyn Executor,
+    task_id: &TaskId,
+    marker: &str,
+    timeout: Duration,
+) -> Option<&'static str> {
+    // Heartbeats sample the host; executors that can't are still usable
+    match executor.host_stats(None, shutdown::token()).await {
+        Ok(Some(stats)) => report("heartbeat", Ok(stats.summary())),
+        Ok(None) | Err(ExecutorError::Unsupported(_)) => {
+            report("heartbeat", Ok("skipped (no host stats)".to_string()))
+        }
+        Err(e) => {
+            report("heartbeat", Err(e.to_string()));
+            return Some("heartbeat");
+        }
+    }
+
+    let deadline = Instant::now() + timeout;
+    let meta = loop {
+        match executor.status(task_id, shutdown::token()).await {
+            Ok(meta) if meta.status.is_terminal() => break meta,
+            Ok(meta) if Instant::now() >= deadline => {
+                report(
+                    "status",
+                    Err(format!(
+                        "still {} after {}s",
+                        meta.status,
+                        timeout.as_secs()
+                    )),
+                );
+                return Some("status");
+            }
+            Ok(_) => {}
+            Err(e) => {
+                report("status", Err(e.to_string()));
+                return Some("status");
+            }
+        }
+        if let Err(e) = shutdown::sleep(Duration::from_secs(1)).await {
+            report("status", Err(e.to_string()));
+            return Some("status");
+        }
+    };
+    if meta.status != TaskStatus::Completed {
+        let exit = meta
+            .exit_code
+            .map(|code| format!(" (exit code {})", code))
+            .unwrap_or_default();
+        report("status", Err(format!("{}{}", meta.status, exit)));
+        return Some("status");
+    }
+    report("status", Ok(meta.status.to_string()));
+
+    match executor
+        .logs(task_id, VERIFY_LOG_LINES, shutdown::token())
+        .await
+    {
+        Ok(lines) if lines.iter().any(|line| line.contains(marker)) => {
+            report("logs", Ok("output found".to_string()))
+        }
+        Ok(lines) => {
+            report(
+                "logs",
+                Err(format!("output missing from {} line(s)", lines.len())),
+            );
+            return Some("logs");
+        }
+        Err(e) => {
+            report("logs", Err(e.to_string()));
+            return Some("logs");
+        }
+    }
+    None
+}
+
+fn report(stage: &str, result: Result<String, String>) {
+    match result {
+        Ok(detail) => println!("  {:<10} ok      {}", stage, detail),
+        Err(detail) => println!("  {:<10} FAILED  {}", stage, detail),
+    }
+}
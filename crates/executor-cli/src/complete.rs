@@ -0,0 +1,48 @@
+//! Values the shell completes dynamically: task IDs and names from local
+//! metadata and executor names from the default config, read each time the
+//! shell asks.
+
+use crate::dispatch;
+use clap_complete::engine::CompletionCandidate;
+use executor_core::Config;
+use std::ffi::OsStr;
+
+/// Task IDs and names starting with what's typed, newest first, described by
+/// their status and executor.
+pub fn task_ids(current: &OsStr) -> Vec<CompletionCandidate> {
+    let current = current.to_string_lossy();
+    let Ok(tasks) = dispatch::recent_tasks() else {
+        return Vec::new();
+    };
+    tasks
+        .into_iter()
+        .flat_map(|t| {
+            let help = format!("{} on {}", t.status, t.executor_name);
+            [Some(t.task_id.0), t.name]
+                .into_iter()
+                .flatten()
+                .map(move |value| (value, help.clone()))
+        })
+        .filter(|(value, _)| value.starts_with(&*current))
+        .map(|(value, help)| CompletionCandidate::new(value).help(Some(help.into())))
+        .collect()
+}
+
+/// Executor names starting with what's typed, described by their type.
+/// `--config` and `--profile` aren't seen here, so only the default config is
+/// read.
+pub fn executor_names(current: &OsStr) -> Vec<CompletionCandidate> {
+    let current = current.to_string_lossy();
+    let Ok(config) = Config::load_default() else {
+        return Vec::new();
+    };
+    config
+        .executors
+        .iter()
+        .filter(|e| e.name.starts_with(&*current))
+        .map(|e| {
+            let help = e.executor_type.to_string();
+            CompletionCandidate::new(&e.name).help(Some(help.into()))
+        })
+        .collect()
+}
@@ -0,0 +1,42 @@
+use crate::{dispatch, finish, shutdown};
+use executor_core::config::Config;
+use executor_core::metadata::TaskMetadata;
+use executor_core::task::{TaskId, TaskStatus};
+use std::time::Duration;
+
+/// Block until every parent task reaches a terminal state.
+/// Fails if any parent did not complete successfully.
+pub async fn wait_for_parents(config: &Config, parent_ids: &[TaskId]) -> anyhow::Result<()> {
+    for parent_id in parent_ids {
+        let meta = wait_for_task(config, parent_id).await?;
+        if meta.status != TaskStatus::Completed {
+            anyhow::bail!(
+                "Parent task {} finished with status '{}', not launching",
+                parent_id,
+                meta.status
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Poll a task through its executor, every `heartbeat_interval_secs` (the
+/// task's own, else its executor's), until it reaches a terminal state.
+pub async fn wait_for_task(config: &Config, task_id: &TaskId) -> anyhow::Result<TaskMetadata> {
+    let meta = dispatch::metadata_store().load(task_id)?;
+    let executor = dispatch::create_executor(config, &meta.executor_name)?;
+    let interval = match meta.heartbeat_interval_secs {
+        Some(secs) => Duration::from_secs(secs),
+        None => dispatch::heartbeat_interval(config, &meta.executor_name),
+    };
+
+    loop {
+        let meta = executor.status(task_id, shutdown::token()).await?;
+        if meta.status.is_terminal() {
+            finish::handle_finished(config, &meta, false).await;
+            return Ok(meta);
+        }
+        eprintln!("Waiting for task {} ({})...", task_id, meta.status);
+        shutdown::sleep(interval).await?;
+    }
+}
@@ -1,28 +1,186 @@
-use executor_core::config::{Config, ExecutorConfig, ExecutorType};
+use executor_core::audit::{self, AuditEntry, AuditedExecutor};
+use executor_core::config::{Config, ExecutorConfig};
 use executor_core::error::ExecutorError;
+use executor_core::events::EventingStore;
+use executor_core::hooks::HookedExecutor;
+use executor_core::log_archive;
+use executor_core::metadata::TaskMetadata;
+use executor_core::registry;
+use executor_core::resolve::{Overrides, ResolvedSettings};
+use executor_core::store::{default_store, MetadataStore};
+use executor_core::task::{self, TaskId, TaskPayload, TaskRequest};
+use executor_core::throttle::ThrottledExecutor;
 use executor_core::Executor;
+use std::sync::Arc;
+use std::time::Duration;
 
-/// Create an executor instance from config by name.
+/// The metadata store shared by the CLI and the executors it creates.
+/// Status transitions written through it are emitted as events.
+pub fn metadata_store() -> Arc<dyn MetadataStore> {
+    Arc::new(EventingStore::new(default_store()))
+}
+
+/// Every task on record, newest first, for offering tasks to choose from.
+pub fn recent_tasks() -> Result<Vec<TaskMetadata>, ExecutorError> {
+    let mut tasks = metadata_store().list()?;
+    tasks.sort_by_key(|t| std::cmp::Reverse(t.started_at));
+    Ok(tasks)
+}
+
+/// The task a command-line reference (ID, `--name` or ID prefix) points at.
+pub fn resolve_task(reference: &str) -> Result<TaskId, ExecutorError> {
+    metadata_store().resolve(reference).map_err(|e| match e {
+        ExecutorError::TaskNotFound(reference) => {
+            ExecutorError::TaskNotFound(format!("{}; `list` shows the tasks on record", reference))
+        }
+        e => e,
+    })
+}
+
+/// Like [`resolve_task`], for commands that also work on tasks whose
+/// metadata is gone (cleaned up): an unknown reference is looked up as an ID
+/// prefix among archived logs, and otherwise taken as an ID.
+pub fn resolve_task_or_id(reference: &str) -> Result<TaskId, ExecutorError> {
+    match resolve_task(reference) {
+        Err(ExecutorError::TaskNotFound(_)) => {}
+        result => return result,
+    }
+    let archived = log_archive::matching_task_ids(reference)?;
+    match archived.as_slice() {
+        [task_id] => Ok(task_id.clone()),
+        [] => Ok(TaskId::from_string(reference.to_string())),
+        _ => {
+            let ids: Vec<String> = archived.iter().map(|id| id.to_string()).collect();
+            Err(ExecutorError::AmbiguousTask(format!(
+                "'{}' matches {} archived tasks: {}; use the full task ID",
+                reference,
+                ids.len(),
+                ids.join(", ")
+            )))
+        }
+    }
+}
+
+/// Check a name for a new task is valid and not taken by a task on record.
+pub fn check_task_name(name: &str) -> anyhow::Result<()> {
+    task::validate_name(name).map_err(anyhow::Error::msg)?;
+    if let Some(task) = metadata_store()
+        .list()?
+        .into_iter()
+        .find(|t| t.name.as_deref() == Some(name))
+    {
+        anyhow::bail!(
+            "Task name '{}' is already taken by task {}; pick another, or clean that task up",
+            name,
+            task.task_id
+        );
+    }
+    Ok(())
+}
+
+/// Create an executor instance from config by name. Starts run the `on_start` hooks.
 pub fn create_executor(
     config: &Config,
     executor_name: &str,
 ) -> Result<Box<dyn Executor>, ExecutorError> {
     let exec_config = config
         .find_executor(executor_name)
-        .ok_or_else(|| ExecutorError::ExecutorNotFound(executor_name.to_string()))?;
+        .ok_or_else(|| executor_not_found(config, executor_name))?;
+
+    // Bake the resolved claude path (defaults → executor → env) into the config
+    let resolved = ResolvedSettings::resolve(config, Some(exec_config), &Overrides::default());
+    let mut exec_config = exec_config.clone();
+    exec_config.claude_path = Some(resolved.claude_path.value);
 
-    create_executor_from_config(exec_config.clone())
+    let executor = create_executor_from_config(exec_config)?;
+    Ok(Box::new(HookedExecutor::new(
+        executor,
+        config.hooks.clone(),
+    )))
 }
 
-/// Create an executor from an ExecutorConfig.
+/// The error for an executor name missing from the config, naming the ones
+/// it has.
+pub fn executor_not_found(config: &Config, executor_name: &str) -> ExecutorError {
+    let names: Vec<&str> = config.executors.iter().map(|e| e.name.as_str()).collect();
+    let known = if names.is_empty() {
+        "the config has none; add one with `config --init` or by editing it".to_string()
+    } else {
+        format!("configured: {}", names.join(", "))
+    };
+    ExecutorError::ExecutorNotFound(format!("{} ({})", executor_name, known))
+}
+
+/// Fill request options the caller left unset from the resolved config.
+pub fn apply_defaults(config: &Config, executor_name: &str, request: &mut TaskRequest) {
+    if let TaskPayload::ClaudeCode { max_turns, .. } = &mut request.payload {
+        // Auto PRs are made from the task's branch
+        if config.find_executor(executor_name).is_some_and(|e| e.git.auto_pr) {
+            request.git_branch = true;
+        }
+        if max_turns.is_none() {
+            *max_turns = Some(resolve(config, executor_name).max_turns.value);
+        }
+    }
+}
+
+/// Settings for an executor, resolved without command-line overrides.
+pub fn resolve(config: &Config, executor_name: &str) -> ResolvedSettings {
+    ResolvedSettings::resolve(
+        config,
+        config.find_executor(executor_name),
+        &Overrides::default(),
+    )
+}
+
+/// How long to wait between status polls of a task on this executor.
+pub fn heartbeat_interval(config: &Config, executor_name: &str) -> Duration {
+    Duration::from_secs(resolve(config, executor_name).heartbeat_interval_secs.value)
+}
+
+/// Create an executor from an ExecutorConfig, held to its rate limits.
+/// Mutating calls are audited.
 pub fn create_executor_from_config(
     exec_config: ExecutorConfig,
 ) -> Result<Box<dyn Executor>, ExecutorError> {
-    match exec_config.executor_type {
-        ExecutorType::Ssh => Ok(Box::new(executor_ssh::SshExecutor::new(exec_config))),
-        ExecutorType::Container => Ok(Box::new(
-            executor_container::ContainerExecutor::new(exec_config),
-        )),
-        ExecutorType::Local => Ok(Box::new(executor_local::LocalExecutor::new(exec_config))),
+    let executor = registry::create(exec_config.clone(), metadata_store())?;
+    let executor = ThrottledExecutor::wrap(executor, &exec_config);
+    Ok(Box::new(AuditedExecutor::new(executor)))
+}
+
+/// Register the built-in executor types. Types registered earlier (e.g. by a
+/// binary embedding the CLI) keep their factory.
+pub fn register_builtin_executors() {
+    registry::register_if_absent("ssh", |config, store| {
+        Ok(Box::new(executor_ssh::SshExecutor::with_store(
+            config, store,
+        )))
+    });
+    registry::register_if_absent("container", |config, store| {
+        Ok(Box::new(executor_container::ContainerExecutor::with_store(
+            config, store,
+        )))
+    });
+    registry::register_if_absent("local", |config, store| {
+        Ok(Box::new(executor_local::LocalExecutor::with_store(
+            config, store,
+        )))
+    });
+    registry::register_if_absent("external", |config, store| {
+        Ok(Box::new(executor_external::ExternalExecutor::with_store(
+            config, store,
+        )))
+    });
+    registry::register_if_absent("mock", |config, store| {
+        Ok(Box::new(executor_mock::MockExecutor::with_store(
+            config, store,
+        )))
+    });
+}
+
+/// Record a CLI-level mutation in the audit log. A failed write is reported, never fatal.
+pub fn audit(entry: AuditEntry) {
+    if let Err(e) = audit::record(&entry) {
+        eprintln!("Warning: failed to write audit log: {}", e);
     }
 }
@@ -0,0 +1,92 @@
+use crate::dispatch;
+use clap::ArgMatches;
+use executor_core::ExecutorError;
+use serde::Serialize;
+use std::fmt;
+
+/// A command's outcome that sets the process exit status to `code` instead
+/// of 1, e.g. `status --exit-code` reporting the task's state. `error` is
+/// what went wrong, if anything did; without one the process just exits.
+/// `run` exits only once the command has returned, so its telemetry is
+/// flushed first.
+#[derive(Debug)]
+pub struct Exit {
+    pub code: i32,
+    pub error: Option<anyhow::Error>,
+}
+
+impl fmt::Display for Exit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.error {
+            Some(ref error) => write!(f, "{:#}", error),
+            None => write!(f, "exit status {}", self.code),
+        }
+    }
+}
+
+impl std::error::Error for Exit {}
+
+/// A failed command as `--json-errors` reports it: one JSON object on
+/// stderr.
+#[derive(Serialize)]
+struct ErrorReport {
+    /// The kind of error, e.g. `task_not_found`; `error` if it has none.
+    code: &'static str,
+    message: String,
+    executor: Option<String>,
+    task_id: Option<String>,
+}
+
+/// What a command was acting on, for its error report: the `executor` and
+/// `task_id` arguments of the innermost subcommand. A task reference is
+/// resolved to its full ID, and its executor filled in, when it is known.
+pub struct ErrorContext {
+    executor: Option<String>,
+    task_id: Option<String>,
+}
+
+impl ErrorContext {
+    pub fn from_matches(matches: &ArgMatches) -> Self {
+        let mut matches = matches;
+        while let Some((_, sub)) = matches.subcommand() {
+            matches = sub;
+        }
+        let arg = |id: &str| matches.try_get_one::<String>(id).ok().flatten().cloned();
+        let mut context = ErrorContext {
+            executor: arg("executor"),
+            task_id: arg("task_id"),
+        };
+        if let Some(task_id) = context
+            .task_id
+            .as_deref()
+            .and_then(|reference| dispatch::resolve_task(reference).ok())
+        {
+            if context.executor.is_none() {
+                context.executor = dispatch::metadata_store()
+                    .load(&task_id)
+                    .ok()
+                    .map(|meta| meta.executor_name);
+            }
+            context.task_id = Some(task_id.to_string());
+        }
+        context
+    }
+}
+
+/// Print `error` on stderr as a single-line JSON object.
+pub fn print_json(error: &anyhow::Error, context: ErrorContext) {
+    let code = error
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<ExecutorError>())
+        .map_or("error", ExecutorError::code);
+    let report = ErrorReport {
+        code,
+        message: format!("{:#}", error),
+        executor: context.executor,
+        task_id: context.task_id,
+    };
+    match serde_json::to_string(&report) {
+        Ok(line) => eprintln!("{}", line),
+        Err(_) => eprintln!("Error: {:#}", error),
+    }
+}
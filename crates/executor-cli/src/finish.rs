@@ -0,0 +1,420 @@
+use crate::{dispatch, plugins, shutdown};
+use anyhow::Context;
+use executor_core::audit::{AuditAction, AuditEntry};
+use executor_core::config::{Config, GitConfig};
+use executor_core::events::{self, Event, EventKind};
+use executor_core::executor::FileRoot;
+use executor_core::github::{self, PullRequest};
+use executor_core::hooks::{self, HookEvent};
+use executor_core::host::{self, HostStats};
+use executor_core::metadata::{TaskMetadata, WebhookDelivery};
+use executor_core::resolve::{Overrides, ResolvedSettings};
+use executor_core::task::{TaskId, TaskStatus};
+use executor_core::usage::ResourceUsage;
+use executor_core::{completion, git, log_cache, notify, Executor, ExecutorError};
+
+/// Log lines kept locally for a finished task whose backend can't hand over
+/// its whole log file.
+const CACHED_LOG_LINES: usize = 500;
+
+/// Log lines included in chat notifications.
+const NOTIFY_LOG_LINES: usize = 10;
+
+/// Longest auto PR title (and commit subject).
+const PR_TITLE_CHARS: usize = 72;
+
+/// Called after every status observation. An unfinished task has the time of
+/// the check recorded, and a running one emits a heartbeat event. With
+/// `sample`, a running task also has its host's resources, its own CPU and
+/// memory use and its progress sampled, each a round-trip to its executor:
+/// worth it only where they are shown, so bulk refreshes pass `false`. A
+/// finished task gets its completion side effects: the completion record
+/// and, the first time only, the final progress, the log snapshot, captured
+/// changes, auto PR, webhook and local notifiers. Returns the metadata with
+/// anything recorded along the way.
+pub async fn handle_finished(config: &Config, meta: &TaskMetadata, sample: bool) -> TaskMetadata {
+    if !meta.status.is_terminal() {
+        let meta = &record_check(meta);
+        if meta.status == TaskStatus::Running {
+            let executor = sample
+                .then(|| dispatch::create_executor(config, &meta.executor_name).ok())
+                .flatten();
+            let meta = match executor {
+                Some(executor) => {
+                    let meta = sample_host(executor.as_ref(), meta).await;
+                    let meta = sample_usage(executor.as_ref(), &meta).await;
+                    sample_progress(executor.as_ref(), &meta).await
+                }
+                None => meta.clone(),
+            };
+            events::emit_or_warn(&Event {
+                detail: meta
+                    .host_stats
+                    .as_ref()
+                    .filter(|_| sample)
+                    .map(HostStats::summary),
+                ..Event::new(EventKind::Heartbeat, &meta)
+            });
+            check_heartbeat_timeout(config, &meta).await;
+            return meta;
+        }
+        return meta.clone();
+    }
+
+    if let Ok(true) = completion::write_completion_record(meta) {
+        let meta = &match dispatch::create_executor(config, &meta.executor_name) {
+            Ok(executor) => sample_progress(executor.as_ref(), meta).await,
+            Err(_) => meta.clone(),
+        };
+        cache_log(config, meta).await;
+        let meta = &capture_changes(config, meta).await;
+        let meta = &open_pull_request(config, meta).await;
+
+        // Fire webhook if configured
+        let resolved = ResolvedSettings::resolve(
+            config,
+            config.find_executor(&meta.executor_name),
+            &Overrides {
+                webhook_url: meta.webhook_url.clone(),
+                ..Overrides::default()
+            },
+        );
+        let plugins = plugins::host(config);
+        let webhook_url = resolved
+            .webhook_url
+            .value
+            .filter(|_| plugins.should_notify("webhook", meta));
+        if let Some(ref webhook_url) = webhook_url {
+            let body = completion::webhook_body(meta, &config.webhook)
+                .and_then(|body| plugins.rewrite_payload(meta, body));
+            let delivery = match body {
+                Ok(body) => completion::deliver_webhook(webhook_url, body, &config.webhook).await,
+                Err(e) => WebhookDelivery::failed(webhook_url, e),
+            };
+            let event = if delivery.delivered {
+                Event::new(EventKind::WebhookSent, meta)
+            } else {
+                eprintln!(
+                    "Warning: webhook POST failed after {} attempt(s): {}",
+                    delivery.attempts,
+                    delivery.last_error.as_deref().unwrap_or("unknown error")
+                );
+                Event {
+                    detail: delivery.last_error.clone(),
+                    ..Event::new(EventKind::WebhookFailed, meta)
+                }
+            };
+            events::emit_or_warn(&event);
+
+            // Record the delivery outcome on the task
+            let mut updated = meta.clone();
+            updated.webhook = Some(delivery);
+            if let Err(e) = dispatch::metadata_store().put(&updated) {
+                eprintln!("Warning: failed to record webhook delivery: {}", e);
+            }
+        }
+
+        let hook = match meta.status {
+            TaskStatus::Completed => Some(HookEvent::Complete),
+            TaskStatus::Failed => Some(HookEvent::Fail),
+            _ => None,
+        };
+        if let Some(hook) = hook {
+            for err in hooks::run(&config.hooks, hook, meta).await {
+                eprintln!("Warning: {}", err);
+            }
+        }
+
+        let send = |channel| plugins.should_notify(channel, meta);
+        if send("local") {
+            for err in notify::notify_local(meta, &config.notifications.local).await {
+                eprintln!("Warning: {}", err);
+            }
+        }
+
+        let log_tail = log_cache::read_cached_log(&meta.task_id)
+            .map(|log| log_cache::tail(log, NOTIFY_LOG_LINES))
+            .unwrap_or_default();
+        if let Some(ref slack) = config.notifications.slack {
+            if send("slack") {
+                if let Err(e) = notify::slack::notify(slack, meta, &log_tail).await {
+                    eprintln!("Warning: slack notification failed: {}", e);
+                }
+            }
+        }
+        if let Some(ref discord) = config.notifications.discord {
+            if send("discord") {
+                if let Err(e) = notify::discord::notify(discord, meta, &log_tail).await {
+                    eprintln!("Warning: discord notification failed: {}", e);
+                }
+            }
+        }
+        if let Some(ref email) = config.notifications.email {
+            if send("email") {
+                if let Err(e) = notify::email::notify(email, meta, &log_tail).await {
+                    eprintln!("Warning: email notification failed: {}", e);
+                }
+            }
+        }
+        if let Some(ref telegram) = config.notifications.telegram {
+            if send("telegram") {
+                if let Err(e) = notify::telegram::notify(telegram, meta, &log_tail).await {
+                    eprintln!("Warning: telegram notification failed: {}", e);
+                }
+            }
+        }
+        return meta.clone();
+    }
+    meta.clone()
+}
+
+/// Note that the executor has just reported on the task, for `status
+/// --cached`.
+fn record_check(meta: &TaskMetadata) -> TaskMetadata {
+    let mut meta = meta.clone();
+    meta.checked_at = Some(chrono::Utc::now());
+    if let Err(e) = dispatch::metadata_store().put(&meta) {
+        eprintln!("Warning: failed to record status check: {}", e);
+    }
+    meta
+}
+
+/// Record the running task's host resources (see [`host::STATS_SCRIPT`]).
+/// Backends that can't run commands in the workspace are skipped quietly.
+async fn sample_host(executor: &dyn Executor, meta: &TaskMetadata) -> TaskMetadata {
+    let mut meta = meta.clone();
+    match executor
+        .exec_output(&meta.task_id, host::STATS_SCRIPT, shutdown::token())
+        .await
+    {
+        Ok((_, output)) => {
+            let Some(stats) = host::parse_stats(&output) else {
+                return meta;
+            };
+            meta.host_stats = Some(stats);
+            if let Err(e) = dispatch::metadata_store().put(&meta) {
+                eprintln!("Warning: failed to record host stats: {}", e);
+            }
+        }
+        Err(ExecutorError::Unsupported(_)) => {}
+        Err(e) => eprintln!("Warning: failed to sample host stats: {}", e),
+    }
+    meta
+}
+
+/// Fold the running task's CPU time and memory into its recorded usage (see
+/// [`executor_core::usage`]). Backends that can't sample are skipped quietly.
+async fn sample_usage(executor: &dyn Executor, meta: &TaskMetadata) -> TaskMetadata {
+    let mut meta = meta.clone();
+    match executor.usage(&meta.task_id, shutdown::token()).await {
+        Ok(Some(sample)) => {
+            meta.resource_usage = Some(ResourceUsage::merge(meta.resource_usage.as_ref(), sample));
+            if let Err(e) = dispatch::metadata_store().put(&meta) {
+                eprintln!("Warning: failed to record resource usage: {}", e);
+            }
+        }
+        Ok(None) | Err(ExecutorError::Unsupported(_)) => {}
+        Err(e) => eprintln!("Warning: failed to sample resource usage: {}", e),
+    }
+    meta
+}
+
+/// Record how many turns the task has taken and its last tool call (see
+/// [`executor_core::progress`]). Backends that can't tell are skipped quietly.
+async fn sample_progress(executor: &dyn Executor, meta: &TaskMetadata) -> TaskMetadata {
+    let mut meta = meta.clone();
+    match executor.progress(&meta.task_id, shutdown::token()).await {
+        Ok(Some(progress)) => {
+            meta.progress = Some(progress);
+            if let Err(e) = dispatch::metadata_store().put(&meta) {
+                eprintln!("Warning: failed to record task progress: {}", e);
+            }
+        }
+        Ok(None) | Err(ExecutorError::Unsupported(_)) => {}
+        Err(e) => eprintln!("Warning: failed to sample task progress: {}", e),
+    }
+    meta
+}
+
+/// Keep a local copy of a finished task's log, so `logs` keeps working once
+/// the backend's task dir is cleaned up or its host goes away. The whole
+/// claude.log (stdout and stderr) is mirrored where the backend can hand it
+/// over, otherwise its last [`CACHED_LOG_LINES`] lines.
+async fn cache_log(config: &Config, meta: &TaskMetadata) {
+    let Ok(executor) = dispatch::create_executor(config, &meta.executor_name) else {
+        return;
+    };
+    match mirror_log(executor.as_ref(), &meta.task_id).await {
+        Ok(()) | Err(ExecutorError::Cancelled) => return,
+        Err(e) => tracing::debug!("Mirroring log of {} failed: {}", meta.task_id, e),
+    }
+    match executor
+        .logs(&meta.task_id, CACHED_LOG_LINES, shutdown::token())
+        .await
+    {
+        Ok(lines) => {
+            if let Err(e) = log_cache::write_cached_log(&meta.task_id, &lines) {
+                eprintln!("Warning: failed to cache log: {}", e);
+            }
+        }
+        Err(e) => eprintln!("Warning: failed to fetch log for caching: {}", e),
+    }
+}
+
+/// Download the task's claude.log into the log cache. It lands in a `.part`
+/// file first, so a failed transfer leaves any previous copy in place.
+async fn mirror_log(executor: &dyn Executor, task_id: &TaskId) -> Result<(), ExecutorError> {
+    std::fs::create_dir_all(log_cache::log_cache_dir())?;
+    let dest = log_cache::cache_path(task_id);
+    let partial = dest.with_extension("log.part");
+    let result = executor
+        .download(task_id, "claude.log", FileRoot::TaskDir, &partial, shutdown::token())
+        .await;
+    if let Err(e) = result {
+        std::fs::remove_file(&partial).ok();
+        return Err(e);
+    }
+    std::fs::rename(&partial, &dest)?;
+    Ok(())
+}
+
+/// Fetch a `git_branch` task's diff into the local cache and record its
+/// changed files in metadata. Returns the metadata to carry on with.
+async fn capture_changes(config: &Config, meta: &TaskMetadata) -> TaskMetadata {
+    let mut meta = meta.clone();
+    if meta.git_branch.is_none() {
+        return meta;
+    }
+    let Ok(executor) = dispatch::create_executor(config, &meta.executor_name) else {
+        return meta;
+    };
+    match executor.changes(&meta.task_id, shutdown::token()).await {
+        Ok(changes) => {
+            if let Err(e) = log_cache::write_cached_diff(&meta.task_id, &changes.diff) {
+                eprintln!("Warning: failed to cache diff: {}", e);
+            }
+            meta.changed_files = changes.files;
+            if let Err(e) = dispatch::metadata_store().put(&meta) {
+                eprintln!("Warning: failed to record changed files: {}", e);
+            }
+        }
+        Err(e) => eprintln!("Warning: failed to capture changes: {}", e),
+    }
+    meta
+}
+
+/// For an executor with `git.auto_pr`, commit a successful claude_code task's
+/// changes on its branch, push it and open a pull request. Returns the
+/// metadata to carry on with.
+async fn open_pull_request(config: &Config, meta: &TaskMetadata) -> TaskMetadata {
+    let mut meta = meta.clone();
+    let Some(git) = config
+        .find_executor(&meta.executor_name)
+        .map(|e| &e.git)
+        .filter(|git| git.auto_pr)
+    else {
+        return meta;
+    };
+    if meta.status != TaskStatus::Completed
+        || meta.task_type != "claude_code"
+        || meta.git_branch.is_none()
+        || meta.changed_files.is_empty()
+    {
+        return meta;
+    }
+
+    let result = publish(config, &meta, git).await;
+    dispatch::audit(AuditEntry {
+        executor: Some(meta.executor_name.clone()),
+        task_id: Some(meta.task_id.clone()),
+        detail: result.as_ref().ok().cloned(),
+        error: result.as_ref().err().map(|e| format!("{:#}", e)),
+        ..AuditEntry::new(AuditAction::PullRequest)
+    });
+
+    match result {
+        Ok(url) => {
+            eprintln!("Pull request: {}", url);
+            meta.pr_url = Some(url);
+            if let Err(e) = dispatch::metadata_store().put(&meta) {
+                eprintln!("Warning: failed to record pull request: {}", e);
+            }
+            if let Err(e) = completion::save_completion_record(&meta) {
+                eprintln!("Warning: failed to update completion record: {}", e);
+            }
+        }
+        Err(e) => eprintln!("Warning: auto PR for task {} failed: {:#}", meta.task_id, e),
+    }
+    meta
+}
+
+/// Commit and push the task's branch, then open its pull request.
+async fn publish(config: &Config, meta: &TaskMetadata, git: &GitConfig) -> anyhow::Result<String> {
+    let title = pr_title(&meta.prompt);
+    let message = format!("{}\n\nOpenClaw task {}", title, meta.task_id);
+
+    let executor = dispatch::create_executor(config, &meta.executor_name)?;
+    let script = git::publish_script(&meta.task_id, &message, git);
+    let (_, output) = executor
+        .exec_output(&meta.task_id, &script, shutdown::token())
+        .await?;
+    let published = git::parse_publish(&output)?;
+
+    let repo = github::repo_slug(&published.origin)
+        .with_context(|| format!("{} is not a GitHub repository", published.origin))?;
+    let files: Vec<String> = meta
+        .changed_files
+        .iter()
+        .map(|f| format!("- `{}`", f))
+        .collect();
+    let pr = PullRequest {
+        repo,
+        head: git::branch_name(&meta.task_id),
+        base: git
+            .pr_base
+            .clone()
+            .or_else(|| meta.repo.as_ref().and_then(|r| r.branch.clone()))
+            .or(published.base),
+        title,
+        body: format!(
+            "{}\n\n---\nOpened by OpenClaw for task `{}` on `{}`.\n\nChanged files:\n{}",
+            meta.prompt,
+            meta.task_id,
+            meta.executor_name,
+            files.join("\n")
+        ),
+    };
+    github::open_pull_request(&pr, git.token().as_deref())
+        .await
+        .map_err(anyhow::Error::msg)
+}
+
+/// First line of the prompt, shortened to fit a commit subject.
+fn pr_title(prompt: &str) -> String {
+    let line = prompt.lines().next().unwrap_or_default().trim();
+    if line.chars().count() <= PR_TITLE_CHARS {
+        return line.to_string();
+    }
+    let cut: String = line.chars().take(PR_TITLE_CHARS - 3).collect();
+    format!("{}...", cut.trim_end())
+}
+
+/// Fire the `on_heartbeat_timeout` hooks, once, for a task still running past
+/// its executor's `heartbeat_timeout_minutes` (or `hooks.heartbeat_timeout_minutes`).
+async fn check_heartbeat_timeout(config: &Config, meta: &TaskMetadata) {
+    let resolved = dispatch::resolve(config, &meta.executor_name);
+    let Some(minutes) = resolved.heartbeat_timeout_minutes.value else {
+        return;
+    };
+    let running_for = chrono::Utc::now() - meta.started_at;
+    if running_for < chrono::Duration::minutes(minutes as i64)
+        || events::task_has_event(&meta.task_id, EventKind::HeartbeatTimeout)
+    {
+        return;
+    }
+
+    events::emit_or_warn(&Event::new(EventKind::HeartbeatTimeout, meta));
+    for err in hooks::run(&config.hooks, HookEvent::HeartbeatTimeout, meta).await {
+        eprintln!("Warning: {}", err);
+    }
+}
@@ -0,0 +1,1141 @@
+mod commands;
+mod complete;
+mod deps;
+mod dispatch;
+mod errors;
+mod finish;
+mod output;
+mod picker;
+mod plugins;
+mod preflight;
+mod shutdown;
+mod telemetry;
+mod wizard;
+
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
+use clap_complete::engine::ArgValueCompleter;
+use clap_complete::CompleteEnv;
+use executor_core::log_filter::LogFilter;
+use executor_core::stats::parse_window;
+use executor_core::task::RepoSpec;
+use output::OutputFormat;
+use std::path::Path;
+
+#[derive(Parser)]
+#[command(
+    name = "openclaw-agent",
+    about = "OpenClaw Coding Agent — Executor Framework",
+    version
+)]
+struct Cli {
+    /// Path to config file (default: ~/.config/openclaw/coding-agent.yaml)
+    #[arg(long, short)]
+    config: Option<String>,
+
+    /// Config profile: a section under `profiles:` in the config file, or a
+    /// file in ~/.config/openclaw/profiles/
+    #[arg(long)]
+    profile: Option<String>,
+
+    /// Enable verbose logging
+    #[arg(long, short)]
+    verbose: bool,
+
+    /// Output format for status, list, executors, start, run, history,
+    /// search, audit and config resolve
+    #[arg(long, global = true, value_enum, default_value_t)]
+    output: OutputFormat,
+
+    /// Report a failure as a JSON object on stderr (code, message, executor,
+    /// task_id) instead of text; implied by any --output other than table
+    #[arg(long, global = true)]
+    json_errors: bool,
+
+    /// Write man pages for every command into this directory and exit
+    #[arg(long, exclusive = true, value_name = "DIR")]
+    generate_man: Option<String>,
+
+    #[command(subcommand)]
+    command: Option<Commands>,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Start a new Claude Code task on an executor
+    Start {
+        /// Executor name (from config); chosen by a plugin if omitted
+        #[arg(
+            long,
+            short,
+            conflicts_with = "fan_out",
+            add = ArgValueCompleter::new(complete::executor_names)
+        )]
+        executor: Option<String>,
+
+        /// Name to refer to the task by instead of its ID (with --fan-out,
+        /// each task is named `<name>-<executor>`)
+        #[arg(long)]
+        name: Option<String>,
+
+        /// Task prompt; `-` (or leaving it out with stdin piped) reads it from stdin
+        #[arg(long, short)]
+        prompt: Option<String>,
+
+        /// Read the prompt, and defaults for the other options, from a Markdown
+        /// file with optional YAML front-matter
+        #[arg(long, conflicts_with = "prompt")]
+        prompt_file: Option<String>,
+
+        /// Workspace directory on the executor
+        #[arg(long, short, conflicts_with = "repo")]
+        workspace: Option<String>,
+
+        /// Clone this repository into a fresh per-task workspace first
+        #[arg(long)]
+        repo: Option<String>,
+
+        /// Branch or tag to check out (default: the remote's default branch)
+        #[arg(long, requires = "repo")]
+        branch: Option<String>,
+
+        /// Shallow-clone to this many commits
+        #[arg(long, requires = "repo")]
+        depth: Option<u32>,
+
+        /// Work on a new `openclaw/<task-id>` branch and capture the diff when done
+        #[arg(long)]
+        git_branch: bool,
+
+        /// Maximum turns for claude
+        #[arg(long)]
+        max_turns: Option<u32>,
+
+        /// Allowed tools (can be repeated)
+        #[arg(long)]
+        allowed_tools: Vec<String>,
+
+        /// Wait for this task to complete successfully before starting (can be repeated)
+        #[arg(long, add = ArgValueCompleter::new(complete::task_ids))]
+        after: Vec<String>,
+
+        /// Launch on every executor matching --labels, under a shared group ID
+        #[arg(long, requires = "labels")]
+        fan_out: bool,
+
+        /// Executor labels to match for --fan-out (can be repeated)
+        #[arg(long, requires = "fan_out")]
+        labels: Vec<String>,
+
+        /// Completion webhook for this task, overriding the config
+        #[arg(long)]
+        webhook: Option<String>,
+
+        /// Tag the task, e.g. for `list --tag` (can be repeated)
+        #[arg(long = "tag")]
+        tags: Vec<String>,
+
+        /// Seconds between status polls while waiting on this task, overriding the executor's
+        #[arg(long)]
+        heartbeat_interval: Option<u64>,
+
+        /// Run in this Nix shell (flake ref like `.#devshell`, or a shell.nix path),
+        /// overriding the executor's nix_shell
+        #[arg(long)]
+        nix_shell: Option<String>,
+
+        /// Start even if the executor is low on disk or over its load limit
+        #[arg(long)]
+        force: bool,
+
+        /// Print only the task ID (one per line with --fan-out)
+        #[arg(long, short)]
+        quiet: bool,
+
+        /// Print the command the task would be launched with, and the metadata
+        /// that would be recorded, without starting it
+        #[arg(long, conflicts_with_all = ["fan_out", "quiet"])]
+        dry_run: bool,
+    },
+
+    /// Run an arbitrary shell command on an executor
+    Run {
+        /// Executor name (from config)
+        #[arg(long, short, add = ArgValueCompleter::new(complete::executor_names))]
+        executor: String,
+
+        /// Name to refer to the task by instead of its ID
+        #[arg(long)]
+        name: Option<String>,
+
+        /// Shell command to execute
+        #[arg(long, short)]
+        cmd: String,
+
+        /// Workspace directory on the executor
+        #[arg(long, short)]
+        workspace: Option<String>,
+
+        /// Run in this Nix shell (flake ref like `.#devshell`, or a shell.nix path),
+        /// overriding the executor's nix_shell
+        #[arg(long)]
+        nix_shell: Option<String>,
+
+        /// Wait for this task to complete successfully before starting (can be repeated)
+        #[arg(long, add = ArgValueCompleter::new(complete::task_ids))]
+        after: Vec<String>,
+
+        /// Start even if the executor is low on disk or over its load limit
+        #[arg(long)]
+        force: bool,
+
+        /// Print only the task ID
+        #[arg(long, short)]
+        quiet: bool,
+    },
+
+    /// Launch every task listed in a YAML/JSON batch file
+    Batch {
+        /// Path to the batch file
+        file: String,
+    },
+
+    /// Run a pipeline file of tasks with dependencies
+    Pipeline {
+        /// Path to the pipeline YAML/JSON file
+        file: String,
+    },
+
+    /// Check status of a task
+    Status {
+        /// Task ID, name or ID prefix (picked interactively if omitted on a terminal)
+        #[arg(long, short, add = ArgValueCompleter::new(complete::task_ids))]
+        task_id: Option<String>,
+
+        /// Same as `--output json`
+        #[arg(long, hide = true)]
+        json: bool,
+
+        /// Exit with the task's state: 0 completed, 1 failed, 2 running,
+        /// 3 killed, 4 starting, 5 unknown (6 if it couldn't be checked)
+        #[arg(long)]
+        exit_code: bool,
+
+        /// Answer from local metadata, without asking the executor, if the
+        /// task was last checked within --max-age (or has finished)
+        #[arg(long)]
+        cached: bool,
+
+        /// How old a cached answer may be, e.g. 30s, 5m
+        #[arg(long, requires = "cached", default_value = "60s")]
+        max_age: String,
+    },
+
+    /// Fetch logs from a task
+    Logs {
+        /// Task ID, name or ID prefix (picked interactively if omitted on a terminal)
+        #[arg(long, short, add = ArgValueCompleter::new(complete::task_ids))]
+        task_id: Option<String>,
+
+        /// Interleave the logs of every running task, each line prefixed with
+        /// its name (or short task ID) and executor
+        #[arg(long, conflicts_with = "task_id")]
+        all: bool,
+
+        /// Number of lines to fetch
+        #[arg(long, short, default_value = "50")]
+        lines: usize,
+
+        /// Follow log output (poll every N seconds)
+        #[arg(long, short)]
+        follow: Option<u64>,
+
+        /// Only lines matching this extended regular expression
+        #[arg(long, value_name = "REGEX")]
+        grep: Option<String>,
+
+        /// Only lines timestamped within this window (e.g. 30m, 2h), and the
+        /// untimestamped lines following them
+        #[arg(long, value_name = "WINDOW")]
+        since: Option<String>,
+
+        /// Only JSON event lines of this type (e.g. result, assistant)
+        #[arg(long, value_name = "TYPE")]
+        level: Option<String>,
+
+        /// Show the timestamps of executors with `log_timestamps`
+        #[arg(long)]
+        timestamps: bool,
+
+        /// Strip ANSI escapes (colors, cursor movement); the default when
+        /// stdout isn't a terminal
+        #[arg(long, conflicts_with = "raw")]
+        no_color: bool,
+
+        /// Print lines untouched: keep ANSI escapes and timestamps
+        #[arg(long)]
+        raw: bool,
+    },
+
+    /// Show the git diff of a task's workspace
+    Diff {
+        /// Task ID, name or ID prefix
+        #[arg(add = ArgValueCompleter::new(complete::task_ids))]
+        task_id: String,
+
+        /// List changed files instead of the full diff
+        #[arg(long, conflicts_with = "stat")]
+        files: bool,
+
+        /// Show per-file line counts instead of the full diff
+        #[arg(long)]
+        stat: bool,
+    },
+
+    /// Copy a file from a task's workspace (or task dir) to this machine
+    Download {
+        /// Task ID, name or ID prefix
+        #[arg(add = ArgValueCompleter::new(complete::task_ids))]
+        task_id: String,
+
+        /// File to copy; relative paths are relative to the task's workspace
+        path: String,
+
+        /// Local file or directory to copy it to (default: current directory)
+        dest: Option<String>,
+
+        /// Resolve a relative PATH against the task dir instead of the workspace
+        #[arg(long)]
+        task_dir: bool,
+    },
+
+    /// Copy a file from this machine into a task's workspace (or task dir)
+    Upload {
+        /// Task ID, name or ID prefix
+        #[arg(add = ArgValueCompleter::new(complete::task_ids))]
+        task_id: String,
+
+        /// Local file to copy
+        source: String,
+
+        /// Where to put it; relative paths are relative to the task's
+        /// workspace, and a trailing `/` keeps SOURCE's file name
+        /// (default: the workspace root)
+        path: Option<String>,
+
+        /// Resolve a relative PATH against the task dir instead of the workspace
+        #[arg(long)]
+        task_dir: bool,
+    },
+
+    /// Run a command in a task's workspace on its executor
+    Exec {
+        /// Task ID, name or ID prefix
+        #[arg(add = ArgValueCompleter::new(complete::task_ids))]
+        task_id: String,
+
+        /// Command to run, after `--`
+        #[arg(last = true, required = true)]
+        command: Vec<String>,
+    },
+
+    /// Kill a running task
+    Kill {
+        /// Task ID, name or ID prefix (a running task is picked interactively
+        /// if omitted on a terminal)
+        #[arg(
+            long,
+            short,
+            add = ArgValueCompleter::new(complete::task_ids),
+            conflicts_with_all = ["all", "executor", "older_than"]
+        )]
+        task_id: Option<String>,
+
+        /// Kill every running task (only those matching --executor and
+        /// --older-than, if given)
+        #[arg(long)]
+        all: bool,
+
+        /// Kill the running tasks on this executor
+        #[arg(long, short, add = ArgValueCompleter::new(complete::executor_names))]
+        executor: Option<String>,
+
+        /// Kill the tasks running for longer than this, e.g. 30m, 2h
+        #[arg(long)]
+        older_than: Option<String>,
+
+        /// Kill several tasks without asking first
+        #[arg(long, short)]
+        yes: bool,
+    },
+
+    /// Cleanup task artifacts
+    Cleanup {
+        /// Task ID, name or ID prefix
+        #[arg(
+            long,
+            short,
+            add = ArgValueCompleter::new(complete::task_ids),
+            required_unless_present_any = ["expired", "all_terminal", "older_than"],
+            conflicts_with_all = ["expired", "all_terminal", "older_than"]
+        )]
+        task_id: Option<String>,
+
+        /// Clean up every finished task its executor's `cleanup` policy says
+        /// is due (`delete_after_days`, `keep_on_failure`)
+        #[arg(long, conflicts_with_all = ["all_terminal", "older_than", "keep_failed"])]
+        expired: bool,
+
+        /// Clean up every completed, failed and killed task, and show the
+        /// space freed
+        #[arg(long)]
+        all_terminal: bool,
+
+        /// Clean up the tasks that finished longer ago than this, e.g. 7d, 12h
+        /// (implies --all-terminal)
+        #[arg(long)]
+        older_than: Option<String>,
+
+        /// With --all-terminal or --older-than, leave failed tasks alone
+        #[arg(long, conflicts_with = "task_id")]
+        keep_failed: bool,
+
+        /// With --expired, --all-terminal or --older-than, list the tasks
+        /// without cleaning them up
+        #[arg(long, conflicts_with = "task_id")]
+        dry_run: bool,
+
+        /// With --all-terminal or --older-than, clean up without asking first
+        #[arg(long, short)]
+        yes: bool,
+    },
+
+    /// Leave a timestamped note on a task, shown by `status` and `history`
+    Annotate {
+        /// Task ID, name or ID prefix
+        #[arg(add = ArgValueCompleter::new(complete::task_ids))]
+        task_id: String,
+
+        /// The note, e.g. "reviewing the diff, looks wrong"
+        note: String,
+    },
+
+    /// List all tasks (from local metadata)
+    List {
+        /// Same as `--output json`
+        #[arg(long, hide = true)]
+        json: bool,
+
+        /// Same as `--output jsonl`
+        #[arg(long, hide = true)]
+        jsonl: bool,
+
+        /// Filter by status
+        #[arg(long)]
+        status: Option<String>,
+
+        /// Filter by executor name
+        #[arg(long, add = ArgValueCompleter::new(complete::executor_names))]
+        executor: Option<String>,
+
+        /// Filter by fan-out group ID
+        #[arg(long)]
+        group: Option<String>,
+
+        /// Filter by tag
+        #[arg(long)]
+        tag: Option<String>,
+
+        /// Check tasks stuck in starting with their executors first (see `reconcile`)
+        #[arg(long)]
+        refresh: bool,
+
+        /// Order of the tasks
+        #[arg(long, value_enum, default_value_t)]
+        sort: commands::list::SortKey,
+
+        /// Reverse the order
+        #[arg(long)]
+        reverse: bool,
+
+        /// Show at most this many tasks
+        #[arg(long)]
+        limit: Option<usize>,
+
+        /// Only tasks started within this window, e.g. 24h, 7d
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Show the tasks listed before this one (ID, prefix or name)
+        #[arg(long, add = ArgValueCompleter::new(complete::task_ids))]
+        before: Option<String>,
+
+        /// Show the tasks listed after this one (ID, prefix or name)
+        #[arg(long, add = ArgValueCompleter::new(complete::task_ids))]
+        after: Option<String>,
+    },
+
+    /// Summarize finished tasks over a time window
+    History {
+        /// Look-back window, e.g. 24h, 7d, 2w
+        #[arg(long, default_value = "7d")]
+        since: String,
+
+        /// Only include tasks from this executor
+        #[arg(long, short, add = ArgValueCompleter::new(complete::executor_names))]
+        executor: Option<String>,
+
+        /// Number of recent failures to show
+        #[arg(long, default_value = "5")]
+        failures: usize,
+
+        /// Same as `--output json`
+        #[arg(long, hide = true)]
+        json: bool,
+    },
+
+    /// Search task prompts, errors and locally cached logs
+    Search {
+        /// Text to look for (case-insensitive)
+        term: String,
+
+        /// Only search tasks from this executor
+        #[arg(long, short, add = ArgValueCompleter::new(complete::executor_names))]
+        executor: Option<String>,
+
+        /// Maximum number of matches to show
+        #[arg(long, default_value = "20")]
+        limit: usize,
+
+        /// Same as `--output json`
+        #[arg(long, hide = true)]
+        json: bool,
+    },
+
+    /// Show the audit log of task and config mutations
+    Audit {
+        /// Filter by action (start, kill, cleanup, annotate, config_init, import, sync,
+        /// agent_upgrade, provision)
+        #[arg(long)]
+        action: Option<String>,
+
+        /// Filter by executor name
+        #[arg(long, short, add = ArgValueCompleter::new(complete::executor_names))]
+        executor: Option<String>,
+
+        /// Filter by task ID, name or ID prefix
+        #[arg(long, short, add = ArgValueCompleter::new(complete::task_ids))]
+        task_id: Option<String>,
+
+        /// Only entries within this window, e.g. 24h, 7d
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Show at most this many (most recent) entries
+        #[arg(long, default_value = "50")]
+        limit: usize,
+
+        /// Same as `--output json`
+        #[arg(long, hide = true)]
+        json: bool,
+    },
+
+    /// List configured executors
+    Executors {
+        /// Same as `--output json`
+        #[arg(long, hide = true)]
+        json: bool,
+    },
+
+    /// Install git, Node.js and the claude CLI on an executor's host where
+    /// missing, create its task directory and check it is ready for tasks
+    Provision {
+        /// Executor to provision
+        #[arg(long, short, add = ArgValueCompleter::new(complete::executor_names))]
+        executor: String,
+
+        /// Only check what is installed; change nothing
+        #[arg(long)]
+        check: bool,
+    },
+
+    /// Smoke-test an executor: run a trivial task through start, heartbeat,
+    /// status, logs and cleanup, and report which stage fails
+    Verify {
+        /// Executor to verify
+        #[arg(long, short, add = ArgValueCompleter::new(complete::executor_names))]
+        executor: String,
+
+        /// How long the task may take to finish, e.g. 30s, 5m
+        #[arg(long, default_value = "2m")]
+        timeout: String,
+    },
+
+    /// Show or initialize the config file
+    Config {
+        /// Print the default config path
+        #[arg(long)]
+        path: bool,
+
+        /// Create the config file, asking for executors on a terminal
+        #[arg(long)]
+        init: bool,
+
+        /// With --init, write the sample config instead of asking
+        #[arg(long, requires = "init")]
+        sample: bool,
+
+        /// Check the config and its includes, listing every problem found
+        #[arg(long, conflicts_with_all = ["path", "init"])]
+        validate: bool,
+
+        #[command(subcommand)]
+        action: Option<ConfigAction>,
+    },
+
+    /// Manage the remote agents of executors with an `agent_binary`
+    Agent {
+        #[command(subcommand)]
+        action: AgentAction,
+    },
+
+    /// Bundle task metadata, completion records and logs into an archive
+    Export {
+        /// Archive path; compression follows the extension (e.g. tasks.tar.zst)
+        archive: String,
+
+        /// Log lines to include per task (0 skips logs)
+        #[arg(long, default_value = "500")]
+        log_lines: usize,
+    },
+
+    /// Restore tasks from an archive written by `export`
+    Import {
+        /// Archive path
+        input: String,
+
+        /// Overwrite tasks that already exist locally
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Run the long-lived controller (launches configured schedules)
+    Daemon,
+
+    /// Reconcile local task metadata with what the executors actually hold
+    Sync {
+        /// Only sync this executor
+        #[arg(long, short, add = ArgValueCompleter::new(complete::executor_names))]
+        executor: Option<String>,
+
+        /// Report discrepancies without changing local metadata
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Promote tasks stuck in starting to running, or fail those that never launched
+    Reconcile,
+
+    /// Print the JSONL event stream (task lifecycle, heartbeats, webhooks)
+    Events {
+        /// Only this task's events
+        #[arg(long, short, add = ArgValueCompleter::new(complete::task_ids))]
+        task_id: Option<String>,
+
+        /// Number of past events to print first
+        #[arg(long, short, default_value = "20")]
+        lines: usize,
+
+        /// Keep printing new events as they arrive
+        #[arg(long, short)]
+        follow: bool,
+    },
+
+    /// Output task status as structured JSON for dashboards
+    Dashboard {
+        /// Stream mode: output JSONL for all tasks, then exit
+        #[arg(long)]
+        stream: bool,
+
+        /// Watch mode: poll every N seconds (with --serve, how often to look
+        /// for changes; default 2)
+        #[arg(long)]
+        watch: Option<u64>,
+
+        /// Serve server-sent events of task changes over HTTP on this address,
+        /// e.g. 127.0.0.1:8787 (GET /events, GET /dashboard)
+        #[arg(long, value_name = "ADDR", conflicts_with = "stream")]
+        serve: Option<String>,
+
+        /// With --serve, let pages from this origin read the events in a
+        /// browser, e.g. http://localhost:3000 (none can without it)
+        #[arg(long, value_name = "ORIGIN", requires = "serve")]
+        cors_origin: Option<String>,
+
+        /// Write task gauges in Prometheus text format to this file (for
+        /// node_exporter's textfile collector) instead of printing JSON
+        #[arg(long, value_name = "PATH", conflicts_with_all = ["stream", "serve"])]
+        prom_textfile: Option<String>,
+    },
+
+    /// Print the shell completion script, e.g. `source <(openclaw-agent completions bash)`
+    Completions {
+        /// Shell to complete in
+        shell: clap_complete::Shell,
+    },
+
+    /// Print the JSON Schema of a machine-readable output
+    Schema {
+        /// Which output
+        #[arg(value_parser = executor_core::schema::KINDS)]
+        kind: String,
+    },
+
+    /// Live overview of running tasks and the load on their executors
+    Top {
+        /// Seconds between refreshes
+        #[arg(long, short, default_value = "5")]
+        interval: u64,
+
+        /// Print one snapshot and exit
+        #[arg(long)]
+        once: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigAction {
+    /// Print the final merged settings and where each value came from
+    Resolve {
+        /// Resolve for this executor (applies its overrides)
+        #[arg(long, short, add = ArgValueCompleter::new(complete::executor_names))]
+        executor: Option<String>,
+
+        /// Command-line max turns, as it would be passed to `start`
+        #[arg(long)]
+        max_turns: Option<u32>,
+
+        /// Command-line claude binary path
+        #[arg(long)]
+        claude_path: Option<String>,
+
+        /// Command-line webhook URL
+        #[arg(long)]
+        webhook_url: Option<String>,
+
+        /// Same as `--output json`
+        #[arg(long, hide = true)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum AgentAction {
+    /// Push the configured agent build again, restart it and remove older
+    /// builds from the host
+    Upgrade {
+        /// Upgrade only this executor (default: every one with an agent_binary)
+        #[arg(long, short, add = ArgValueCompleter::new(complete::executor_names))]
+        executor: Option<String>,
+    },
+}
+
+/// Parse the command line and run the CLI. Custom executor types registered
+/// in `executor_core::registry` beforehand are available to every command.
+pub async fn run() -> anyhow::Result<()> {
+    // Answer the shell when a completion script calls back for candidates
+    CompleteEnv::with_factory(Cli::command)
+        .var(commands::completions::COMPLETE_VAR)
+        .complete();
+
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    let json_errors = cli.json_errors || !cli.output.is_table();
+    let error = match run_cli(cli).await {
+        Ok(()) => return Ok(()),
+        Err(e) => e,
+    };
+    let (code, error) = match error.downcast::<errors::Exit>() {
+        Ok(exit) => (exit.code, exit.error),
+        Err(e) if !json_errors => return Err(e),
+        Err(e) => (1, Some(e)),
+    };
+    match error {
+        Some(e) if json_errors => {
+            errors::print_json(&e, errors::ErrorContext::from_matches(&matches))
+        }
+        Some(e) => eprintln!("Error: {:#}", e),
+        None => {}
+    }
+    std::process::exit(code)
+}
+
+/// Run the parsed command line.
+async fn run_cli(cli: Cli) -> anyhow::Result<()> {
+    if let Some(dir) = cli.generate_man {
+        return commands::completions::generate_man(Cli::command(), Path::new(&dir));
+    }
+    let Some(command) = cli.command else {
+        Cli::command()
+            .error(clap::error::ErrorKind::MissingSubcommand, "a command is required")
+            .exit()
+    };
+    // Completions don't need the config, so a broken one can't stop them
+    if let Commands::Completions { shell } = command {
+        return commands::completions::run(Cli::command(), shell);
+    }
+
+    dispatch::register_builtin_executors();
+
+    shutdown::install();
+
+    let config_path = match cli.config {
+        Some(ref path) => std::path::PathBuf::from(path),
+        None => executor_core::Config::default_path(),
+    };
+    let profile = cli.profile.as_deref();
+
+    // Validate before loading, which would stop at the first error
+    if let Commands::Config { validate: true, .. } = command {
+        return commands::config::validate(&config_path, profile);
+    }
+
+    // Load config
+    let config = if cli.config.is_some() || profile.is_some() {
+        executor_core::Config::load_profile(&config_path, profile)?
+    } else {
+        executor_core::Config::load_default()?
+    };
+
+    // Init tracing (and the OTLP exporter, if configured)
+    let _telemetry = telemetry::init(&config.telemetry, cli.verbose);
+
+    match command {
+        Commands::Start {
+            executor,
+            name,
+            prompt,
+            prompt_file,
+            workspace,
+            repo,
+            branch,
+            depth,
+            git_branch,
+            max_turns,
+            allowed_tools,
+            after,
+            fan_out,
+            labels,
+            webhook,
+            tags,
+            heartbeat_interval,
+            nix_shell,
+            force,
+            quiet,
+            dry_run,
+        } => {
+            let prompt = match prompt_file {
+                // Filled in from the file below
+                Some(_) => String::new(),
+                None => commands::start::read_prompt(prompt)?,
+            };
+            let mut args = commands::start::StartArgs {
+                executor,
+                name,
+                prompt,
+                workspace,
+                repo: repo.map(|url| RepoSpec { url, branch, depth }),
+                git_branch,
+                max_turns,
+                allowed_tools,
+                after,
+                labels,
+                fan_out,
+                webhook,
+                tags,
+                heartbeat_interval,
+                nix_shell,
+                force,
+                quiet,
+                dry_run,
+                output: cli.output,
+                services: Vec::new(),
+            };
+            if let Some(path) = prompt_file {
+                commands::start::apply_prompt_file(&mut args, &path)?;
+            }
+            commands::start::run(&config, args).await
+        }
+        Commands::Run {
+            executor,
+            name,
+            cmd,
+            workspace,
+            nix_shell,
+            after,
+            force,
+            quiet,
+        } => {
+            let args = commands::run::RunArgs {
+                executor,
+                name,
+                cmd,
+                workspace,
+                nix_shell,
+                after,
+                force,
+                quiet,
+                output: cli.output,
+            };
+            commands::run::run(&config, args).await
+        }
+        Commands::Batch { file } => commands::batch::run(&config, &file).await,
+        Commands::Pipeline { file } => commands::pipeline::run(&config, &file).await,
+        Commands::Status {
+            task_id,
+            json,
+            exit_code,
+            cached,
+            max_age,
+        } => {
+            let task_id = picker::task_or_pick(task_id, |_| true)?;
+            let max_age = if cached {
+                Some(parse_window(&max_age).map_err(anyhow::Error::msg)?)
+            } else {
+                None
+            };
+            let format = cli.output.or_flags(json, false);
+            commands::status::run(&config, &task_id, format, exit_code, max_age).await
+        },
+        Commands::Logs {
+            task_id,
+            lines,
+            follow,
+            grep,
+            since,
+            level,
+            timestamps,
+            no_color,
+            raw,
+            all,
+        } => {
+            let since = match since {
+                Some(window) => {
+                    Some(chrono::Utc::now() - parse_window(&window).map_err(anyhow::Error::msg)?)
+                }
+                None => None,
+            };
+            let filter = LogFilter { grep, since, level };
+            let view = commands::logs::LogView {
+                timestamps,
+                no_color,
+                raw,
+            };
+            if all {
+                commands::logs::run_all(&config, lines, follow, filter, view).await
+            } else {
+                let task_id = picker::task_or_pick(task_id, |_| true)?;
+                commands::logs::run(&config, &task_id, lines, follow, filter, view).await
+            }
+        }
+        Commands::Diff {
+            task_id,
+            files,
+            stat,
+        } => commands::diff::run(&config, &task_id, files, stat).await,
+        Commands::Download {
+            task_id,
+            path,
+            dest,
+            task_dir,
+        } => commands::download::run(&config, &task_id, &path, dest.as_deref(), task_dir).await,
+        Commands::Upload {
+            task_id,
+            source,
+            path,
+            task_dir,
+        } => commands::upload::run(&config, &task_id, &source, path.as_deref(), task_dir).await,
+        Commands::Exec { task_id, command } => {
+            commands::exec::run(&config, &task_id, &command).await
+        }
+        Commands::Kill {
+            task_id,
+            all,
+            executor,
+            older_than,
+            yes,
+        } => {
+            if all || executor.is_some() || older_than.is_some() {
+                commands::kill::run_bulk(&config, executor, older_than.as_deref(), yes).await
+            } else {
+                let task_id = picker::task_or_pick(task_id, |t| !t.status.is_terminal())?;
+                commands::kill::run(&config, &task_id).await
+            }
+        }
+        Commands::Cleanup {
+            task_id,
+            expired,
+            older_than,
+            keep_failed,
+            dry_run,
+            yes,
+            ..
+        } => match task_id {
+            Some(task_id) => commands::cleanup::run(&config, &task_id).await,
+            None if expired => commands::cleanup::run_expired(&config, dry_run).await,
+            None => {
+                commands::cleanup::run_terminal(
+                    &config,
+                    older_than.as_deref(),
+                    keep_failed,
+                    dry_run,
+                    yes,
+                )
+                .await
+            }
+        },
+        Commands::Annotate { task_id, note } => commands::annotate::run(&task_id, &note),
+        Commands::List {
+            json,
+            jsonl,
+            status,
+            executor,
+            group,
+            tag,
+            refresh,
+            sort,
+            reverse,
+            limit,
+            since,
+            before,
+            after,
+        } => {
+            if refresh {
+                commands::reconcile::reconcile(&config).await?;
+            }
+            commands::list::run(commands::list::ListArgs {
+                format: cli.output.or_flags(json, jsonl),
+                status,
+                executor,
+                group,
+                tag,
+                since,
+                sort,
+                reverse,
+                limit,
+                before,
+                after,
+            })
+            .await
+        },
+        Commands::History {
+            since,
+            executor,
+            failures,
+            json,
+        } => {
+            commands::history::run(&since, executor, failures, cli.output.or_flags(json, false))
+                .await
+        },
+        Commands::Search {
+            term,
+            executor,
+            limit,
+            json,
+        } => {
+            commands::search::run(&term, executor, limit, cli.output.or_flags(json, false)).await
+        },
+        Commands::Audit {
+            action,
+            executor,
+            task_id,
+            since,
+            limit,
+            json,
+        } => {
+            let filter = commands::audit::AuditFilter {
+                action,
+                executor,
+                task_id,
+                since,
+                limit,
+            };
+            commands::audit::run(filter, cli.output.or_flags(json, false)).await
+        }
+        Commands::Executors { json } => {
+            commands::executors::run(&config, cli.output.or_flags(json, false)).await
+        }
+        Commands::Provision { executor, check } => {
+            commands::provision::run(&config, &executor, check).await
+        }
+        Commands::Verify { executor, timeout } => {
+            let timeout = parse_window(&timeout)
+                .map_err(anyhow::Error::msg)?
+                .to_std()
+                .map_err(|_| anyhow::anyhow!("--timeout must not be negative"))?;
+            commands::verify::run(&config, &executor, timeout).await
+        }
+        Commands::Config {
+            action:
+                Some(ConfigAction::Resolve {
+                    executor,
+                    max_turns,
+                    claude_path,
+                    webhook_url,
+                    json,
+                }),
+            ..
+        } => {
+            let overrides = executor_core::resolve::Overrides {
+                max_turns,
+                claude_path,
+                webhook_url,
+            };
+            let format = cli.output.or_flags(json, false);
+            commands::config::resolve(&config, executor.as_deref(), &overrides, format).await
+        }
+        Commands::Config {
+            path,
+            init,
+            sample,
+            ..
+        } => commands::config::run(&config, path, init, sample).await,
+        Commands::Agent {
+            action: AgentAction::Upgrade { executor },
+        } => commands::agent::upgrade(&config, executor.as_deref()).await,
+        Commands::Export { archive, log_lines } => {
+            commands::archive::export(&config, Path::new(&archive), log_lines).await
+        }
+        Commands::Import { input, force } => {
+            commands::archive::import(Path::new(&input), force).await
+        }
+        Commands::Daemon => commands::daemon::run(config, config_path).await,
+        Commands::Sync { executor, dry_run } => {
+            commands::sync::run(&config, executor, dry_run).await
+        }
+        Commands::Reconcile => commands::reconcile::run(&config).await,
+        Commands::Events {
+            task_id,
+            lines,
+            follow,
+        } => commands::events::run(task_id, lines, follow).await,
+        Commands::Dashboard {
+            serve: Some(addr),
+            watch,
+            cors_origin,
+            ..
+        } => commands::dashboard::serve(&addr, watch.unwrap_or(2), cors_origin.as_deref()).await,
+        Commands::Dashboard {
+            stream,
+            watch,
+            prom_textfile,
+            ..
+        } => {
+            let prom_textfile = prom_textfile.as_deref().map(Path::new);
+            commands::dashboard::run(stream, watch, prom_textfile).await
+        }
+        Commands::Schema { kind } => commands::schema::run(&kind),
+        Commands::Top { interval, once } => commands::top::run(&config, interval, once).await,
+        // Handled before the config is loaded
+        Commands::Completions { shell } => commands::completions::run(Cli::command(), shell),
+    }
+}
@@ -0,0 +1,56 @@
+use serde::Serialize;
+
+/// How commands print their results: the global `--output` flag.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable tables and summaries
+    #[default]
+    Table,
+    /// Pretty-printed JSON
+    Json,
+    /// YAML
+    Yaml,
+    /// Compact JSON, one record per line
+    Jsonl,
+}
+
+impl OutputFormat {
+    /// This format, or the one picked by a command's older `--json` /
+    /// `--jsonl` flag, which still work.
+    pub fn or_flags(self, json: bool, jsonl: bool) -> Self {
+        match (json, jsonl) {
+            (_, true) => OutputFormat::Jsonl,
+            (true, _) => OutputFormat::Json,
+            _ => self,
+        }
+    }
+
+    pub fn is_table(self) -> bool {
+        self == OutputFormat::Table
+    }
+
+    /// Print one record. Commands print their own human view for `Table`;
+    /// those without one fall back to JSON.
+    pub fn print<T: Serialize + ?Sized>(self, value: &T) -> anyhow::Result<()> {
+        match self {
+            OutputFormat::Table | OutputFormat::Json => {
+                println!("{}", serde_json::to_string_pretty(value)?)
+            }
+            OutputFormat::Yaml => print!("{}", serde_yaml::to_string(value)?),
+            OutputFormat::Jsonl => println!("{}", serde_json::to_string(value)?),
+        }
+        Ok(())
+    }
+
+    /// Print a list of records: a JSON array, a YAML sequence, or one JSON
+    /// line per record.
+    pub fn print_list<T: Serialize>(self, values: &[T]) -> anyhow::Result<()> {
+        if self == OutputFormat::Jsonl {
+            for value in values {
+                println!("{}", serde_json::to_string(value)?);
+            }
+            return Ok(());
+        }
+        self.print(values)
+    }
+}
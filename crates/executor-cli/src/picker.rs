@@ -0,0 +1,62 @@
+//! Interactive task choice for commands run on a terminal without
+//! `--task-id`: a fuzzy-searchable list of recent tasks.
+
+use crate::dispatch;
+use dialoguer::theme::ColorfulTheme;
+use dialoguer::FuzzySelect;
+use executor_core::metadata::TaskMetadata;
+use executor_core::notify;
+use std::io::IsTerminal;
+
+/// Most tasks offered, newest first.
+const PICKER_TASKS: usize = 200;
+
+/// Longest prompt excerpt shown (and searched) per task.
+const PROMPT_CHARS: usize = 50;
+
+/// `task_id` if given, otherwise a task picked from the recent ones that
+/// `offer` accepts. Without a terminal to ask on, `--task-id` is required.
+pub fn task_or_pick(
+    task_id: Option<String>,
+    offer: impl Fn(&TaskMetadata) -> bool,
+) -> anyhow::Result<String> {
+    if let Some(task_id) = task_id {
+        return Ok(task_id);
+    }
+    if !std::io::stdin().is_terminal() || !std::io::stderr().is_terminal() {
+        anyhow::bail!("--task-id is required (no terminal to pick a task on)");
+    }
+
+    let mut tasks: Vec<TaskMetadata> = dispatch::recent_tasks()?
+        .into_iter()
+        .filter(|t| offer(t))
+        .take(PICKER_TASKS)
+        .collect();
+    if tasks.is_empty() {
+        anyhow::bail!("No tasks to pick from; pass --task-id");
+    }
+
+    let items: Vec<String> = tasks.iter().map(item).collect();
+    let picked = FuzzySelect::with_theme(&ColorfulTheme::default())
+        .with_prompt("Task (type to search, Esc to cancel)")
+        .items(&items)
+        .default(0)
+        .interact_opt()?;
+    match picked {
+        Some(index) => Ok(tasks.swap_remove(index).task_id.0),
+        None => anyhow::bail!("No task picked"),
+    }
+}
+
+/// One line of the list: short ID, name, status, executor and prompt.
+fn item(task: &TaskMetadata) -> String {
+    let short_id = task.task_id.0.get(..8).unwrap_or(&task.task_id.0);
+    format!(
+        "{}  {:<20} {:<10} {:<12} {}",
+        short_id,
+        task.name.as_deref().unwrap_or("-"),
+        task.status.to_string(),
+        task.executor_name,
+        notify::excerpt(&task.prompt, PROMPT_CHARS)
+    )
+}
@@ -0,0 +1,179 @@
+//! WebAssembly policy plugins.
+//!
+//! A plugin is a core wasm module with no imports that exports `memory`,
+//! `alloc(len: i32) -> i32` and any of the hook functions below. Each hook
+//! takes a UTF-8 JSON document `(ptr: i32, len: i32)` and returns its JSON
+//! answer packed as `(ptr << 32) | len`, or 0 for "no opinion".
+//!
+//! - `select_executor`: `{"request", "executors"}` → `{"executor": name}`
+//! - `route_notification`: `{"channel", "task"}` → `{"send": bool}`
+//! - `rewrite_payload`: `{"task", "payload"}` → `{"payload": ...}`
+
+use anyhow::Context;
+use executor_core::config::{Config, ExecutorConfig};
+use executor_core::metadata::TaskMetadata;
+use executor_core::task::TaskRequest;
+use serde_json::{json, Value};
+use std::path::Path;
+use std::sync::OnceLock;
+use wasmtime::{Engine, Instance, Module, Store};
+
+/// Instructions one hook call may execute before it is aborted.
+const FUEL_PER_CALL: u64 = 100_000_000;
+
+struct Plugin {
+    name: String,
+    module: Module,
+}
+
+pub struct PluginHost {
+    engine: Engine,
+    plugins: Vec<Plugin>,
+}
+
+/// The process-wide plugin host, loaded from `plugins.dir` on first use.
+/// Modules that fail to load are reported and skipped.
+pub fn host(config: &Config) -> &'static PluginHost {
+    static HOST: OnceLock<PluginHost> = OnceLock::new();
+    HOST.get_or_init(|| PluginHost::load(config.plugins.dir_path().as_deref()))
+}
+
+impl PluginHost {
+    fn load(dir: Option<&Path>) -> Self {
+        let mut engine_config = wasmtime::Config::new();
+        engine_config.consume_fuel(true);
+        let engine = Engine::new(&engine_config).expect("default wasmtime config is valid");
+
+        let mut host = Self {
+            engine,
+            plugins: Vec::new(),
+        };
+        let Some(dir) = dir else {
+            return host;
+        };
+
+        let mut paths: Vec<_> = match std::fs::read_dir(dir) {
+            Ok(entries) => entries
+                .filter_map(|e| e.ok().map(|e| e.path()))
+                .filter(|p| p.extension().is_some_and(|ext| ext == "wasm"))
+                .collect(),
+            Err(e) => {
+                eprintln!("Warning: cannot read plugins dir {}: {}", dir.display(), e);
+                return host;
+            }
+        };
+        paths.sort();
+
+        for path in paths {
+            match Module::from_file(&host.engine, &path) {
+                Ok(module) => host.plugins.push(Plugin {
+                    name: path
+                        .file_stem()
+                        .map(|s| s.to_string_lossy().into_owned())
+                        .unwrap_or_default(),
+                    module,
+                }),
+                Err(e) => eprintln!("Warning: failed to load plugin {}: {:#}", path.display(), e),
+            }
+        }
+        host
+    }
+
+    /// Ask plugins to pick an executor for a request; the first answer wins.
+    pub fn select_executor(
+        &self,
+        request: &TaskRequest,
+        executors: &[ExecutorConfig],
+    ) -> Option<String> {
+        let candidates: Vec<Value> = executors
+            .iter()
+            .map(|e| json!({ "name": e.name, "type": e.executor_type, "labels": e.labels }))
+            .collect();
+        let input = json!({ "request": request, "executors": candidates });
+
+        self.plugins.iter().find_map(|plugin| {
+            let answer = self.call_or_warn(plugin, "select_executor", &input)?;
+            answer["executor"].as_str().map(str::to_string)
+        })
+    }
+
+    /// Whether a finished task should go out on `channel` (`webhook`, `local`,
+    /// `slack`, `discord`, `email`, `telegram`). Any plugin can veto.
+    pub fn should_notify(&self, channel: &str, meta: &TaskMetadata) -> bool {
+        if self.plugins.is_empty() {
+            return true;
+        }
+        let input = json!({ "channel": channel, "task": meta });
+        self.plugins.iter().all(|plugin| {
+            self.call_or_warn(plugin, "route_notification", &input)
+                .and_then(|answer| answer["send"].as_bool())
+                .unwrap_or(true)
+        })
+    }
+
+    /// Pass a webhook body through each plugin's `rewrite_payload` in turn.
+    /// JSON bodies are handed over parsed; anything else as a string.
+    pub fn rewrite_payload(&self, meta: &TaskMetadata, body: Vec<u8>) -> Result<Vec<u8>, String> {
+        if self.plugins.is_empty() {
+            return Ok(body);
+        }
+
+        let mut payload = serde_json::from_slice(&body)
+            .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&body).into_owned()));
+        for plugin in &self.plugins {
+            let input = json!({ "task": meta, "payload": payload });
+            match self.call(plugin, "rewrite_payload", &input) {
+                Ok(Some(mut answer)) => payload = answer["payload"].take(),
+                Ok(None) => {}
+                Err(e) => return Err(format!("plugin {} rewrite_payload: {:#}", plugin.name, e)),
+            }
+        }
+
+        match payload {
+            Value::String(s) => Ok(s.into_bytes()),
+            other => serde_json::to_vec(&other).map_err(|e| e.to_string()),
+        }
+    }
+
+    fn call_or_warn(&self, plugin: &Plugin, hook: &str, input: &Value) -> Option<Value> {
+        self.call(plugin, hook, input).unwrap_or_else(|e| {
+            eprintln!("Warning: plugin {} {}: {:#}", plugin.name, hook, e);
+            None
+        })
+    }
+
+    /// Run one hook in a fresh instance. `Ok(None)` if the plugin doesn't
+    /// export it or has no opinion.
+    fn call(&self, plugin: &Plugin, hook: &str, input: &Value) -> anyhow::Result<Option<Value>> {
+        if plugin.module.get_export(hook).is_none() {
+            return Ok(None);
+        }
+
+        let mut store = Store::new(&self.engine, ());
+        store.set_fuel(FUEL_PER_CALL)?;
+        let instance = Instance::new(&mut store, &plugin.module, &[])?;
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .context("plugin does not export `memory`")?;
+        let alloc = instance.get_typed_func::<i32, i32>(&mut store, "alloc")?;
+        let func = instance.get_typed_func::<(i32, i32), i64>(&mut store, hook)?;
+
+        let input = serde_json::to_vec(input)?;
+        let len = i32::try_from(input.len()).context("input too large")?;
+        let ptr = alloc.call(&mut store, len)?;
+        memory.write(&mut store, ptr as u32 as usize, &input)?;
+
+        let packed = func.call(&mut store, (ptr, len))?;
+        if packed == 0 {
+            return Ok(None);
+        }
+        let out_ptr = (packed as u64 >> 32) as usize;
+        let out_len = (packed as u64 & 0xffff_ffff) as usize;
+        let mut output = vec![0; out_len];
+        memory.read(&store, out_ptr, &mut output)?;
+
+        Ok(Some(
+            serde_json::from_slice(&output).context("hook returned invalid JSON")?,
+        ))
+    }
+}
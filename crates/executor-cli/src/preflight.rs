@@ -0,0 +1,53 @@
+use crate::{dispatch, shutdown};
+use executor_core::config::Config;
+use executor_core::task::TaskRequest;
+use executor_core::{Executor, ExecutorError};
+
+/// Check the executor's host against its `min_free_disk_mb` and `max_load`
+/// before launching. A host past either threshold is refused, or only warned
+/// about with `force`. Executors that can't sample their host, or fail to,
+/// are let through.
+pub async fn check(
+    config: &Config,
+    executor_name: &str,
+    executor: &dyn Executor,
+    request: &TaskRequest,
+    force: bool,
+) -> anyhow::Result<()> {
+    let resolved = dispatch::resolve(config, executor_name);
+    let (min_free_disk_mb, max_load) = (resolved.min_free_disk_mb.value, resolved.max_load.value);
+    if min_free_disk_mb.is_none() && max_load.is_none() {
+        return Ok(());
+    }
+
+    // A --repo clone (or template copy) lands in the task dir
+    let dir = match request.repo {
+        Some(_) => None,
+        None => request.workspace.as_deref(),
+    };
+    let stats = match executor.host_stats(dir, shutdown::token()).await {
+        Ok(Some(stats)) => stats,
+        Ok(None) | Err(ExecutorError::Unsupported(_)) => return Ok(()),
+        Err(ExecutorError::Cancelled) => return Err(ExecutorError::Cancelled.into()),
+        Err(e) => {
+            eprintln!("Warning: pre-flight check on {} failed: {}", executor_name, e);
+            return Ok(());
+        }
+    };
+
+    let problems = stats.preflight_problems(min_free_disk_mb, max_load);
+    if problems.is_empty() {
+        return Ok(());
+    }
+    if force {
+        for problem in &problems {
+            eprintln!("Warning: {}: {}", executor_name, problem);
+        }
+        return Ok(());
+    }
+    anyhow::bail!(
+        "Not starting on {}: {} (use --force to start anyway)",
+        executor_name,
+        problems.join("; ")
+    )
+}
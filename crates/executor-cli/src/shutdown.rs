@@ -0,0 +1,33 @@
+use executor_core::{CancellationToken, ExecutorError};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+static TOKEN: OnceLock<CancellationToken> = OnceLock::new();
+
+/// Process-wide cancellation token, cancelled on the first Ctrl-C.
+pub fn token() -> &'static CancellationToken {
+    TOKEN.get_or_init(CancellationToken::new)
+}
+
+/// Install the Ctrl-C handler: the first press cancels in-flight executor
+/// operations so they can unwind cleanly, the second exits immediately.
+pub fn install() {
+    let token = token().clone();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            eprintln!("Interrupted, cancelling (press Ctrl-C again to force quit)");
+            token.cancel();
+        }
+        if tokio::signal::ctrl_c().await.is_ok() {
+            std::process::exit(130);
+        }
+    });
+}
+
+/// Sleep for `duration`, returning early with `ExecutorError::Cancelled` on Ctrl-C.
+pub async fn sleep(duration: Duration) -> Result<(), ExecutorError> {
+    tokio::select! {
+        _ = token().cancelled() => Err(ExecutorError::Cancelled),
+        _ = tokio::time::sleep(duration) => Ok(()),
+    }
+}
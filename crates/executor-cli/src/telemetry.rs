@@ -0,0 +1,68 @@
+use executor_core::config::TelemetryConfig;
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use opentelemetry_sdk::Resource;
+use tracing_subscriber::filter::{filter_fn, LevelFilter};
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::EnvFilter;
+
+/// Flushes buffered spans when dropped at the end of `main`.
+pub struct Guard(Option<SdkTracerProvider>);
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        if let Some(provider) = self.0.take() {
+            if let Err(e) = provider.shutdown() {
+                eprintln!("Warning: failed to flush traces: {}", e);
+            }
+        }
+    }
+}
+
+/// Install the log formatter and, if an OTLP endpoint is configured, the span exporter.
+pub fn init(config: &TelemetryConfig, verbose: bool) -> Guard {
+    let filter = if verbose { "debug" } else { "info" };
+
+    // Spans are for the exporter only; keep terminal output free of span prefixes
+    let fmt_layer = tracing_subscriber::fmt::layer()
+        .with_target(false)
+        .with_filter(EnvFilter::new(filter))
+        .with_filter(filter_fn(|meta| meta.is_event()));
+
+    let provider = config.otlp_endpoint.as_ref().and_then(|endpoint| {
+        match SpanExporter::builder()
+            .with_http()
+            .with_endpoint(endpoint)
+            .build()
+        {
+            Ok(exporter) => Some(
+                SdkTracerProvider::builder()
+                    .with_batch_exporter(exporter)
+                    .with_resource(
+                        Resource::builder()
+                            .with_service_name(config.service_name.clone())
+                            .build(),
+                    )
+                    .build(),
+            ),
+            Err(e) => {
+                eprintln!("Warning: OTLP exporter disabled: {}", e);
+                None
+            }
+        }
+    });
+
+    let otel_layer = provider.as_ref().map(|provider| {
+        tracing_opentelemetry::layer()
+            .with_tracer(provider.tracer("openclaw-agent"))
+            .with_filter(LevelFilter::DEBUG)
+    });
+
+    tracing_subscriber::registry()
+        .with(fmt_layer)
+        .with(otel_layer)
+        .init();
+
+    Guard(provider)
+}
@@ -0,0 +1,283 @@
+//! `config --init` on a terminal: a guided setup that asks for executors one
+//! at a time, checks that each one can be reached and where its `claude` is,
+//! and returns the config to write.
+
+use crate::{dispatch, shutdown};
+use dialoguer::theme::ColorfulTheme;
+use dialoguer::{Confirm, Input, Select};
+use executor_core::audit::current_user;
+use executor_core::config::ExecutorConfig;
+use serde_yaml::{Mapping, Value};
+use std::path::PathBuf;
+
+/// Key files offered for SSH, if they exist under `~/.ssh`.
+const SSH_KEYS: &[&str] = &["id_ed25519", "id_ecdsa", "id_rsa"];
+
+/// Container runtimes offered, if found on `PATH`.
+const RUNTIMES: &[&str] = &["docker", "podman"];
+
+const DEFAULT_IMAGE: &str = "claude-code:latest";
+
+/// Ask for executors and defaults, probing each executor as it is added.
+/// Returns the config as YAML.
+pub async fn config_yaml() -> anyhow::Result<String> {
+    let theme = ColorfulTheme::default();
+    let mut executors: Vec<Mapping> = Vec::new();
+
+    loop {
+        let taken: Vec<String> = executors.iter().filter_map(executor_name).collect();
+        let kind = Select::with_theme(&theme)
+            .with_prompt("Executor type")
+            .items(&[
+                "ssh: a machine reached over SSH",
+                "container: a docker or podman container on this machine",
+                "local: this machine",
+            ])
+            .default(0)
+            .interact()?;
+        let executor = match kind {
+            0 => ssh_executor(&theme, &taken).await?,
+            1 => Some(container_executor(&theme, &taken)?),
+            _ => Some(local_executor(&theme, &taken).await?),
+        };
+        executors.extend(executor);
+
+        let more = Confirm::with_theme(&theme)
+            .with_prompt("Add another executor?")
+            .default(false)
+            .interact()?;
+        if more {
+            continue;
+        }
+        if !executors.is_empty() {
+            break;
+        }
+        eprintln!("A config needs at least one executor.");
+    }
+
+    let max_turns: u32 = Input::with_theme(&theme)
+        .with_prompt("Default max turns per claude task")
+        .default(100)
+        .interact_text()?;
+
+    let mut defaults = Mapping::new();
+    defaults.insert("max_turns".into(), max_turns.into());
+    let mut config = Mapping::new();
+    config.insert(
+        "executors".into(),
+        Value::Sequence(executors.into_iter().map(Value::Mapping).collect()),
+    );
+    config.insert("defaults".into(), Value::Mapping(defaults));
+    Ok(format!(
+        "# OpenClaw Coding Agent configuration, written by `openclaw-agent config --init`.\n\
+         # See the README for every option.\n\n{}",
+        serde_yaml::to_string(&config)?
+    ))
+}
+
+/// An SSH executor, or `None` if the user gives up on one that can't be
+/// reached.
+async fn ssh_executor(
+    theme: &ColorfulTheme,
+    taken: &[String],
+) -> anyhow::Result<Option<Mapping>> {
+    loop {
+        let host: String = Input::with_theme(theme)
+            .with_prompt("Host (name or address)")
+            .interact_text()?;
+        // `gpu01.lab.example.com` suggests `gpu01`; an address suggests nothing
+        let suggested = match host.parse::<std::net::IpAddr>() {
+            Ok(_) => "",
+            Err(_) => host.split('.').next().unwrap_or(&host),
+        };
+        let name = ask_name(theme, taken, suggested)?;
+        let user: String = Input::with_theme(theme)
+            .with_prompt("User")
+            .default(current_user())
+            .interact_text()?;
+        let port: u16 = Input::with_theme(theme)
+            .with_prompt("Port")
+            .default(22)
+            .interact_text()?;
+        let key_path = ask_key(theme)?;
+
+        let mut executor = executor_mapping(&name, "ssh");
+        executor.insert("host".into(), host.clone().into());
+        executor.insert("user".into(), user.clone().into());
+        if port != 22 {
+            executor.insert("port".into(), port.into());
+        }
+        if let Some(key_path) = key_path {
+            executor.insert("key_path".into(), key_path.into());
+        }
+
+        eprintln!("Connecting to {}@{}:{}...", user, host, port);
+        match probe(&executor).await {
+            Ok(claude) => {
+                eprintln!("Connected.");
+                set_claude_path(theme, &mut executor, claude)?;
+                return Ok(Some(executor));
+            }
+            Err(e) => eprintln!("Could not reach {}: {}", host, e),
+        }
+
+        let choice = Select::with_theme(theme)
+            .with_prompt("What now?")
+            .items(&["Re-enter the details", "Keep it anyway", "Skip this executor"])
+            .default(0)
+            .interact()?;
+        match choice {
+            0 => continue,
+            1 => return Ok(Some(executor)),
+            _ => return Ok(None),
+        }
+    }
+}
+
+fn container_executor(theme: &ColorfulTheme, taken: &[String]) -> anyhow::Result<Mapping> {
+    let found: Vec<&str> = RUNTIMES.iter().copied().filter(|r| on_path(r)).collect();
+    let offered = if found.is_empty() {
+        eprintln!("Neither docker nor podman is on PATH; install one before starting tasks.");
+        RUNTIMES.to_vec()
+    } else {
+        found
+    };
+    let runtime = offered[Select::with_theme(theme)
+        .with_prompt("Container runtime")
+        .items(&offered)
+        .default(0)
+        .interact()?];
+    let name = ask_name(theme, taken, runtime)?;
+    let image: String = Input::with_theme(theme)
+        .with_prompt("Image (with claude installed)")
+        .default(DEFAULT_IMAGE.to_string())
+        .interact_text()?;
+    let volumes: String = Input::with_theme(theme)
+        .with_prompt("Volumes to mount (host:container, comma-separated, empty for none)")
+        .allow_empty(true)
+        .interact_text()?;
+
+    let mut executor = executor_mapping(&name, "container");
+    executor.insert("image".into(), image.into());
+    executor.insert("runtime".into(), runtime.into());
+    let volumes: Vec<Value> = volumes
+        .split(',')
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(Value::from)
+        .collect();
+    if !volumes.is_empty() {
+        executor.insert("volumes".into(), Value::Sequence(volumes));
+    }
+    Ok(executor)
+}
+
+async fn local_executor(theme: &ColorfulTheme, taken: &[String]) -> anyhow::Result<Mapping> {
+    let name = ask_name(theme, taken, "local")?;
+    let mut executor = executor_mapping(&name, "local");
+    match probe(&executor).await {
+        Ok(claude) => set_claude_path(theme, &mut executor, claude)?,
+        Err(e) => eprintln!("Could not look for claude: {}", e),
+    }
+    Ok(executor)
+}
+
+/// An executor name not already `taken`, suggesting `suggested`.
+fn ask_name(theme: &ColorfulTheme, taken: &[String], suggested: &str) -> anyhow::Result<String> {
+    let taken = taken.to_vec();
+    let mut input = Input::with_theme(theme)
+        .with_prompt("Executor name")
+        .validate_with(move |name: &String| -> Result<(), String> {
+            if name.trim().is_empty() {
+                Err("A name is required".into())
+            } else if taken.iter().any(|t| t == name.trim()) {
+                Err(format!("There is already an executor named {}", name.trim()))
+            } else {
+                Ok(())
+            }
+        });
+    if !suggested.is_empty() {
+        input = input.default(suggested.to_string());
+    }
+    Ok(input.interact_text()?.trim().to_string())
+}
+
+/// A key file to log in with, or `None` to use the SSH agent.
+fn ask_key(theme: &ColorfulTheme) -> anyhow::Result<Option<String>> {
+    let home = std::env::var_os("HOME").map(PathBuf::from).unwrap_or_default();
+    let keys: Vec<String> = SSH_KEYS
+        .iter()
+        .map(|key| home.join(".ssh").join(key))
+        .filter(|path| path.exists())
+        .map(|path| path.display().to_string())
+        .collect();
+    let mut items = vec!["ssh-agent".to_string()];
+    items.extend(keys.iter().cloned());
+    items.push("Another key file...".to_string());
+
+    let choice = Select::with_theme(theme)
+        .with_prompt("Log in with")
+        .items(&items)
+        .default(if keys.is_empty() { 0 } else { 1 })
+        .interact()?;
+    if choice == 0 {
+        Ok(None)
+    } else if choice <= keys.len() {
+        Ok(Some(keys[choice - 1].clone()))
+    } else {
+        let path: String = Input::with_theme(theme)
+            .with_prompt("Key file")
+            .interact_text()?;
+        Ok(Some(path))
+    }
+}
+
+/// Record where claude was found, or ask for its path if it wasn't.
+fn set_claude_path(
+    theme: &ColorfulTheme,
+    executor: &mut Mapping,
+    claude: Option<String>,
+) -> anyhow::Result<()> {
+    let path = match claude {
+        Some(path) => {
+            eprintln!("Found claude at {}", path);
+            path
+        }
+        None => {
+            eprintln!("No claude found; shell command tasks still work.");
+            Input::with_theme(theme)
+                .with_prompt("Path to claude there (empty to set it later)")
+                .allow_empty(true)
+                .interact_text()?
+        }
+    };
+    if !path.is_empty() {
+        executor.insert("claude_path".into(), path.into());
+    }
+    Ok(())
+}
+
+/// Reach the executor and find its claude.
+async fn probe(executor: &Mapping) -> anyhow::Result<Option<String>> {
+    let config: ExecutorConfig = serde_yaml::from_value(Value::Mapping(executor.clone()))?;
+    let executor = dispatch::create_executor_from_config(config)?;
+    Ok(executor.find_claude(shutdown::token()).await?)
+}
+
+fn executor_mapping(name: &str, executor_type: &str) -> Mapping {
+    let mut executor = Mapping::new();
+    executor.insert("name".into(), name.into());
+    executor.insert("type".into(), executor_type.into());
+    executor
+}
+
+fn executor_name(executor: &Mapping) -> Option<String> {
+    executor.get("name")?.as_str().map(String::from)
+}
+
+/// Whether `program` is an executable file in a `PATH` directory.
+fn on_path(program: &str) -> bool {
+    std::env::var_os("PATH").is_some_and(|path| {
+        std::env::split_paths(&path).any(|dir| dir.join(program).is_file())
+    })
+}
@@ -1,20 +1,54 @@
-use executor_core::config::{ContainerRuntime, ExecutorConfig};
+use executor_core::activate;
+use executor_core::config::{ContainerRuntime, ExecutorConfig, GpuSpec, ImageBuild, PullPolicy};
 use executor_core::error::ExecutorError;
-use executor_core::metadata::{metadata_dir, TaskMetadata};
-use executor_core::task::{TaskId, TaskPayload, TaskRequest, TaskStatus};
-use executor_core::Executor;
-use std::path::PathBuf;
+use executor_core::fixture::Fixture;
+use executor_core::executor::{
+    resolve_path, with_cancel, FileRoot, RemoteTask, StartPlan, TaskChanges,
+};
+use executor_core::git;
+use executor_core::host::{self, HostStats};
+use executor_core::image;
+use executor_core::metadata::TaskMetadata;
+use executor_core::nix;
+use executor_core::secrets;
+use executor_core::store::{default_store, MetadataStore};
+use executor_core::task::{
+    KillSignal, RepoSpec, ServiceSpec, TaskId, TaskPayload, TaskRequest, TaskStatus,
+};
+use executor_core::usage::{self, ResourceUsage};
+use executor_core::workspace;
+use crate::systemd::{self, UnitState};
+use executor_core::{CancellationToken, Executor};
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
 use tokio::process::Command;
-use tracing::{debug, info, warn};
+use tracing::{debug, info, instrument, warn};
 
 /// Container executor: runs claude or shell commands in Docker/Podman containers.
 pub struct ContainerExecutor {
     config: ExecutorConfig,
+    store: Arc<dyn MetadataStore>,
+    /// Where runtime commands are recorded to or replayed from, if anywhere.
+    fixture: Option<Fixture>,
 }
 
 impl ContainerExecutor {
     pub fn new(config: ExecutorConfig) -> Self {
-        Self { config }
+        Self::with_store(config, default_store())
+    }
+
+    pub fn with_store(config: ExecutorConfig, store: Arc<dyn MetadataStore>) -> Self {
+        Self {
+            fixture: config
+                .fixture
+                .clone()
+                .map(|fixture| Fixture::new(fixture, &config)),
+            config,
+            store,
+        }
     }
 
     /// Get the container runtime command ("docker" or "podman").
@@ -25,67 +59,617 @@ impl ContainerExecutor {
         }
     }
 
+    /// `run` flags for the configured GPUs: docker's `--gpus`, or CDI devices
+    /// for podman.
+    fn gpu_args(&self) -> Vec<String> {
+        let Some(ref gpus) = self.config.gpus else {
+            return Vec::new();
+        };
+        let podman = self.config.runtime == Some(ContainerRuntime::Podman);
+        match gpus {
+            GpuSpec::Devices(ids) if podman => ids
+                .iter()
+                .flat_map(|id| ["--device".to_string(), format!("nvidia.com/gpu={}", id)])
+                .collect(),
+            GpuSpec::Devices(ids) => {
+                // docker reads the value as CSV, so a list needs quoting
+                vec!["--gpus".to_string(), format!("\"device={}\"", ids.join(","))]
+            }
+            GpuSpec::All(_) if podman => {
+                vec!["--device".to_string(), "nvidia.com/gpu=all".to_string()]
+            }
+            GpuSpec::All(_) => vec!["--gpus".to_string(), "all".to_string()],
+        }
+    }
+
+    /// `run` flags for the configured network and extra hosts, shared by task
+    /// containers and the snapshots `exec` runs in. A task with sidecar
+    /// services joins their network instead.
+    fn network_args(&self, services_network: Option<&str>) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(network) = services_network.or(self.config.network.as_deref()) {
+            args.push("--network".to_string());
+            args.push(network.to_string());
+        }
+        for host in &self.config.extra_hosts {
+            args.push("--add-host".to_string());
+            args.push(host.clone());
+        }
+        args
+    }
+
     /// Container name for a given task.
     fn container_name(&self, task_id: &TaskId) -> String {
         format!("openclaw-{}-{}", self.config.name, &task_id.0[..8])
     }
 
-    fn local_meta_dir(&self) -> PathBuf {
-        metadata_dir()
+    /// Where a task file `path`, relative to `root`, lives in its container.
+    fn file_path(
+        &self,
+        task_id: &TaskId,
+        path: &str,
+        root: FileRoot,
+    ) -> Result<String, ExecutorError> {
+        let dir = match root {
+            FileRoot::Workspace => {
+                let meta = self.store.load(task_id)?;
+                meta.workspace.unwrap_or_else(|| "/".to_string())
+            }
+            FileRoot::TaskDir => CHANGES_DIR.to_string(),
+        };
+        Ok(resolve_path(path, &dir))
+    }
+
+    /// Network a task shares with its sidecar services.
+    fn services_network(&self, task_id: &TaskId) -> String {
+        format!("{}-services", self.container_name(task_id))
     }
 
-    /// Run a container runtime command and return stdout.
-    async fn run_cmd(&self, args: &[&str]) -> Result<String, ExecutorError> {
-        let runtime = self.runtime_cmd();
-        debug!("Running: {} {}", runtime, args.join(" "));
+    /// The executor's services plus the requested ones, a requested service
+    /// replacing the executor's of the same name.
+    fn task_services(&self, requested: &[ServiceSpec]) -> Vec<ServiceSpec> {
+        let mut services: Vec<ServiceSpec> = self
+            .config
+            .services
+            .iter()
+            .filter(|s| !requested.iter().any(|r| r.name == s.name))
+            .cloned()
+            .collect();
+        services.extend(requested.iter().cloned());
+        services
+    }
+
+    /// Create a task's services network and start its sidecars on it, each
+    /// reachable by its name. Returns the network, or `None` without
+    /// services. Under `network: none` the network is internal, so the task
+    /// reaches its services and nothing else. Sidecars are started, not
+    /// waited on: a task needing one ready should retry its connection.
+    async fn start_services(
+        &self,
+        task_id: &TaskId,
+        services: &[ServiceSpec],
+        cancel: &CancellationToken,
+    ) -> Result<Option<String>, ExecutorError> {
+        if services.is_empty() {
+            return Ok(None);
+        }
+        let network = self.services_network(task_id);
+        let label = format!("{}={}", LABEL_SIDECAR_OF, task_id);
+        let mut args = vec!["network", "create", "--label", label.as_str()];
+        if self.config.network.as_deref() == Some("none") {
+            args.push("--internal");
+        }
+        args.push(&network);
+        self.run_cmd(&args, cancel).await?;
+
+        for service in services {
+            if let Err(e) = self.start_service(task_id, service, &network, cancel).await {
+                // Don't leave half the sidecars running, even if cancelled
+                self.remove_services(task_id, &CancellationToken::new()).await?;
+                return Err(e);
+            }
+        }
+        info!("Started {} service(s) for task {} on {}", services.len(), task_id, network);
+        Ok(Some(network))
+    }
+
+    async fn start_service(
+        &self,
+        task_id: &TaskId,
+        service: &ServiceSpec,
+        network: &str,
+        cancel: &CancellationToken,
+    ) -> Result<(), ExecutorError> {
+        let mut args: Vec<String> = vec![
+            "run".to_string(),
+            "-d".to_string(),
+            "--name".to_string(),
+            format!("{}-{}", self.container_name(task_id), service.name),
+            "--label".to_string(),
+            format!("{}={}", LABEL_SIDECAR_OF, task_id),
+            "--network".to_string(),
+            network.to_string(),
+            "--network-alias".to_string(),
+            service.name.clone(),
+        ];
+        let env = secrets::resolve_env(&service.env)?;
+        args.extend(env_args(&env));
+        args.push(service.image.clone());
+        args.extend(service.command.iter().cloned());
 
-        let output = Command::new(runtime)
-            .args(args)
-            .output()
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        self.run_cmd_env(&args, &env, cancel)
             .await
-            .map_err(|e| {
-                ExecutorError::ContainerRuntime(format!("Failed to run {}: {}", runtime, e))
+            .map_err(|e| match e {
+                ExecutorError::ContainerRuntime(msg) => ExecutorError::ContainerRuntime(format!(
+                    "Failed to start service {}: {}",
+                    service.name, msg
+                )),
+                e => e,
             })?;
+        Ok(())
+    }
+
+    /// Remove a task's sidecars (with their anonymous volumes) and services
+    /// network, ignoring any already gone.
+    async fn remove_services(
+        &self,
+        task_id: &TaskId,
+        cancel: &CancellationToken,
+    ) -> Result<(), ExecutorError> {
+        let filter = format!("label={}={}", LABEL_SIDECAR_OF, task_id);
+        let ids = self
+            .run_cmd_or(&["ps", "-aq", "--filter", &filter], cancel, "")
+            .await?;
+        if !ids.is_empty() {
+            let mut args = vec!["rm", "-f", "-v"];
+            args.extend(ids.lines());
+            self.run_cmd_or(&args, cancel, "").await?;
+        }
+        let network = self.services_network(task_id);
+        self.run_cmd_or(&["network", "rm", &network], cancel, "").await?;
+        Ok(())
+    }
+
+    /// Volume holding the clone for a `--repo` task.
+    fn workspace_volume(&self, task_id: &TaskId) -> String {
+        format!("{}-workspace", self.container_name(task_id))
+    }
+
+    /// Clone a task's repo into its workspace volume with a throwaway
+    /// container, returning the checked-out commit.
+    async fn clone_repo(
+        &self,
+        repo: &RepoSpec,
+        image: &str,
+        volume: &str,
+        cancel: &CancellationToken,
+    ) -> Result<String, ExecutorError> {
+        let script = git::clone_script(repo, REPO_WORKSPACE, &self.config.git);
+        info!("Cloning {} into volume {}", repo.url, volume);
+        let output = self.run_in_volume(image, volume, &[], &script, cancel).await?;
+        git::parse_commit(&output)
+    }
+
+    /// Copy a `workspace_template` directory on the runtime's host into a
+    /// task's workspace volume with a throwaway container.
+    async fn copy_template(
+        &self,
+        template: &str,
+        image: &str,
+        volume: &str,
+        cancel: &CancellationToken,
+    ) -> Result<(), ExecutorError> {
+        let script = workspace::copy_script(TEMPLATE_MOUNT, REPO_WORKSPACE);
+        info!("Copying workspace template {} into volume {}", template, volume);
+        let mount = format!("{}:{}:ro", template, TEMPLATE_MOUNT);
+        let output = self.run_in_volume(image, volume, &[mount], &script, cancel).await?;
+        workspace::parse_copy(&output)
+    }
+
+    /// Run `script` in a throwaway container with `volume` mounted as the
+    /// workspace, plus any extra `mounts`, and return its stdout.
+    async fn run_in_volume(
+        &self,
+        image: &str,
+        volume: &str,
+        mounts: &[String],
+        script: &str,
+        cancel: &CancellationToken,
+    ) -> Result<String, ExecutorError> {
+        let mount = format!("{}:{}", volume, REPO_WORKSPACE);
+        let mut args = vec!["run", "--rm", "-v", &mount];
+        // Fill the volume as root, then hand it to the user tasks run as
+        let script = match self.config.user {
+            Some(ref user) => {
+                args.extend(["--user", "0"]);
+                format!("{}\nchown -R {} {}", script, shell_escape(user), REPO_WORKSPACE)
+            }
+            None => script.to_string(),
+        };
+        if let Some(ref userns) = self.config.userns {
+            args.extend(["--userns", userns]);
+        }
+        for extra in mounts {
+            args.push("-v");
+            args.push(extra);
+        }
+        let env = self.config.resolved_env()?;
+        let env_args = env_args(&env);
+        args.extend(env_args.iter().map(String::as_str));
+        args.extend(["--entrypoint", "sh", image, "-c", &script]);
+
+        self.run_cmd_env(&args, &env, cancel).await
+    }
+
+    /// Run a container runtime command and return stdout, recorded to or
+    /// replayed from the fixture if there is one.
+    #[instrument(
+        skip_all,
+        fields(runtime = self.runtime_cmd(), command = args.first().copied())
+    )]
+    async fn run_cmd(
+        &self,
+        args: &[&str],
+        cancel: &CancellationToken,
+    ) -> Result<String, ExecutorError> {
+        self.run_cmd_env(args, &HashMap::new(), cancel).await
+    }
+
+    /// `run_cmd` with `env` set in the runtime's own environment, for the
+    /// `-e KEY` arguments from [`env_args`] to pick up.
+    async fn run_cmd_env(
+        &self,
+        args: &[&str],
+        env: &HashMap<String, String>,
+        cancel: &CancellationToken,
+    ) -> Result<String, ExecutorError> {
+        let Some(ref fixture) = self.fixture else {
+            return self.run_runtime(args, env, cancel).await;
+        };
+        let command = self.command_line(args);
+        if let Some(replayed) = fixture.replay(&command) {
+            return replayed;
+        }
+        let result = self.run_runtime(args, env, cancel).await;
+        fixture.record(&command, &result);
+        result
+    }
+
+    /// `args` as a runtime command line, quoted for a shell.
+    fn command_line<S: AsRef<str>>(&self, args: &[S]) -> String {
+        std::iter::once(self.runtime_cmd().to_string())
+            .chain(args.iter().map(|arg| shell_word(arg.as_ref())))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// `run_cmd` without the fixture.
+    async fn run_runtime(
+        &self,
+        args: &[&str],
+        env: &HashMap<String, String>,
+        cancel: &CancellationToken,
+    ) -> Result<String, ExecutorError> {
+        let runtime = self.runtime_cmd();
+        debug!("Running: {} {}", runtime, args.join(" "));
+
+        let output = with_cancel(cancel, async {
+            Command::new(runtime)
+                .args(args)
+                .envs(env)
+                .kill_on_drop(true)
+                .output()
+                .await
+                .map_err(|e| spawn_error(runtime, e))
+        })
+        .await?;
 
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
             return Err(ExecutorError::ContainerRuntime(format!(
-                "{} {} failed: {}",
+                "{} {} failed: {}{}",
                 runtime,
                 args.first().unwrap_or(&""),
-                stderr.trim()
+                stderr.trim(),
+                runtime_hint(&stderr)
             )));
         }
 
         Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
     }
-}
 
-#[async_trait::async_trait]
-impl Executor for ContainerExecutor {
-    fn name(&self) -> &str {
-        &self.config.name
-    }
+    /// Run a container runtime command with output going straight to the
+    /// terminal. Returns its exit code.
+    async fn run_cmd_streaming(
+        &self,
+        args: &[&str],
+        env: &HashMap<String, String>,
+        cancel: &CancellationToken,
+    ) -> Result<i32, ExecutorError> {
+        let runtime = self.runtime_cmd();
+        debug!("Running: {} {}", runtime, args.join(" "));
 
-    fn executor_type(&self) -> &str {
-        "container"
+        let status = with_cancel(cancel, async {
+            Command::new(runtime)
+                .args(args)
+                .envs(env)
+                .kill_on_drop(true)
+                .status()
+                .await
+                .map_err(|e| spawn_error(runtime, e))
+        })
+        .await?;
+
+        Ok(status.code().unwrap_or(-1))
     }
 
-    async fn start(&self, request: TaskRequest) -> Result<TaskMetadata, ExecutorError> {
-        let task_id = TaskId::new();
-        let container_name = self.container_name(&task_id);
+    /// The image to run a task in: built from `image_build`, or `image`
+    /// pulled as the `pull` policy asks.
+    async fn task_image(&self, cancel: &CancellationToken) -> Result<String, ExecutorError> {
+        if let Some(ref build) = self.config.image_build {
+            return self.build_image(build, cancel).await;
+        }
         let image = self
             .config
             .image
             .as_deref()
             .ok_or_else(|| ExecutorError::Config("Container executor requires 'image'".into()))?;
+        self.ensure_image(image, cancel).await?;
+        Ok(image.to_string())
+    }
+
+    /// Build `image_build` unless an image of its current context already
+    /// exists, with build output on stderr. Returns the image's tag.
+    async fn build_image(
+        &self,
+        build: &ImageBuild,
+        cancel: &CancellationToken,
+    ) -> Result<String, ExecutorError> {
+        let tag = image::build_tag(build, self.config.image.as_deref(), &self.config.name)
+            .map_err(|e| {
+                ExecutorError::Config(format!("image_build context {}: {}", build.context, e))
+            })?;
+        match self.run_cmd(&["image", "inspect", &tag], cancel).await {
+            Ok(_) => return Ok(tag),
+            Err(ExecutorError::Cancelled) => return Err(ExecutorError::Cancelled),
+            Err(_) => {}
+        }
+
+        let dockerfile = Path::new(&build.context)
+            .join(build.dockerfile.as_deref().unwrap_or("Dockerfile"))
+            .display()
+            .to_string();
+        let runtime = self.runtime_cmd();
+        info!("Building {} from {}", tag, dockerfile);
+        let status = with_cancel(cancel, async {
+            Command::new(runtime)
+                .args(["build", "-t", &tag, "-f", &dockerfile, &build.context])
+                .stdout(std::io::stderr())
+                .kill_on_drop(true)
+                .status()
+                .await
+                .map_err(|e| spawn_error(runtime, e))
+        })
+        .await?;
+        if !status.success() {
+            return Err(ExecutorError::ContainerRuntime(format!(
+                "{} build of {} failed",
+                runtime, dockerfile
+            )));
+        }
+        Ok(tag)
+    }
+
+    /// Make sure `image` is present as the `pull` policy asks, pulling it (after
+    /// logging in to `registry`) with progress on stderr.
+    async fn ensure_image(
+        &self,
+        image: &str,
+        cancel: &CancellationToken,
+    ) -> Result<(), ExecutorError> {
+        let policy = self.config.pull.unwrap_or_default();
+        if policy != PullPolicy::Always {
+            match self.run_cmd(&["image", "inspect", image], cancel).await {
+                Ok(_) => return Ok(()),
+                Err(ExecutorError::Cancelled) => return Err(ExecutorError::Cancelled),
+                Err(_) if policy == PullPolicy::Never => {
+                    return Err(ExecutorError::ContainerRuntime(format!(
+                        "image {} is not present locally and pull is 'never'; run \
+                         `{} pull {}`, or set `pull: missing`",
+                        image,
+                        self.runtime_cmd(),
+                        image
+                    )));
+                }
+                Err(_) => {}
+            }
+        }
+
+        self.login(cancel).await?;
 
-        // Build docker/podman run command
+        let runtime = self.runtime_cmd();
+        info!("Pulling {}", image);
+        let status = with_cancel(cancel, async {
+            Command::new(runtime)
+                .args(["pull", image])
+                .stdout(std::io::stderr())
+                .kill_on_drop(true)
+                .status()
+                .await
+                .map_err(|e| spawn_error(runtime, e))
+        })
+        .await?;
+        if !status.success() {
+            return Err(ExecutorError::ContainerRuntime(format!(
+                "{} pull {} failed; check the image name and tag, set `registry` if the \
+                 image is private, or build it with `image_build`",
+                runtime, image
+            )));
+        }
+        Ok(())
+    }
+
+    /// Log in to the configured registry, passing the password on stdin.
+    async fn login(&self, cancel: &CancellationToken) -> Result<(), ExecutorError> {
+        let Some(ref registry) = self.config.registry else {
+            return Ok(());
+        };
+        let password = registry.password()?;
+        let mut args = vec!["login", "--username", &registry.username, "--password-stdin"];
+        if let Some(ref server) = registry.server {
+            args.push(server);
+        }
+
+        let runtime = self.runtime_cmd();
+        debug!("Running: {} {}", runtime, args.join(" "));
+        let output = with_cancel(cancel, async {
+            let spawn_error = |e| spawn_error(runtime, e);
+            let mut child = Command::new(runtime)
+                .args(&args)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .kill_on_drop(true)
+                .spawn()
+                .map_err(spawn_error)?;
+            if let Some(mut stdin) = child.stdin.take() {
+                stdin.write_all(password.as_bytes()).await.map_err(spawn_error)?;
+            }
+            child.wait_with_output().await.map_err(spawn_error)
+        })
+        .await?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(ExecutorError::ContainerRuntime(format!(
+                "{} login failed: {}{}",
+                runtime,
+                stderr.trim(),
+                runtime_hint(&stderr)
+            )));
+        }
+        Ok(())
+    }
+
+    /// Run a command with stdout captured, returning its exit code and stdout.
+    async fn run_cmd_capture(
+        &self,
+        args: &[&str],
+        env: &HashMap<String, String>,
+        cancel: &CancellationToken,
+    ) -> Result<(i32, String), ExecutorError> {
+        let runtime = self.runtime_cmd();
+        debug!("Running: {} {}", runtime, args.join(" "));
+
+        let output = with_cancel(cancel, async {
+            Command::new(runtime)
+                .args(args)
+                .envs(env)
+                .kill_on_drop(true)
+                .output()
+                .await
+                .map_err(|e| spawn_error(runtime, e))
+        })
+        .await?;
+
+        Ok((
+            output.status.code().unwrap_or(-1),
+            String::from_utf8_lossy(&output.stdout).into_owned(),
+        ))
+    }
+
+    async fn run_exec(
+        &self,
+        args: &[&str],
+        env: &HashMap<String, String>,
+        capture: bool,
+        cancel: &CancellationToken,
+    ) -> Result<(i32, String), ExecutorError> {
+        if capture {
+            self.run_cmd_capture(args, env, cancel).await
+        } else {
+            Ok((self.run_cmd_streaming(args, env, cancel).await?, String::new()))
+        }
+    }
+
+    /// Run `sh -c command` in a task's container, or in a snapshot of it if it
+    /// has exited. Output is streamed, or captured and returned if `capture`.
+    async fn exec_in_task(
+        &self,
+        task_id: &TaskId,
+        command: &str,
+        capture: bool,
+        cancel: &CancellationToken,
+    ) -> Result<(i32, String), ExecutorError> {
+        let meta = self.store.load(task_id)?;
+        let container_name = self.container_name(task_id);
+
+        let mut options: Vec<String> = Vec::new();
+        if let Some(ref workspace) = meta.workspace {
+            options.push("-w".to_string());
+            options.push(workspace.clone());
+        }
+        let env = self.config.resolved_env()?;
+        options.extend(env_args(&env));
+        let options: Vec<&str> = options.iter().map(String::as_str).collect();
+
+        let running = self
+            .run_cmd(
+                &["inspect", "--format", "{{.State.Running}}", &container_name],
+                cancel,
+            )
+            .await?;
+
+        if running == "true" {
+            let mut args = vec!["exec"];
+            args.extend(&options);
+            args.extend([container_name.as_str(), "sh", "-c", command]);
+            return self.run_exec(&args, &env, capture, cancel).await;
+        }
+
+        // A stopped container can't be exec'd into: run the command in a
+        // snapshot of it instead, sharing its volumes
+        let snapshot = format!("{}-exec", container_name).to_lowercase();
+        info!("Container {} has exited, running in snapshot {}", container_name, snapshot);
+        self.run_cmd(&["commit", &container_name, &snapshot], cancel).await?;
+
+        // Rejoin the task's services, which keep running until cleanup
+        let services_network = (!self.task_services(&meta.services).is_empty())
+            .then(|| self.services_network(task_id));
+        let network = self.network_args(services_network.as_deref());
+        let mut args = vec!["run", "--rm", "--volumes-from", container_name.as_str()];
+        if let Some(ref userns) = self.config.userns {
+            args.extend(["--userns", userns]);
+        }
+        args.extend(network.iter().map(String::as_str));
+        args.extend(&options);
+        args.extend(["--entrypoint", "sh", snapshot.as_str(), "-c", command]);
+        let result = self.run_exec(&args, &env, capture, cancel).await;
+
+        self.run_cmd_or(&["rmi", &snapshot], cancel, "").await?;
+        result
+    }
+
+    /// The `run` arguments up to the workspace: name, labels, mounts, limits,
+    /// networks and env.
+    fn run_args(
+        &self,
+        task_id: &TaskId,
+        request: &TaskRequest,
+        services_network: Option<&str>,
+        env: &HashMap<String, String>,
+    ) -> Vec<String> {
         let mut args: Vec<String> = vec![
             "run".to_string(),
             "-d".to_string(),
             "--name".to_string(),
-            container_name.clone(),
+            self.container_name(task_id),
+            "--label".to_string(),
+            format!("{}={}", LABEL_EXECUTOR, self.config.name),
+            "--label".to_string(),
+            format!("{}={}", LABEL_TASK_ID, task_id),
+            "--label".to_string(),
+            format!("{}={}", LABEL_TASK_TYPE, request.payload.type_str()),
         ];
 
         // Mount volumes
@@ -94,21 +678,62 @@ impl Executor for ContainerExecutor {
             args.push(vol.clone());
         }
 
-        // Set environment variables
-        for (key, val) in &self.config.env {
-            args.push("-e".to_string());
-            args.push(format!("{}={}", key, val));
+        // Run as a host user, so files written to mounts aren't left to root
+        if let Some(ref user) = self.config.user {
+            args.push("--user".to_string());
+            args.push(user.clone());
+        }
+        if let Some(ref userns) = self.config.userns {
+            args.push("--userns".to_string());
+            args.push(userns.clone());
         }
 
-        // Set workspace directory
-        if let Some(ref workspace) = request.workspace {
-            args.push("-w".to_string());
-            args.push(workspace.clone());
+        // Resource limits, so one runaway task can't starve the host
+        if let Some(cpus) = self.config.cpus {
+            args.push("--cpus".to_string());
+            args.push(cpus.to_string());
+        }
+        if let Some(ref memory) = self.config.memory {
+            args.push("--memory".to_string());
+            args.push(memory.clone());
+        }
+        if let Some(pids) = self.config.pids_limit {
+            args.push("--pids-limit".to_string());
+            args.push(pids.to_string());
+        }
+        args.extend(self.gpu_args());
+        for device in &self.config.devices {
+            args.push("--device".to_string());
+            args.push(device.clone());
         }
 
-        args.push(image.to_string());
+        args.extend(self.network_args(services_network));
+        let podman = self.config.runtime == Some(ContainerRuntime::Podman);
+        if let (true, Some(network)) = (podman, self.also_join(services_network)) {
+            args.push("--network".to_string());
+            args.push(network.to_string());
+        }
+        for port in &self.config.publish {
+            args.push("--publish".to_string());
+            args.push(port.clone());
+        }
+
+        // Set environment variables
+        args.extend(env_args(env));
+        args
+    }
+
+    /// A named network the task joins as well as its services network:
+    /// podman takes both up front, docker connects after starting.
+    fn also_join(&self, services_network: Option<&str>) -> Option<&str> {
+        match self.config.network.as_deref() {
+            Some(network) if services_network.is_some() && network != "none" => Some(network),
+            _ => None,
+        }
+    }
 
-        // Build the command inside the container based on payload type
+    /// The command run inside a task's container, from its payload.
+    fn container_command(&self, task_id: &TaskId, request: &TaskRequest) -> String {
         let inner_cmd = match &request.payload {
             TaskPayload::ClaudeCode {
                 prompt,
@@ -117,8 +742,9 @@ impl Executor for ContainerExecutor {
             } => {
                 let claude_bin = self.config.claude_binary();
                 let mut cmd = format!(
-                    "{} --print --output-format json -p {}",
+                    "{} --print {} -p {}",
                     claude_bin,
+                    self.config.claude_output_args(),
                     shell_escape(prompt)
                 );
 
@@ -136,25 +762,155 @@ impl Executor for ContainerExecutor {
                 command.clone()
             }
         };
+        let inner_cmd = match self.config.activate.as_deref() {
+            Some(activate) => activate::wrap(activate, &inner_cmd),
+            None => inner_cmd,
+        };
+        let inner_cmd = match nix::for_request(&self.config, request) {
+            Some(shell) => nix::wrap(shell, &inner_cmd),
+            None => inner_cmd,
+        };
 
-        args.push("sh".to_string());
-        args.push("-c".to_string());
-        args.push(inner_cmd);
+        // The container can't be reached before it runs, so the task's branch
+        // and diff are made inside it, around the command
+        if request.git_branch {
+            format!(
+                "mkdir -p {dir}\n{branch}\nsh -c {cmd}\ncode=$?\n{changes}\nexit $code",
+                dir = CHANGES_DIR,
+                branch = git::branch_script(task_id, CHANGES_DIR),
+                cmd = shell_escape(&inner_cmd),
+                changes = git::changes_script(CHANGES_DIR),
+            )
+        } else {
+            inner_cmd
+        }
+    }
 
-        let args_refs: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
-        let container_id = self.run_cmd(&args_refs).await?;
+    /// Start a task's container from its `run -d` arguments, directly or as a
+    /// systemd unit, and return the PID of its main process (0 if unknown).
+    /// `env` holds the values of the arguments' `-e KEY`s.
+    async fn launch(
+        &self,
+        container_name: &str,
+        mut args: Vec<String>,
+        env: &HashMap<String, String>,
+        cancel: &CancellationToken,
+    ) -> Result<u32, ExecutorError> {
+        if self.config.systemd {
+            // The unit runs the container in the foreground
+            if args.get(1).map(String::as_str) == Some("-d") {
+                args.remove(1);
+            }
+            let unit = systemd::unit_name(container_name);
+            systemd::start(&unit, self.runtime_cmd(), &args, env, cancel).await?;
+            return systemd::main_pid(&unit, cancel).await;
+        }
 
-        info!(
-            "Task {} started in container {} ({})",
-            task_id, container_name, &container_id[..12]
-        );
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        let container_id = self.run_cmd_env(&args, env, cancel).await?;
+        debug!("Container {} is {}", container_name, container_id);
 
-        // Get the PID of the main process inside the container
-        let pid_str = self
-            .run_cmd(&["inspect", "--format", "{{.State.Pid}}", &container_name])
-            .await
-            .unwrap_or_else(|_| "0".to_string());
-        let pid: u32 = pid_str.trim().parse().unwrap_or(0);
+        let pid = self
+            .run_cmd_or(
+                &["inspect", "--format", "{{.State.Pid}}", container_name],
+                cancel,
+                "0",
+            )
+            .await?;
+        Ok(pid.trim().parse().unwrap_or(0))
+    }
+
+    /// Like `run_cmd`, but substitutes `fallback` for any failure other than cancellation.
+    async fn run_cmd_or(
+        &self,
+        args: &[&str],
+        cancel: &CancellationToken,
+        fallback: &str,
+    ) -> Result<String, ExecutorError> {
+        match self.run_cmd(args, cancel).await {
+            Err(ExecutorError::Cancelled) => Err(ExecutorError::Cancelled),
+            Err(_) => Ok(fallback.to_string()),
+            ok => ok,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Executor for ContainerExecutor {
+    fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    fn executor_type(&self) -> &str {
+        "container"
+    }
+
+    #[instrument(
+        skip_all,
+        fields(executor = %self.config.name, payload = request.payload.type_str())
+    )]
+    async fn start(
+        &self,
+        mut request: TaskRequest,
+        cancel: &CancellationToken,
+    ) -> Result<TaskMetadata, ExecutorError> {
+        let task_id = TaskId::new();
+        let container_name = self.container_name(&task_id);
+        let image = self.task_image(cancel).await?;
+
+        let services = self.task_services(&request.services);
+        let services_network = (!services.is_empty()).then(|| self.services_network(&task_id));
+        let env = self.config.resolved_env()?;
+        let mut args = self.run_args(&task_id, &request, services_network.as_deref(), &env);
+
+        // A --repo task works in a fresh clone on a volume of its own, and a
+        // task started from a workspace_template in a fresh copy
+        let template =
+            workspace::apply_template(self.config.workspace_template.as_deref(), &mut request);
+        let volume = self.workspace_volume(&task_id);
+        let (workspace, commit) = match (&request.repo, template) {
+            (Some(repo), _) => {
+                let commit = self.clone_repo(repo, &image, &volume, cancel).await?;
+                args.push("-v".to_string());
+                args.push(format!("{}:{}", volume, REPO_WORKSPACE));
+                (Some(REPO_WORKSPACE.to_string()), Some(commit))
+            }
+            (None, Some(template)) => {
+                self.copy_template(&template, &image, &volume, cancel).await?;
+                args.push("-v".to_string());
+                args.push(format!("{}:{}", volume, REPO_WORKSPACE));
+                (Some(REPO_WORKSPACE.to_string()), None)
+            }
+            (None, None) => (request.workspace.clone(), None),
+        };
+
+        // Set workspace directory
+        if let Some(ref workspace) = workspace {
+            args.push("-w".to_string());
+            args.push(workspace.clone());
+        }
+
+        args.push(image.to_string());
+        args.push("sh".to_string());
+        args.push("-c".to_string());
+        args.push(self.container_command(&task_id, &request));
+
+        self.start_services(&task_id, &services, cancel).await?;
+        let pid = match self.launch(&container_name, args, &env, cancel).await {
+            Ok(pid) => pid,
+            Err(e) => {
+                self.remove_services(&task_id, &CancellationToken::new()).await?;
+                return Err(e);
+            }
+        };
+        let podman = self.config.runtime == Some(ContainerRuntime::Podman);
+        if let (false, Some(network)) = (podman, self.also_join(services_network.as_deref())) {
+            let connect = ["network", "connect", network, container_name.as_str()];
+            if let Err(e) = self.run_cmd(&connect, cancel).await {
+                warn!("Task {} could not join network {}: {}", task_id, network, e);
+            }
+        }
+        info!("Task {} started in container {}", task_id, container_name);
 
         let mut meta = TaskMetadata::new(
             task_id.clone(),
@@ -162,53 +918,181 @@ impl Executor for ContainerExecutor {
             "container".to_string(),
             request.payload.type_str().to_string(),
             request.payload.description().to_string(),
-            request.workspace,
+            workspace,
         );
+        meta.inherit_from(&request);
+        meta.commit = commit;
+        if request.git_branch {
+            meta.git_branch = Some(git::branch_name(&task_id));
+        }
         meta.mark_running(pid);
 
-        let local_dir = self.local_meta_dir();
-        std::fs::create_dir_all(&local_dir)?;
-        meta.write_to_dir(&local_dir)?;
+        self.store.put(&meta)?;
 
         Ok(meta)
     }
 
-    async fn status(&self, task_id: &TaskId) -> Result<TaskMetadata, ExecutorError> {
-        let local_dir = self.local_meta_dir();
-        let local_path = local_dir.join(format!("{}.meta.json", task_id));
+    fn plan_start(&self, mut request: TaskRequest) -> Result<StartPlan, ExecutorError> {
+        let task_id = TaskId::new();
+        let mut steps = Vec::new();
+        let image = match self.config.image_build {
+            Some(ref build) => {
+                let tag = image::build_tag(build, self.config.image.as_deref(), &self.config.name)
+                    .map_err(|e| {
+                        ExecutorError::Config(format!(
+                            "image_build context {}: {}",
+                            build.context, e
+                        ))
+                    })?;
+                steps.push(format!("Build {} from {}, unless it exists", tag, build.context));
+                tag
+            }
+            None => self.config.image.clone().ok_or_else(|| {
+                ExecutorError::Config("Container executor requires 'image'".into())
+            })?,
+        };
+
+        let services = self.task_services(&request.services);
+        let services_network = (!services.is_empty()).then(|| self.services_network(&task_id));
+        if let Some(ref network) = services_network {
+            for service in &services {
+                steps.push(format!(
+                    "Start service {} ({}) on {}",
+                    service.name, service.image, network
+                ));
+            }
+        }
+        // Only env names reach the arguments, so nothing needs resolving
+        let mut args = self.run_args(
+            &task_id,
+            &request,
+            services_network.as_deref(),
+            &self.config.env,
+        );
 
-        let mut meta = if local_path.exists() {
-            TaskMetadata::read_from_file(&local_path)?
+        let template =
+            workspace::apply_template(self.config.workspace_template.as_deref(), &mut request);
+        let volume = self.workspace_volume(&task_id);
+        let fresh = match (&request.repo, template) {
+            (Some(repo), _) => {
+                steps.push(format!("Clone {} into volume {}", repo.url, volume));
+                true
+            }
+            (None, Some(template)) => {
+                steps.push(format!("Copy workspace template {} into volume {}", template, volume));
+                true
+            }
+            (None, None) => false,
+        };
+        let workspace = if fresh {
+            args.push("-v".to_string());
+            args.push(format!("{}:{}", volume, REPO_WORKSPACE));
+            Some(REPO_WORKSPACE.to_string())
         } else {
-            return Err(ExecutorError::TaskNotFound(task_id.to_string()));
+            request.workspace.clone()
         };
+        if let Some(ref workspace) = workspace {
+            args.push("-w".to_string());
+            args.push(workspace.clone());
+        }
+        args.push(image);
+        args.push("sh".to_string());
+        args.push("-c".to_string());
+        args.push(self.container_command(&task_id, &request));
 
-        if meta.status == TaskStatus::Running {
+        if self.config.systemd {
+            args.remove(1);
+            let unit = systemd::unit_name(&self.container_name(&task_id));
+            steps.push(format!("Run the container in the foreground as systemd unit {}", unit));
+        }
+        let command = self.command_line(&args);
+
+        let mut metadata = TaskMetadata::new(
+            task_id.clone(),
+            self.config.name.clone(),
+            "container".to_string(),
+            request.payload.type_str().to_string(),
+            request.payload.description().to_string(),
+            workspace,
+        );
+        metadata.inherit_from(&request);
+        if request.git_branch {
+            metadata.git_branch = Some(git::branch_name(&task_id));
+        }
+        Ok(StartPlan {
+            steps,
+            command,
+            metadata,
+        })
+    }
+
+    #[instrument(skip_all, fields(executor = %self.config.name, task_id = %task_id))]
+    async fn status(
+        &self,
+        task_id: &TaskId,
+        cancel: &CancellationToken,
+    ) -> Result<TaskMetadata, ExecutorError> {
+        let mut meta = self.store.load(task_id)?;
+
+        // Never recorded as running: the container tells whether it launched
+        if meta.status == TaskStatus::Starting {
+            let container_name = self.container_name(task_id);
+            let pid = self
+                .run_cmd_or(
+                    &["inspect", "--format", "{{.State.Pid}}", &container_name],
+                    cancel,
+                    "",
+                )
+                .await?;
+            match pid.trim().parse() {
+                Ok(pid) => meta.mark_running(pid),
+                Err(_) => {
+                    meta.mark_failed(format!("Container {} was never created", container_name))
+                }
+            }
+            self.store.put(&meta)?;
+        }
+
+        if meta.status == TaskStatus::Running && self.config.systemd {
+            let unit = systemd::unit_name(&self.container_name(task_id));
+            match systemd::state(&unit, cancel).await? {
+                UnitState::Running => {}
+                UnitState::Exited(exit_code) => {
+                    meta.mark_completed(exit_code);
+                    self.store.put(&meta)?;
+                }
+                UnitState::Missing => {
+                    meta.mark_failed(format!("systemd unit {} not found", unit));
+                    self.store.put(&meta)?;
+                }
+            }
+        } else if meta.status == TaskStatus::Running {
             let container_name = self.container_name(task_id);
             let state = self
-                .run_cmd(&["inspect", "--format", "{{.State.Status}}", &container_name])
-                .await
-                .unwrap_or_else(|_| "unknown".to_string());
+                .run_cmd_or(
+                    &["inspect", "--format", "{{.State.Status}}", &container_name],
+                    cancel,
+                    "unknown",
+                )
+                .await?;
 
             match state.trim() {
                 "running" => {} // still running
                 "exited" => {
                     let exit_str = self
-                        .run_cmd(&[
-                            "inspect",
-                            "--format",
-                            "{{.State.ExitCode}}",
-                            &container_name,
-                        ])
-                        .await
-                        .unwrap_or_else(|_| "1".to_string());
+                        .run_cmd_or(
+                            &["inspect", "--format", "{{.State.ExitCode}}", &container_name],
+                            cancel,
+                            "1",
+                        )
+                        .await?;
                     let exit_code: i32 = exit_str.trim().parse().unwrap_or(1);
                     meta.mark_completed(exit_code);
-                    meta.write_to_dir(&local_dir)?;
+                    self.store.put(&meta)?;
                 }
                 _ => {
                     meta.mark_failed(format!("Container in unexpected state: {}", state.trim()));
-                    meta.write_to_dir(&local_dir)?;
+                    self.store.put(&meta)?;
                 }
             }
         }
@@ -216,47 +1100,359 @@ impl Executor for ContainerExecutor {
         Ok(meta)
     }
 
-    async fn logs(&self, task_id: &TaskId, lines: usize) -> Result<Vec<String>, ExecutorError> {
+    #[instrument(skip_all, fields(executor = %self.config.name, task_id = %task_id))]
+    async fn logs(
+        &self,
+        task_id: &TaskId,
+        lines: usize,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<String>, ExecutorError> {
         let container_name = self.container_name(task_id);
-        let output = self
-            .run_cmd(&["logs", "--tail", &lines.to_string(), &container_name])
-            .await?;
+        if self.config.systemd {
+            return systemd::logs(&systemd::unit_name(&container_name), lines, cancel).await;
+        }
+        let lines = lines.to_string();
+        let mut args = vec!["logs", "--tail", &lines, &container_name];
+        if self.config.log_timestamps {
+            args.insert(1, "--timestamps");
+        }
+        let output = self.run_cmd(&args, cancel).await?;
 
         Ok(output.lines().map(|l| l.to_string()).collect())
     }
 
-    async fn kill(&self, task_id: &TaskId) -> Result<(), ExecutorError> {
+    #[instrument(skip_all, fields(executor = %self.config.name, task_id = %task_id))]
+    async fn log_size(
+        &self,
+        task_id: &TaskId,
+        cancel: &CancellationToken,
+    ) -> Result<u64, ExecutorError> {
+        let container_name = self.container_name(task_id);
+        if self.config.systemd {
+            return systemd::log_size(&systemd::unit_name(&container_name), cancel).await;
+        }
+        let output = self.run_cmd(&["logs", &container_name], cancel).await?;
+        Ok(output.len() as u64)
+    }
+
+    #[instrument(skip_all, fields(executor = %self.config.name, task_id = %task_id))]
+    async fn kill(&self, task_id: &TaskId, cancel: &CancellationToken) -> Result<(), ExecutorError> {
         let container_name = self.container_name(task_id);
-        warn!("Killing container {} for task {}", container_name, task_id);
-        self.run_cmd(&["kill", &container_name]).await?;
+        let grace_secs = self.config.kill_grace_secs().to_string();
+        warn!(
+            "Stopping container {} for task {} (SIGKILL after {}s)",
+            container_name, task_id, grace_secs
+        );
+        self.run_cmd(&["stop", "-t", &grace_secs, &container_name], cancel)
+            .await?;
+        if self.config.systemd {
+            systemd::stop(&systemd::unit_name(&container_name), cancel).await?;
+        }
 
-        let local_dir = self.local_meta_dir();
-        let local_path = local_dir.join(format!("{}.meta.json", task_id));
-        if local_path.exists() {
-            let mut meta = TaskMetadata::read_from_file(&local_path)?;
-            meta.mark_killed();
-            meta.write_to_dir(&local_dir)?;
+        // `stop` sends SIGKILL once the grace period is up: exit code 128 + 9
+        let exit_code = self
+            .run_cmd_or(
+                &["inspect", "--format", "{{.State.ExitCode}}", &container_name],
+                cancel,
+                "",
+            )
+            .await?;
+        let signal = match exit_code.trim() {
+            "" => None,
+            "137" => Some(KillSignal::Kill),
+            _ => Some(KillSignal::Term),
+        };
+
+        if let Some(mut meta) = self.store.get(task_id)? {
+            meta.mark_killed(signal);
+            self.store.put(&meta)?;
         }
 
         Ok(())
     }
 
-    async fn cleanup(&self, task_id: &TaskId) -> Result<(), ExecutorError> {
+    #[instrument(skip_all, fields(executor = %self.config.name, task_id = %task_id))]
+    async fn cleanup(
+        &self,
+        task_id: &TaskId,
+        cancel: &CancellationToken,
+    ) -> Result<(), ExecutorError> {
         let container_name = self.container_name(task_id);
         info!("Cleaning up container {} for task {}", container_name, task_id);
 
         // Stop + remove, ignore errors if already stopped/removed
-        let _ = self.run_cmd(&["rm", "-f", &container_name]).await;
+        if self.config.systemd {
+            systemd::remove(&systemd::unit_name(&container_name), cancel).await?;
+        }
+        if let Err(ExecutorError::Cancelled) =
+            self.run_cmd(&["rm", "-f", &container_name], cancel).await
+        {
+            return Err(ExecutorError::Cancelled);
+        }
+        let volume = self.workspace_volume(task_id);
+        if let Err(ExecutorError::Cancelled) =
+            self.run_cmd(&["volume", "rm", "-f", &volume], cancel).await
+        {
+            return Err(ExecutorError::Cancelled);
+        }
+        self.remove_services(task_id, cancel).await?;
+
+        self.store.delete(task_id)?;
+
+        Ok(())
+    }
 
-        let local_path = self
-            .local_meta_dir()
-            .join(format!("{}.meta.json", task_id));
-        if local_path.exists() {
-            std::fs::remove_file(local_path)?;
+    #[instrument(skip_all, fields(executor = %self.config.name, task_id = %task_id))]
+    async fn changes(
+        &self,
+        task_id: &TaskId,
+        cancel: &CancellationToken,
+    ) -> Result<TaskChanges, ExecutorError> {
+        let meta = self.store.load(task_id)?;
+        if meta.git_branch.is_none() {
+            return Err(ExecutorError::Git(format!("task {} has no branch", task_id)));
         }
 
+        // `cp` works on stopped containers too
+        let local_dir = std::env::temp_dir().join(format!("openclaw-changes-{}", task_id));
+        let source = format!("{}:{}/.", self.container_name(task_id), CHANGES_DIR);
+        std::fs::create_dir_all(&local_dir)?;
+        let copied = self
+            .run_cmd(&["cp", &source, &local_dir.display().to_string()], cancel)
+            .await;
+
+        let changes = copied.map(|_| TaskChanges {
+            files: std::fs::read_to_string(local_dir.join("changed_files.txt"))
+                .unwrap_or_default()
+                .lines()
+                .map(String::from)
+                .collect(),
+            diff: std::fs::read_to_string(local_dir.join("changes.diff")).unwrap_or_default(),
+        });
+        std::fs::remove_dir_all(&local_dir).ok();
+        changes
+    }
+
+    #[instrument(skip_all, fields(executor = %self.config.name, task_id = %task_id))]
+    async fn download(
+        &self,
+        task_id: &TaskId,
+        path: &str,
+        root: FileRoot,
+        dest: &Path,
+        cancel: &CancellationToken,
+    ) -> Result<(), ExecutorError> {
+        let source = format!(
+            "{}:{}",
+            self.container_name(task_id),
+            self.file_path(task_id, path, root)?
+        );
+        // `cp` works on stopped containers too
+        self.run_cmd(&["cp", &source, &dest.display().to_string()], cancel)
+            .await?;
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(executor = %self.config.name, task_id = %task_id))]
+    async fn upload(
+        &self,
+        task_id: &TaskId,
+        source: &Path,
+        path: &str,
+        root: FileRoot,
+        cancel: &CancellationToken,
+    ) -> Result<(), ExecutorError> {
+        let container_name = self.container_name(task_id);
+        let dest = self.file_path(task_id, path, root)?;
+        // Only a running container can create the parent; `cp` reports it missing otherwise
+        if let Some(parent) = Path::new(&dest).parent() {
+            let parent = parent.display().to_string();
+            let mkdir = self
+                .run_cmd(&["exec", &container_name, "mkdir", "-p", &parent], cancel)
+                .await;
+            if let Err(ExecutorError::Cancelled) = mkdir {
+                return Err(ExecutorError::Cancelled);
+            }
+        }
+        let target = format!("{}:{}", container_name, dest);
+        self.run_cmd(&["cp", &source.display().to_string(), &target], cancel)
+            .await?;
         Ok(())
     }
+
+    #[instrument(skip_all, fields(executor = %self.config.name))]
+    async fn list_remote(
+        &self,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<RemoteTask>, ExecutorError> {
+        let filter = format!("label={}={}", LABEL_EXECUTOR, self.config.name);
+        let format = format!(
+            "{{{{.Label \"{}\"}}}}\t{{{{.Label \"{}\"}}}}",
+            LABEL_TASK_ID, LABEL_TASK_TYPE
+        );
+        let output = self
+            .run_cmd(&["ps", "-a", "--filter", &filter, "--format", &format], cancel)
+            .await?;
+
+        // Containers only carry their labels, so adopted tasks get a placeholder description
+        let tasks = output
+            .lines()
+            .filter_map(|line| {
+                let (id, task_type) = line.split_once('\t')?;
+                let task_id = TaskId::from_string(id.trim().to_string());
+                let mut meta = TaskMetadata::new(
+                    task_id.clone(),
+                    self.config.name.clone(),
+                    "container".to_string(),
+                    task_type.trim().to_string(),
+                    format!("(adopted from container {})", self.container_name(&task_id)),
+                    None,
+                );
+                meta.mark_running(0);
+                Some(RemoteTask {
+                    task_id,
+                    metadata: Some(meta),
+                })
+            })
+            .collect();
+
+        Ok(tasks)
+    }
+
+    #[instrument(skip_all, fields(executor = %self.config.name, task_id = %task_id))]
+    async fn exec(
+        &self,
+        task_id: &TaskId,
+        command: &str,
+        cancel: &CancellationToken,
+    ) -> Result<i32, ExecutorError> {
+        let (code, _) = self.exec_in_task(task_id, command, false, cancel).await?;
+        Ok(code)
+    }
+
+    #[instrument(skip_all, fields(executor = %self.config.name, task_id = %task_id))]
+    async fn exec_output(
+        &self,
+        task_id: &TaskId,
+        command: &str,
+        cancel: &CancellationToken,
+    ) -> Result<(i32, String), ExecutorError> {
+        // Merge stderr into stdout inside the shell so the output stays in order
+        let command = format!("exec 2>&1\n{}", command);
+        self.exec_in_task(task_id, &command, true, cancel).await
+    }
+
+    #[instrument(skip_all, fields(executor = %self.config.name))]
+    async fn host_stats(
+        &self,
+        dir: Option<&str>,
+        cancel: &CancellationToken,
+    ) -> Result<Option<HostStats>, ExecutorError> {
+        // Containers run on this machine; without a mounted workspace their
+        // files live under the runtime's storage root
+        let dir = match dir {
+            Some(dir) => dir.to_string(),
+            None => {
+                let format = match self.config.runtime {
+                    Some(ContainerRuntime::Podman) => "{{.Store.GraphRoot}}",
+                    _ => "{{.DockerRootDir}}",
+                };
+                let root = self.run_cmd_or(&["info", "--format", format], cancel, "/").await?;
+                root.trim().to_string()
+            }
+        };
+        let output = with_cancel(cancel, async {
+            Command::new("sh")
+                .arg("-c")
+                .arg(host::stats_script_for(&dir))
+                .kill_on_drop(true)
+                .output()
+                .await
+                .map_err(|e| ExecutorError::Process(format!("Failed to spawn sh: {}", e)))
+        })
+        .await?;
+        Ok(host::parse_stats(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    #[instrument(skip_all, fields(executor = %self.config.name, task_id = %task_id))]
+    async fn usage(
+        &self,
+        task_id: &TaskId,
+        cancel: &CancellationToken,
+    ) -> Result<Option<ResourceUsage>, ExecutorError> {
+        // The stored PID is the host's; the container's cgroup covers its
+        // whole process tree anyway
+        let (_, output) = self.exec_output(task_id, usage::CGROUP_SCRIPT, cancel).await?;
+        Ok(usage::parse_usage(&output))
+    }
+}
+
+/// Labels set on every task container, used to find them again in `list_remote`.
+const LABEL_EXECUTOR: &str = "openclaw.executor";
+const LABEL_TASK_ID: &str = "openclaw.task_id";
+const LABEL_TASK_TYPE: &str = "openclaw.task_type";
+/// Marks a sidecar service with the task it serves. Sidecars carry no
+/// executor label, so they aren't listed as tasks.
+const LABEL_SIDECAR_OF: &str = "openclaw.sidecar_of";
+/// Where a `--repo` task's workspace volume is mounted.
+const REPO_WORKSPACE: &str = "/workspace";
+
+/// Where a `workspace_template` directory is mounted while it's copied.
+const TEMPLATE_MOUNT: &str = "/template";
+/// Where a `git_branch` task's base commit and diff are kept in the container.
+const CHANGES_DIR: &str = "/tmp/openclaw";
+
+/// A runtime command that could not be run, with what to check.
+fn spawn_error(runtime: &str, e: std::io::Error) -> ExecutorError {
+    let hint = if e.kind() == std::io::ErrorKind::NotFound {
+        format!(
+            "; is {} installed and on PATH? (`runtime` picks docker or podman)",
+            runtime
+        )
+    } else {
+        String::new()
+    };
+    ExecutorError::ContainerRuntime(format!("Failed to run {}: {}{}", runtime, e, hint))
+}
+
+/// What to do about a runtime command that failed with `stderr`, for the
+/// failures new setups hit most; empty if there's nothing to suggest.
+fn runtime_hint(stderr: &str) -> String {
+    let stderr = stderr.to_lowercase();
+    if stderr.contains("cannot connect to the docker daemon") {
+        "; start the daemon (e.g. `sudo systemctl start docker`) or check DOCKER_HOST".into()
+    } else if stderr.contains("permission denied") && stderr.contains(".sock") {
+        "; add your user to the docker group (`sudo usermod -aG docker $USER`, then log in \
+         again)"
+            .into()
+    } else if stderr.contains("pull access denied") || stderr.contains("unauthorized") {
+        "; set `registry` credentials on the executor if the image is private".into()
+    } else if stderr.contains("manifest unknown") || stderr.contains("no such image") {
+        "; check the image name and tag, or build it with `image_build`".into()
+    } else {
+        String::new()
+    }
+}
+
+/// `-e KEY` for each of `env`, in key order. Values stay off the runtime's
+/// command line, where any user on the host could read them: it takes them
+/// from its own environment, set by `run_cmd_env`.
+fn env_args(env: &HashMap<String, String>) -> Vec<String> {
+    let mut keys: Vec<&String> = env.keys().collect();
+    keys.sort_unstable();
+    keys.into_iter()
+        .flat_map(|key| ["-e".to_string(), key.clone()])
+        .collect()
+}
+
+/// `arg` as one shell word, quoted only if it needs to be.
+fn shell_word(arg: &str) -> String {
+    let plain = |c: char| c.is_ascii_alphanumeric() || "-_=./:,@%+".contains(c);
+    if !arg.is_empty() && arg.chars().all(plain) {
+        arg.to_string()
+    } else {
+        shell_escape(arg)
+    }
 }
 
 fn shell_escape(s: &str) -> String {
@@ -1,3 +1,4 @@
 mod container_executor;
+mod systemd;
 
 pub use container_executor::ContainerExecutor;
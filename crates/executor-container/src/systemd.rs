@@ -0,0 +1,166 @@
+//! Task containers run as transient systemd user units (`systemd: true`,
+//! podman only). systemd rather than the controller then supervises the
+//! container, its output goes to the journal, and its state is read back with
+//! `systemctl`.
+
+use executor_core::error::ExecutorError;
+use executor_core::executor::with_cancel;
+use executor_core::CancellationToken;
+use std::collections::HashMap;
+use tokio::process::Command;
+use tracing::debug;
+
+/// State of a task's unit.
+pub enum UnitState {
+    Running,
+    /// The container exited with this code.
+    Exited(i32),
+    /// systemd doesn't know the unit (e.g. it was stopped and collected).
+    Missing,
+}
+
+/// Unit a task's container runs under.
+pub fn unit_name(container_name: &str) -> String {
+    format!("{}.service", container_name)
+}
+
+/// Start `runtime run_args`, a foreground `run`, as the transient unit `unit`.
+/// The unit stays loaded once the container exits, so its exit status can
+/// still be read. `env` is handed to the unit by name (`--setenv KEY`), so
+/// its values stay off `systemd-run`'s command line.
+pub async fn start(
+    unit: &str,
+    runtime: &str,
+    run_args: &[String],
+    env: &HashMap<String, String>,
+    cancel: &CancellationToken,
+) -> Result<(), ExecutorError> {
+    let mut args = vec!["--user", "--quiet", "--unit", unit, "--property", "RemainAfterExit=yes"];
+    let mut keys: Vec<&str> = env.keys().map(String::as_str).collect();
+    keys.sort_unstable();
+    for key in keys {
+        args.extend(["--setenv", key]);
+    }
+    args.extend(["--", runtime]);
+    args.extend(run_args.iter().map(String::as_str));
+    run("systemd-run", &args, env, cancel).await?;
+    Ok(())
+}
+
+/// PID of the unit's `run` process, or 0 if it has none.
+pub async fn main_pid(unit: &str, cancel: &CancellationToken) -> Result<u32, ExecutorError> {
+    let output = systemctl(&["show", "--property", "MainPID", "--value", unit], cancel).await?;
+    Ok(output.trim().parse().unwrap_or(0))
+}
+
+pub async fn state(unit: &str, cancel: &CancellationToken) -> Result<UnitState, ExecutorError> {
+    let output = systemctl(
+        &[
+            "show",
+            "--property",
+            "LoadState,ActiveState,SubState,ExecMainStatus",
+            unit,
+        ],
+        cancel,
+    )
+    .await?;
+    Ok(parse_state(&output))
+}
+
+fn parse_state(output: &str) -> UnitState {
+    let props: HashMap<&str, &str> = output.lines().filter_map(|l| l.split_once('=')).collect();
+    let get = |key| props.get(key).copied().unwrap_or("");
+    let code = get("ExecMainStatus").parse().unwrap_or(1);
+
+    if get("LoadState") == "not-found" {
+        return UnitState::Missing;
+    }
+    match (get("ActiveState"), get("SubState")) {
+        ("active", "exited") | ("failed", _) | ("inactive", _) => UnitState::Exited(code),
+        _ => UnitState::Running,
+    }
+}
+
+/// Stop the unit, which stops its container.
+pub async fn stop(unit: &str, cancel: &CancellationToken) -> Result<(), ExecutorError> {
+    systemctl(&["stop", unit], cancel).await?;
+    Ok(())
+}
+
+/// Stop the unit and clear a failed state, so systemd forgets it. Errors other
+/// than cancellation (e.g. the unit is already gone) are ignored.
+pub async fn remove(unit: &str, cancel: &CancellationToken) -> Result<(), ExecutorError> {
+    for action in ["stop", "reset-failed"] {
+        if let Err(ExecutorError::Cancelled) = systemctl(&[action, unit], cancel).await {
+            return Err(ExecutorError::Cancelled);
+        }
+    }
+    Ok(())
+}
+
+/// The last `lines` lines the unit wrote to the journal.
+pub async fn logs(
+    unit: &str,
+    lines: usize,
+    cancel: &CancellationToken,
+) -> Result<Vec<String>, ExecutorError> {
+    let lines = lines.to_string();
+    let output = run(
+        "journalctl",
+        &[
+            "--user", "--unit", unit, "--lines", &lines, "--output", "cat", "--no-pager",
+        ],
+        &HashMap::new(),
+        cancel,
+    )
+    .await?;
+    Ok(output.lines().map(|l| l.to_string()).collect())
+}
+
+/// Bytes the unit has written to the journal.
+pub async fn log_size(unit: &str, cancel: &CancellationToken) -> Result<u64, ExecutorError> {
+    let output = run(
+        "journalctl",
+        &["--user", "--unit", unit, "--output", "cat", "--no-pager"],
+        &HashMap::new(),
+        cancel,
+    )
+    .await?;
+    Ok(output.len() as u64)
+}
+
+async fn systemctl(args: &[&str], cancel: &CancellationToken) -> Result<String, ExecutorError> {
+    let mut all = vec!["--user"];
+    all.extend(args);
+    run("systemctl", &all, &HashMap::new(), cancel).await
+}
+
+async fn run(
+    program: &str,
+    args: &[&str],
+    env: &HashMap<String, String>,
+    cancel: &CancellationToken,
+) -> Result<String, ExecutorError> {
+    debug!("Running: {} {}", program, args.join(" "));
+    let output = with_cancel(cancel, async {
+        Command::new(program)
+            .args(args)
+            .envs(env)
+            .kill_on_drop(true)
+            .output()
+            .await
+            .map_err(|e| {
+                ExecutorError::ContainerRuntime(format!("Failed to run {}: {}", program, e))
+            })
+    })
+    .await?;
+
+    if !output.status.success() {
+        return Err(ExecutorError::ContainerRuntime(format!(
+            "{} failed: {}",
+            program,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
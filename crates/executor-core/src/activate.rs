@@ -0,0 +1,46 @@
+//! Environment activation (`activate`): a Python virtualenv or conda
+//! environment entered before each task's command, for repos whose tests
+//! only pass inside one.
+
+use crate::git::quote;
+
+/// Where conda is looked for when it isn't on `PATH`, as in a
+/// non-interactive SSH session that never ran `conda init`'s .bashrc lines.
+const CONDA_CANDIDATES: &[&str] = &[
+    "conda",
+    "\"$HOME\"/miniforge3/bin/conda",
+    "\"$HOME\"/miniconda3/bin/conda",
+    "\"$HOME\"/anaconda3/bin/conda",
+    "/opt/conda/bin/conda",
+];
+
+/// `command` (a `sh` command line) run once `activate` has succeeded.
+pub fn wrap(activate: &str, command: &str) -> String {
+    format!("sh -c {}", quote(&format!("{} && {}", script(activate), command)))
+}
+
+/// The `sh` that activates `activate`, which is one of:
+///
+/// - a command, if it has a space (`source ~/venvs/ml/bin/activate`); a
+///   leading `source` becomes `.`, since `sh` may not have it
+/// - a path to an activate script (`~/venvs/ml/bin/activate`)
+/// - otherwise a conda environment name (`ml`)
+pub fn script(activate: &str) -> String {
+    let activate = activate.trim();
+    if activate.contains(char::is_whitespace) {
+        match activate.strip_prefix("source ") {
+            Some(rest) => format!(". {}", rest.trim_start()),
+            None => activate.to_string(),
+        }
+    } else if activate.contains('/') {
+        // Unquoted, so a leading ~ expands
+        format!(". {}", activate)
+    } else {
+        format!(
+            "for c in {}; do command -v \"$c\" >/dev/null 2>&1 && break; done; \
+             eval \"$(\"$c\" shell.posix hook)\" && conda activate {}",
+            CONDA_CANDIDATES.join(" "),
+            quote(activate)
+        )
+    }
+}
@@ -0,0 +1,25 @@
+//! ANSI escape sequences (colors, cursor movement, terminal titles) in
+//! captured task output.
+
+use regex::Regex;
+use std::borrow::Cow;
+use std::sync::OnceLock;
+
+/// CSI sequences (`ESC [ ... final`), OSC sequences (`ESC ] ... BEL` or
+/// `ESC ] ... ESC \`) and the remaining two-byte escapes.
+const ESCAPE_PATTERN: &str = concat!(
+    r"\x1b\[[0-?]*[ -/]*[@-~]",
+    r"|\x1b\][^\x07\x1b]*(?:\x07|\x1b\\)",
+    r"|\x1b[@-Z\\-_]"
+);
+
+/// `line` without its escape sequences.
+pub fn strip(line: &str) -> Cow<'_, str> {
+    static ESCAPE: OnceLock<Regex> = OnceLock::new();
+    if !line.contains('\x1b') {
+        return Cow::Borrowed(line);
+    }
+    ESCAPE
+        .get_or_init(|| Regex::new(ESCAPE_PATTERN).expect("valid pattern"))
+        .replace_all(line, "")
+}
@@ -0,0 +1,384 @@
+use crate::error::ExecutorError;
+use crate::executor::{
+    AgentInfo, CleanupOutcome, Executor, FileRoot, RemoteTask, StartPlan, TaskChanges,
+};
+use crate::host::HostStats;
+use crate::log_filter::LogFilter;
+use crate::metadata::TaskMetadata;
+use crate::progress::TaskProgress;
+use crate::provision::ProvisionReport;
+use crate::task::{TaskId, TaskRequest};
+use crate::usage::ResourceUsage;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tokio_util::sync::CancellationToken;
+
+/// Append-only audit log: ~/.openclaw-agent/audit.jsonl
+pub fn audit_log_path() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("/tmp"))
+        .join(".openclaw-agent")
+        .join("audit.jsonl")
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    Start,
+    Kill,
+    Cleanup,
+    Exec,
+    Upload,
+    PullRequest,
+    ConfigInit,
+    Import,
+    Sync,
+    Annotate,
+    AgentUpgrade,
+    Provision,
+}
+
+impl fmt::Display for AuditAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuditAction::Start => write!(f, "start"),
+            AuditAction::Kill => write!(f, "kill"),
+            AuditAction::Cleanup => write!(f, "cleanup"),
+            AuditAction::Exec => write!(f, "exec"),
+            AuditAction::Upload => write!(f, "upload"),
+            AuditAction::PullRequest => write!(f, "pull_request"),
+            AuditAction::ConfigInit => write!(f, "config_init"),
+            AuditAction::Import => write!(f, "import"),
+            AuditAction::Sync => write!(f, "sync"),
+            AuditAction::Annotate => write!(f, "annotate"),
+            AuditAction::AgentUpgrade => write!(f, "agent_upgrade"),
+            AuditAction::Provision => write!(f, "provision"),
+        }
+    }
+}
+
+/// One mutation performed by the CLI or daemon.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: DateTime<Utc>,
+    pub user: String,
+    pub action: AuditAction,
+    #[serde(default)]
+    pub executor: Option<String>,
+    #[serde(default)]
+    pub task_id: Option<TaskId>,
+    #[serde(default)]
+    pub detail: Option<String>,
+    /// Set when the operation failed.
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+impl AuditEntry {
+    pub fn new(action: AuditAction) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            user: current_user(),
+            action,
+            executor: None,
+            task_id: None,
+            detail: None,
+            error: None,
+        }
+    }
+}
+
+/// Append an entry to the audit log.
+pub fn record(entry: &AuditEntry) -> Result<(), std::io::Error> {
+    let path = audit_log_path();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let mut line = serde_json::to_string(entry).map_err(std::io::Error::other)?;
+    line.push('\n');
+
+    // A single write to an O_APPEND file keeps concurrent writers from interleaving
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(line.as_bytes())
+}
+
+/// All entries, oldest first. Unparseable lines are skipped.
+pub fn read_entries() -> Result<Vec<AuditEntry>, std::io::Error> {
+    let path = audit_log_path();
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    Ok(std::fs::read_to_string(path)?
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// The local user name, as recorded in audit entries and annotations.
+pub fn current_user() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "unknown".into())
+}
+
+/// Wraps an executor and records `start`, `kill` and `cleanup` in the audit log.
+pub struct AuditedExecutor {
+    inner: Box<dyn Executor>,
+}
+
+impl AuditedExecutor {
+    pub fn new(inner: Box<dyn Executor>) -> Self {
+        Self { inner }
+    }
+
+    fn record<T, E: std::fmt::Display>(
+        &self,
+        action: AuditAction,
+        task_id: Option<&TaskId>,
+        detail: Option<String>,
+        result: &Result<T, E>,
+    ) {
+        let entry = AuditEntry {
+            executor: Some(self.inner.name().to_string()),
+            task_id: task_id.cloned(),
+            detail,
+            error: result.as_ref().err().map(|e| e.to_string()),
+            ..AuditEntry::new(action)
+        };
+        if let Err(e) = record(&entry) {
+            eprintln!("Warning: failed to write audit log: {}", e);
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Executor for AuditedExecutor {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn executor_type(&self) -> &str {
+        self.inner.executor_type()
+    }
+
+    async fn start(
+        &self,
+        request: TaskRequest,
+        cancel: &CancellationToken,
+    ) -> Result<TaskMetadata, ExecutorError> {
+        let detail = Some(request.payload.type_str().to_string());
+        let result = self.inner.start(request, cancel).await;
+        let task_id = result.as_ref().ok().map(|m| &m.task_id);
+        self.record(AuditAction::Start, task_id, detail, &result);
+        result
+    }
+
+    fn plan_start(&self, request: TaskRequest) -> Result<StartPlan, ExecutorError> {
+        self.inner.plan_start(request)
+    }
+
+    async fn status(
+        &self,
+        task_id: &TaskId,
+        cancel: &CancellationToken,
+    ) -> Result<TaskMetadata, ExecutorError> {
+        self.inner.status(task_id, cancel).await
+    }
+
+    async fn status_many(
+        &self,
+        task_ids: &[TaskId],
+        cancel: &CancellationToken,
+    ) -> Result<Vec<TaskMetadata>, ExecutorError> {
+        self.inner.status_many(task_ids, cancel).await
+    }
+
+    async fn logs(
+        &self,
+        task_id: &TaskId,
+        lines: usize,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<String>, ExecutorError> {
+        self.inner.logs(task_id, lines, cancel).await
+    }
+
+    async fn filtered_logs(
+        &self,
+        task_id: &TaskId,
+        lines: usize,
+        filter: &LogFilter,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<String>, ExecutorError> {
+        self.inner.filtered_logs(task_id, lines, filter, cancel).await
+    }
+
+    async fn kill(
+        &self,
+        task_id: &TaskId,
+        cancel: &CancellationToken,
+    ) -> Result<(), ExecutorError> {
+        let result = self.inner.kill(task_id, cancel).await;
+        self.record(AuditAction::Kill, Some(task_id), None, &result);
+        result
+    }
+
+    async fn cleanup(
+        &self,
+        task_id: &TaskId,
+        cancel: &CancellationToken,
+    ) -> Result<(), ExecutorError> {
+        let result = self.inner.cleanup(task_id, cancel).await;
+        self.record(AuditAction::Cleanup, Some(task_id), None, &result);
+        result
+    }
+
+    async fn cleanup_many(
+        &self,
+        task_ids: &[TaskId],
+        cancel: &CancellationToken,
+    ) -> Result<Vec<CleanupOutcome>, ExecutorError> {
+        let outcomes = self.inner.cleanup_many(task_ids, cancel).await?;
+        for outcome in &outcomes {
+            let result = outcome.error.as_deref().map_or(Ok(()), Err);
+            self.record(AuditAction::Cleanup, Some(&outcome.task_id), None, &result);
+        }
+        Ok(outcomes)
+    }
+
+    async fn list_remote(
+        &self,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<RemoteTask>, ExecutorError> {
+        self.inner.list_remote(cancel).await
+    }
+
+    async fn exec(
+        &self,
+        task_id: &TaskId,
+        command: &str,
+        cancel: &CancellationToken,
+    ) -> Result<i32, ExecutorError> {
+        let result = self.inner.exec(task_id, command, cancel).await;
+        self.record(
+            AuditAction::Exec,
+            Some(task_id),
+            Some(command.to_string()),
+            &result,
+        );
+        result
+    }
+
+    /// Not recorded: callers run internal scripts (which may carry
+    /// credentials) and audit what those scripts accomplish themselves.
+    async fn exec_output(
+        &self,
+        task_id: &TaskId,
+        command: &str,
+        cancel: &CancellationToken,
+    ) -> Result<(i32, String), ExecutorError> {
+        self.inner.exec_output(task_id, command, cancel).await
+    }
+
+    async fn host_stats(
+        &self,
+        dir: Option<&str>,
+        cancel: &CancellationToken,
+    ) -> Result<Option<HostStats>, ExecutorError> {
+        self.inner.host_stats(dir, cancel).await
+    }
+
+    async fn find_claude(
+        &self,
+        cancel: &CancellationToken,
+    ) -> Result<Option<String>, ExecutorError> {
+        self.inner.find_claude(cancel).await
+    }
+
+    async fn provision(
+        &self,
+        install: bool,
+        cancel: &CancellationToken,
+    ) -> Result<ProvisionReport, ExecutorError> {
+        let result = self.inner.provision(install, cancel).await;
+        if install {
+            let detail = result.as_ref().ok().map(|report| report.installed.join(", "));
+            self.record(AuditAction::Provision, None, detail, &result);
+        }
+        result
+    }
+
+    async fn upgrade_agent(&self, cancel: &CancellationToken) -> Result<AgentInfo, ExecutorError> {
+        let result = self.inner.upgrade_agent(cancel).await;
+        let detail = result.as_ref().ok().map(|agent| agent.version.clone());
+        self.record(AuditAction::AgentUpgrade, None, detail, &result);
+        result
+    }
+
+    async fn usage(
+        &self,
+        task_id: &TaskId,
+        cancel: &CancellationToken,
+    ) -> Result<Option<ResourceUsage>, ExecutorError> {
+        self.inner.usage(task_id, cancel).await
+    }
+
+    async fn progress(
+        &self,
+        task_id: &TaskId,
+        cancel: &CancellationToken,
+    ) -> Result<Option<TaskProgress>, ExecutorError> {
+        self.inner.progress(task_id, cancel).await
+    }
+
+    async fn log_size(
+        &self,
+        task_id: &TaskId,
+        cancel: &CancellationToken,
+    ) -> Result<u64, ExecutorError> {
+        self.inner.log_size(task_id, cancel).await
+    }
+
+    async fn changes(
+        &self,
+        task_id: &TaskId,
+        cancel: &CancellationToken,
+    ) -> Result<TaskChanges, ExecutorError> {
+        self.inner.changes(task_id, cancel).await
+    }
+
+    async fn download(
+        &self,
+        task_id: &TaskId,
+        path: &str,
+        root: FileRoot,
+        dest: &Path,
+        cancel: &CancellationToken,
+    ) -> Result<(), ExecutorError> {
+        self.inner.download(task_id, path, root, dest, cancel).await
+    }
+
+    async fn upload(
+        &self,
+        task_id: &TaskId,
+        source: &Path,
+        path: &str,
+        root: FileRoot,
+        cancel: &CancellationToken,
+    ) -> Result<(), ExecutorError> {
+        let result = self.inner.upload(task_id, source, path, root, cancel).await;
+        self.record(
+            AuditAction::Upload,
+            Some(task_id),
+            Some(format!("{} -> {}", source.display(), path)),
+            &result,
+        );
+        result
+    }
+}
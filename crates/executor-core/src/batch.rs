@@ -0,0 +1,39 @@
+use crate::spec::TaskSpec;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// One entry of a batch file.
+///
+/// ```yaml
+/// - executor: crib
+///   prompt: "Fix clippy warnings"
+///   workspace: ~/myapp
+///   tools: [Edit, Bash]
+/// - executor: builder
+///   command: cargo test --release
+///   detach: false
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchTask {
+    #[serde(flatten)]
+    pub spec: TaskSpec,
+    /// When false, wait for the task to finish before launching the next entry.
+    #[serde(default = "default_detach")]
+    pub detach: bool,
+}
+
+fn default_detach() -> bool {
+    true
+}
+
+/// Load a list of batch task specs from a YAML (or JSON) file.
+pub fn load_batch(path: &Path) -> anyhow::Result<Vec<BatchTask>> {
+    let contents = std::fs::read_to_string(path)?;
+    let tasks: Vec<BatchTask> = serde_yaml::from_str(&contents)?;
+    for (i, task) in tasks.iter().enumerate() {
+        task.spec
+            .payload()
+            .map_err(|e| anyhow::anyhow!("Batch entry #{}: {}", i + 1, e))?;
+    }
+    Ok(tasks)
+}
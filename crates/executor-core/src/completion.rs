@@ -1,6 +1,17 @@
-use crate::metadata::TaskMetadata;
-use crate::task::TaskStatus;
+use crate::config::WebhookConfig;
+use crate::http;
+use crate::metadata::{TaskMetadata, WebhookDelivery};
+use crate::task::{KillSignal, TaskStatus};
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use schemars::JsonSchema;
+use serde::Serialize;
+use sha2::Sha256;
 use std::path::PathBuf;
+use std::time::Duration;
+
+/// Header carrying `sha256=<hex HMAC of the body>` when a webhook secret is configured.
+pub const SIGNATURE_HEADER: &str = "X-OpenClaw-Signature";
 
 /// Directory for completion records: ~/.openclaw-agent/completions/
 pub fn completions_dir() -> PathBuf {
@@ -10,6 +21,63 @@ pub fn completions_dir() -> PathBuf {
         .join("completions")
 }
 
+/// The completion record shared by the on-disk file and the webhook body.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct CompletionRecord {
+    pub task_id: String,
+    pub status: CompletionStatus,
+    /// -1 if the task has no exit code (e.g. it was killed).
+    pub exit_code: i32,
+    /// RFC 3339, or empty if unknown.
+    pub completed_at: String,
+    pub executor: String,
+    /// Signal that ended a killed task, if known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub killed_by: Option<KillSignal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pr_url: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// CPU time as of the last heartbeat.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu_secs: Option<f64>,
+    /// Peak memory as of the last heartbeat.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub peak_rss_kb: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CompletionStatus {
+    Success,
+    Failure,
+}
+
+impl CompletionRecord {
+    pub fn new(meta: &TaskMetadata) -> Self {
+        Self {
+            task_id: meta.task_id.0.clone(),
+            status: match meta.status {
+                TaskStatus::Completed => CompletionStatus::Success,
+                _ => CompletionStatus::Failure,
+            },
+            exit_code: meta.exit_code.unwrap_or(-1),
+            completed_at: meta.finished_at.map(|t| t.to_rfc3339()).unwrap_or_default(),
+            executor: meta.executor_name.clone(),
+            killed_by: meta.killed_by,
+            pr_url: meta.pr_url.clone(),
+            tags: meta.tags.clone(),
+            cpu_secs: meta.resource_usage.as_ref().map(|u| u.cpu_secs),
+            peak_rss_kb: meta.resource_usage.as_ref().map(|u| u.peak_rss_kb),
+        }
+    }
+}
+
+/// A task's [`CompletionRecord`] as JSON.
+pub fn completion_record(meta: &TaskMetadata) -> serde_json::Value {
+    serde_json::to_value(CompletionRecord::new(meta)).unwrap_or_default()
+}
+
 /// Write a completion record JSON file for a finished task.
 /// Returns Ok(true) if written, Ok(false) if already exists or not terminal.
 pub fn write_completion_record(meta: &TaskMetadata) -> Result<bool, std::io::Error> {
@@ -17,77 +85,103 @@ pub fn write_completion_record(meta: &TaskMetadata) -> Result<bool, std::io::Err
         return Ok(false);
     }
 
-    let dir = completions_dir();
-    let path = dir.join(format!("{}.json", meta.task_id));
-
+    let path = completions_dir().join(format!("{}.json", meta.task_id));
     if path.exists() {
         return Ok(false);
     }
 
+    save_completion_record(meta)?;
+    Ok(true)
+}
+
+/// Write a task's completion record, replacing any earlier one (e.g. once a
+/// pull request has been opened for it).
+pub fn save_completion_record(meta: &TaskMetadata) -> Result<(), std::io::Error> {
+    let dir = completions_dir();
     std::fs::create_dir_all(&dir)?;
 
-    let status_str = match meta.status {
-        TaskStatus::Completed => "success",
-        _ => "failure",
-    };
+    let json =
+        serde_json::to_string_pretty(&completion_record(meta)).map_err(std::io::Error::other)?;
+    std::fs::write(dir.join(format!("{}.json", meta.task_id)), json)
+}
 
-    let record = serde_json::json!({
-        "task_id": meta.task_id.0,
-        "status": status_str,
-        "exit_code": meta.exit_code.unwrap_or(-1),
-        "completed_at": meta.finished_at.map(|t| t.to_rfc3339()).unwrap_or_default(),
-        "executor": meta.executor_name,
-    });
-
-    let json = serde_json::to_string_pretty(&record)
-        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
-    std::fs::write(&path, json)?;
-    Ok(true)
+/// POST the completion record to a webhook URL, retrying with exponential
+/// backoff and signing the body if a secret is configured.
+pub async fn post_webhook(
+    meta: &TaskMetadata,
+    webhook_url: &str,
+    config: &WebhookConfig,
+) -> WebhookDelivery {
+    match webhook_body(meta, config) {
+        Ok(body) => deliver_webhook(webhook_url, body, config).await,
+        Err(e) => WebhookDelivery::failed(webhook_url, e),
+    }
 }
 
-/// POST the completion record to a webhook URL using curl.
-/// Runs asynchronously via tokio::process::Command.
-pub async fn post_webhook(meta: &TaskMetadata, webhook_url: &str) -> Result<(), String> {
-    if !meta.status.is_terminal() {
-        return Ok(());
+/// POST an already-built webhook body, with the same retries and signing.
+pub async fn deliver_webhook(
+    webhook_url: &str,
+    body: Vec<u8>,
+    config: &WebhookConfig,
+) -> WebhookDelivery {
+    let signature = config.signing_secret().map(|secret| sign(&secret, &body));
+    let headers: Vec<(&str, &str)> = signature
+        .as_deref()
+        .map(|sig| (SIGNATURE_HEADER, sig))
+        .into_iter()
+        .collect();
+
+    let timeout = Duration::from_secs(config.timeout_secs);
+    let mut backoff = Duration::from_secs(config.backoff_secs);
+    let mut attempts = 0;
+    let mut last_error = None;
+
+    while attempts <= config.retries {
+        if attempts > 0 {
+            tokio::time::sleep(backoff).await;
+            backoff *= 2;
+        }
+        attempts += 1;
+
+        match http::post(webhook_url, &headers, body.clone(), timeout).await {
+            Ok(_) => {
+                last_error = None;
+                break;
+            }
+            Err(e) => last_error = Some(e),
+        }
+    }
+
+    WebhookDelivery {
+        url: webhook_url.to_string(),
+        delivered: last_error.is_none(),
+        attempts,
+        attempted_at: Utc::now(),
+        last_error,
     }
+}
 
-    let status_str = match meta.status {
-        TaskStatus::Completed => "success",
-        _ => "failure",
+/// The request body: the configured template rendered against the task, or
+/// the completion record if there is none.
+pub fn webhook_body(meta: &TaskMetadata, config: &WebhookConfig) -> Result<Vec<u8>, String> {
+    let Some(template) = config.payload_template()? else {
+        return serde_json::to_vec(&completion_record(meta)).map_err(|e| e.to_string());
     };
 
-    let record = serde_json::json!({
-        "task_id": meta.task_id.0,
-        "status": status_str,
-        "exit_code": meta.exit_code.unwrap_or(-1),
-        "completed_at": meta.finished_at.map(|t| t.to_rfc3339()).unwrap_or_default(),
-        "executor": meta.executor_name,
-    });
-
-    let body = serde_json::to_string(&record).map_err(|e| e.to_string())?;
-
-    let output = tokio::process::Command::new("curl")
-        .args([
-            "-s",
-            "-X",
-            "POST",
-            "-H",
-            "Content-Type: application/json",
-            "-d",
-            &body,
-            "--max-time",
-            "10",
-            webhook_url,
-        ])
-        .output()
-        .await
-        .map_err(|e| format!("Failed to run curl: {}", e))?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(format!("Webhook POST failed: {}", stderr));
-    }
+    let mut context = serde_json::to_value(meta).map_err(|e| e.to_string())?;
+    context["record"] = completion_record(meta);
+    context["duration_secs"] = serde_json::json!(meta.duration().map(|d| d.num_seconds()));
+
+    let env = minijinja::Environment::new();
+    env.render_str(&template, context)
+        .map(String::into_bytes)
+        .map_err(|e| format!("webhook template error: {}", e))
+}
 
-    Ok(())
+/// `sha256=<hex>` HMAC-SHA256 of the body.
+fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
 }
@@ -1,5 +1,12 @@
+use crate::error::ExecutorError;
+use crate::metadata::TaskMetadata;
+use crate::spec::TaskSpec;
+use crate::task::{ServiceSpec, TaskStatus};
+use anyhow::Context;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serde_yaml::{Mapping, Value};
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
 
 /// Top-level configuration. Covers GitHub issue #5.
@@ -9,6 +16,29 @@ pub struct Config {
     pub executors: Vec<ExecutorConfig>,
     #[serde(default)]
     pub defaults: Defaults,
+    #[serde(default)]
+    pub notifications: NotificationsConfig,
+    #[serde(default)]
+    pub schedules: Vec<Schedule>,
+    #[serde(default)]
+    pub telemetry: TelemetryConfig,
+    #[serde(default)]
+    pub webhook: WebhookConfig,
+    #[serde(default)]
+    pub hooks: HooksConfig,
+    #[serde(default)]
+    pub plugins: PluginsConfig,
+    /// Named overlays selected with `--profile`, each a partial config merged
+    /// on top of the rest of the file.
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Value>,
+    /// The profile this config was loaded with, if any.
+    #[serde(skip)]
+    pub profile: Option<String>,
+    /// Files this config was merged from, lowest precedence first (see
+    /// `include:`). Empty for a config that wasn't loaded from disk.
+    #[serde(skip)]
+    pub sources: Vec<PathBuf>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,22 +50,293 @@ pub struct ExecutorConfig {
     pub host: Option<String>,
     #[serde(default)]
     pub port: Option<u16>,
+    /// SSH login user, or for a container executor the user tasks run as
+    /// (`--user`, e.g. `1000:1000`).
     #[serde(default)]
     pub user: Option<String>,
     #[serde(default)]
     pub key_path: Option<String>,
     #[serde(default)]
     pub claude_path: Option<String>,
+    /// A static `openclaw-remote-agent` build for an SSH executor's host.
+    /// It is pushed to the host, and task launches, status checks, log tails
+    /// and kills go through it over one persistent channel instead of shell
+    /// commands.
+    #[serde(default)]
+    pub agent_binary: Option<String>,
+    /// Operating system of an SSH executor's host. Detected from the SSH
+    /// server's banner when unset; set it for a Windows host whose server
+    /// doesn't say so.
+    #[serde(default)]
+    pub platform: Option<HostPlatform>,
+    /// Nix shell every task runs in: a flake reference (`.#devshell`) for
+    /// `nix develop`, or a shell.nix path for `nix-shell`.
+    #[serde(default)]
+    pub nix_shell: Option<String>,
+    /// Environment each task's command runs in: a command such as
+    /// `source ~/venvs/ml/bin/activate`, an activate script's path, or a
+    /// conda environment name.
+    #[serde(default)]
+    pub activate: Option<String>,
+    /// Directory task dirs are created under on the executor's host
+    /// (default `/tmp/openclaw-tasks`, which many hosts clear on reboot). A
+    /// leading `~` is the home directory there.
+    #[serde(default)]
+    pub task_root: Option<String>,
     #[serde(default)]
     pub image: Option<String>,
+    /// Build the container image from a Dockerfile instead of pulling `image`.
+    #[serde(default)]
+    pub image_build: Option<ImageBuild>,
     #[serde(default)]
     pub runtime: Option<ContainerRuntime>,
+    /// Run each task container as a transient systemd user unit (podman
+    /// only), logging to the journal.
+    #[serde(default)]
+    pub systemd: bool,
+    /// When a container executor pulls `image` before a task runs.
+    #[serde(default)]
+    pub pull: Option<PullPolicy>,
+    /// Registry login for pulling `image`. Without it, the runtime's own
+    /// credential store is used.
+    #[serde(default)]
+    pub registry: Option<RegistryAuth>,
     #[serde(default)]
     pub volumes: Vec<String>,
+    /// User namespace for task containers (`--userns`), e.g. podman's
+    /// `keep-id`.
+    #[serde(default)]
+    pub userns: Option<String>,
+    /// CPUs a task container may use, e.g. `1.5` (`--cpus`).
+    #[serde(default)]
+    pub cpus: Option<f64>,
+    /// Memory limit for a task container, e.g. `4g` (`--memory`).
+    #[serde(default)]
+    pub memory: Option<String>,
+    /// Most processes a task container may run (`--pids-limit`).
+    #[serde(default)]
+    pub pids_limit: Option<u64>,
+    /// GPUs made available to a task container.
+    #[serde(default)]
+    pub gpus: Option<GpuSpec>,
+    /// Host devices passed to a task container (`--device`), e.g. `/dev/dri`.
+    #[serde(default)]
+    pub devices: Vec<String>,
+    /// Network a task container joins (`--network`), e.g. `none` to cut
+    /// untrusted tasks off.
+    #[serde(default)]
+    pub network: Option<String>,
+    /// `host:ip` entries added to a task container's /etc/hosts (`--add-host`).
+    #[serde(default)]
+    pub extra_hosts: Vec<String>,
+    /// Ports a task container publishes (`--publish`), e.g. `8080:80`.
+    #[serde(default)]
+    pub publish: Vec<String>,
+    /// Sidecar containers (databases, caches, ...) started for every task on
+    /// a container executor, reachable from it by name.
+    #[serde(default)]
+    pub services: Vec<ServiceSpec>,
     #[serde(default)]
     pub labels: Vec<String>,
     #[serde(default)]
     pub env: HashMap<String, String>,
+    /// Completion webhook for tasks on this executor, overriding `defaults.webhook_url`.
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+    /// Plugin binary driven over JSON stdio (`type: external`).
+    #[serde(default)]
+    pub command: Option<String>,
+    /// Arguments passed to `command`.
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Free-form settings for executor types registered outside this crate
+    /// (and passed to external plugins).
+    #[serde(default)]
+    pub options: HashMap<String, serde_yaml::Value>,
+    /// Git credentials and pull request settings for this executor.
+    #[serde(default)]
+    pub git: GitConfig,
+    /// Directory (on the executor's host) or git URL that each task without
+    /// `--workspace` or `--repo` gets a fresh copy of as its workspace.
+    #[serde(default)]
+    pub workspace_template: Option<String>,
+    /// Turn limit for tasks on this executor, overriding `defaults.max_turns`.
+    #[serde(default)]
+    pub max_turns: Option<u32>,
+    /// Seconds between status polls of this executor's tasks, overriding
+    /// `defaults.heartbeat_interval_secs`.
+    #[serde(default)]
+    pub heartbeat_interval_secs: Option<u64>,
+    /// Overrides `hooks.heartbeat_timeout_minutes` for this executor's tasks.
+    #[serde(default)]
+    pub heartbeat_timeout_minutes: Option<u64>,
+    /// Overrides `defaults.min_free_disk_mb` for this executor.
+    #[serde(default)]
+    pub min_free_disk_mb: Option<u64>,
+    /// Overrides `defaults.max_load` for this executor.
+    #[serde(default)]
+    pub max_load: Option<f64>,
+    /// Seconds `kill` waits after SIGTERM before sending SIGKILL (default 10).
+    #[serde(default)]
+    pub kill_grace_secs: Option<u64>,
+    /// Most operations (status checks, log fetches, `exec`...) run against
+    /// this executor at once, across all `openclaw-agent` processes here.
+    /// Others wait their turn.
+    #[serde(default)]
+    pub max_concurrent_ops: Option<usize>,
+    /// Least time between the starts of two operations against this
+    /// executor, in milliseconds.
+    #[serde(default)]
+    pub min_op_interval_ms: Option<u64>,
+    /// Prefix each captured log line with the UTC time it was written (for
+    /// `logs --timestamps` and `logs --since`). Not supported with `systemd`.
+    #[serde(default)]
+    pub log_timestamps: bool,
+    /// Run claude with `--output-format stream-json`, logging one JSON event
+    /// per line as it goes. Needed for turn-by-turn progress in `status`.
+    #[serde(default)]
+    pub stream_json: bool,
+    /// When this executor's finished tasks are cleaned up without being
+    /// asked.
+    #[serde(default)]
+    pub cleanup: CleanupPolicy,
+    /// How a `mock` executor's simulated tasks behave.
+    #[serde(default)]
+    pub mock: Option<MockConfig>,
+    /// Record the commands this executor runs on its backend to a fixture
+    /// file, or replay them from one (`ssh` and `container` only).
+    #[serde(default)]
+    pub fixture: Option<FixtureConfig>,
+}
+
+/// Where task dirs go when an executor sets no `task_root`.
+pub const DEFAULT_TASK_ROOT: &str = "/tmp/openclaw-tasks";
+
+/// When an executor's finished tasks are cleaned up automatically, by
+/// `cleanup --expired` and hourly by `daemon`. Unset, only by hand.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CleanupPolicy {
+    /// Days after a task finishes before it is cleaned up (0: at the first
+    /// sweep after it finishes).
+    #[serde(default)]
+    pub delete_after_days: Option<u32>,
+    /// Leave failed tasks alone however old they get, to look into.
+    #[serde(default)]
+    pub keep_on_failure: bool,
+}
+
+impl CleanupPolicy {
+    /// Whether `meta`'s task is due to be cleaned up as of `now`.
+    pub fn is_due(&self, meta: &TaskMetadata, now: DateTime<Utc>) -> bool {
+        let (Some(days), Some(finished_at)) = (self.delete_after_days, meta.finished_at) else {
+            return false;
+        };
+        if self.keep_on_failure && meta.status == TaskStatus::Failed {
+            return false;
+        }
+        meta.status.is_terminal() && now - finished_at >= chrono::Duration::days(days.into())
+    }
+}
+
+/// The task lifecycle a `mock` executor simulates. Nothing is run: a task's
+/// state and log follow from how long ago it started.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MockConfig {
+    /// Seconds each task runs for.
+    #[serde(default = "default_mock_duration")]
+    pub duration_secs: u64,
+    /// Exit code each task finishes with; anything but 0 fails it.
+    #[serde(default)]
+    pub exit_code: i32,
+    /// Log lines each task prints, spread evenly over its run.
+    #[serde(default = "default_mock_log_lines")]
+    pub log_lines: Vec<String>,
+}
+
+fn default_mock_duration() -> u64 {
+    10
+}
+
+fn default_mock_log_lines() -> Vec<String> {
+    ["Mock task started", "Reading the workspace", "Making changes", "Mock task done"]
+        .map(String::from)
+        .to_vec()
+}
+
+impl Default for MockConfig {
+    fn default() -> Self {
+        Self {
+            duration_secs: default_mock_duration(),
+            exit_code: 0,
+            log_lines: default_mock_log_lines(),
+        }
+    }
+}
+
+/// A fixture file an executor records to or replays from (see
+/// [`crate::fixture`]).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FixtureConfig {
+    pub mode: FixtureMode,
+    /// The fixture file, JSON lines.
+    pub path: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FixtureMode {
+    /// Append every command run, and its result, to the file.
+    Record,
+    /// Answer commands from the file, in order, instead of running them.
+    Replay,
+}
+
+/// How an executor authenticates to git remotes, and what it does with a
+/// successful task's branch.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GitConfig {
+    /// Token for HTTPS remotes.
+    #[serde(default)]
+    pub token: Option<String>,
+    /// Environment variable holding the token, read on this machine.
+    #[serde(default)]
+    pub token_env: Option<String>,
+    /// Username sent with the token (default `x-access-token`).
+    #[serde(default)]
+    pub username: Option<String>,
+    /// Private key for SSH remotes, as a path on the executor's host.
+    #[serde(default)]
+    pub ssh_key: Option<String>,
+    /// After a successful claude_code task, commit its changes on the task
+    /// branch, push it and open a pull request. Implies `start --git-branch`.
+    #[serde(default)]
+    pub auto_pr: bool,
+    /// Branch pull requests target (default: the branch the task started from).
+    #[serde(default)]
+    pub pr_base: Option<String>,
+}
+
+impl GitConfig {
+    /// The HTTPS token, from the environment if `token_env` is set.
+    pub fn token(&self) -> Option<String> {
+        match self.token_env {
+            Some(ref var) => std::env::var(var).ok(),
+            None => self.token.clone(),
+        }
+    }
+
+    /// `url` with the token embedded, for HTTPS remotes when one is configured.
+    pub fn authenticated_url(&self, url: &str) -> String {
+        match (url.strip_prefix("https://"), self.token()) {
+            (Some(rest), Some(token)) => format!(
+                "https://{}:{}@{}",
+                self.username.as_deref().unwrap_or("x-access-token"),
+                token,
+                rest
+            ),
+            _ => url.to_string(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -44,31 +345,112 @@ pub enum ExecutorType {
     Ssh,
     Container,
     Local,
+    External,
+    Mock,
+    /// Any other `type:`, resolved through `registry`.
+    #[serde(untagged)]
+    Custom(String),
 }
 
-impl std::fmt::Display for ExecutorType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl ExecutorType {
+    pub fn as_str(&self) -> &str {
         match self {
-            ExecutorType::Ssh => write!(f, "ssh"),
-            ExecutorType::Container => write!(f, "container"),
-            ExecutorType::Local => write!(f, "local"),
+            ExecutorType::Ssh => "ssh",
+            ExecutorType::Container => "container",
+            ExecutorType::Local => "local",
+            ExecutorType::External => "external",
+            ExecutorType::Mock => "mock",
+            ExecutorType::Custom(name) => name,
         }
     }
 }
 
+impl std::fmt::Display for ExecutorType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(self.as_str())
+    }
+}
+
+/// What an SSH executor's host runs: `sh` and the usual Unix tools, or
+/// Windows, driven with PowerShell.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HostPlatform {
+    Unix,
+    Windows,
+}
+
+/// A Dockerfile a container executor builds its image from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageBuild {
+    /// Build context directory, relative to the config file.
+    pub context: String,
+    /// Dockerfile, relative to `context` (default `Dockerfile`).
+    #[serde(default)]
+    pub dockerfile: Option<String>,
+}
+
+/// When to pull a container executor's image.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PullPolicy {
+    /// Before every task, so a moved tag is picked up.
+    Always,
+    /// Only when the image isn't present locally.
+    #[default]
+    Missing,
+    /// Never; a missing image fails the task.
+    Never,
+}
+
+/// Credentials a container executor logs in to its registry with.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegistryAuth {
+    /// Registry host, e.g. `ghcr.io` (default: the runtime's, i.e. Docker Hub).
+    #[serde(default)]
+    pub server: Option<String>,
+    pub username: String,
+    /// Password or token; may be a secret reference (see `secrets`).
+    #[serde(default)]
+    pub password: Option<String>,
+    /// Environment variable holding the password, read on this machine.
+    #[serde(default)]
+    pub password_env: Option<String>,
+}
+
+impl RegistryAuth {
+    /// The password, from the environment if `password_env` is set, with any
+    /// secret reference resolved.
+    pub fn password(&self) -> Result<String, ExecutorError> {
+        let password = match self.password_env {
+            Some(ref var) => std::env::var(var).map_err(|_| {
+                ExecutorError::Config(format!("registry password_env {} is not set", var))
+            })?,
+            None => self.password.clone().ok_or_else(|| {
+                ExecutorError::Config("registry requires 'password' or 'password_env'".into())
+            })?,
+        };
+        crate::secrets::resolve(&password)
+    }
+}
+
+/// GPUs for a container task: `all`, or a list of device indexes or UUIDs.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum GpuSpec {
+    Devices(Vec<String>),
+    /// Only `all` is valid (checked by `config --validate`).
+    All(String),
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum ContainerRuntime {
+    #[default]
     Docker,
     Podman,
 }
 
-impl Default for ContainerRuntime {
-    fn default() -> Self {
-        Self::Docker
-    }
-}
-
 impl std::fmt::Display for ContainerRuntime {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -78,31 +460,334 @@ impl std::fmt::Display for ContainerRuntime {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Settings for every executor. Those left unset fall back to built-in
+/// values; keeping them unset lets `config resolve` tell the two apart.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Defaults {
-    #[serde(default = "default_max_turns")]
-    pub max_turns: u32,
-    #[serde(default = "default_claude_path")]
-    pub claude_path: String,
+    /// Turn limit for claude tasks (built-in: 100).
+    #[serde(default)]
+    pub max_turns: Option<u32>,
+    /// The claude binary (built-in: `claude` on the PATH).
+    #[serde(default)]
+    pub claude_path: Option<String>,
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+    /// Seconds between status polls while waiting on a task; each poll of a
+    /// running task emits a heartbeat (built-in: 10).
+    #[serde(default)]
+    pub heartbeat_interval_secs: Option<u64>,
+    /// Refuse to start a task when the filesystem its workspace lands on has
+    /// less than this much free space.
+    #[serde(default)]
+    pub min_free_disk_mb: Option<u64>,
+    /// Refuse to start a task when the executor's 1-minute load average is
+    /// above this.
+    #[serde(default)]
+    pub max_load: Option<f64>,
+}
+
+/// A task the daemon launches on a cron schedule.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Schedule {
+    pub name: String,
+    /// Five-field cron expression, evaluated in local time.
+    pub cron: String,
+    #[serde(flatten)]
+    pub spec: TaskSpec,
+}
+
+/// How to tell the user a task finished, in addition to completion records/webhooks.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NotificationsConfig {
+    /// Terminal-local notifiers fired when the CLI observes a task finishing.
+    #[serde(default)]
+    pub local: Vec<LocalNotifier>,
+    #[serde(default)]
+    pub slack: Option<SlackConfig>,
+    #[serde(default)]
+    pub discord: Option<DiscordConfig>,
+    #[serde(default)]
+    pub email: Option<EmailConfig>,
+    #[serde(default)]
+    pub telegram: Option<TelegramConfig>,
+}
+
+/// Discord webhook delivery, optionally routed per executor.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DiscordConfig {
+    /// Webhook used for executors without a route.
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+    /// Executor name → webhook URL (i.e. channel).
+    #[serde(default)]
+    pub routes: HashMap<String, String>,
+}
+
+impl DiscordConfig {
+    /// Webhook for tasks from this executor, if any.
+    pub fn webhook_for(&self, executor: &str) -> Option<&str> {
+        self.routes
+            .get(executor)
+            .or(self.webhook_url.as_ref())
+            .map(String::as_str)
+    }
+}
+
+/// Telegram bot delivery.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TelegramConfig {
+    #[serde(default)]
+    pub bot_token: Option<String>,
+    /// Read the bot token from this environment variable instead of the config.
+    #[serde(default)]
+    pub bot_token_env: Option<String>,
+    /// Numeric chat ID, or `@channelname`.
+    pub chat_id: String,
+    /// Attach Retry / Clean up buttons, handled while `daemon` is running.
+    #[serde(default = "default_telegram_actions")]
+    pub actions: bool,
+}
+
+impl TelegramConfig {
+    /// The bot token, from the environment if `bot_token_env` is set.
+    pub fn token(&self) -> Option<String> {
+        match self.bot_token_env {
+            Some(ref var) => std::env::var(var).ok(),
+            None => self.bot_token.clone(),
+        }
+    }
+}
+
+/// SMTP email delivery.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailConfig {
+    pub smtp_host: String,
+    /// Defaults to the standard port for `tls` (587 / 465 / 25).
+    #[serde(default)]
+    pub smtp_port: Option<u16>,
+    #[serde(default)]
+    pub tls: SmtpTls,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    /// Read the password from this environment variable instead of the config.
+    #[serde(default)]
+    pub password_env: Option<String>,
+    pub from: String,
+    pub to: Vec<String>,
+    /// Only mail tasks that ran at least this long...
+    #[serde(default)]
+    pub min_duration_minutes: Option<u64>,
+    /// ...or that failed. With neither filter set every finished task is mailed.
+    #[serde(default)]
+    pub on_failure: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SmtpTls {
+    /// Plain connection upgraded with STARTTLS (port 587).
+    #[default]
+    Starttls,
+    /// Implicit TLS (port 465).
+    Tls,
+    /// No encryption, for local relays (port 25).
+    None,
+}
+
+/// Slack delivery: an incoming webhook, or a bot token posting to a channel.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SlackConfig {
     #[serde(default)]
     pub webhook_url: Option<String>,
+    #[serde(default)]
+    pub bot_token: Option<String>,
+    /// Channel for `bot_token` delivery, e.g. `#agents` or a channel ID.
+    #[serde(default)]
+    pub channel: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LocalNotifier {
+    /// Ring the terminal bell (BEL).
+    Bell,
+    /// `tmux display-message` in the current tmux session.
+    Tmux,
+    /// OSC 777 desktop notification (WezTerm, urxvt, foot).
+    Wezterm,
+    /// OSC 99 desktop notification (kitty).
+    Kitty,
+}
+
+/// Delivery settings for the completion webhook (the URL itself is `defaults.webhook_url`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    /// Retries after the first failed attempt.
+    #[serde(default = "default_webhook_retries")]
+    pub retries: u32,
+    /// Delay before the first retry; doubles after each one.
+    #[serde(default = "default_webhook_backoff_secs")]
+    pub backoff_secs: u64,
+    #[serde(default = "default_webhook_timeout_secs")]
+    pub timeout_secs: u64,
+    /// Shared secret for the `X-OpenClaw-Signature` HMAC-SHA256 header.
+    #[serde(default)]
+    pub secret: Option<String>,
+    /// Read the secret from this environment variable instead of the config.
+    #[serde(default)]
+    pub secret_env: Option<String>,
+    /// minijinja template for the request body, rendered with the task metadata.
+    #[serde(default)]
+    pub template: Option<String>,
+    /// Read the body template from this file instead.
+    #[serde(default)]
+    pub template_file: Option<String>,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            retries: default_webhook_retries(),
+            backoff_secs: default_webhook_backoff_secs(),
+            timeout_secs: default_webhook_timeout_secs(),
+            secret: None,
+            secret_env: None,
+            template: None,
+            template_file: None,
+        }
+    }
+}
+
+impl WebhookConfig {
+    /// The signing secret, from the environment if `secret_env` is set.
+    pub fn signing_secret(&self) -> Option<String> {
+        match self.secret_env {
+            Some(ref var) => std::env::var(var).ok(),
+            None => self.secret.clone(),
+        }
+    }
+
+    /// The body template, if one is configured inline or via `template_file`.
+    pub fn payload_template(&self) -> Result<Option<String>, String> {
+        match self.template_file {
+            Some(ref path) => std::fs::read_to_string(path)
+                .map(Some)
+                .map_err(|e| format!("failed to read webhook template {}: {}", path, e)),
+            None => Ok(self.template.clone()),
+        }
+    }
+}
+
+fn default_telegram_actions() -> bool {
+    true
+}
+
+fn default_webhook_retries() -> u32 {
+    3
+}
+
+fn default_webhook_backoff_secs() -> u64 {
+    2
+}
+
+fn default_webhook_timeout_secs() -> u64 {
+    10
+}
+
+/// Local shell commands run on task state transitions, with the task's
+/// metadata in `OPENCLAW_*` environment variables.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HooksConfig {
+    /// After a task is started.
+    #[serde(default)]
+    pub on_start: Vec<String>,
+    /// When a task is first seen completed successfully.
+    #[serde(default)]
+    pub on_complete: Vec<String>,
+    /// When a task is first seen failed.
+    #[serde(default)]
+    pub on_fail: Vec<String>,
+    /// When a status check finds a task still running past `heartbeat_timeout_minutes`.
+    #[serde(default)]
+    pub on_heartbeat_timeout: Vec<String>,
+    #[serde(default)]
+    pub heartbeat_timeout_minutes: Option<u64>,
+    /// Each command is killed after this long.
+    #[serde(default = "default_hook_timeout_secs")]
+    pub timeout_secs: u64,
 }
 
-impl Default for Defaults {
+impl Default for HooksConfig {
     fn default() -> Self {
         Self {
-            max_turns: default_max_turns(),
-            claude_path: default_claude_path(),
-            webhook_url: None,
+            on_start: Vec::new(),
+            on_complete: Vec::new(),
+            on_fail: Vec::new(),
+            on_heartbeat_timeout: Vec::new(),
+            heartbeat_timeout_minutes: None,
+            timeout_secs: default_hook_timeout_secs(),
         }
     }
 }
 
-fn default_max_turns() -> u32 {
+fn default_hook_timeout_secs() -> u64 {
+    60
+}
+
+/// WebAssembly policy plugins (executor selection, notification routing,
+/// webhook payload rewriting).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PluginsConfig {
+    /// Directory of `.wasm` modules, consulted in file-name order.
+    #[serde(default)]
+    pub dir: Option<String>,
+}
+
+impl PluginsConfig {
+    /// The plugins directory, with a leading `~/` expanded.
+    pub fn dir_path(&self) -> Option<PathBuf> {
+        let dir = self.dir.as_deref()?;
+        match (dir.strip_prefix("~/"), dirs::home_dir()) {
+            (Some(rest), Some(home)) => Some(home.join(rest)),
+            _ => Some(PathBuf::from(dir)),
+        }
+    }
+}
+
+/// OpenTelemetry export of task lifecycle spans. Disabled unless an endpoint is set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TelemetryConfig {
+    /// OTLP/HTTP collector endpoint, e.g. `http://localhost:4318/v1/traces`.
+    #[serde(default)]
+    pub otlp_endpoint: Option<String>,
+    #[serde(default = "default_service_name")]
+    pub service_name: String,
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        Self {
+            otlp_endpoint: None,
+            service_name: default_service_name(),
+        }
+    }
+}
+
+fn default_service_name() -> String {
+    "openclaw-agent".to_string()
+}
+
+pub(crate) fn default_max_turns() -> u32 {
     100
 }
 
-fn default_claude_path() -> String {
+pub(crate) fn default_heartbeat_interval_secs() -> u64 {
+    10
+}
+
+pub(crate) fn default_claude_path() -> String {
     "claude".to_string()
 }
 
@@ -117,19 +802,80 @@ impl Config {
         }
     }
 
-    /// Load config from a specific path.
+    /// Load config from a specific path, merged with the files it `include`s.
     pub fn load_from(path: &Path) -> anyhow::Result<Self> {
-        let contents = std::fs::read_to_string(path)?;
-        let config: Config = serde_yaml::from_str(&contents)?;
+        Self::load_profile(path, None)
+    }
+
+    /// Load config from `path` with a profile applied: the one of that name
+    /// under `profiles:`, or else the profile file of that name (see
+    /// [`Config::profile_path`]) in place of `path`.
+    pub fn load_profile(path: &Path, profile: Option<&str>) -> anyhow::Result<Self> {
+        let mut sources = Vec::new();
+        let merged = load_merged(path, profile, &mut sources)?;
+        let file = sources.last().map_or(path, PathBuf::as_path);
+        let mut config: Config = serde_yaml::from_value(Value::Mapping(merged))
+            .with_context(|| format!("Invalid config {}", file.display()))?;
+        config.resolve_paths(file);
+        config.profile = profile.map(str::to_string);
+        config.sources = sources;
         Ok(config)
     }
 
-    /// Default config file path.
+    /// Make paths given relative to the config file (`image_build.context`)
+    /// absolute, so they work wherever the agent is run from.
+    pub(crate) fn resolve_paths(&mut self, file: &Path) {
+        let dir = file.parent().unwrap_or(Path::new("."));
+        for executor in &mut self.executors {
+            if let Some(ref mut build) = executor.image_build {
+                build.context = include_path(dir, &build.context).display().to_string();
+            }
+        }
+    }
+
+    /// Default config file path: the first of `coding-agent.yaml`, `.toml`
+    /// and `.json` that exists, or the YAML one if none does.
     pub fn default_path() -> PathBuf {
-        dirs::config_dir()
+        let dir = dirs::config_dir()
             .unwrap_or_else(|| PathBuf::from("/etc"))
-            .join("openclaw")
-            .join("coding-agent.yaml")
+            .join("openclaw");
+        CONFIG_EXTENSIONS
+            .iter()
+            .map(|ext| dir.join(format!("coding-agent.{}", ext)))
+            .find(|path| path.exists())
+            .unwrap_or_else(|| dir.join("coding-agent.yaml"))
+    }
+
+    /// Directory holding one config file per profile, such as `work.yaml`.
+    pub fn profiles_dir() -> PathBuf {
+        Self::default_path().with_file_name("profiles")
+    }
+
+    /// The profile file for `name` in [`Config::profiles_dir`], if there is one.
+    pub fn profile_path(name: &str) -> Option<PathBuf> {
+        let dir = Self::profiles_dir();
+        CONFIG_EXTENSIONS
+            .iter()
+            .map(|ext| dir.join(format!("{}.{}", name, ext)))
+            .find(|path| path.exists())
+    }
+
+    /// Names of the profiles in [`Config::profiles_dir`].
+    pub fn profile_files() -> Vec<String> {
+        let Ok(entries) = std::fs::read_dir(Self::profiles_dir()) else {
+            return Vec::new();
+        };
+        let mut names: Vec<String> = entries
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                let ext = path.extension()?.to_str()?;
+                CONFIG_EXTENSIONS.contains(&ext).then_some(())?;
+                Some(path.file_stem()?.to_string_lossy().into_owned())
+            })
+            .collect();
+        names.sort();
+        names.dedup();
+        names
     }
 
     /// Empty config with no executors.
@@ -137,6 +883,15 @@ impl Config {
         Self {
             executors: Vec::new(),
             defaults: Defaults::default(),
+            notifications: NotificationsConfig::default(),
+            schedules: Vec::new(),
+            telemetry: TelemetryConfig::default(),
+            webhook: WebhookConfig::default(),
+            hooks: HooksConfig::default(),
+            plugins: PluginsConfig::default(),
+            profiles: BTreeMap::new(),
+            profile: None,
+            sources: Vec::new(),
         }
     }
 
@@ -155,13 +910,242 @@ impl Config {
 }
 
 impl ExecutorConfig {
+    /// `env` with secret references (`keyring:`, `vault:`, `sops:`) resolved.
+    pub fn resolved_env(&self) -> Result<HashMap<String, String>, ExecutorError> {
+        crate::secrets::resolve_env(&self.env)
+    }
+
     /// Get the claude binary path, falling back to "claude".
     pub fn claude_binary(&self) -> &str {
         self.claude_path.as_deref().unwrap_or("claude")
     }
 
+    /// The `--output-format` arguments for claude.
+    pub fn claude_output_args(&self) -> &str {
+        if self.stream_json {
+            "--output-format stream-json --verbose"
+        } else {
+            "--output-format json"
+        }
+    }
+
     /// Get the SSH port, falling back to 22.
     pub fn ssh_port(&self) -> u16 {
         self.port.unwrap_or(22)
     }
+
+    /// Where task dirs go on the executor's host, with any leading `~` left
+    /// for the executor to expand there.
+    pub fn task_root(&self) -> &str {
+        self.task_root.as_deref().unwrap_or(DEFAULT_TASK_ROOT)
+    }
+
+    /// Get the kill grace period, falling back to 10 seconds.
+    pub fn kill_grace_secs(&self) -> u64 {
+        self.kill_grace_secs.unwrap_or(10)
+    }
+
+    /// The executor's name, made safe to use as a file name.
+    pub fn file_name(&self) -> String {
+        self.name
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || "._-".contains(c) { c } else { '_' })
+            .collect()
+    }
+}
+
+/// Extensions of the config file formats, in the order `default_path` tries them.
+const CONFIG_EXTENSIONS: [&str; 3] = ["yaml", "toml", "json"];
+
+/// Top-level lists whose entries are merged across config layers by `name`.
+pub(crate) const NAMED_LISTS: [&str; 2] = ["executors", "schedules"];
+
+/// Read `path` and, depth-first, the files in its `include:` list into one
+/// YAML mapping. Later includes override earlier ones and the including file
+/// overrides them all. `stack` holds the files being loaded, to catch cycles;
+/// `sources` collects every file read, lowest precedence first.
+pub(crate) fn load_layered(
+    path: &Path,
+    stack: &mut Vec<PathBuf>,
+    sources: &mut Vec<PathBuf>,
+) -> anyhow::Result<Mapping> {
+    let canonical = path
+        .canonicalize()
+        .with_context(|| format!("Failed to read config {}", path.display()))?;
+    if stack.contains(&canonical) {
+        anyhow::bail!("Config include cycle through {}", path.display());
+    }
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config {}", path.display()))?;
+    let mut layer = match parse_file(path, &contents)
+        .with_context(|| format!("Invalid config {}", path.display()))?
+    {
+        Value::Mapping(fields) => fields,
+        Value::Null => Mapping::new(),
+        _ => anyhow::bail!("Invalid config {}: expected a mapping", path.display()),
+    };
+
+    let includes: Vec<String> = match layer.remove("include") {
+        Some(list) => serde_yaml::from_value(list).with_context(|| {
+            format!(
+                "Invalid config {}: `include` must be a list of paths",
+                path.display()
+            )
+        })?,
+        None => Vec::new(),
+    };
+    for list in NAMED_LISTS {
+        check_unique_names(layer.get(list), list, path)?;
+    }
+
+    stack.push(canonical);
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let mut merged = Mapping::new();
+    for include in &includes {
+        let included = load_layered(&include_path(dir, include), stack, sources)?;
+        merge_layer(&mut merged, included);
+    }
+    stack.pop();
+
+    merge_layer(&mut merged, layer);
+    sources.push(path.to_path_buf());
+    Ok(merged)
+}
+
+/// Parse a config file's contents by its extension: `.toml` and `.json` are
+/// read as TOML and JSON, anything else as YAML. All three map onto the same
+/// YAML value, so layering and deserialization don't care about the format.
+pub(crate) fn parse_file(path: &Path, contents: &str) -> anyhow::Result<Value> {
+    let value = match path.extension().and_then(|e| e.to_str()) {
+        Some("toml") => toml::from_str(contents)?,
+        Some("json") => serde_json::from_str(contents)?,
+        _ => serde_yaml::from_str(contents)?,
+    };
+    Ok(value)
+}
+
+/// The merged config mapping for `path` with `profile` applied (see
+/// [`Config::load_profile`]). `sources` collects every file read.
+pub(crate) fn load_merged(
+    path: &Path,
+    profile: Option<&str>,
+    sources: &mut Vec<PathBuf>,
+) -> anyhow::Result<Mapping> {
+    let Some(name) = profile else {
+        return load_layered(path, &mut Vec::new(), sources);
+    };
+    // A profile file may stand in for a config file that doesn't exist
+    let mut merged = if path.exists() {
+        load_layered(path, &mut Vec::new(), sources)?
+    } else {
+        Mapping::new()
+    };
+
+    match merged.get("profiles").and_then(|p| p.get(name)).cloned() {
+        Some(Value::Mapping(layer)) => {
+            for list in NAMED_LISTS {
+                check_unique_names(layer.get(list), list, path)?;
+            }
+            merge_layer(&mut merged, layer);
+            Ok(merged)
+        }
+        Some(_) => anyhow::bail!(
+            "Invalid config {}: profile '{}' must be a mapping",
+            path.display(),
+            name
+        ),
+        None => match Config::profile_path(name) {
+            Some(file) => {
+                sources.clear();
+                load_layered(&file, &mut Vec::new(), sources)
+            }
+            None => anyhow::bail!(
+                "Profile '{}' not found under `profiles:` in {} or in {}",
+                name,
+                path.display(),
+                Config::profiles_dir().display()
+            ),
+        },
+    }
+}
+
+/// An `include:` entry (or other path in a config file), relative to the
+/// including file's directory.
+fn include_path(dir: &Path, include: &str) -> PathBuf {
+    match include.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir().unwrap_or_default().join(rest),
+        None => dir.join(include),
+    }
+}
+
+/// Fail if two entries of a named list in one file share a name.
+fn check_unique_names(list: Option<&Value>, field: &str, path: &Path) -> anyhow::Result<()> {
+    let Some(Value::Sequence(entries)) = list else {
+        return Ok(());
+    };
+    let mut seen = Vec::new();
+    for name in entries.iter().filter_map(entry_name) {
+        if seen.contains(&name) {
+            anyhow::bail!(
+                "Invalid config {}: {} entry '{}' is defined more than once",
+                path.display(),
+                field,
+                name
+            );
+        }
+        seen.push(name);
+    }
+    Ok(())
+}
+
+fn entry_name(entry: &Value) -> Option<&str> {
+    entry.get("name").and_then(Value::as_str)
+}
+
+/// Merge a higher-precedence top-level layer into `base`.
+fn merge_layer(base: &mut Mapping, layer: Mapping) {
+    for (key, value) in layer {
+        let named = key.as_str().is_some_and(|k| NAMED_LISTS.contains(&k));
+        match base.get_mut(&key) {
+            Some(existing) if named => merge_named(existing, value),
+            Some(existing) => merge_value(existing, value),
+            None => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Merge named entries (executors, schedules): an entry whose name is already
+/// present is merged into it field by field, any other entry is appended.
+fn merge_named(base: &mut Value, layer: Value) {
+    let (Value::Sequence(base), Value::Sequence(layer)) = (&mut *base, &layer) else {
+        *base = layer;
+        return;
+    };
+    for entry in layer {
+        let existing = entry_name(entry)
+            .and_then(|name| base.iter_mut().find(|e| entry_name(e) == Some(name)));
+        match existing {
+            Some(existing) => merge_value(existing, entry.clone()),
+            None => base.push(entry.clone()),
+        }
+    }
+}
+
+/// Merge mappings key by key; anything else (scalars, lists) is replaced.
+fn merge_value(base: &mut Value, layer: Value) {
+    match (base, layer) {
+        (Value::Mapping(base), Value::Mapping(layer)) => {
+            for (key, value) in layer {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_value(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, layer) => *base = layer,
+    }
 }
@@ -0,0 +1,126 @@
+use chrono::{Datelike, Timelike};
+use std::str::FromStr;
+
+/// A standard five-field cron expression: `minute hour day-of-month month day-of-week`.
+///
+/// Each field accepts `*`, single values, ranges (`1-5`), lists (`1,15`) and
+/// steps (`*/15`, `0-30/10`). Day-of-week is 0-7 with both 0 and 7 meaning
+/// Sunday. As in classic cron, when both day fields are restricted a time
+/// matches if either of them does.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronExpr {
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    dom_restricted: bool,
+    dow_restricted: bool,
+}
+
+impl CronExpr {
+    /// Whether the expression fires in the minute containing `t`.
+    pub fn matches<T: Datelike + Timelike>(&self, t: &T) -> bool {
+        let bit = |mask: u64, v: u32| mask & (1 << v) != 0;
+
+        if !bit(self.minutes, t.minute()) || !bit(self.hours, t.hour()) {
+            return false;
+        }
+        if !bit(self.months, t.month()) {
+            return false;
+        }
+
+        let dom = bit(self.days_of_month, t.day());
+        let dow = bit(self.days_of_week, t.weekday().num_days_from_sunday());
+        match (self.dom_restricted, self.dow_restricted) {
+            (true, true) => dom || dow,
+            (true, false) => dom,
+            (false, true) => dow,
+            (false, false) => true,
+        }
+    }
+}
+
+impl FromStr for CronExpr {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = s.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(format!(
+                "expected 5 fields (minute hour day month weekday), got {}",
+                fields.len()
+            ));
+        }
+
+        let mut days_of_week = parse_field(fields[4], 0, 7)?;
+        // 7 is an alias for Sunday
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week |= 1;
+        }
+
+        Ok(Self {
+            minutes: parse_field(fields[0], 0, 59)?,
+            hours: parse_field(fields[1], 0, 23)?,
+            days_of_month: parse_field(fields[2], 1, 31)?,
+            months: parse_field(fields[3], 1, 12)?,
+            days_of_week,
+            dom_restricted: fields[2] != "*",
+            dow_restricted: fields[4] != "*",
+        })
+    }
+}
+
+/// Parse one cron field into a bitmask of allowed values within `min..=max`.
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64, String> {
+    let mut mask = 0u64;
+
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .map_err(|_| format!("invalid step '{}' in '{}'", step, field))?;
+                if step == 0 {
+                    return Err(format!("step must be positive in '{}'", field));
+                }
+                (range, step)
+            }
+            None => (part, 1),
+        };
+
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((a, b)) = range.split_once('-') {
+            (parse_value(a, field)?, parse_value(b, field)?)
+        } else {
+            let v = parse_value(range, field)?;
+            // "5/10" means "from 5 to the end in steps of 10"
+            if step > 1 {
+                (v, max)
+            } else {
+                (v, v)
+            }
+        };
+
+        if start < min || end > max || start > end {
+            return Err(format!(
+                "'{}' is out of range {}-{} in '{}'",
+                range, min, max, field
+            ));
+        }
+
+        let mut v = start;
+        while v <= end {
+            mask |= 1 << v;
+            v += step;
+        }
+    }
+
+    Ok(mask)
+}
+
+fn parse_value(s: &str, field: &str) -> Result<u32, String> {
+    s.parse()
+        .map_err(|_| format!("invalid value '{}' in '{}'", s, field))
+}
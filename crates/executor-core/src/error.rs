@@ -14,6 +14,9 @@ pub enum ExecutorError {
     #[error("Task not found: {0}")]
     TaskNotFound(String),
 
+    #[error("Ambiguous task: {0}")]
+    AmbiguousTask(String),
+
     #[error("Task already running: {0}")]
     TaskAlreadyRunning(String),
 
@@ -31,4 +34,44 @@ pub enum ExecutorError {
 
     #[error("Executor not found: {0}")]
     ExecutorNotFound(String),
+
+    #[error("Operation cancelled")]
+    Cancelled,
+
+    #[error("Not supported by this executor: {0}")]
+    Unsupported(String),
+
+    #[error("External executor error: {0}")]
+    External(String),
+
+    #[error("Git error: {0}")]
+    Git(String),
+
+    #[error("Secret error: {0}")]
+    Secret(String),
+}
+
+impl ExecutorError {
+    /// A stable, machine-readable name for the kind of error, e.g.
+    /// `task_not_found`, for tooling that matches on failures.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ExecutorError::SshConnection(_) => "ssh_connection",
+            ExecutorError::SshCommand(_) => "ssh_command",
+            ExecutorError::ContainerRuntime(_) => "container_runtime",
+            ExecutorError::TaskNotFound(_) => "task_not_found",
+            ExecutorError::AmbiguousTask(_) => "ambiguous_task",
+            ExecutorError::TaskAlreadyRunning(_) => "task_already_running",
+            ExecutorError::Config(_) => "config",
+            ExecutorError::Io(_) => "io",
+            ExecutorError::Json(_) => "json",
+            ExecutorError::Process(_) => "process",
+            ExecutorError::ExecutorNotFound(_) => "executor_not_found",
+            ExecutorError::Cancelled => "cancelled",
+            ExecutorError::Unsupported(_) => "unsupported",
+            ExecutorError::External(_) => "external",
+            ExecutorError::Git(_) => "git",
+            ExecutorError::Secret(_) => "secret",
+        }
+    }
 }
@@ -0,0 +1,173 @@
+use crate::error::ExecutorError;
+use crate::metadata::TaskMetadata;
+use crate::store::{MetadataQuery, MetadataStore};
+use crate::task::{TaskId, TaskStatus};
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Directory for event streams: ~/.openclaw-agent/events/
+pub fn events_dir() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("/tmp"))
+        .join(".openclaw-agent")
+        .join("events")
+}
+
+/// The global stream every event is appended to.
+pub fn global_events_path() -> PathBuf {
+    events_dir().join("events.jsonl")
+}
+
+/// The stream holding only one task's events.
+pub fn task_events_path(task_id: &TaskId) -> PathBuf {
+    events_dir()
+        .join("tasks")
+        .join(format!("{}.jsonl", task_id))
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    TaskCreated,
+    TaskRunning,
+    /// A status check found the task still running.
+    Heartbeat,
+    TaskCompleted,
+    TaskFailed,
+    TaskKilled,
+    /// Any other status change (e.g. to `unknown`).
+    StatusChanged,
+    WebhookSent,
+    WebhookFailed,
+    /// A running task passed `hooks.heartbeat_timeout_minutes`.
+    HeartbeatTimeout,
+}
+
+/// One line of an event stream.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Event {
+    pub timestamp: DateTime<Utc>,
+    pub kind: EventKind,
+    pub task_id: TaskId,
+    pub executor: String,
+    pub status: TaskStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl Event {
+    pub fn new(kind: EventKind, meta: &TaskMetadata) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            kind,
+            task_id: meta.task_id.clone(),
+            executor: meta.executor_name.clone(),
+            status: meta.status,
+            detail: None,
+        }
+    }
+}
+
+/// Append an event to the global stream and the task's own stream.
+pub fn emit(event: &Event) -> Result<(), std::io::Error> {
+    let mut line = serde_json::to_string(event).map_err(std::io::Error::other)?;
+    line.push('\n');
+
+    for path in [global_events_path(), task_events_path(&event.task_id)] {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?
+            .write_all(line.as_bytes())?;
+    }
+    Ok(())
+}
+
+/// Whether a task's own stream already holds an event of this kind.
+pub fn task_has_event(task_id: &TaskId, kind: EventKind) -> bool {
+    std::fs::read_to_string(task_events_path(task_id))
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Event>(line).ok())
+        .any(|event| event.kind == kind)
+}
+
+/// When a task's own stream last got an event of this kind.
+pub fn last_event_at(task_id: &TaskId, kind: EventKind) -> Option<DateTime<Utc>> {
+    std::fs::read_to_string(task_events_path(task_id))
+        .unwrap_or_default()
+        .lines()
+        .rev()
+        .filter_map(|line| serde_json::from_str::<Event>(line).ok())
+        .find(|event| event.kind == kind)
+        .map(|event| event.timestamp)
+}
+
+/// Emit an event, reporting (not propagating) a failed write.
+pub fn emit_or_warn(event: &Event) {
+    if let Err(e) = emit(event) {
+        eprintln!("Warning: failed to write event: {}", e);
+    }
+}
+
+/// Wraps a store and emits lifecycle events as metadata is written, so every
+/// executor's status transitions show up without the executors knowing.
+pub struct EventingStore {
+    inner: Arc<dyn MetadataStore>,
+}
+
+impl EventingStore {
+    pub fn new(inner: Arc<dyn MetadataStore>) -> Self {
+        Self { inner }
+    }
+}
+
+impl MetadataStore for EventingStore {
+    fn get(&self, task_id: &TaskId) -> Result<Option<TaskMetadata>, ExecutorError> {
+        self.inner.get(task_id)
+    }
+
+    fn put(&self, meta: &TaskMetadata) -> Result<(), ExecutorError> {
+        let previous = self.inner.get(&meta.task_id)?.map(|m| m.status);
+        self.inner.put(meta)?;
+
+        let mut kinds = Vec::new();
+        if previous.is_none() {
+            kinds.push(EventKind::TaskCreated);
+        }
+        if previous != Some(meta.status) {
+            kinds.extend(match meta.status {
+                TaskStatus::Starting => None,
+                TaskStatus::Running => Some(EventKind::TaskRunning),
+                TaskStatus::Completed => Some(EventKind::TaskCompleted),
+                TaskStatus::Failed => Some(EventKind::TaskFailed),
+                TaskStatus::Killed => Some(EventKind::TaskKilled),
+                TaskStatus::Unknown => Some(EventKind::StatusChanged),
+            });
+        }
+
+        for kind in kinds {
+            emit_or_warn(&Event::new(kind, meta));
+        }
+        Ok(())
+    }
+
+    fn list(&self) -> Result<Vec<TaskMetadata>, ExecutorError> {
+        self.inner.list()
+    }
+
+    fn delete(&self, task_id: &TaskId) -> Result<(), ExecutorError> {
+        self.inner.delete(task_id)
+    }
+
+    fn query(&self, query: &MetadataQuery) -> Result<Vec<TaskMetadata>, ExecutorError> {
+        self.inner.query(query)
+    }
+}
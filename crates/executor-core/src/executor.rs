@@ -1,30 +1,337 @@
 use crate::error::ExecutorError;
+use crate::host::HostStats;
+use crate::log_filter::LogFilter;
 use crate::metadata::TaskMetadata;
+use crate::progress::TaskProgress;
+use crate::provision::ProvisionReport;
 use crate::task::{TaskId, TaskRequest};
+use crate::usage::ResourceUsage;
+use std::future::Future;
+use std::path::Path;
+use tokio_util::sync::CancellationToken;
 
 /// Core executor trait. Every executor type implements this.
 /// Maps to the interface described in the README:
 ///   start, status, logs (fetch_logs), kill, cleanup
+///
+/// Every operation takes a `CancellationToken`; implementations must stop at
+/// their next await point or remote call once it fires and return
+/// `ExecutorError::Cancelled`.
 #[async_trait::async_trait]
 pub trait Executor: Send + Sync {
     /// Name of this executor instance (from config).
     fn name(&self) -> &str;
 
-    /// Executor type string ("ssh", "container", "local").
+    /// Executor type string ("ssh", "container", "local", "external", "mock", ...).
     fn executor_type(&self) -> &str;
 
     /// Start a task. Returns task ID and initial metadata.
-    async fn start(&self, request: TaskRequest) -> Result<TaskMetadata, ExecutorError>;
+    async fn start(
+        &self,
+        request: TaskRequest,
+        cancel: &CancellationToken,
+    ) -> Result<TaskMetadata, ExecutorError>;
+
+    /// What `start` would do with `request`, without running anything on
+    /// the backend or recording the task (`start --dry-run`).
+    fn plan_start(&self, _request: TaskRequest) -> Result<StartPlan, ExecutorError> {
+        Err(ExecutorError::Unsupported("start --dry-run".into()))
+    }
 
     /// Get current status/metadata for a task.
-    async fn status(&self, task_id: &TaskId) -> Result<TaskMetadata, ExecutorError>;
+    async fn status(
+        &self,
+        task_id: &TaskId,
+        cancel: &CancellationToken,
+    ) -> Result<TaskMetadata, ExecutorError>;
+
+    /// Status of several of this executor's tasks, in the order given. Tasks
+    /// with no recorded metadata are left out. Backends that pay for every
+    /// round-trip should override this to check them all at once.
+    async fn status_many(
+        &self,
+        task_ids: &[TaskId],
+        cancel: &CancellationToken,
+    ) -> Result<Vec<TaskMetadata>, ExecutorError> {
+        let mut checked = Vec::new();
+        for task_id in task_ids {
+            match self.status(task_id, cancel).await {
+                Ok(meta) => checked.push(meta),
+                Err(ExecutorError::TaskNotFound(_)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(checked)
+    }
 
     /// Fetch recent log lines from the task.
-    async fn logs(&self, task_id: &TaskId, lines: usize) -> Result<Vec<String>, ExecutorError>;
+    async fn logs(
+        &self,
+        task_id: &TaskId,
+        lines: usize,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<String>, ExecutorError>;
+
+    /// The last `lines` log lines that pass `filter`, filtered where the log
+    /// lives so only matching lines are transferred.
+    async fn filtered_logs(
+        &self,
+        _task_id: &TaskId,
+        _lines: usize,
+        _filter: &LogFilter,
+        _cancel: &CancellationToken,
+    ) -> Result<Vec<String>, ExecutorError> {
+        Err(ExecutorError::Unsupported("filtering logs".into()))
+    }
 
     /// Kill a running task.
-    async fn kill(&self, task_id: &TaskId) -> Result<(), ExecutorError>;
+    async fn kill(&self, task_id: &TaskId, cancel: &CancellationToken) -> Result<(), ExecutorError>;
 
     /// Cleanup task artifacts (containers, temp files, etc.).
-    async fn cleanup(&self, task_id: &TaskId) -> Result<(), ExecutorError>;
+    async fn cleanup(&self, task_id: &TaskId, cancel: &CancellationToken)
+        -> Result<(), ExecutorError>;
+
+    /// Clean up several of this executor's tasks, with an outcome per task
+    /// in the order given; one task failing doesn't stop the rest. Backends
+    /// that pay for every round-trip should override this to clean them all
+    /// up at once, and report the space freed if they can measure it.
+    async fn cleanup_many(
+        &self,
+        task_ids: &[TaskId],
+        cancel: &CancellationToken,
+    ) -> Result<Vec<CleanupOutcome>, ExecutorError> {
+        let mut outcomes = Vec::new();
+        for task_id in task_ids {
+            let error = match self.cleanup(task_id, cancel).await {
+                Ok(()) => None,
+                Err(ExecutorError::Cancelled) => return Err(ExecutorError::Cancelled),
+                Err(e) => Some(e.to_string()),
+            };
+            outcomes.push(CleanupOutcome {
+                task_id: task_id.clone(),
+                freed_kb: None,
+                error,
+            });
+        }
+        Ok(outcomes)
+    }
+
+    /// Tasks the backend still holds artifacts for (task dirs, containers),
+    /// used to reconcile local metadata with reality.
+    async fn list_remote(&self, _cancel: &CancellationToken) -> Result<Vec<RemoteTask>, ExecutorError> {
+        Err(ExecutorError::Unsupported("listing remote tasks".into()))
+    }
+
+    /// Run a shell command in a task's workspace, streaming its output to this
+    /// process's stdout/stderr. Returns the command's exit code.
+    async fn exec(
+        &self,
+        _task_id: &TaskId,
+        _command: &str,
+        _cancel: &CancellationToken,
+    ) -> Result<i32, ExecutorError> {
+        Err(ExecutorError::Unsupported("exec".into()))
+    }
+
+    /// Like `exec`, but capture the command's combined stdout/stderr instead
+    /// of streaming it. Returns the exit code and the output.
+    async fn exec_output(
+        &self,
+        _task_id: &TaskId,
+        _command: &str,
+        _cancel: &CancellationToken,
+    ) -> Result<(i32, String), ExecutorError> {
+        Err(ExecutorError::Unsupported("exec".into()))
+    }
+
+    /// Sample the resources of the host this executor's tasks run on, with
+    /// disk figures for `dir` (the backend's task root when `None`). Used
+    /// before a task exists, so it can't go through `exec_output`.
+    async fn host_stats(
+        &self,
+        _dir: Option<&str>,
+        _cancel: &CancellationToken,
+    ) -> Result<Option<HostStats>, ExecutorError> {
+        Err(ExecutorError::Unsupported("sampling host stats".into()))
+    }
+
+    /// The full path of the `claude` this executor's host would run (its
+    /// `claude_path` if that works, else one on `PATH` or in the usual
+    /// install dirs), or `None` if it has none. Reaching the host at all is
+    /// part of the check, so `config --init` uses it to probe new executors.
+    async fn find_claude(
+        &self,
+        _cancel: &CancellationToken,
+    ) -> Result<Option<String>, ExecutorError> {
+        Err(ExecutorError::Unsupported("finding claude".into()))
+    }
+
+    /// Install what tasks need on the host (git, Node.js, the claude CLI) and
+    /// create the task root; with `install` unset, only check for them.
+    async fn provision(
+        &self,
+        _install: bool,
+        _cancel: &CancellationToken,
+    ) -> Result<ProvisionReport, ExecutorError> {
+        Err(ExecutorError::Unsupported("provisioning".into()))
+    }
+
+    /// Push the configured remote agent to the host again, restart it and
+    /// remove older builds there. Tasks already running are left alone.
+    async fn upgrade_agent(&self, _cancel: &CancellationToken) -> Result<AgentInfo, ExecutorError> {
+        Err(ExecutorError::Unsupported("remote agents".into()))
+    }
+
+    /// Sample the CPU time and memory of a running task's processes, or
+    /// `None` if there is nothing left to sample.
+    async fn usage(
+        &self,
+        _task_id: &TaskId,
+        _cancel: &CancellationToken,
+    ) -> Result<Option<ResourceUsage>, ExecutorError> {
+        Err(ExecutorError::Unsupported("resource usage".into()))
+    }
+
+    /// Turns taken and the last tool called by a `claude_code` task run with
+    /// `stream_json`, or `None` if its log doesn't record them.
+    async fn progress(
+        &self,
+        _task_id: &TaskId,
+        _cancel: &CancellationToken,
+    ) -> Result<Option<TaskProgress>, ExecutorError> {
+        Err(ExecutorError::Unsupported("task progress".into()))
+    }
+
+    /// Bytes of output a task has logged so far, for watching how fast its
+    /// log grows.
+    async fn log_size(
+        &self,
+        _task_id: &TaskId,
+        _cancel: &CancellationToken,
+    ) -> Result<u64, ExecutorError> {
+        Err(ExecutorError::Unsupported("log size".into()))
+    }
+
+    /// What a finished `git_branch` task changed in its workspace, captured
+    /// into the task dir on the backend.
+    async fn changes(
+        &self,
+        _task_id: &TaskId,
+        _cancel: &CancellationToken,
+    ) -> Result<TaskChanges, ExecutorError> {
+        Err(ExecutorError::Unsupported("capturing changes".into()))
+    }
+
+    /// Copy the file at `path` on a task's backend to `dest` on this machine.
+    /// A relative `path` is resolved against `root`.
+    async fn download(
+        &self,
+        _task_id: &TaskId,
+        _path: &str,
+        _root: FileRoot,
+        _dest: &Path,
+        _cancel: &CancellationToken,
+    ) -> Result<(), ExecutorError> {
+        Err(ExecutorError::Unsupported("download".into()))
+    }
+
+    /// Copy `source` on this machine to `path` on a task's backend, creating
+    /// missing parent directories and replacing any existing file. A relative
+    /// `path` is resolved against `root`.
+    async fn upload(
+        &self,
+        _task_id: &TaskId,
+        _source: &Path,
+        _path: &str,
+        _root: FileRoot,
+        _cancel: &CancellationToken,
+    ) -> Result<(), ExecutorError> {
+        Err(ExecutorError::Unsupported("upload".into()))
+    }
+}
+
+/// What a relative path given to `Executor::download` or `Executor::upload`
+/// is relative to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FileRoot {
+    /// The task's workspace.
+    #[default]
+    Workspace,
+    /// The task's own directory on the backend (logs, captured diffs).
+    TaskDir,
+}
+
+/// Join a relative `path` onto `dir`, the directory a `FileRoot` stands for.
+/// Absolute paths are returned as-is.
+pub fn resolve_path(path: &str, dir: &str) -> String {
+    if path.starts_with('/') {
+        path.to_string()
+    } else {
+        format!("{}/{}", dir.trim_end_matches('/'), path)
+    }
+}
+
+/// A task's changes relative to the commit its branch started from.
+#[derive(Debug, Clone, Default)]
+pub struct TaskChanges {
+    /// Paths changed, added or removed, relative to the workspace.
+    pub files: Vec<String>,
+    /// Full `git diff` of those changes.
+    pub diff: String,
+}
+
+/// A remote agent as it answered after an upgrade.
+#[derive(Debug, Clone)]
+pub struct AgentInfo {
+    /// Where the agent runs from on the host.
+    pub path: String,
+    pub version: String,
+    pub protocol: u32,
+    /// Older builds removed from the host.
+    pub removed: usize,
+}
+
+/// What `Executor::start` would do with a request.
+#[derive(Debug, Clone)]
+pub struct StartPlan {
+    /// What would be done on the backend before launching, such as cloning
+    /// the task's repo; their scripts are left out, as they may hold tokens.
+    pub steps: Vec<String>,
+    /// The command line the task would be launched with, escaped as it
+    /// would be run.
+    pub command: String,
+    /// The metadata that would be recorded, before the task runs.
+    pub metadata: TaskMetadata,
+}
+
+/// How cleaning up one task in `Executor::cleanup_many` went.
+#[derive(Debug, Clone)]
+pub struct CleanupOutcome {
+    pub task_id: TaskId,
+    /// Space the task's artifacts took, if the backend measured it.
+    pub freed_kb: Option<u64>,
+    /// Why the task couldn't be cleaned up; `None` if it was.
+    pub error: Option<String>,
+}
+
+/// A task found on an executor's backend.
+#[derive(Debug, Clone)]
+pub struct RemoteTask {
+    pub task_id: TaskId,
+    /// Metadata recovered from the backend, if it keeps any. Adopted tasks
+    /// are reported as running so the next status check settles their state.
+    pub metadata: Option<TaskMetadata>,
+}
+
+/// Run a future unless the token fires first, in which case the future is
+/// dropped and `ExecutorError::Cancelled` is returned.
+pub async fn with_cancel<T, F>(cancel: &CancellationToken, fut: F) -> Result<T, ExecutorError>
+where
+    F: Future<Output = Result<T, ExecutorError>>,
+{
+    tokio::select! {
+        biased;
+        _ = cancel.cancelled() => Err(ExecutorError::Cancelled),
+        res = fut => res,
+    }
 }
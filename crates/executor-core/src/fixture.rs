@@ -0,0 +1,178 @@
+//! Record/replay fixtures (`fixture`): an executor set to record appends
+//! every command it runs on its backend, and what came back, to a JSONL
+//! file; one set to replay answers its commands from such a file, in order,
+//! without touching the backend. Tests can then drive `SshExecutor` or
+//! `ContainerExecutor` logic deterministically with no host or runtime.
+//!
+//! Task IDs differ from run to run, so commands are compared with every
+//! task ID (and the 8-digit prefix container names use) masked out.
+//!
+//! Fixtures are meant to be committed, so secrets never reach the file: the
+//! executor's resolved `env` values (and its services'), and the git token,
+//! are recorded as `<secret:NAME>`, and live commands are redacted the same
+//! way before they are compared on replay.
+
+use crate::config::{ExecutorConfig, FixtureConfig, FixtureMode};
+use crate::error::ExecutorError;
+use crate::secrets::{self, SecretRef};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::io::Write;
+use std::sync::{Mutex, OnceLock};
+
+/// One command an executor ran, and how it went.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Interaction {
+    pub command: String,
+    /// What the command printed, if it succeeded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    /// Why it failed, if it did.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// A fixture file an executor records to or replays from.
+pub struct Fixture {
+    config: FixtureConfig,
+    /// The executor's config, whose secrets are redacted.
+    executor: ExecutorConfig,
+    /// `(name, value)` of each secret, longest value first, resolved on
+    /// first use.
+    secrets: OnceLock<Vec<(String, String)>>,
+    /// Interactions not replayed yet, read from the file on first use.
+    pending: Mutex<Option<VecDeque<Interaction>>>,
+}
+
+impl Fixture {
+    pub fn new(config: FixtureConfig, executor: &ExecutorConfig) -> Self {
+        Self {
+            config,
+            executor: executor.clone(),
+            secrets: OnceLock::new(),
+            pending: Mutex::new(None),
+        }
+    }
+
+    pub fn is_replay(&self) -> bool {
+        self.config.mode == FixtureMode::Replay
+    }
+
+    /// The recorded result of `command`, if replaying: the next interaction
+    /// in the file, which must be for the same command. `None` if recording.
+    pub fn replay(&self, command: &str) -> Option<Result<String, ExecutorError>> {
+        if !self.is_replay() {
+            return None;
+        }
+        let path = &self.config.path;
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        if pending.is_none() {
+            match load(path) {
+                Ok(interactions) => *pending = Some(interactions),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        let Some(next) = pending.as_mut().and_then(VecDeque::pop_front) else {
+            return Some(Err(ExecutorError::Config(format!(
+                "fixture {}: no recorded command left for `{}`",
+                path, command
+            ))));
+        };
+        if mask(&next.command) != mask(&self.redact(command)) {
+            return Some(Err(ExecutorError::Config(format!(
+                "fixture {}: expected `{}`, got `{}`",
+                path, next.command, command
+            ))));
+        }
+        Some(match next.error {
+            Some(error) => Err(ExecutorError::Process(error)),
+            None => Ok(next.output.unwrap_or_default()),
+        })
+    }
+
+    /// Append `command` and its `result` to the file, if recording.
+    /// Cancelled commands never finished, so they aren't recorded. A failed
+    /// write is reported, never fatal.
+    pub fn record(&self, command: &str, result: &Result<String, ExecutorError>) {
+        if self.config.mode != FixtureMode::Record {
+            return;
+        }
+        let path = &self.config.path;
+        let interaction = match result {
+            Err(ExecutorError::Cancelled) => return,
+            Ok(output) => Interaction {
+                command: self.redact(command),
+                output: Some(self.redact(output)),
+                error: None,
+            },
+            Err(e) => Interaction {
+                command: self.redact(command),
+                output: None,
+                error: Some(self.redact(&e.to_string())),
+            },
+        };
+        let appended = serde_json::to_string(&interaction)
+            .map_err(std::io::Error::from)
+            .and_then(|line| {
+                std::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .and_then(|mut file| writeln!(file, "{}", line))
+            });
+        if let Err(e) = appended {
+            eprintln!("Warning: failed to record to fixture {}: {}", path, e);
+        }
+    }
+
+    /// `text` with every secret replaced by `<secret:NAME>`.
+    fn redact(&self, text: &str) -> String {
+        let secrets = self.secrets.get_or_init(|| secret_values(&self.executor));
+        secrets.iter().fold(text.to_string(), |text, (name, value)| {
+            text.replace(value.as_str(), &format!("<secret:{}>", name))
+        })
+    }
+}
+
+/// `(name, value)` of each secret `executor` hands its tasks, longest value
+/// first so one containing another is redacted whole. Plain (not referenced)
+/// env values under 8 characters are left alone: they are rarely
+/// credentials, and would match unrelated text. Secrets that can't be
+/// resolved are skipped; the command using them fails anyway.
+fn secret_values(executor: &ExecutorConfig) -> Vec<(String, String)> {
+    let envs = std::iter::once(&executor.env).chain(executor.services.iter().map(|s| &s.env));
+    let mut secrets: Vec<(String, String)> = envs
+        .flat_map(|env| {
+            env.iter().filter_map(|(key, value)| {
+                let referenced = matches!(SecretRef::parse(value), Ok(Some(_)));
+                let resolved = secrets::lookup(value).ok()?;
+                (referenced || resolved.len() >= 8).then(|| (key.clone(), resolved))
+            })
+        })
+        .chain(executor.git.token().map(|token| ("git.token".to_string(), token)))
+        .filter(|(_, value)| !value.is_empty())
+        .collect();
+    secrets.sort_by_key(|(_, value)| std::cmp::Reverse(value.len()));
+    secrets
+}
+
+/// Every interaction in the fixture file at `path`.
+fn load(path: &str) -> Result<VecDeque<Interaction>, ExecutorError> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| ExecutorError::Config(format!("fixture {}: {}", path, e)))?;
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| serde_json::from_str(line).map_err(ExecutorError::from))
+        .collect()
+}
+
+/// `command` with task IDs, and their 8-digit prefixes, masked out.
+fn mask(command: &str) -> String {
+    static TASK_ID: OnceLock<Regex> = OnceLock::new();
+    let task_id = TASK_ID.get_or_init(|| {
+        Regex::new(r"\b[0-9a-f]{8}(-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12})?\b")
+            .expect("valid pattern")
+    });
+    task_id.replace_all(command, "<task>").into_owned()
+}
@@ -0,0 +1,207 @@
+//! Git plumbing for task workspaces: cloning a fresh workspace before a task
+//! starts, and giving a task a branch of its own whose changes are captured
+//! when it finishes.
+//!
+//! Executors run these scripts on their own backend, so git runs where the
+//! task does.
+
+use crate::config::GitConfig;
+use crate::error::ExecutorError;
+use crate::task::{RepoSpec, TaskId};
+
+/// A `sh` script that clones `repo` into `dest` and prints the checked-out
+/// commit SHA as its last line. Errors go to stdout and the script always
+/// exits 0, so every backend can hand its output straight to [`parse_commit`].
+pub fn clone_script(repo: &RepoSpec, dest: &str, git: &GitConfig) -> String {
+    let mut clone = String::from("git clone --quiet");
+    if let Some(depth) = repo.depth {
+        clone.push_str(&format!(" --depth {}", depth));
+    }
+    if let Some(ref branch) = repo.branch {
+        clone.push_str(&format!(" --branch {}", quote(branch)));
+    }
+    let url = git.authenticated_url(&repo.url);
+    clone.push_str(&format!(" {} {}", quote(&url), quote(dest)));
+
+    let mut lines = vec!["set -e".to_string()];
+    lines.extend(ssh_key_export(git));
+    lines.push(clone);
+    if url != repo.url {
+        // Keep the token out of the workspace's .git/config
+        lines.push(format!(
+            "git -C {} remote set-url origin {}",
+            quote(dest),
+            quote(&repo.url)
+        ));
+    }
+    lines.push(format!("git -C {} rev-parse HEAD", quote(dest)));
+
+    format!("(\n{}\n) 2>&1 || true", lines.join("\n"))
+}
+
+/// The commit SHA printed by a [`clone_script`] run, or the script's output
+/// as the error if it didn't get that far.
+pub fn parse_commit(output: &str) -> Result<String, ExecutorError> {
+    parse_sha(output).ok_or_else(|| ExecutorError::Git(format!("clone failed: {}", output.trim())))
+}
+
+/// The branch a `git_branch` task works on.
+pub fn branch_name(task_id: &TaskId) -> String {
+    format!("openclaw/{}", task_id)
+}
+
+/// A `sh` script, run in the workspace, that creates the task's branch and
+/// records the commit it starts from in `<out_dir>/base` (also printed; see
+/// [`parse_sha`]). Prints nothing useful if the workspace isn't a git work tree.
+pub fn branch_script(task_id: &TaskId, out_dir: &str) -> String {
+    format!(
+        "git rev-parse --is-inside-work-tree >/dev/null 2>&1 && git checkout -q -b {} 2>&1 && git rev-parse HEAD | tee {}/base",
+        quote(&branch_name(task_id)),
+        quote(out_dir)
+    )
+}
+
+/// A `sh` script, run in the workspace, that diffs it (including new files)
+/// against the commit recorded by [`branch_script`], writing `changes.diff`
+/// and `changed_files.txt` into `out_dir` and printing the file list. Prints
+/// nothing if there is no recorded base.
+pub fn changes_script(out_dir: &str) -> String {
+    let out = quote(out_dir);
+    let lines = [
+        "set -e".to_string(),
+        format!("base=$(cat {}/base)", out),
+        // Intent-to-add, so new files show up in the diff
+        "git add -A -N .".to_string(),
+        format!("git diff \"$base\" > {}/changes.diff", out),
+        format!(
+            "git diff --name-only \"$base\" | tee {}/changed_files.txt",
+            out
+        ),
+    ];
+    format!("(\n{}\n) 2>/dev/null || true", lines.join("\n"))
+}
+
+/// A `sh` script, run in a finished task's workspace, that commits all its
+/// changes on the task's branch and pushes the branch to `origin`. See
+/// [`parse_publish`] for what it prints.
+pub fn publish_script(task_id: &TaskId, message: &str, git: &GitConfig) -> String {
+    let mut lines = vec!["set -e".to_string()];
+    lines.extend(ssh_key_export(git));
+    lines.extend([
+        "origin=$(git config --get remote.origin.url)".to_string(),
+        "echo \"origin=$origin\"".to_string(),
+        // The branch checked out before the task's branch was created
+        "echo \"base=$(git rev-parse --abbrev-ref @{-1} 2>/dev/null || true)\"".to_string(),
+        // Commit as the agent when the host has no git identity
+        "git config user.name >/dev/null || export GIT_AUTHOR_NAME='OpenClaw Agent' GIT_COMMITTER_NAME='OpenClaw Agent'".to_string(),
+        "git config user.email >/dev/null || export GIT_AUTHOR_EMAIL='openclaw@localhost' GIT_COMMITTER_EMAIL='openclaw@localhost'".to_string(),
+        "git add -A".to_string(),
+        format!("git diff --cached --quiet || git commit -q -m {}", quote(message)),
+        "push=\"$origin\"".to_string(),
+    ]);
+    if let Some(token) = git.token() {
+        let user = git.username.as_deref().unwrap_or("x-access-token");
+        lines.push(format!("cred={}", quote(&format!("{}:{}", user, token))));
+        lines.push(
+            "case \"$origin\" in https://*) push=\"https://$cred@${origin#https://}\";; esac"
+                .to_string(),
+        );
+    }
+    lines.push(format!(
+        "git push -q \"$push\" HEAD:refs/heads/{}",
+        branch_name(task_id)
+    ));
+    lines.push("echo pushed".to_string());
+
+    format!("(\n{}\n) 2>&1", lines.join("\n"))
+}
+
+/// Where a [`publish_script`] run pushed the task's branch.
+#[derive(Debug, Clone)]
+pub struct Published {
+    /// `origin`'s URL in the workspace.
+    pub origin: String,
+    /// The branch the task's branch was created from, if git still knows it.
+    pub base: Option<String>,
+}
+
+/// Read a [`publish_script`] run's output, or return it as the error if the
+/// push didn't succeed.
+pub fn parse_publish(output: &str) -> Result<Published, ExecutorError> {
+    let pushed = output.lines().any(|l| l.trim() == "pushed");
+    let field = |name: &str| {
+        output
+            .lines()
+            .find_map(|l| l.strip_prefix(name))
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    };
+    match (pushed, field("origin=")) {
+        (true, Some(origin)) => Ok(Published {
+            origin,
+            base: field("base=").filter(|b| b != "HEAD"),
+        }),
+        _ => Err(ExecutorError::Git(format!(
+            "push failed: {}",
+            output.trim()
+        ))),
+    }
+}
+
+/// Lines added and removed in one file of a diff.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileStat {
+    pub path: String,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+/// Per-file line counts of a unified `git diff`, like `git diff --stat`.
+pub fn diff_stat(diff: &str) -> Vec<FileStat> {
+    let mut stats: Vec<FileStat> = Vec::new();
+    let mut in_hunk = false;
+    for line in diff.lines() {
+        if let Some(header) = line.strip_prefix("diff --git ") {
+            // `a/<path> b/<path>`: the new path is the second half
+            let path = header
+                .split_once(" b/")
+                .map_or(header, |(_, b)| b)
+                .to_string();
+            stats.push(FileStat {
+                path,
+                insertions: 0,
+                deletions: 0,
+            });
+            in_hunk = false;
+            continue;
+        }
+        let Some(stat) = stats.last_mut() else {
+            continue;
+        };
+        if line.starts_with("@@") {
+            in_hunk = true;
+        } else if in_hunk && line.starts_with('+') {
+            stat.insertions += 1;
+        } else if in_hunk && line.starts_with('-') {
+            stat.deletions += 1;
+        }
+    }
+    stats
+}
+
+/// The commit SHA on the last line of a script's output, if there is one.
+pub fn parse_sha(output: &str) -> Option<String> {
+    let last = output.lines().rev().find(|l| !l.trim().is_empty())?.trim();
+    (last.len() >= 40 && last.chars().all(|c| c.is_ascii_hexdigit())).then(|| last.to_string())
+}
+
+/// `export GIT_SSH_COMMAND=...` when an SSH key is configured.
+fn ssh_key_export(git: &GitConfig) -> Option<String> {
+    let key = git.ssh_key.as_ref()?;
+    let ssh = format!("ssh -i {} -o IdentitiesOnly=yes", quote(key));
+    Some(format!("export GIT_SSH_COMMAND={}", quote(&ssh)))
+}
+
+pub(crate) fn quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
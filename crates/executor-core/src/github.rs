@@ -0,0 +1,108 @@
+//! Opening pull requests for finished tasks (`git.auto_pr`), through the
+//! GitHub API when a token is configured or the `gh` CLI otherwise.
+
+use crate::http;
+use serde_json::json;
+use tokio::process::Command;
+
+const API_URL: &str = "https://api.github.com";
+
+/// A pull request to open from a pushed task branch.
+#[derive(Debug, Clone)]
+pub struct PullRequest {
+    /// `owner/name`
+    pub repo: String,
+    pub head: String,
+    /// Target branch; the repository's default branch if unset.
+    pub base: Option<String>,
+    pub title: String,
+    pub body: String,
+}
+
+/// `owner/name` of a GitHub remote, from its HTTPS or SSH URL.
+pub fn repo_slug(url: &str) -> Option<String> {
+    let path = url
+        .strip_prefix("https://github.com/")
+        .or_else(|| url.strip_prefix("git@github.com:"))
+        .or_else(|| url.strip_prefix("ssh://git@github.com/"))?;
+    let path = path.trim_end_matches('/').trim_end_matches(".git");
+    let mut parts = path.split('/');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(owner), Some(name), None) if !owner.is_empty() && !name.is_empty() => {
+            Some(format!("{}/{}", owner, name))
+        }
+        _ => None,
+    }
+}
+
+/// Open the pull request and return its URL.
+pub async fn open_pull_request(pr: &PullRequest, token: Option<&str>) -> Result<String, String> {
+    match token {
+        Some(token) => open_with_api(pr, token).await,
+        None => open_with_gh(pr).await,
+    }
+}
+
+async fn open_with_api(pr: &PullRequest, token: &str) -> Result<String, String> {
+    let auth = format!("Bearer {}", token);
+    let headers = [
+        ("Authorization", auth.as_str()),
+        ("Accept", "application/vnd.github+json"),
+        ("User-Agent", "openclaw-agent"),
+    ];
+
+    let base = match pr.base {
+        Some(ref base) => base.clone(),
+        None => {
+            let repo = http::get(&format!("{}/repos/{}", API_URL, pr.repo), &headers).await?;
+            let repo: serde_json::Value = serde_json::from_str(&repo).map_err(|e| e.to_string())?;
+            repo["default_branch"]
+                .as_str()
+                .ok_or("repository has no default branch")?
+                .to_string()
+        }
+    };
+
+    let body = json!({
+        "title": pr.title,
+        "head": pr.head,
+        "base": base,
+        "body": pr.body,
+    });
+    let url = format!("{}/repos/{}/pulls", API_URL, pr.repo);
+    let response = http::post_json(&url, &headers, &body).await?;
+    let response: serde_json::Value = serde_json::from_str(&response).map_err(|e| e.to_string())?;
+    response["html_url"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| "response has no html_url".to_string())
+}
+
+async fn open_with_gh(pr: &PullRequest) -> Result<String, String> {
+    let mut cmd = Command::new("gh");
+    cmd.args(["pr", "create", "--repo", &pr.repo, "--head", &pr.head])
+        .args(["--title", &pr.title, "--body", &pr.body]);
+    if let Some(ref base) = pr.base {
+        cmd.args(["--base", base]);
+    }
+
+    let output = cmd
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| format!("failed to run gh: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "gh pr create failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    // gh prints the new PR's URL last
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .rev()
+        .find(|l| l.starts_with("https://"))
+        .map(str::to_string)
+        .ok_or_else(|| "gh did not print a pull request URL".to_string())
+}
@@ -0,0 +1,325 @@
+use crate::config::HooksConfig;
+use crate::error::ExecutorError;
+use crate::executor::{
+    AgentInfo, CleanupOutcome, Executor, FileRoot, RemoteTask, StartPlan, TaskChanges,
+};
+use crate::host::HostStats;
+use crate::log_filter::LogFilter;
+use crate::metadata::TaskMetadata;
+use crate::progress::TaskProgress;
+use crate::provision::ProvisionReport;
+use crate::task::{TaskId, TaskRequest};
+use crate::usage::ResourceUsage;
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+/// The state transition a hook fires on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookEvent {
+    Start,
+    Complete,
+    Fail,
+    HeartbeatTimeout,
+}
+
+impl HookEvent {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            HookEvent::Start => "on_start",
+            HookEvent::Complete => "on_complete",
+            HookEvent::Fail => "on_fail",
+            HookEvent::HeartbeatTimeout => "on_heartbeat_timeout",
+        }
+    }
+
+    fn commands(self, config: &HooksConfig) -> &[String] {
+        match self {
+            HookEvent::Start => &config.on_start,
+            HookEvent::Complete => &config.on_complete,
+            HookEvent::Fail => &config.on_fail,
+            HookEvent::HeartbeatTimeout => &config.on_heartbeat_timeout,
+        }
+    }
+}
+
+/// Environment a hook command sees for a task.
+pub fn hook_env(event: HookEvent, meta: &TaskMetadata) -> Vec<(&'static str, String)> {
+    let opt = |v: Option<String>| v.unwrap_or_default();
+    vec![
+        ("OPENCLAW_HOOK", event.as_str().to_string()),
+        ("OPENCLAW_TASK_ID", meta.task_id.to_string()),
+        ("OPENCLAW_EXECUTOR", meta.executor_name.clone()),
+        ("OPENCLAW_EXECUTOR_TYPE", meta.executor_type.clone()),
+        ("OPENCLAW_TASK_TYPE", meta.task_type.clone()),
+        ("OPENCLAW_STATUS", meta.status.to_string()),
+        ("OPENCLAW_PROMPT", meta.prompt.clone()),
+        ("OPENCLAW_WORKSPACE", opt(meta.workspace.clone())),
+        ("OPENCLAW_PID", opt(meta.pid.map(|p| p.to_string()))),
+        (
+            "OPENCLAW_EXIT_CODE",
+            opt(meta.exit_code.map(|c| c.to_string())),
+        ),
+        ("OPENCLAW_ERROR", opt(meta.error.clone())),
+        ("OPENCLAW_STARTED_AT", meta.started_at.to_rfc3339()),
+        (
+            "OPENCLAW_FINISHED_AT",
+            opt(meta.finished_at.map(|t| t.to_rfc3339())),
+        ),
+        (
+            "OPENCLAW_DURATION_SECS",
+            opt(meta.duration().map(|d| d.num_seconds().to_string())),
+        ),
+        ("OPENCLAW_GROUP_ID", opt(meta.group_id.clone())),
+        ("OPENCLAW_SCHEDULE", opt(meta.schedule_name.clone())),
+        (
+            "OPENCLAW_TASK_JSON",
+            serde_json::to_string(meta).unwrap_or_default(),
+        ),
+    ]
+}
+
+/// Run the commands configured for `event` one after another with `sh -c`.
+/// Output goes to stderr. Failures are returned per command but don't stop the others.
+pub async fn run(config: &HooksConfig, event: HookEvent, meta: &TaskMetadata) -> Vec<String> {
+    let timeout = Duration::from_secs(config.timeout_secs);
+    let mut errors = Vec::new();
+
+    for command in event.commands(config) {
+        if let Err(e) = run_one(command, event, meta, timeout).await {
+            errors.push(format!("{} hook `{}`: {}", event.as_str(), command, e));
+        }
+    }
+
+    errors
+}
+
+async fn run_one(
+    command: &str,
+    event: HookEvent,
+    meta: &TaskMetadata,
+    timeout: Duration,
+) -> Result<(), String> {
+    let mut child = tokio::process::Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .envs(hook_env(event, meta))
+        .stdin(Stdio::null())
+        .stdout(std::io::stderr())
+        .stderr(std::io::stderr())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| e.to_string())?;
+
+    match tokio::time::timeout(timeout, child.wait()).await {
+        Ok(Ok(status)) if status.success() => Ok(()),
+        Ok(Ok(status)) => Err(format!("exited with {}", status)),
+        Ok(Err(e)) => Err(e.to_string()),
+        Err(_) => Err(format!("timed out after {}s", timeout.as_secs())),
+    }
+}
+
+/// Wraps an executor and runs the `on_start` hooks after each successful start.
+pub struct HookedExecutor {
+    inner: Box<dyn Executor>,
+    hooks: HooksConfig,
+}
+
+impl HookedExecutor {
+    pub fn new(inner: Box<dyn Executor>, hooks: HooksConfig) -> Self {
+        Self { inner, hooks }
+    }
+}
+
+#[async_trait::async_trait]
+impl Executor for HookedExecutor {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn executor_type(&self) -> &str {
+        self.inner.executor_type()
+    }
+
+    async fn start(
+        &self,
+        request: TaskRequest,
+        cancel: &CancellationToken,
+    ) -> Result<TaskMetadata, ExecutorError> {
+        let meta = self.inner.start(request, cancel).await?;
+        for err in run(&self.hooks, HookEvent::Start, &meta).await {
+            eprintln!("Warning: {}", err);
+        }
+        Ok(meta)
+    }
+
+    fn plan_start(&self, request: TaskRequest) -> Result<StartPlan, ExecutorError> {
+        self.inner.plan_start(request)
+    }
+
+    async fn status(
+        &self,
+        task_id: &TaskId,
+        cancel: &CancellationToken,
+    ) -> Result<TaskMetadata, ExecutorError> {
+        self.inner.status(task_id, cancel).await
+    }
+
+    async fn status_many(
+        &self,
+        task_ids: &[TaskId],
+        cancel: &CancellationToken,
+    ) -> Result<Vec<TaskMetadata>, ExecutorError> {
+        self.inner.status_many(task_ids, cancel).await
+    }
+
+    async fn logs(
+        &self,
+        task_id: &TaskId,
+        lines: usize,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<String>, ExecutorError> {
+        self.inner.logs(task_id, lines, cancel).await
+    }
+
+    async fn filtered_logs(
+        &self,
+        task_id: &TaskId,
+        lines: usize,
+        filter: &LogFilter,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<String>, ExecutorError> {
+        self.inner.filtered_logs(task_id, lines, filter, cancel).await
+    }
+
+    async fn kill(
+        &self,
+        task_id: &TaskId,
+        cancel: &CancellationToken,
+    ) -> Result<(), ExecutorError> {
+        self.inner.kill(task_id, cancel).await
+    }
+
+    async fn cleanup(
+        &self,
+        task_id: &TaskId,
+        cancel: &CancellationToken,
+    ) -> Result<(), ExecutorError> {
+        self.inner.cleanup(task_id, cancel).await
+    }
+
+    async fn cleanup_many(
+        &self,
+        task_ids: &[TaskId],
+        cancel: &CancellationToken,
+    ) -> Result<Vec<CleanupOutcome>, ExecutorError> {
+        self.inner.cleanup_many(task_ids, cancel).await
+    }
+
+    async fn list_remote(
+        &self,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<RemoteTask>, ExecutorError> {
+        self.inner.list_remote(cancel).await
+    }
+
+    async fn exec(
+        &self,
+        task_id: &TaskId,
+        command: &str,
+        cancel: &CancellationToken,
+    ) -> Result<i32, ExecutorError> {
+        self.inner.exec(task_id, command, cancel).await
+    }
+
+    async fn exec_output(
+        &self,
+        task_id: &TaskId,
+        command: &str,
+        cancel: &CancellationToken,
+    ) -> Result<(i32, String), ExecutorError> {
+        self.inner.exec_output(task_id, command, cancel).await
+    }
+
+    async fn host_stats(
+        &self,
+        dir: Option<&str>,
+        cancel: &CancellationToken,
+    ) -> Result<Option<HostStats>, ExecutorError> {
+        self.inner.host_stats(dir, cancel).await
+    }
+
+    async fn find_claude(
+        &self,
+        cancel: &CancellationToken,
+    ) -> Result<Option<String>, ExecutorError> {
+        self.inner.find_claude(cancel).await
+    }
+
+    async fn provision(
+        &self,
+        install: bool,
+        cancel: &CancellationToken,
+    ) -> Result<ProvisionReport, ExecutorError> {
+        self.inner.provision(install, cancel).await
+    }
+
+    async fn upgrade_agent(&self, cancel: &CancellationToken) -> Result<AgentInfo, ExecutorError> {
+        self.inner.upgrade_agent(cancel).await
+    }
+
+    async fn usage(
+        &self,
+        task_id: &TaskId,
+        cancel: &CancellationToken,
+    ) -> Result<Option<ResourceUsage>, ExecutorError> {
+        self.inner.usage(task_id, cancel).await
+    }
+
+    async fn progress(
+        &self,
+        task_id: &TaskId,
+        cancel: &CancellationToken,
+    ) -> Result<Option<TaskProgress>, ExecutorError> {
+        self.inner.progress(task_id, cancel).await
+    }
+
+    async fn log_size(
+        &self,
+        task_id: &TaskId,
+        cancel: &CancellationToken,
+    ) -> Result<u64, ExecutorError> {
+        self.inner.log_size(task_id, cancel).await
+    }
+
+    async fn changes(
+        &self,
+        task_id: &TaskId,
+        cancel: &CancellationToken,
+    ) -> Result<TaskChanges, ExecutorError> {
+        self.inner.changes(task_id, cancel).await
+    }
+
+    async fn download(
+        &self,
+        task_id: &TaskId,
+        path: &str,
+        root: FileRoot,
+        dest: &Path,
+        cancel: &CancellationToken,
+    ) -> Result<(), ExecutorError> {
+        self.inner.download(task_id, path, root, dest, cancel).await
+    }
+
+    async fn upload(
+        &self,
+        task_id: &TaskId,
+        source: &Path,
+        path: &str,
+        root: FileRoot,
+        cancel: &CancellationToken,
+    ) -> Result<(), ExecutorError> {
+        self.inner.upload(task_id, source, path, root, cancel).await
+    }
+}
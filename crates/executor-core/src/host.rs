@@ -0,0 +1,238 @@
+//! Resource stats for the host a task runs on, sampled on every heartbeat so
+//! a machine that is swapping or out of disk shows up in `status` while the
+//! task is still running.
+//!
+//! Executors run [`STATS_SCRIPT`] in the task's workspace on their own
+//! backend, so the numbers describe the machine (and filesystem) the task
+//! actually uses.
+//!
+//! [`claude_script`] finds the `claude` binary on a host, for `config --init`.
+
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// A `sh` script printing one `<tag> <values...>` line per stat it can read:
+/// `load` (1/5/15-minute averages), `mem` (total, available and swap used, in
+/// KiB) and `disk` (total and available KiB on the workspace's filesystem).
+/// Missing sources are skipped, and the script always exits 0.
+pub const STATS_SCRIPT: &str = "\
+awk '{print \"load\", $1, $2, $3}' /proc/loadavg 2>/dev/null
+awk '/^MemTotal:/{t=$2} /^MemAvailable:/{a=$2} /^SwapTotal:/{st=$2} /^SwapFree:/{sf=$2} \
+END{if (t) print \"mem\", t, a, st-sf}' /proc/meminfo 2>/dev/null
+df -Pk . 2>/dev/null | awk 'NR==2{print \"disk\", $2, $4}'
+true";
+
+/// [`STATS_SCRIPT`] with disk figures for `dir` instead of the current
+/// directory, for sampling a host before a task (and its workspace) exists.
+pub fn stats_script_for(dir: &str) -> String {
+    STATS_SCRIPT.replace("df -Pk .", &format!("df -Pk {}", crate::git::quote(dir)))
+}
+
+/// Where `claude` is usually installed besides `PATH`, which is often short
+/// in non-interactive SSH sessions.
+const CLAUDE_DIRS: &[&str] = &[
+    "$HOME/.npm-global/bin",
+    "$HOME/.local/bin",
+    "$HOME/.claude/local",
+    "/usr/local/bin",
+    "/opt/homebrew/bin",
+];
+
+/// A `sh` script printing the full path of the first `claude` that can be
+/// run: `configured` (a `claude_path`, where `~/` is allowed), then the one
+/// on `PATH`, then one in the usual install dirs. Prints nothing if there is
+/// none.
+pub fn claude_script(configured: Option<&str>) -> String {
+    let mut candidates = Vec::new();
+    if let Some(path) = configured {
+        candidates.push(match path.strip_prefix("~/") {
+            Some(rest) => format!("\"$HOME\"/{}", crate::git::quote(rest)),
+            None => crate::git::quote(path),
+        });
+    }
+    candidates.push("claude".to_string());
+    candidates.extend(CLAUDE_DIRS.iter().map(|dir| format!("\"{}/claude\"", dir)));
+    format!(
+        "\
+for c in {}; do
+  p=$(command -v \"$c\" 2>/dev/null) && [ -x \"$p\" ] && {{ echo \"$p\"; exit 0; }}
+done
+true",
+        candidates.join(" ")
+    )
+}
+
+/// The path printed by [`claude_script`], if it found one.
+pub fn parse_claude_path(output: &str) -> Option<String> {
+    output
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(String::from)
+}
+
+/// One sample of a task host's resources.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct HostStats {
+    pub sampled_at: DateTime<Utc>,
+    /// 1, 5 and 15-minute load averages.
+    #[serde(default)]
+    pub load_avg: Option<[f64; 3]>,
+    #[serde(default)]
+    pub mem_total_kb: Option<u64>,
+    #[serde(default)]
+    pub mem_available_kb: Option<u64>,
+    #[serde(default)]
+    pub swap_used_kb: Option<u64>,
+    /// Size of the filesystem holding the workspace.
+    #[serde(default)]
+    pub disk_total_kb: Option<u64>,
+    #[serde(default)]
+    pub disk_available_kb: Option<u64>,
+}
+
+impl HostStats {
+    /// Percentage of the workspace filesystem in use.
+    pub fn disk_used_percent(&self) -> Option<u64> {
+        let total = self.disk_total_kb.filter(|&t| t > 0)?;
+        let available = self.disk_available_kb?;
+        Some(total.saturating_sub(available) * 100 / total)
+    }
+
+    /// Load averages, e.g. `1.20 0.80 0.50`.
+    pub fn load_line(&self) -> Option<String> {
+        self.load_avg
+            .map(|[one, five, fifteen]| format!("{:.2} {:.2} {:.2}", one, five, fifteen))
+    }
+
+    /// Memory, e.g. `1.2 GiB free of 8.0 GiB, 512 MiB swap used`.
+    pub fn memory_line(&self) -> Option<String> {
+        let total = self.mem_total_kb?;
+        let mut line = match self.mem_available_kb {
+            Some(available) => format!("{} free of {}", kib(available), kib(total)),
+            None => format!("{} total", kib(total)),
+        };
+        if let Some(swap) = self.swap_used_kb {
+            line.push_str(&format!(", {} swap used", kib(swap)));
+        }
+        Some(line)
+    }
+
+    /// Workspace disk, e.g. `20.1 GiB free of 100.0 GiB (80% used)`.
+    pub fn disk_line(&self) -> Option<String> {
+        let total = self.disk_total_kb?;
+        let available = self.disk_available_kb?;
+        Some(format!(
+            "{} free of {} ({}% used)",
+            kib(available),
+            kib(total),
+            self.disk_used_percent().unwrap_or(0)
+        ))
+    }
+
+    /// Why a task shouldn't start on this host: free disk below
+    /// `min_free_disk_mb` or 1-minute load above `max_load`. Stats the
+    /// sample lacks are not checked.
+    pub fn preflight_problems(
+        &self,
+        min_free_disk_mb: Option<u64>,
+        max_load: Option<f64>,
+    ) -> Vec<String> {
+        let mut problems = Vec::new();
+        if let (Some(min), Some(available)) = (min_free_disk_mb, self.disk_available_kb) {
+            if available < min * 1024 {
+                problems.push(format!(
+                    "only {} free on the workspace's filesystem (min_free_disk_mb: {})",
+                    kib(available),
+                    min
+                ));
+            }
+        }
+        if let (Some(max), Some([one, _, _])) = (max_load, self.load_avg) {
+            if one > max {
+                problems.push(format!("load average {:.2} is above max_load {}", one, max));
+            }
+        }
+        problems
+    }
+
+    /// A one-line summary for heartbeat events.
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some([one, _, _]) = self.load_avg {
+            parts.push(format!("load {:.2}", one));
+        }
+        if let Some(available) = self.mem_available_kb {
+            parts.push(format!("mem {} free", kib(available)));
+        }
+        if let Some(swap) = self.swap_used_kb.filter(|&s| s > 0) {
+            parts.push(format!("swap {} used", kib(swap)));
+        }
+        if let Some(used) = self.disk_used_percent() {
+            parts.push(format!("disk {}% used", used));
+        }
+        parts.join(", ")
+    }
+}
+
+/// The stats printed by a [`STATS_SCRIPT`] run, or `None` if it printed none
+/// (e.g. a host without `/proc` or `df`).
+pub fn parse_stats(output: &str) -> Option<HostStats> {
+    let mut stats = HostStats {
+        sampled_at: Utc::now(),
+        load_avg: None,
+        mem_total_kb: None,
+        mem_available_kb: None,
+        swap_used_kb: None,
+        disk_total_kb: None,
+        disk_available_kb: None,
+    };
+    for line in output.lines() {
+        let mut fields = line.split_whitespace();
+        let tag = fields.next();
+        let values: Vec<&str> = fields.collect();
+        match (tag, values.as_slice()) {
+            (Some("load"), [one, five, fifteen]) => {
+                if let (Ok(one), Ok(five), Ok(fifteen)) =
+                    (one.parse(), five.parse(), fifteen.parse())
+                {
+                    stats.load_avg = Some([one, five, fifteen]);
+                }
+            }
+            (Some("mem"), [total, available, swap]) => {
+                stats.mem_total_kb = total.parse().ok();
+                stats.mem_available_kb = available.parse().ok();
+                stats.swap_used_kb = swap.parse().ok();
+            }
+            (Some("disk"), [total, available]) => {
+                stats.disk_total_kb = total.parse().ok();
+                stats.disk_available_kb = available.parse().ok();
+            }
+            _ => {}
+        }
+    }
+    let empty = stats.load_avg.is_none()
+        && stats.mem_total_kb.is_none()
+        && stats.disk_total_kb.is_none();
+    (!empty).then_some(stats)
+}
+
+/// A KiB count in the largest unit that keeps it above 1.
+pub fn kib(kb: u64) -> String {
+    const UNITS: [&str; 3] = ["MiB", "GiB", "TiB"];
+    if kb < 1024 {
+        return format!("{} KiB", kb);
+    }
+    let mut value = kb as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{:.0} {}", value, UNITS[unit])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
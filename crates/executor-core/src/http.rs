@@ -0,0 +1,58 @@
+use std::sync::OnceLock;
+use std::time::Duration;
+
+/// Timeout for notification requests that don't configure their own.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+fn client() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(reqwest::Client::new)
+}
+
+/// POST a JSON value and return the response body. Non-2xx responses are errors.
+pub async fn post_json(
+    url: &str,
+    headers: &[(&str, &str)],
+    body: &serde_json::Value,
+) -> Result<String, String> {
+    let body = serde_json::to_vec(body).map_err(|e| e.to_string())?;
+    post(url, headers, body, DEFAULT_TIMEOUT).await
+}
+
+/// GET a URL and return the response body. Non-2xx responses are errors.
+pub async fn get(url: &str, headers: &[(&str, &str)]) -> Result<String, String> {
+    let mut request = client().get(url).timeout(DEFAULT_TIMEOUT);
+    for (name, value) in headers {
+        request = request.header(*name, *value);
+    }
+    send(request).await
+}
+
+/// POST an already-serialized JSON body.
+pub async fn post(
+    url: &str,
+    headers: &[(&str, &str)],
+    body: Vec<u8>,
+    timeout: Duration,
+) -> Result<String, String> {
+    let mut request = client()
+        .post(url)
+        .timeout(timeout)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(body);
+    for (name, value) in headers {
+        request = request.header(*name, *value);
+    }
+    send(request).await
+}
+
+async fn send(request: reqwest::RequestBuilder) -> Result<String, String> {
+    let response = request.send().await.map_err(|e| e.to_string())?;
+    let status = response.status();
+    let text = response.text().await.unwrap_or_default();
+
+    if !status.is_success() {
+        return Err(format!("HTTP {}: {}", status.as_u16(), text.trim()));
+    }
+    Ok(text)
+}
@@ -0,0 +1,67 @@
+//! Container images built from an executor's `image_build` Dockerfile.
+//!
+//! Each build is tagged with a hash of its context, so an unchanged context
+//! reuses the image already built and any edit to the Dockerfile or the files
+//! it copies produces a new one.
+
+use crate::config::ImageBuild;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+/// Hex characters of the context hash kept in the image tag.
+const TAG_HASH_LEN: usize = 12;
+
+/// The tag an `image_build` context builds to: `image`'s repository (or
+/// `openclaw-<executor>`) with the context hash as the tag.
+pub fn build_tag(
+    build: &ImageBuild,
+    image: Option<&str>,
+    executor: &str,
+) -> std::io::Result<String> {
+    let repository = match image {
+        Some(image) => strip_tag(image).to_string(),
+        None => format!("openclaw-{}", executor.to_lowercase()),
+    };
+    let hash = context_hash(Path::new(&build.context))?;
+    Ok(format!("{}:{}", repository, &hash[..TAG_HASH_LEN]))
+}
+
+/// `image` without its `:tag`, keeping a registry's `:port`.
+fn strip_tag(image: &str) -> &str {
+    match image.rsplit_once(':') {
+        Some((repository, tag)) if !tag.contains('/') => repository,
+        _ => image,
+    }
+}
+
+/// SHA-256 over every file under `dir` (path and contents, in path order),
+/// skipping `.git`.
+pub fn context_hash(dir: &Path) -> std::io::Result<String> {
+    let mut files = Vec::new();
+    collect_files(dir, dir, &mut files)?;
+    files.sort();
+
+    let mut hasher = Sha256::new();
+    for relative in &files {
+        hasher.update(relative.as_bytes());
+        hasher.update([0]);
+        hasher.update(std::fs::read(dir.join(relative))?);
+        hasher.update([0]);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+fn collect_files(root: &Path, dir: &Path, files: &mut Vec<String>) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            if entry.file_name() != ".git" {
+                collect_files(root, &path, files)?;
+            }
+        } else if let Ok(relative) = path.strip_prefix(root) {
+            files.push(relative.display().to_string());
+        }
+    }
+    Ok(())
+}
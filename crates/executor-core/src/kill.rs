@@ -0,0 +1,43 @@
+//! Graceful termination of a task's processes: SIGTERM, a grace period for
+//! them to exit, then SIGKILL. Executors run [`terminate_script`] where the
+//! task runs and read back which signal ended it.
+//!
+//! Tasks are launched under `setsid`, so their recorded PID leads a process
+//! group holding everything the task spawned (node, tool subprocesses), and
+//! the signals go to the whole group.
+
+use crate::task::KillSignal;
+
+/// A `sh` script that sends SIGTERM to `pid`'s process group, waits up to
+/// `grace_secs` for all of it to exit, sends SIGKILL if it hasn't, and prints
+/// the signal that ended it. Only `pid` is signalled if it doesn't lead a
+/// group (tasks started before launches used `setsid`). Prints nothing if
+/// `pid` was already gone.
+pub fn terminate_script(pid: u32, grace_secs: u64) -> String {
+    [
+        format!(
+            "if kill -0 -{} 2>/dev/null; then t=-{}; else t={}; fi",
+            pid, pid, pid
+        ),
+        "kill -TERM $t 2>/dev/null || exit 0".to_string(),
+        "i=0".to_string(),
+        "while kill -0 $t 2>/dev/null; do".to_string(),
+        format!(
+            "  if [ $i -ge {} ]; then kill -KILL $t 2>/dev/null; echo SIGKILL; exit 0; fi",
+            grace_secs
+        ),
+        "  sleep 1; i=$((i+1))".to_string(),
+        "done".to_string(),
+        "echo SIGTERM".to_string(),
+    ]
+    .join("\n")
+}
+
+/// The signal [`terminate_script`] reported, if any.
+pub fn parse_signal(output: &str) -> Option<KillSignal> {
+    match output.trim().lines().last()? {
+        "SIGTERM" => Some(KillSignal::Term),
+        "SIGKILL" => Some(KillSignal::Kill),
+        _ => None,
+    }
+}
@@ -1,12 +1,49 @@
+pub mod activate;
+pub mod ansi;
+pub mod audit;
+pub mod batch;
 pub mod completion;
 pub mod config;
+pub mod cron;
 pub mod error;
+pub mod events;
 pub mod executor;
+pub mod fixture;
+pub mod git;
+pub mod github;
+pub mod hooks;
+pub mod image;
+pub mod kill;
+pub mod host;
+pub mod http;
+pub mod log_archive;
+pub mod log_cache;
+pub mod log_filter;
+pub mod log_timestamps;
 pub mod metadata;
+pub mod nix;
+pub mod notify;
+pub mod pipeline;
+pub mod prometheus;
+pub mod progress;
+pub mod provision;
+pub mod registry;
+pub mod resolve;
+pub mod schema;
+pub mod search;
+pub mod secrets;
+pub mod spec;
+pub mod stats;
+pub mod store;
 pub mod task;
+pub mod throttle;
+pub mod usage;
+pub mod validate;
+pub mod workspace;
 
 pub use config::Config;
 pub use error::ExecutorError;
 pub use executor::Executor;
 pub use metadata::TaskMetadata;
 pub use task::{TaskId, TaskPayload, TaskRequest, TaskStatus};
+pub use tokio_util::sync::CancellationToken;
@@ -0,0 +1,104 @@
+//! zstd-compressed logs of cleaned-up tasks: ~/.local/share/openclaw/log-archive/
+//!
+//! `cleanup` moves a finished task's cached log (see [`crate::log_cache`])
+//! here as `<task_id>.log.zst` and appends a line to `index.jsonl`. Cached log
+//! reads fall back to the archive, so `logs` and `search` keep working.
+
+use crate::task::TaskId;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::PathBuf;
+
+/// One archived log, as recorded in `index.jsonl`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveEntry {
+    pub task_id: String,
+    /// File name within the archive directory.
+    pub file: String,
+    pub original_bytes: u64,
+    pub compressed_bytes: u64,
+    pub archived_at: DateTime<Utc>,
+}
+
+pub fn archive_dir() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("/tmp"))
+        .join("openclaw")
+        .join("log-archive")
+}
+
+fn index_path() -> PathBuf {
+    archive_dir().join("index.jsonl")
+}
+
+fn archive_file(task_id: &TaskId) -> String {
+    format!("{}.log.zst", task_id)
+}
+
+/// Compress the task's cached log into the archive and drop the plain copy.
+/// `None` if there is no cached log to archive.
+pub fn archive_log(task_id: &TaskId) -> Result<Option<ArchiveEntry>, std::io::Error> {
+    let source = crate::log_cache::cache_path(task_id);
+    let data = match std::fs::read(&source) {
+        Ok(data) => data,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    let compressed = zstd::encode_all(data.as_slice(), 0)?;
+
+    std::fs::create_dir_all(archive_dir())?;
+    let entry = ArchiveEntry {
+        task_id: task_id.to_string(),
+        file: archive_file(task_id),
+        original_bytes: data.len() as u64,
+        compressed_bytes: compressed.len() as u64,
+        archived_at: Utc::now(),
+    };
+    std::fs::write(archive_dir().join(&entry.file), compressed)?;
+
+    let mut line = serde_json::to_string(&entry)?;
+    line.push('\n');
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(index_path())?
+        .write_all(line.as_bytes())?;
+
+    std::fs::remove_file(source)?;
+    Ok(Some(entry))
+}
+
+/// Read an archived log, if one exists.
+pub fn read_archived_log(task_id: &TaskId) -> Option<Vec<String>> {
+    let compressed = std::fs::read(archive_dir().join(archive_file(task_id))).ok()?;
+    let data = zstd::decode_all(compressed.as_slice()).ok()?;
+    Some(String::from_utf8_lossy(&data).lines().map(String::from).collect())
+}
+
+/// The archived tasks whose ID starts with `prefix` (case-insensitively),
+/// each once.
+pub fn matching_task_ids(prefix: &str) -> Result<Vec<TaskId>, std::io::Error> {
+    let prefix = prefix.to_ascii_lowercase();
+    let mut ids: Vec<String> = read_index()?
+        .into_iter()
+        .map(|entry| entry.task_id)
+        .filter(|id| !prefix.is_empty() && id.starts_with(&prefix))
+        .collect();
+    ids.sort();
+    ids.dedup();
+    Ok(ids.into_iter().map(TaskId::from_string).collect())
+}
+
+/// All index entries, oldest first. A task archived more than once has an
+/// entry per archival; the last one is current. Unparseable lines are skipped.
+pub fn read_index() -> Result<Vec<ArchiveEntry>, std::io::Error> {
+    let path = index_path();
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    Ok(std::fs::read_to_string(path)?
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
@@ -0,0 +1,58 @@
+use crate::task::TaskId;
+use std::path::PathBuf;
+
+/// Directory for log snapshots (and task diffs) kept locally: ~/.local/share/openclaw/logs/
+///
+/// Filled when the CLI observes a task finishing and by `import`, so history
+/// stays readable (and searchable) without access to the executor that ran it.
+pub fn log_cache_dir() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("/tmp"))
+        .join("openclaw")
+        .join("logs")
+}
+
+/// Where a task's cached log is kept.
+pub fn cache_path(task_id: &TaskId) -> PathBuf {
+    log_cache_dir().join(format!("{}.log", task_id))
+}
+
+/// Read a cached log, if one exists, from the log archive once `cleanup`
+/// has moved it there.
+pub fn read_cached_log(task_id: &TaskId) -> Option<Vec<String>> {
+    match std::fs::read_to_string(cache_path(task_id)) {
+        Ok(data) => Some(data.lines().map(String::from).collect()),
+        Err(_) => crate::log_archive::read_archived_log(task_id),
+    }
+}
+
+/// Store a log snapshot, replacing any previous one.
+pub fn write_cached_log(task_id: &TaskId, lines: &[String]) -> Result<(), std::io::Error> {
+    std::fs::create_dir_all(log_cache_dir())?;
+    let mut data = lines.join("\n");
+    data.push('\n');
+    std::fs::write(cache_path(task_id), data)
+}
+
+fn diff_path(task_id: &TaskId) -> PathBuf {
+    log_cache_dir().join(format!("{}.diff", task_id))
+}
+
+/// Read a task's cached diff, if one exists.
+pub fn read_cached_diff(task_id: &TaskId) -> Option<String> {
+    std::fs::read_to_string(diff_path(task_id)).ok()
+}
+
+/// Store a task's diff, replacing any previous one.
+pub fn write_cached_diff(task_id: &TaskId, diff: &str) -> Result<(), std::io::Error> {
+    std::fs::create_dir_all(log_cache_dir())?;
+    std::fs::write(diff_path(task_id), diff)
+}
+
+/// Keep only the last `lines` entries.
+pub fn tail(mut log: Vec<String>, lines: usize) -> Vec<String> {
+    if log.len() > lines {
+        log.drain(..log.len() - lines);
+    }
+    log
+}
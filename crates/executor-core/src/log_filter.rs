@@ -0,0 +1,119 @@
+//! Line filters for `logs --grep / --since / --level`.
+//!
+//! Backends that can run commands where the log lives filter it there with
+//! [`LogFilter::script`], so only matching lines cross the link. Anything else
+//! (cached logs, other backends) is filtered here with [`LogFilter::apply`].
+//! Both give the same result.
+
+use crate::log_timestamps::TIMESTAMP_PATTERN;
+use chrono::{DateTime, Utc};
+use regex::Regex;
+
+#[derive(Debug, Clone, Default)]
+pub struct LogFilter {
+    /// Extended regular expression a line must match.
+    pub grep: Option<String>,
+    /// Keep lines timestamped at or after this time, and the untimestamped
+    /// lines that follow them. Lines before the first timestamp are dropped.
+    pub since: Option<DateTime<Utc>>,
+    /// Keep JSON event lines of this `type` (e.g. `result`, `assistant`).
+    pub level: Option<String>,
+}
+
+impl LogFilter {
+    pub fn is_empty(&self) -> bool {
+        self.grep.is_none() && self.since.is_none() && self.level.is_none()
+    }
+
+    /// Check the filter can be used both here and in a shell pipeline.
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(ref grep) = self.grep {
+            Regex::new(grep).map_err(|e| format!("Invalid --grep pattern: {}", e))?;
+        }
+        if let Some(ref level) = self.level {
+            if level.is_empty() || !level.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                return Err(format!(
+                    "Invalid --level '{}': expected an event type such as 'result'",
+                    level
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Shell pipeline printing the last `lines` matching lines of `file`.
+    /// `file` must already be shell-quoted.
+    pub fn script(&self, file: &str, lines: usize) -> String {
+        let mut script = format!("cat {}", file);
+        // The time window goes first: it follows every timestamp in the log
+        if let Some(since) = self.since {
+            let awk = format!(
+                "/{}/ {{ keep = ($1 >= \"{}\") }} keep",
+                TIMESTAMP_PATTERN,
+                since_prefix(since)
+            );
+            script.push_str(&format!(" | awk {}", quote(&awk)));
+        }
+        if let Some(ref grep) = self.grep {
+            script.push_str(&format!(" | grep -E -e {}", quote(grep)));
+        }
+        if let Some(ref level) = self.level {
+            script.push_str(&format!(" | grep -E -e {}", quote(&level_pattern(level))));
+        }
+        format!("{} | tail -n {}", script, lines)
+    }
+
+    /// The last `lines` of `log` that match. Call [`LogFilter::validate`]
+    /// first; an invalid pattern matches nothing.
+    pub fn apply(&self, log: Vec<String>, lines: usize) -> Vec<String> {
+        let grep = self.grep.as_deref().map(Regex::new);
+        let level = self.level.as_deref().map(|l| Regex::new(&level_pattern(l)));
+        let timestamp = Regex::new(TIMESTAMP_PATTERN).expect("valid pattern");
+        let since = self.since.map(since_prefix);
+
+        let mut keep = false;
+        // The time window goes first, as in `script`
+        let mut matched: Vec<String> = log
+            .into_iter()
+            .filter(|line| {
+                let Some(ref since) = since else {
+                    return true;
+                };
+                if timestamp.is_match(line) {
+                    let first = line.split_whitespace().next().unwrap_or_default();
+                    keep = first >= since.as_str();
+                }
+                keep
+            })
+            .filter(|line| match grep {
+                Some(Ok(ref re)) => re.is_match(line),
+                Some(Err(_)) => false,
+                None => true,
+            })
+            .filter(|line| match level {
+                Some(Ok(ref re)) => re.is_match(line),
+                Some(Err(_)) => false,
+                None => true,
+            })
+            .collect();
+        if matched.len() > lines {
+            matched.drain(..matched.len() - lines);
+        }
+        matched
+    }
+}
+
+/// Matches a JSON line with `"type": "<level>"`.
+fn level_pattern(level: &str) -> String {
+    format!("\"type\": *\"{}\"", level)
+}
+
+/// `since` as compared against a line's leading timestamp. Comparing strings
+/// works for UTC timestamps, whatever their fractional seconds or suffix.
+fn since_prefix(since: DateTime<Utc>) -> String {
+    since.format("%Y-%m-%dT%H:%M:%S").to_string()
+}
+
+fn quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
@@ -0,0 +1,35 @@
+//! Timestamp prefixes on captured log lines (`log_timestamps: true`).
+//!
+//! Local and SSH tasks pipe their output through [`STAMP_PIPE`], so every
+//! line of claude.log starts with the UTC time it was written. Container
+//! tasks get the runtime's own `logs --timestamps` prefixes instead.
+
+/// Lines starting with something shaped like an RFC 3339 timestamp.
+pub const TIMESTAMP_PATTERN: &str = "^[0-9][0-9][0-9][0-9]-[0-9][0-9]-[0-9][0-9]T";
+
+/// Shell command prefixing each line of its input with the current UTC time,
+/// flushing line by line so the log stays live. `strftime` is in gawk, mawk
+/// and busybox awk; mawk also needs `-W interactive` to read lines as they
+/// arrive rather than stamping a whole buffer at once.
+pub const STAMP_PIPE: &str = concat!(
+    "TZ=UTC awk $(awk -W version 2>&1 | grep -q mawk && echo -W interactive) ",
+    "'{ print strftime(\"%Y-%m-%dT%H:%M:%SZ\"), $0; fflush() }'"
+);
+
+/// `line` without its timestamp prefix, if it has one.
+pub fn strip(line: &str) -> &str {
+    let bytes = line.as_bytes();
+    let stamped = bytes.len() > 11
+        && bytes[..10]
+            .iter()
+            .enumerate()
+            .all(|(i, b)| if i == 4 || i == 7 { *b == b'-' } else { b.is_ascii_digit() })
+        && bytes[10] == b'T';
+    if !stamped {
+        return line;
+    }
+    match line.split_once(' ') {
+        Some((_, rest)) => rest,
+        None => "",
+    }
+}
@@ -1,5 +1,11 @@
-use crate::task::{TaskId, TaskStatus};
+use crate::host::HostStats;
+use crate::progress::TaskProgress;
+use crate::task::{
+    KillSignal, RepoSpec, ServiceSpec, TaskId, TaskPayload, TaskRequest, TaskStatus,
+};
+use crate::usage::ResourceUsage;
 use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 
@@ -8,7 +14,7 @@ fn default_task_type() -> String {
 }
 
 /// Task metadata stored as .meta.json alongside task artifacts.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TaskMetadata {
     pub task_id: TaskId,
     pub executor_name: String,
@@ -24,6 +30,118 @@ pub struct TaskMetadata {
     pub finished_at: Option<DateTime<Utc>>,
     pub exit_code: Option<i32>,
     pub error: Option<String>,
+    /// Signal that ended the task, if it was killed and the executor could
+    /// tell.
+    #[serde(default)]
+    pub killed_by: Option<KillSignal>,
+    #[serde(default)]
+    pub parent_ids: Vec<TaskId>,
+    #[serde(default)]
+    pub group_id: Option<String>,
+    #[serde(default)]
+    pub schedule_name: Option<String>,
+    /// Per-task webhook given at start, taking precedence over the config.
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+    /// Outcome of the completion webhook, once one has been attempted.
+    #[serde(default)]
+    pub webhook: Option<WebhookDelivery>,
+    /// Repository cloned into the workspace at start.
+    #[serde(default)]
+    pub repo: Option<RepoSpec>,
+    /// Commit SHA the clone resolved to.
+    #[serde(default)]
+    pub commit: Option<String>,
+    /// Branch created for the task (`openclaw/<task-id>`), if any.
+    #[serde(default)]
+    pub git_branch: Option<String>,
+    /// Commit the task's branch started from.
+    #[serde(default)]
+    pub base_commit: Option<String>,
+    /// Files the task changed, captured when it finished.
+    #[serde(default)]
+    pub changed_files: Vec<String>,
+    /// Pull request opened for the task's branch (`git.auto_pr`).
+    #[serde(default)]
+    pub pr_url: Option<String>,
+    /// Labels given at start.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Poll interval given at start (`--heartbeat-interval`), if any.
+    #[serde(default)]
+    pub heartbeat_interval_secs: Option<u64>,
+    /// Sidecar services requested at start (on top of the executor's own).
+    #[serde(default)]
+    pub services: Vec<ServiceSpec>,
+    /// Nix shell requested at start (`--nix-shell`), overriding the executor's.
+    #[serde(default)]
+    pub nix_shell: Option<String>,
+    /// Host resources at the last heartbeat.
+    #[serde(default)]
+    pub host_stats: Option<HostStats>,
+    /// CPU time and peak memory of the task's processes, as of the last
+    /// heartbeat.
+    #[serde(default)]
+    pub resource_usage: Option<ResourceUsage>,
+    /// Turn limit given at start (`claude_code` tasks).
+    #[serde(default)]
+    pub max_turns: Option<u32>,
+    /// Tools allowed at start (`--allowed-tools`, `claude_code` tasks).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_tools: Vec<String>,
+    /// Turns taken and the last tool called, as of the last heartbeat
+    /// (`stream_json` only).
+    #[serde(default)]
+    pub progress: Option<TaskProgress>,
+    /// Name given at start (`start --name`), usable in place of the ID.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Notes left with `annotate`, oldest first.
+    #[serde(default)]
+    pub annotations: Vec<Annotation>,
+    /// When the executor was last asked for the task's state (a status
+    /// check or heartbeat).
+    #[serde(default)]
+    pub checked_at: Option<DateTime<Utc>>,
+}
+
+/// A note someone left on a task with `annotate`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Annotation {
+    pub at: DateTime<Utc>,
+    pub author: String,
+    pub note: String,
+}
+
+impl Annotation {
+    /// e.g. `2026-10-16 08:00 alice: reviewing the diff, looks wrong`.
+    pub fn line(&self) -> String {
+        format!("{} {}: {}", self.at.format("%Y-%m-%d %H:%M"), self.author, self.note)
+    }
+}
+
+/// Result of POSTing a task's completion record to its webhook.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct WebhookDelivery {
+    pub url: String,
+    pub delivered: bool,
+    pub attempts: u32,
+    pub attempted_at: DateTime<Utc>,
+    #[serde(default)]
+    pub last_error: Option<String>,
+}
+
+impl WebhookDelivery {
+    /// A delivery that failed before anything was sent.
+    pub fn failed(url: &str, error: String) -> Self {
+        Self {
+            url: url.to_string(),
+            delivered: false,
+            attempts: 0,
+            attempted_at: Utc::now(),
+            last_error: Some(error),
+        }
+    }
 }
 
 impl TaskMetadata {
@@ -50,6 +168,55 @@ impl TaskMetadata {
             finished_at: None,
             exit_code: None,
             error: None,
+            killed_by: None,
+            parent_ids: Vec::new(),
+            group_id: None,
+            schedule_name: None,
+            webhook_url: None,
+            webhook: None,
+            repo: None,
+            commit: None,
+            git_branch: None,
+            base_commit: None,
+            changed_files: Vec::new(),
+            pr_url: None,
+            tags: Vec::new(),
+            services: Vec::new(),
+            nix_shell: None,
+            heartbeat_interval_secs: None,
+            host_stats: None,
+            resource_usage: None,
+            max_turns: None,
+            allowed_tools: Vec::new(),
+            progress: None,
+            name: None,
+            annotations: Vec::new(),
+            checked_at: None,
+        }
+    }
+
+    /// Copy the bookkeeping fields a request carries (name, dependencies, fan-out
+    /// group, schedule, webhook, repo, tags, poll interval, services, Nix shell,
+    /// turn limit).
+    pub fn inherit_from(&mut self, request: &TaskRequest) {
+        self.name = request.name.clone();
+        self.parent_ids = request.parent_ids.clone();
+        self.group_id = request.group_id.clone();
+        self.schedule_name = request.schedule_name.clone();
+        self.webhook_url = request.webhook_url.clone();
+        self.repo = request.repo.clone();
+        self.tags = request.tags.clone();
+        self.heartbeat_interval_secs = request.heartbeat_interval_secs;
+        self.services = request.services.clone();
+        self.nix_shell = request.nix_shell.clone();
+        if let TaskPayload::ClaudeCode {
+            max_turns,
+            ref allowed_tools,
+            ..
+        } = request.payload
+        {
+            self.max_turns = max_turns;
+            self.allowed_tools = allowed_tools.clone();
         }
     }
 
@@ -71,9 +238,10 @@ impl TaskMetadata {
         self.updated_at = now;
     }
 
-    pub fn mark_killed(&mut self) {
+    pub fn mark_killed(&mut self, signal: Option<KillSignal>) {
         let now = Utc::now();
         self.status = TaskStatus::Killed;
+        self.killed_by = signal;
         self.finished_at = Some(now);
         self.updated_at = now;
     }
@@ -86,11 +254,54 @@ impl TaskMetadata {
         self.updated_at = now;
     }
 
+    /// Append a note from `author`.
+    pub fn annotate(&mut self, author: String, note: String) {
+        self.annotations.push(Annotation {
+            at: Utc::now(),
+            author,
+            note,
+        });
+    }
+
+    /// Whether the recorded state can stand in for asking the executor: the
+    /// task has finished, or was checked at most `max_age` ago.
+    pub fn is_fresh(&self, max_age: chrono::Duration) -> bool {
+        self.status.is_terminal()
+            || self.checked_at.is_some_and(|checked| Utc::now() - checked <= max_age)
+    }
+
+    /// Wall-clock run time, once the task has finished.
+    pub fn duration(&self) -> Option<chrono::Duration> {
+        self.finished_at.map(|end| end - self.started_at)
+    }
+
+    /// Wall-clock time since the task started, up to when it finished.
+    pub fn elapsed(&self) -> chrono::Duration {
+        self.finished_at.unwrap_or_else(Utc::now) - self.started_at
+    }
+
+    /// Share of its turn limit a `claude_code` task has used, from 0 to 1.
+    pub fn progress_ratio(&self) -> Option<f64> {
+        let max_turns = self.max_turns.filter(|&max| max > 0)?;
+        let turns = self.progress.as_ref()?.turns;
+        Some((turns as f64 / max_turns as f64).min(1.0))
+    }
+
+    /// Rough time left for a running task, if it keeps its pace so far and
+    /// uses its whole turn limit. Most tasks finish sooner.
+    pub fn eta(&self) -> Option<chrono::Duration> {
+        if self.status != TaskStatus::Running {
+            return None;
+        }
+        let ratio = self.progress_ratio().filter(|&ratio| ratio > 0.0)?;
+        let elapsed = self.elapsed().num_seconds() as f64;
+        Some(chrono::Duration::seconds((elapsed * (1.0 - ratio) / ratio) as i64))
+    }
+
     /// Write metadata to a .meta.json file in the given directory.
     pub fn write_to_dir(&self, dir: &Path) -> Result<(), std::io::Error> {
         let path = dir.join(format!("{}.meta.json", self.task_id));
-        let json = serde_json::to_string_pretty(self)
-            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+        let json = serde_json::to_string_pretty(self).map_err(std::io::Error::other)?;
         std::fs::write(path, json)
     }
 
@@ -101,21 +312,47 @@ impl TaskMetadata {
             .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
     }
 
+    /// The task as dashboards see it (`dashboard`, `list --json`).
+    pub fn to_dashboard(&self) -> DashboardTask {
+        DashboardTask {
+            task_id: self.task_id.0.clone(),
+            name: self.name.clone(),
+            executor: self.executor_name.clone(),
+            executor_type: self.executor_type.clone(),
+            task_type: self.task_type.clone(),
+            status: self.status,
+            pid: self.pid,
+            started_at: self.started_at.to_rfc3339(),
+            updated_at: self.updated_at.to_rfc3339(),
+            finished_at: self.finished_at.map(|t| t.to_rfc3339()),
+            exit_code: self.exit_code,
+            error: self.error.clone(),
+            killed_by: self.killed_by,
+            parent_ids: self.parent_ids.clone(),
+            group_id: self.group_id.clone(),
+            schedule_name: self.schedule_name.clone(),
+            webhook: self.webhook.clone(),
+            repo: self.repo.clone(),
+            commit: self.commit.clone(),
+            git_branch: self.git_branch.clone(),
+            changed_files: self.changed_files.clone(),
+            pr_url: self.pr_url.clone(),
+            tags: self.tags.clone(),
+            host_stats: self.host_stats.clone(),
+            resource_usage: self.resource_usage.clone(),
+            max_turns: self.max_turns,
+            progress: self.progress.clone(),
+            elapsed_secs: self.elapsed().num_seconds(),
+            progress_ratio: self.progress_ratio(),
+            eta_secs: self.eta().map(|eta| eta.num_seconds()),
+            annotations: self.annotations.clone(),
+            checked_at: self.checked_at.map(|t| t.to_rfc3339()),
+        }
+    }
+
     /// Produce structured JSON for dashboard integration.
     pub fn to_dashboard_json(&self) -> serde_json::Value {
-        serde_json::json!({
-            "task_id": self.task_id.0,
-            "executor": self.executor_name,
-            "executor_type": self.executor_type,
-            "task_type": self.task_type,
-            "status": self.status,
-            "pid": self.pid,
-            "started_at": self.started_at.to_rfc3339(),
-            "updated_at": self.updated_at.to_rfc3339(),
-            "finished_at": self.finished_at.map(|t| t.to_rfc3339()),
-            "exit_code": self.exit_code,
-            "error": self.error,
-        })
+        serde_json::to_value(self.to_dashboard()).unwrap_or_default()
     }
 
     /// Produce a JSONL line for dashboard streaming.
@@ -132,6 +369,77 @@ impl TaskMetadata {
     }
 }
 
+/// A task in dashboard output: one line of `dashboard --stream`, or an entry
+/// of `dashboard` and `list --json`.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct DashboardTask {
+    pub task_id: String,
+    pub name: Option<String>,
+    pub executor: String,
+    pub executor_type: String,
+    pub task_type: String,
+    pub status: TaskStatus,
+    pub pid: Option<u32>,
+    /// RFC 3339.
+    pub started_at: String,
+    /// RFC 3339.
+    pub updated_at: String,
+    /// RFC 3339, once the task has finished.
+    pub finished_at: Option<String>,
+    pub exit_code: Option<i32>,
+    pub error: Option<String>,
+    pub killed_by: Option<KillSignal>,
+    pub parent_ids: Vec<TaskId>,
+    pub group_id: Option<String>,
+    pub schedule_name: Option<String>,
+    pub webhook: Option<WebhookDelivery>,
+    pub repo: Option<RepoSpec>,
+    pub commit: Option<String>,
+    pub git_branch: Option<String>,
+    pub changed_files: Vec<String>,
+    pub pr_url: Option<String>,
+    pub tags: Vec<String>,
+    pub host_stats: Option<HostStats>,
+    pub resource_usage: Option<ResourceUsage>,
+    pub max_turns: Option<u32>,
+    pub progress: Option<TaskProgress>,
+    /// Seconds since the task started, up to when it finished.
+    pub elapsed_secs: i64,
+    /// Share of `max_turns` used so far, from 0 to 1.
+    pub progress_ratio: Option<f64>,
+    /// Rough seconds left for a running task, if it uses all of `max_turns`.
+    pub eta_secs: Option<i64>,
+    pub annotations: Vec<Annotation>,
+    /// RFC 3339: when the executor was last asked for the task's state.
+    pub checked_at: Option<String>,
+}
+
+/// The `dashboard` snapshot: task counts and every task.
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct Dashboard {
+    /// RFC 3339.
+    pub timestamp: String,
+    pub task_count: usize,
+    pub running: usize,
+    pub completed: usize,
+    pub failed: usize,
+    pub tasks: Vec<DashboardTask>,
+}
+
+impl Dashboard {
+    pub fn new(tasks: &[TaskMetadata]) -> Self {
+        let count = |status| tasks.iter().filter(|t| t.status == status).count();
+        Self {
+            timestamp: Utc::now().to_rfc3339(),
+            task_count: tasks.len(),
+            running: count(TaskStatus::Running),
+            completed: count(TaskStatus::Completed),
+            failed: count(TaskStatus::Failed),
+            tasks: tasks.iter().map(TaskMetadata::to_dashboard).collect(),
+        }
+    }
+}
+
 /// Get the default metadata storage directory.
 pub fn metadata_dir() -> std::path::PathBuf {
     dirs::data_local_dir()
@@ -139,27 +447,3 @@ pub fn metadata_dir() -> std::path::PathBuf {
         .join("openclaw")
         .join("tasks")
 }
-
-/// List all task metadata files in the metadata directory.
-pub fn list_all_metadata() -> Result<Vec<TaskMetadata>, std::io::Error> {
-    let dir = metadata_dir();
-    if !dir.exists() {
-        return Ok(Vec::new());
-    }
-    let mut results = Vec::new();
-    for entry in std::fs::read_dir(dir)? {
-        let entry = entry?;
-        let path = entry.path();
-        if path.extension().is_some_and(|ext| ext == "json")
-            && path
-                .file_name()
-                .is_some_and(|n| n.to_string_lossy().ends_with(".meta.json"))
-        {
-            if let Ok(meta) = TaskMetadata::read_from_file(&path) {
-                results.push(meta);
-            }
-        }
-    }
-    results.sort_by(|a, b| b.started_at.cmp(&a.started_at));
-    Ok(results)
-}
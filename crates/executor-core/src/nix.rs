@@ -0,0 +1,33 @@
+//! Nix development shells (`nix_shell`): a task's command runs inside one,
+//! so the toolchain it (and claude) needs is there whatever the host has
+//! installed. A `.nix` file (a shell.nix) is entered with `nix-shell`;
+//! anything else is a flake reference such as `.#devshell`, entered with
+//! `nix develop`. Relative paths resolve against the task's workspace.
+
+use crate::config::ExecutorConfig;
+use crate::git::quote;
+use crate::task::TaskRequest;
+
+/// The shell a task runs in: its own (`start --nix-shell`), else the
+/// executor's.
+pub fn for_request<'a>(executor: &'a ExecutorConfig, request: &'a TaskRequest) -> Option<&'a str> {
+    request
+        .nix_shell
+        .as_deref()
+        .or(executor.nix_shell.as_deref())
+        .filter(|shell| !shell.is_empty())
+}
+
+/// `command` (a `sh` command line) run inside `shell`. Flakes are enabled
+/// for the one command, in case the host's Nix doesn't enable them.
+pub fn wrap(shell: &str, command: &str) -> String {
+    if shell.ends_with(".nix") {
+        format!("nix-shell {} --run {}", quote(shell), quote(command))
+    } else {
+        format!(
+            "nix --extra-experimental-features 'nix-command flakes' develop {} --command sh -c {}",
+            quote(shell),
+            quote(command)
+        )
+    }
+}
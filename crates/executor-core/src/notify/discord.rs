@@ -0,0 +1,79 @@
+use crate::config::DiscordConfig;
+use crate::http;
+use crate::metadata::TaskMetadata;
+use crate::notify::excerpt;
+use crate::stats::format_duration;
+use crate::task::TaskStatus;
+use serde_json::json;
+
+/// Longest prompt excerpt shown in an embed.
+const PROMPT_EXCERPT_LEN: usize = 300;
+
+/// Discord caps embed field values at 1024 characters; leave room for the fence.
+const LOG_TAIL_MAX_CHARS: usize = 1000;
+
+/// Post a finished task as an embed to the webhook routed for its executor.
+/// Tasks from executors with no route and no default webhook are skipped.
+pub async fn notify(
+    config: &DiscordConfig,
+    meta: &TaskMetadata,
+    log_tail: &[String],
+) -> Result<(), String> {
+    let Some(url) = config.webhook_for(&meta.executor_name) else {
+        return Ok(());
+    };
+    http::post_json(url, &[], &message(meta, log_tail)).await?;
+    Ok(())
+}
+
+fn message(meta: &TaskMetadata, log_tail: &[String]) -> serde_json::Value {
+    let color = match meta.status {
+        TaskStatus::Completed => 0x2ecc71,
+        TaskStatus::Killed => 0x95a5a6,
+        _ => 0xe74c3c,
+    };
+    let duration = meta
+        .duration()
+        .map(|d| format_duration(d.num_seconds()))
+        .unwrap_or_else(|| "-".into());
+
+    let mut fields = vec![
+        json!({ "name": "Task ID", "value": format!("`{}`", meta.task_id), "inline": false }),
+        json!({ "name": "Type", "value": meta.task_type, "inline": true }),
+        json!({ "name": "Duration", "value": duration, "inline": true }),
+        json!({
+            "name": "Exit code",
+            "value": meta.exit_code.map(|c| c.to_string()).unwrap_or_else(|| "-".into()),
+            "inline": true,
+        }),
+    ];
+
+    if let Some(ref error) = meta.error {
+        fields.push(json!({ "name": "Error", "value": excerpt(error, 1000), "inline": false }));
+    }
+
+    if !log_tail.is_empty() {
+        let mut tail = log_tail.join("\n");
+        if let Some((idx, _)) = tail.char_indices().rev().nth(LOG_TAIL_MAX_CHARS) {
+            tail = tail[idx..].to_string();
+        }
+        fields.push(
+            json!({ "name": "Log tail", "value": format!("```\n{}\n```", tail), "inline": false }),
+        );
+    }
+
+    let mut embed = json!({
+        "title": format!("Task {} on {}", meta.status, meta.executor_name),
+        "description": excerpt(&meta.prompt, PROMPT_EXCERPT_LEN),
+        "color": color,
+        "fields": fields,
+    });
+    if let Some(finished) = meta.finished_at {
+        embed["timestamp"] = json!(finished.to_rfc3339());
+    }
+
+    json!({
+        "content": super::summary_line(meta),
+        "embeds": [embed],
+    })
+}
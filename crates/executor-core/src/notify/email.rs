@@ -0,0 +1,106 @@
+use crate::config::{EmailConfig, SmtpTls};
+use crate::metadata::TaskMetadata;
+use crate::stats::format_duration;
+use crate::task::TaskStatus;
+use lettre::message::header::ContentType;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+
+/// Whether a finished task passes the configured duration/failure filters.
+pub fn should_notify(config: &EmailConfig, meta: &TaskMetadata) -> bool {
+    if config.min_duration_minutes.is_none() && !config.on_failure {
+        return true;
+    }
+    let long_running = config.min_duration_minutes.is_some_and(|min| {
+        meta.duration()
+            .is_some_and(|d| d.num_minutes() >= min as i64)
+    });
+    let failed = config.on_failure && meta.status == TaskStatus::Failed;
+    long_running || failed
+}
+
+/// Mail a finished task to the configured recipients, if it passes the filters.
+pub async fn notify(
+    config: &EmailConfig,
+    meta: &TaskMetadata,
+    log_tail: &[String],
+) -> Result<(), String> {
+    if !should_notify(config, meta) {
+        return Ok(());
+    }
+
+    let mut builder = Message::builder()
+        .from(
+            config
+                .from
+                .parse()
+                .map_err(|e| format!("invalid from address: {}", e))?,
+        )
+        .subject(super::summary_line(meta))
+        .header(ContentType::TEXT_PLAIN);
+    for to in &config.to {
+        builder = builder.to(to
+            .parse()
+            .map_err(|e| format!("invalid recipient '{}': {}", to, e))?);
+    }
+    let message = builder
+        .body(body(meta, log_tail))
+        .map_err(|e| e.to_string())?;
+
+    transport(config)?
+        .send(message)
+        .await
+        .map_err(|e| format!("SMTP send failed: {}", e))?;
+    Ok(())
+}
+
+fn transport(config: &EmailConfig) -> Result<AsyncSmtpTransport<Tokio1Executor>, String> {
+    let host = config.smtp_host.as_str();
+    let mut builder = match config.tls {
+        SmtpTls::Starttls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host),
+        SmtpTls::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(host),
+        SmtpTls::None => Ok(AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(
+            host,
+        )),
+    }
+    .map_err(|e| format!("SMTP setup failed: {}", e))?;
+
+    if let Some(port) = config.smtp_port {
+        builder = builder.port(port);
+    }
+
+    if let Some(ref username) = config.username {
+        let password = match config.password_env {
+            Some(ref var) => std::env::var(var)
+                .map_err(|_| format!("SMTP password variable {} is not set", var))?,
+            None => config.password.clone().unwrap_or_default(),
+        };
+        builder = builder.credentials(Credentials::new(username.clone(), password));
+    }
+
+    Ok(builder.build())
+}
+
+fn body(meta: &TaskMetadata, log_tail: &[String]) -> String {
+    let mut body = format!(
+        "Task:      {}\nExecutor:  {} ({})\nType:      {}\nStatus:    {}\n",
+        meta.task_id, meta.executor_name, meta.executor_type, meta.task_type, meta.status
+    );
+    if let Some(d) = meta.duration() {
+        body.push_str(&format!(
+            "Duration:  {}\n",
+            format_duration(d.num_seconds())
+        ));
+    }
+    if let Some(code) = meta.exit_code {
+        body.push_str(&format!("Exit code: {}\n", code));
+    }
+    if let Some(ref error) = meta.error {
+        body.push_str(&format!("Error:     {}\n", error));
+    }
+    body.push_str(&format!("\nPrompt:\n{}\n", meta.prompt));
+    if !log_tail.is_empty() {
+        body.push_str(&format!("\nLast log lines:\n{}\n", log_tail.join("\n")));
+    }
+    body
+}
@@ -0,0 +1,92 @@
+pub mod discord;
+pub mod email;
+pub mod slack;
+pub mod telegram;
+
+use crate::config::LocalNotifier;
+use crate::metadata::TaskMetadata;
+use std::io::Write;
+
+/// One-line summary of a finished task, used as the notification body.
+pub fn summary_line(meta: &TaskMetadata) -> String {
+    let short_id = meta.task_id.0.get(..8).unwrap_or(&meta.task_id.0);
+    match meta.exit_code {
+        Some(code) => format!(
+            "openclaw: task {} on {} {} (exit {})",
+            short_id, meta.executor_name, meta.status, code
+        ),
+        None => format!(
+            "openclaw: task {} on {} {}",
+            short_id, meta.executor_name, meta.status
+        ),
+    }
+}
+
+/// Fire all configured local notifiers for a finished task.
+/// Escape sequences go to stderr so they never mix with JSON on stdout.
+/// Failures are returned per notifier but don't stop the others.
+pub async fn notify_local(meta: &TaskMetadata, notifiers: &[LocalNotifier]) -> Vec<String> {
+    let message = summary_line(meta);
+    let mut errors = Vec::new();
+
+    for notifier in notifiers {
+        let result = match notifier {
+            LocalNotifier::Bell => write_stderr("\x07"),
+            LocalNotifier::Wezterm => write_stderr(&format!(
+                "\x1b]777;notify;openclaw-agent;{}\x1b\\",
+                sanitize(&message)
+            )),
+            LocalNotifier::Kitty => write_stderr(&format!(
+                "\x1b]99;;{}\x1b\\",
+                sanitize(&message)
+            )),
+            LocalNotifier::Tmux => tmux_display(&message).await,
+        };
+        if let Err(e) = result {
+            errors.push(format!("{:?} notifier: {}", notifier, e));
+        }
+    }
+
+    errors
+}
+
+fn write_stderr(s: &str) -> Result<(), String> {
+    let mut stderr = std::io::stderr();
+    stderr
+        .write_all(s.as_bytes())
+        .and_then(|_| stderr.flush())
+        .map_err(|e| e.to_string())
+}
+
+async fn tmux_display(message: &str) -> Result<(), String> {
+    if std::env::var_os("TMUX").is_none() {
+        return Err("not running inside tmux".into());
+    }
+
+    let output = tokio::process::Command::new("tmux")
+        .args(["display-message", message])
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run tmux: {}", e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(stderr.trim().to_string());
+    }
+
+    Ok(())
+}
+
+/// At most `max` characters of the first line of `text`, with an ellipsis if cut.
+pub fn excerpt(text: &str, max: usize) -> String {
+    let line = text.lines().next().unwrap_or_default();
+    match line.char_indices().nth(max) {
+        Some((idx, _)) => format!("{}…", &line[..idx]),
+        None => line.to_string(),
+    }
+}
+
+/// Strip control characters that would terminate an OSC sequence early.
+fn sanitize(s: &str) -> String {
+    s.chars().filter(|c| !c.is_control()).collect()
+}
@@ -0,0 +1,108 @@
+use crate::config::SlackConfig;
+use crate::http;
+use crate::metadata::TaskMetadata;
+use crate::notify::excerpt;
+use crate::stats::format_duration;
+use crate::task::TaskStatus;
+use serde_json::json;
+
+const CHAT_POST_MESSAGE: &str = "https://slack.com/api/chat.postMessage";
+
+/// Longest prompt excerpt shown in a message.
+const PROMPT_EXCERPT_LEN: usize = 200;
+
+/// Slack caps a section's text at 3000 characters; leave room for the fence.
+const LOG_TAIL_MAX_CHARS: usize = 2800;
+
+/// Post a finished task to Slack via the configured webhook or bot token.
+pub async fn notify(
+    config: &SlackConfig,
+    meta: &TaskMetadata,
+    log_tail: &[String],
+) -> Result<(), String> {
+    let mut message = message(meta, log_tail);
+
+    if let Some(ref url) = config.webhook_url {
+        http::post_json(url, &[], &message).await?;
+        return Ok(());
+    }
+
+    let (Some(token), Some(channel)) = (&config.bot_token, &config.channel) else {
+        return Err("slack needs either webhook_url or bot_token + channel".into());
+    };
+    message["channel"] = json!(channel);
+    let response = http::post_json(
+        CHAT_POST_MESSAGE,
+        &[("Authorization", &format!("Bearer {}", token))],
+        &message,
+    )
+    .await?;
+
+    // The Web API answers 200 with {"ok": false, "error": ...} on failure
+    let body: serde_json::Value = serde_json::from_str(&response).unwrap_or_default();
+    if body["ok"].as_bool() != Some(true) {
+        let error = body["error"].as_str().unwrap_or("unexpected response");
+        return Err(format!("slack API error: {}", error));
+    }
+    Ok(())
+}
+
+/// Block Kit message with a plain-text fallback.
+fn message(meta: &TaskMetadata, log_tail: &[String]) -> serde_json::Value {
+    let icon = match meta.status {
+        TaskStatus::Completed => ":white_check_mark:",
+        TaskStatus::Killed => ":octagonal_sign:",
+        _ => ":x:",
+    };
+    let duration = meta
+        .duration()
+        .map(|d| format_duration(d.num_seconds()))
+        .unwrap_or_else(|| "-".into());
+
+    let mut blocks = vec![
+        json!({
+            "type": "section",
+            "text": {
+                "type": "mrkdwn",
+                "text": format!("{} *Task {}* on `{}`", icon, meta.status, meta.executor_name),
+            },
+        }),
+        json!({
+            "type": "section",
+            "fields": [
+                { "type": "mrkdwn", "text": format!("*Task ID*\n`{}`", meta.task_id) },
+                { "type": "mrkdwn", "text": format!("*Type*\n{}", meta.task_type) },
+                { "type": "mrkdwn", "text": format!("*Duration*\n{}", duration) },
+                { "type": "mrkdwn", "text": format!("*Exit code*\n{}",
+                    meta.exit_code.map(|c| c.to_string()).unwrap_or_else(|| "-".into())) },
+            ],
+        }),
+        json!({
+            "type": "section",
+            "text": { "type": "mrkdwn", "text": format!("> {}", excerpt(&meta.prompt, PROMPT_EXCERPT_LEN)) },
+        }),
+    ];
+
+    if let Some(ref error) = meta.error {
+        blocks.push(json!({
+            "type": "section",
+            "text": { "type": "mrkdwn", "text": format!("*Error:* {}", error) },
+        }));
+    }
+
+    if !log_tail.is_empty() {
+        let mut tail = log_tail.join("\n");
+        if let Some((idx, _)) = tail.char_indices().rev().nth(LOG_TAIL_MAX_CHARS) {
+            tail = tail[idx..].to_string();
+        }
+        blocks.push(json!({
+            "type": "section",
+            "text": { "type": "mrkdwn", "text": format!("```{}```", tail) },
+        }));
+    }
+
+    json!({
+        "text": super::summary_line(meta),
+        "blocks": blocks,
+    })
+}
@@ -0,0 +1,205 @@
+use crate::config::TelegramConfig;
+use crate::http;
+use crate::metadata::TaskMetadata;
+use crate::notify::excerpt;
+use crate::stats::format_duration;
+use crate::task::{TaskId, TaskStatus};
+use serde_json::json;
+use std::time::Duration;
+
+const API_BASE: &str = "https://api.telegram.org";
+
+/// Longest prompt excerpt shown in a message.
+const PROMPT_EXCERPT_LEN: usize = 300;
+
+/// Telegram caps messages at 4096 characters; leave room for the rest.
+const LOG_TAIL_MAX_CHARS: usize = 2500;
+
+/// How long `getUpdates` holds the connection open waiting for a callback.
+const POLL_TIMEOUT_SECS: u64 = 25;
+
+/// What an inline button asks the daemon to do with a task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Retry,
+    Cleanup,
+}
+
+impl Action {
+    fn as_str(self) -> &'static str {
+        match self {
+            Action::Retry => "retry",
+            Action::Cleanup => "cleanup",
+        }
+    }
+
+    /// Parse button callback data of the form `<action>:<task_id>`.
+    pub fn parse(data: &str) -> Option<(Action, TaskId)> {
+        let (action, id) = data.split_once(':')?;
+        let action = match action {
+            "retry" => Action::Retry,
+            "cleanup" => Action::Cleanup,
+            _ => return None,
+        };
+        Some((action, TaskId::from_string(id.to_string())))
+    }
+}
+
+/// A button press received from `getUpdates`.
+#[derive(Debug, Clone)]
+pub struct Callback {
+    pub id: String,
+    pub chat_id: String,
+    pub data: String,
+}
+
+/// Post a finished task to the configured chat.
+pub async fn notify(
+    config: &TelegramConfig,
+    meta: &TaskMetadata,
+    log_tail: &[String],
+) -> Result<(), String> {
+    let mut body = json!({
+        "chat_id": config.chat_id,
+        "text": message(meta, log_tail),
+        "parse_mode": "HTML",
+        "disable_web_page_preview": true,
+    });
+    if config.actions {
+        let button = |action: Action, label: &str| json!({ "text": label, "callback_data": format!("{}:{}", action.as_str(), meta.task_id) });
+        body["reply_markup"] = json!({
+            "inline_keyboard": [[button(Action::Retry, "Retry"), button(Action::Cleanup, "Clean up")]],
+        });
+    }
+    call(config, "sendMessage", &body, http::DEFAULT_TIMEOUT).await?;
+    Ok(())
+}
+
+/// Long-poll for button presses after update `offset`. Returns the callbacks
+/// and the offset to pass next time.
+pub async fn poll_callbacks(
+    config: &TelegramConfig,
+    offset: i64,
+) -> Result<(Vec<Callback>, i64), String> {
+    let body = json!({
+        "offset": offset,
+        "timeout": POLL_TIMEOUT_SECS,
+        "allowed_updates": ["callback_query"],
+    });
+    let timeout = Duration::from_secs(POLL_TIMEOUT_SECS) + http::DEFAULT_TIMEOUT;
+    let result = call(config, "getUpdates", &body, timeout).await?;
+
+    let mut next = offset;
+    let mut callbacks = Vec::new();
+    for update in result.as_array().into_iter().flatten() {
+        if let Some(id) = update["update_id"].as_i64() {
+            next = next.max(id + 1);
+        }
+        let query = &update["callback_query"];
+        let (Some(id), Some(data)) = (query["id"].as_str(), query["data"].as_str()) else {
+            continue;
+        };
+        let chat_id = &query["message"]["chat"];
+        let chat_id = match chat_id["username"].as_str() {
+            Some(name) if config.chat_id.starts_with('@') => format!("@{}", name),
+            _ => chat_id["id"].to_string(),
+        };
+        callbacks.push(Callback {
+            id: id.to_string(),
+            chat_id,
+            data: data.to_string(),
+        });
+    }
+    Ok((callbacks, next))
+}
+
+/// Acknowledge a button press with a short toast.
+pub async fn answer_callback(
+    config: &TelegramConfig,
+    callback_id: &str,
+    text: &str,
+) -> Result<(), String> {
+    let body = json!({ "callback_query_id": callback_id, "text": text });
+    call(config, "answerCallbackQuery", &body, http::DEFAULT_TIMEOUT).await?;
+    Ok(())
+}
+
+/// Call a Bot API method and return its `result`.
+async fn call(
+    config: &TelegramConfig,
+    method: &str,
+    body: &serde_json::Value,
+    timeout: Duration,
+) -> Result<serde_json::Value, String> {
+    let token = config
+        .token()
+        .ok_or_else(|| "telegram bot token is not set".to_string())?;
+    let url = format!("{}/bot{}/{}", API_BASE, token, method);
+    let body = serde_json::to_vec(body).map_err(|e| e.to_string())?;
+
+    // Errors from reqwest include the URL; keep the token out of them
+    let response = http::post(&url, &[], body, timeout)
+        .await
+        .map_err(|e| e.replace(&token, "<token>"))?;
+    let mut response: serde_json::Value = serde_json::from_str(&response).unwrap_or_default();
+    if response["ok"].as_bool() != Some(true) {
+        let error = response["description"]
+            .as_str()
+            .unwrap_or("unexpected response");
+        return Err(format!("telegram API error: {}", error));
+    }
+    Ok(response["result"].take())
+}
+
+fn message(meta: &TaskMetadata, log_tail: &[String]) -> String {
+    let icon = match meta.status {
+        TaskStatus::Completed => "\u{2705}",
+        TaskStatus::Killed => "\u{1F6D1}",
+        _ => "\u{274C}",
+    };
+    let duration = meta
+        .duration()
+        .map(|d| format_duration(d.num_seconds()))
+        .unwrap_or_else(|| "-".into());
+
+    let mut text = format!(
+        "{} <b>Task {}</b> on <code>{}</code>\n\
+         ID: <code>{}</code>\n\
+         Type: {} · Duration: {} · Exit: {}\n\n\
+         <i>{}</i>",
+        icon,
+        meta.status,
+        escape(&meta.executor_name),
+        meta.task_id,
+        escape(&meta.task_type),
+        duration,
+        meta.exit_code
+            .map(|c| c.to_string())
+            .unwrap_or_else(|| "-".into()),
+        escape(&excerpt(&meta.prompt, PROMPT_EXCERPT_LEN)),
+    );
+
+    if let Some(ref error) = meta.error {
+        text.push_str(&format!(
+            "\n\n<b>Error:</b> {}",
+            escape(&excerpt(error, 500))
+        ));
+    }
+
+    if !log_tail.is_empty() {
+        let mut tail = log_tail.join("\n");
+        if let Some((idx, _)) = tail.char_indices().rev().nth(LOG_TAIL_MAX_CHARS) {
+            tail = tail[idx..].to_string();
+        }
+        text.push_str(&format!("\n\n<pre>{}</pre>", escape(&tail)));
+    }
+
+    text
+}
+
+/// Escape text for Telegram's HTML parse mode.
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
@@ -0,0 +1,104 @@
+use crate::spec::TaskSpec;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// A pipeline file: a list of tasks where each task may declare which
+/// other tasks (by name) must complete successfully before it is launched.
+///
+/// ```yaml
+/// tasks:
+///   - name: generate
+///     executor: crib
+///     prompt: "Implement the parser"
+///   - name: test
+///     executor: crib
+///     command: cargo test
+///     after: [generate]
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Pipeline {
+    pub tasks: Vec<PipelineTask>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PipelineTask {
+    pub name: String,
+    #[serde(flatten)]
+    pub spec: TaskSpec,
+    /// Names of tasks in the same pipeline that must succeed first.
+    #[serde(default)]
+    pub after: Vec<String>,
+}
+
+impl Pipeline {
+    /// Load a pipeline from a YAML (or JSON) file.
+    pub fn load_from(path: &Path) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(path)?;
+        let pipeline: Pipeline = serde_yaml::from_str(&contents)?;
+        pipeline.execution_order()?;
+        Ok(pipeline)
+    }
+
+    /// Find a task by name.
+    pub fn find_task(&self, name: &str) -> Option<&PipelineTask> {
+        self.tasks.iter().find(|t| t.name == name)
+    }
+
+    /// Topologically sort the tasks, returning their names in an order where
+    /// every task appears after all of its dependencies. Fails on duplicate
+    /// names, unknown dependencies, or cycles.
+    pub fn execution_order(&self) -> anyhow::Result<Vec<String>> {
+        let mut seen = HashSet::new();
+        for task in &self.tasks {
+            if !seen.insert(task.name.as_str()) {
+                anyhow::bail!("Duplicate pipeline task name '{}'", task.name);
+            }
+            task.spec
+                .payload()
+                .map_err(|e| anyhow::anyhow!("Pipeline task '{}': {}", task.name, e))?;
+        }
+        for task in &self.tasks {
+            for dep in &task.after {
+                if !seen.contains(dep.as_str()) {
+                    anyhow::bail!("Task '{}' depends on unknown task '{}'", task.name, dep);
+                }
+            }
+        }
+
+        let mut remaining: HashMap<&str, usize> = self
+            .tasks
+            .iter()
+            .map(|t| {
+                let deps: HashSet<&str> = t.after.iter().map(|d| d.as_str()).collect();
+                (t.name.as_str(), deps.len())
+            })
+            .collect();
+        let mut order: Vec<String> = Vec::new();
+
+        while order.len() < self.tasks.len() {
+            let ready: Vec<&str> = self
+                .tasks
+                .iter()
+                .map(|t| t.name.as_str())
+                .filter(|n| remaining.get(n) == Some(&0))
+                .collect();
+            if ready.is_empty() {
+                anyhow::bail!("Pipeline contains a dependency cycle");
+            }
+            for name in ready {
+                remaining.remove(name);
+                for task in &self.tasks {
+                    if task.after.iter().any(|d| d == name) {
+                        if let Some(count) = remaining.get_mut(task.name.as_str()) {
+                            *count -= 1;
+                        }
+                    }
+                }
+                order.push(name.to_string());
+            }
+        }
+
+        Ok(order)
+    }
+}
@@ -0,0 +1,119 @@
+//! Turn-by-turn progress of a running `claude_code` task.
+//!
+//! With `stream_json`, claude logs one JSON event per line. Each turn is an
+//! `assistant` message (split over several events that share its id), and
+//! tool calls are its `tool_use` content blocks. [`script`] counts turns and
+//! picks the last tool call where the log lives; [`parse_progress`] turns that
+//! into a [`TaskProgress`]. Like resource usage, progress is sampled on every
+//! heartbeat.
+
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Input fields naming what a tool call works on, most telling first.
+const TOOL_TARGET_FIELDS: &[&str] = &[
+    "file_path",
+    "notebook_path",
+    "path",
+    "pattern",
+    "command",
+    "url",
+    "query",
+    "description",
+];
+
+/// Longest tool target shown before it is cut short.
+const TOOL_TARGET_CHARS: usize = 60;
+
+/// How far a `claude_code` task has got.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct TaskProgress {
+    /// Assistant turns taken so far.
+    pub turns: u32,
+    /// The most recent tool call, e.g. `Edit(src/lib.rs)`.
+    pub last_tool: Option<String>,
+    pub sampled_at: DateTime<Utc>,
+}
+
+impl TaskProgress {
+    /// e.g. `turn 14/100, last tool: Edit(src/lib.rs)`.
+    pub fn summary(&self, max_turns: Option<u32>) -> String {
+        let mut summary = match max_turns {
+            Some(max) => format!("turn {}/{}", self.turns, max),
+            None => format!("turn {}", self.turns),
+        };
+        if let Some(ref tool) = self.last_tool {
+            summary.push_str(&format!(", last tool: {}", tool));
+        }
+        summary
+    }
+}
+
+/// A `sh` script printing `turns <n>` and, once a tool has been called,
+/// `last <event>` with the last assistant event that called one. `log_file`
+/// must already be shell-quoted. Prints nothing if there is no log yet.
+pub fn script(log_file: &str) -> String {
+    format!(
+        "\
+f={log_file}
+[ -r \"$f\" ] || exit 0
+echo \"turns $(grep -F '\"type\":\"assistant\"' \"$f\" | grep -o '\"id\":\"msg_[^\"]*\"' | sort -u | wc -l)\"
+last=$(grep -F '\"type\":\"assistant\"' \"$f\" | grep -F '\"type\":\"tool_use\"' | tail -n 1)
+[ -n \"$last\" ] && printf 'last %s\\n' \"$last\"
+true"
+    )
+}
+
+/// The progress printed by [`script`], or `None` if it printed none. Paths
+/// under `workspace` are shown relative to it.
+pub fn parse_progress(output: &str, workspace: Option<&str>) -> Option<TaskProgress> {
+    let mut turns = None;
+    let mut last_tool = None;
+    for line in output.lines() {
+        if let Some(count) = line.strip_prefix("turns ") {
+            turns = count.trim().parse().ok();
+        } else if let Some(event) = line.strip_prefix("last ") {
+            last_tool = last_tool_call(crate::log_timestamps::strip(event), workspace);
+        }
+    }
+    Some(TaskProgress {
+        turns: turns?,
+        last_tool,
+        sampled_at: Utc::now(),
+    })
+}
+
+/// The last `tool_use` block of an assistant event, as `Name(target)`.
+fn last_tool_call(event: &str, workspace: Option<&str>) -> Option<String> {
+    let event: Value = serde_json::from_str(event).ok()?;
+    let block = event["message"]["content"]
+        .as_array()?
+        .iter()
+        .rev()
+        .find(|block| block["type"] == "tool_use")?;
+    let name = block["name"].as_str()?;
+    let target = TOOL_TARGET_FIELDS
+        .iter()
+        .find_map(|field| block["input"][field].as_str());
+    Some(match target {
+        Some(target) => format!("{}({})", name, shorten(target, workspace)),
+        None => name.to_string(),
+    })
+}
+
+/// A tool target on one short line, relative to `workspace` if under it.
+fn shorten(target: &str, workspace: Option<&str>) -> String {
+    let target = workspace
+        .and_then(|ws| target.strip_prefix(ws.trim_end_matches('/')))
+        .and_then(|rest| rest.strip_prefix('/'))
+        .unwrap_or(target);
+    let line = target.lines().next().unwrap_or_default();
+    if line.chars().count() > TOOL_TARGET_CHARS || line.len() < target.len() {
+        let cut: String = line.chars().take(TOOL_TARGET_CHARS).collect();
+        format!("{}...", cut)
+    } else {
+        line.to_string()
+    }
+}
@@ -0,0 +1,112 @@
+//! Task gauges in the Prometheus text exposition format, for node_exporter's
+//! textfile collector (`dashboard --prom-textfile`).
+
+use crate::metadata::TaskMetadata;
+use crate::task::TaskStatus;
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// Every status, so each executor reports a (possibly zero) count for all of
+/// them and series don't vanish between refreshes.
+const STATUSES: [TaskStatus; 6] = [
+    TaskStatus::Starting,
+    TaskStatus::Running,
+    TaskStatus::Completed,
+    TaskStatus::Failed,
+    TaskStatus::Killed,
+    TaskStatus::Unknown,
+];
+
+/// The gauges for `tasks` as of `now`: task counts per executor and status,
+/// and the run time and resource usage of each running task.
+pub fn render(tasks: &[TaskMetadata], now: DateTime<Utc>) -> String {
+    let mut out = String::new();
+
+    let mut counts: BTreeMap<&str, BTreeMap<String, usize>> = BTreeMap::new();
+    for task in tasks {
+        let by_status = counts.entry(task.executor_name.as_str()).or_insert_with(|| {
+            STATUSES.iter().map(|s| (s.to_string(), 0)).collect()
+        });
+        *by_status.entry(task.status.to_string()).or_default() += 1;
+    }
+    header(&mut out, "openclaw_tasks", "Tasks by executor and status.");
+    for (executor, by_status) in &counts {
+        for (status, count) in by_status {
+            let _ = writeln!(
+                out,
+                "openclaw_tasks{{executor=\"{}\",status=\"{}\"}} {}",
+                escape(executor),
+                status,
+                count
+            );
+        }
+    }
+
+    let running: Vec<&TaskMetadata> = tasks
+        .iter()
+        .filter(|t| t.status == TaskStatus::Running)
+        .collect();
+    header(
+        &mut out,
+        "openclaw_task_running_seconds",
+        "Time since each running task started.",
+    );
+    for task in &running {
+        let secs = (now - task.started_at).num_seconds().max(0);
+        sample(&mut out, "openclaw_task_running_seconds", task, secs as f64);
+    }
+    header(
+        &mut out,
+        "openclaw_task_cpu_seconds",
+        "CPU time of each running task's processes, as of its last heartbeat.",
+    );
+    for task in &running {
+        if let Some(ref usage) = task.resource_usage {
+            sample(&mut out, "openclaw_task_cpu_seconds", task, usage.cpu_secs);
+        }
+    }
+    header(
+        &mut out,
+        "openclaw_task_peak_memory_bytes",
+        "Peak resident memory of each running task's processes, as of its last heartbeat.",
+    );
+    for task in &running {
+        if let Some(ref usage) = task.resource_usage {
+            let bytes = usage.peak_rss_kb as f64 * 1024.0;
+            sample(&mut out, "openclaw_task_peak_memory_bytes", task, bytes);
+        }
+    }
+
+    header(
+        &mut out,
+        "openclaw_textfile_generated_seconds",
+        "Unix time this file was written.",
+    );
+    let _ = writeln!(out, "openclaw_textfile_generated_seconds {}", now.timestamp());
+    out
+}
+
+fn header(out: &mut String, name: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} gauge", name);
+}
+
+fn sample(out: &mut String, name: &str, task: &TaskMetadata, value: f64) {
+    let _ = writeln!(
+        out,
+        "{}{{task_id=\"{}\",executor=\"{}\"}} {}",
+        name,
+        escape(&task.task_id.0),
+        escape(&task.executor_name),
+        value
+    );
+}
+
+/// A label value with `\`, `"` and newlines escaped.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
@@ -0,0 +1,207 @@
+//! `provision`: get an executor's host ready for tasks. Executors run
+//! [`script`] on the host, which installs whatever of git, Node.js and the
+//! claude CLI is missing and creates the task root; [`parse_report`] reads
+//! back what it found. The last report per executor is kept under
+//! ~/.local/share/openclaw/provisioned/ so `executors` can show which are
+//! ready.
+
+use crate::config::ExecutorConfig;
+use crate::host;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Oldest Node.js major version the claude CLI runs on.
+pub const MIN_NODE_MAJOR: u32 = 18;
+
+/// npm package the claude CLI is installed from.
+const CLAUDE_PACKAGE: &str = "@anthropic-ai/claude-code";
+
+/// What a provisioning run found (and installed) on a host.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProvisionReport {
+    pub git: Option<String>,
+    pub node: Option<String>,
+    pub claude: Option<String>,
+    pub claude_path: Option<String>,
+    /// Whether `claude` alone runs claude there, without a `claude_path`.
+    #[serde(default)]
+    pub claude_on_path: bool,
+    /// Prerequisites this run installed.
+    #[serde(default)]
+    pub installed: Vec<String>,
+    pub task_root: String,
+    /// Why the host isn't ready; empty if it is.
+    #[serde(default)]
+    pub problems: Vec<String>,
+}
+
+impl ProvisionReport {
+    pub fn is_ready(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+/// A report as kept for an executor, with when it was made.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProvisionRecord {
+    pub provisioned_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub report: ProvisionReport,
+}
+
+pub fn provision_dir() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("/tmp"))
+        .join("openclaw")
+        .join("provisioned")
+}
+
+/// A `sh` script that checks for git, node and claude, installs the missing
+/// ones if `install` is set, and creates `task_root`. Packages come from the
+/// host's package manager (as root or through passwordless sudo), claude
+/// from npm: globally if npm's prefix is writable, otherwise under
+/// `~/.local`. Prints `key=value` lines for [`parse_report`]; installer
+/// output goes to stderr.
+pub fn script(task_root: &str, install: bool, claude_path: Option<&str>) -> String {
+    let find_claude = host::claude_script(claude_path);
+    format!(
+        "\
+root={root}; install={install}
+SUDO=
+if [ \"$(id -u)\" != 0 ] && command -v sudo >/dev/null 2>&1 && sudo -n true 2>/dev/null; then
+  SUDO='sudo -n'
+fi
+pm=
+for p in apt-get dnf yum apk pacman brew; do
+  command -v $p >/dev/null 2>&1 && {{ pm=$p; break; }}
+done
+pkg() {{
+  case $pm in
+    apt-get) $SUDO apt-get update -qq &&
+      $SUDO env DEBIAN_FRONTEND=noninteractive apt-get install -y -qq \"$@\" ;;
+    dnf|yum) $SUDO $pm install -y -q \"$@\" ;;
+    apk) $SUDO apk add -q \"$@\" ;;
+    pacman) $SUDO pacman -S --noconfirm --needed \"$@\" ;;
+    brew) brew install \"$@\" ;;
+  esac >&2
+}}
+need() {{
+  n=$1; shift
+  [ $install = 1 ] || return 0
+  if [ -z \"$pm\" ]; then
+    echo \"problem=no package manager found to install $n with\"
+  elif [ $pm != brew ] && [ \"$(id -u)\" != 0 ] && [ -z \"$SUDO\" ]; then
+    echo \"problem=installing $n needs root or passwordless sudo\"
+  elif pkg \"$@\"; then
+    echo \"installed=$n\"
+  else
+    echo \"problem=$pm failed to install $n\"
+  fi
+}}
+command -v git >/dev/null 2>&1 || need git git
+if ! command -v node >/dev/null 2>&1; then
+  if [ \"$pm\" = brew ]; then need node node; else need node nodejs npm; fi
+fi
+claude=$(
+{find_claude}
+)
+if [ -z \"$claude\" ] && [ $install = 1 ]; then
+  if ! command -v npm >/dev/null 2>&1; then
+    echo 'problem=npm is needed to install claude'
+  else
+    set --
+    [ -w \"$(npm prefix -g 2>/dev/null)\" ] || set -- --prefix \"$HOME/.local\"
+    if npm install -g \"$@\" {package} >&2; then
+      echo installed=claude
+    else
+      echo 'problem=npm failed to install claude'
+    fi
+    claude=$(
+{find_claude}
+)
+  fi
+fi
+v=$(git --version 2>/dev/null) && echo \"git=$v\"
+v=$(node --version 2>/dev/null) && echo \"node=$v\"
+if [ -n \"$claude\" ]; then
+  echo \"claude_path=$claude\"
+  command -v claude >/dev/null 2>&1 && echo claude_on_path=1
+  v=$(\"$claude\" --version 2>/dev/null | head -n 1) && echo \"claude=$v\"
+fi
+if mkdir -p \"$root\" 2>/dev/null && [ -w \"$root\" ]; then
+  echo task_root=ok
+else
+  echo \"problem=can't create or write to $root\"
+fi
+true",
+        root = crate::git::quote(task_root),
+        install = if install { 1 } else { 0 },
+        find_claude = find_claude,
+        package = CLAUDE_PACKAGE,
+    )
+}
+
+/// Read [`script`]'s output, adding a problem for each prerequisite missing
+/// or too old.
+pub fn parse_report(output: &str, task_root: &str) -> ProvisionReport {
+    let mut report = ProvisionReport {
+        task_root: task_root.to_string(),
+        ..ProvisionReport::default()
+    };
+    for line in output.lines() {
+        let Some((key, value)) = line.trim().split_once('=') else {
+            continue;
+        };
+        let value = value.trim().to_string();
+        match key {
+            "git" => {
+                report.git = Some(value.trim_start_matches("git version ").to_string())
+            }
+            "node" => report.node = Some(value.trim_start_matches('v').to_string()),
+            "claude" => report.claude = Some(value),
+            "claude_path" => report.claude_path = Some(value),
+            "claude_on_path" => report.claude_on_path = true,
+            "installed" => report.installed.push(value),
+            "problem" => report.problems.push(value),
+            _ => {}
+        }
+    }
+
+    if report.git.is_none() {
+        report.problems.push("git is not installed".into());
+    }
+    match report.node.as_deref() {
+        None => report.problems.push("node is not installed".into()),
+        Some(version) => {
+            let major = version.split('.').next().and_then(|m| m.parse::<u32>().ok());
+            if major.is_some_and(|major| major < MIN_NODE_MAJOR) {
+                report.problems.push(format!(
+                    "node {} is older than {}, the oldest the claude CLI runs on",
+                    version, MIN_NODE_MAJOR
+                ));
+            }
+        }
+    }
+    if report.claude_path.is_none() {
+        report.problems.push("claude is not installed".into());
+    }
+    report
+}
+
+/// Keep `report` as `executor`'s latest.
+pub fn record(executor: &ExecutorConfig, report: &ProvisionReport) -> Result<(), std::io::Error> {
+    let record = ProvisionRecord {
+        provisioned_at: Utc::now(),
+        report: report.clone(),
+    };
+    std::fs::create_dir_all(provision_dir())?;
+    let path = provision_dir().join(format!("{}.json", executor.file_name()));
+    std::fs::write(path, serde_json::to_string_pretty(&record)?)
+}
+
+/// `executor`'s latest report, if it has been provisioned.
+pub fn load(executor: &ExecutorConfig) -> Option<ProvisionRecord> {
+    let path = provision_dir().join(format!("{}.json", executor.file_name()));
+    serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()
+}
@@ -0,0 +1,83 @@
+use crate::config::ExecutorConfig;
+use crate::error::ExecutorError;
+use crate::executor::Executor;
+use crate::store::MetadataStore;
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, RwLock};
+
+/// Builds an executor from its config entry and the shared metadata store.
+pub type ExecutorFactory = Arc<
+    dyn Fn(ExecutorConfig, Arc<dyn MetadataStore>) -> Result<Box<dyn Executor>, ExecutorError>
+        + Send
+        + Sync,
+>;
+
+fn factories() -> &'static RwLock<HashMap<String, ExecutorFactory>> {
+    static FACTORIES: OnceLock<RwLock<HashMap<String, ExecutorFactory>>> = OnceLock::new();
+    FACTORIES.get_or_init(Default::default)
+}
+
+/// Register the factory for executors with `type: <type_name>`, replacing any
+/// existing one (including built-in types).
+pub fn register<F>(type_name: impl Into<String>, factory: F)
+where
+    F: Fn(ExecutorConfig, Arc<dyn MetadataStore>) -> Result<Box<dyn Executor>, ExecutorError>
+        + Send
+        + Sync
+        + 'static,
+{
+    factories()
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(type_name.into(), Arc::new(factory));
+}
+
+/// Register a factory unless one already exists for `type_name`, so earlier
+/// registrations (e.g. a downstream override of a built-in) win.
+pub fn register_if_absent<F>(type_name: impl Into<String>, factory: F)
+where
+    F: Fn(ExecutorConfig, Arc<dyn MetadataStore>) -> Result<Box<dyn Executor>, ExecutorError>
+        + Send
+        + Sync
+        + 'static,
+{
+    factories()
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .entry(type_name.into())
+        .or_insert_with(|| Arc::new(factory));
+}
+
+/// Registered type names, sorted.
+pub fn registered_types() -> Vec<String> {
+    let mut types: Vec<String> = factories()
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .keys()
+        .cloned()
+        .collect();
+    types.sort();
+    types
+}
+
+/// Create an executor using the factory registered for its `type`.
+pub fn create(
+    config: ExecutorConfig,
+    store: Arc<dyn MetadataStore>,
+) -> Result<Box<dyn Executor>, ExecutorError> {
+    let type_name = config.executor_type.to_string();
+    let factory = factories()
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&type_name)
+        .cloned()
+        .ok_or_else(|| {
+            ExecutorError::Config(format!(
+                "executor '{}' has unknown type '{}' (registered: {})",
+                config.name,
+                type_name,
+                registered_types().join(", ")
+            ))
+        })?;
+    factory(config, store)
+}
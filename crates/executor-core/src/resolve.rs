@@ -0,0 +1,190 @@
+use crate::config::{
+    default_claude_path, default_heartbeat_interval_secs, default_max_turns, Config,
+    ExecutorConfig,
+};
+use serde::Serialize;
+use std::fmt;
+
+/// Environment variables that override config values.
+pub const ENV_MAX_TURNS: &str = "OPENCLAW_MAX_TURNS";
+pub const ENV_CLAUDE_PATH: &str = "OPENCLAW_CLAUDE_PATH";
+pub const ENV_WEBHOOK_URL: &str = "OPENCLAW_WEBHOOK_URL";
+
+/// Where a resolved setting came from, lowest to highest precedence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Source {
+    Builtin,
+    Defaults,
+    Executor,
+    Env,
+    Cli,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Builtin => write!(f, "built-in"),
+            Source::Defaults => write!(f, "config defaults"),
+            Source::Executor => write!(f, "executor config"),
+            Source::Env => write!(f, "environment"),
+            Source::Cli => write!(f, "command line"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Resolved<T> {
+    pub value: T,
+    pub source: Source,
+}
+
+impl<T> Resolved<T> {
+    fn new(value: T, source: Source) -> Self {
+        Self { value, source }
+    }
+
+    fn layer(&mut self, value: Option<T>, source: Source) {
+        if let Some(value) = value {
+            self.value = value;
+            self.source = source;
+        }
+    }
+}
+
+/// Values passed explicitly on the command line.
+#[derive(Debug, Clone, Default)]
+pub struct Overrides {
+    pub max_turns: Option<u32>,
+    pub claude_path: Option<String>,
+    pub webhook_url: Option<String>,
+}
+
+/// Final values after layering built-in defaults → config defaults →
+/// executor config → environment → command line.
+#[derive(Debug, Clone, Serialize)]
+pub struct ResolvedSettings {
+    pub max_turns: Resolved<u32>,
+    pub claude_path: Resolved<String>,
+    pub webhook_url: Resolved<Option<String>>,
+    pub heartbeat_interval_secs: Resolved<u64>,
+    pub heartbeat_timeout_minutes: Resolved<Option<u64>>,
+    pub min_free_disk_mb: Resolved<Option<u64>>,
+    pub max_load: Resolved<Option<f64>>,
+}
+
+impl ResolvedSettings {
+    pub fn resolve(config: &Config, executor: Option<&ExecutorConfig>, cli: &Overrides) -> Self {
+        let defaults = &config.defaults;
+
+        // A layer counts only where the setting is present, even if it
+        // repeats the value below it
+        let mut max_turns = Resolved::new(default_max_turns(), Source::Builtin);
+        max_turns.layer(defaults.max_turns, Source::Defaults);
+        max_turns.layer(executor.and_then(|e| e.max_turns), Source::Executor);
+        max_turns.layer(
+            std::env::var(ENV_MAX_TURNS).ok().and_then(|v| v.parse().ok()),
+            Source::Env,
+        );
+        max_turns.layer(cli.max_turns, Source::Cli);
+
+        let mut claude_path = Resolved::new(default_claude_path(), Source::Builtin);
+        claude_path.layer(defaults.claude_path.clone(), Source::Defaults);
+        claude_path.layer(executor.and_then(|e| e.claude_path.clone()), Source::Executor);
+        claude_path.layer(std::env::var(ENV_CLAUDE_PATH).ok(), Source::Env);
+        claude_path.layer(cli.claude_path.clone(), Source::Cli);
+
+        let mut webhook_url = Resolved::new(None, Source::Builtin);
+        webhook_url.layer(defaults.webhook_url.clone().map(Some), Source::Defaults);
+        webhook_url.layer(executor.and_then(|e| e.webhook_url.clone()).map(Some), Source::Executor);
+        webhook_url.layer(std::env::var(ENV_WEBHOOK_URL).ok().map(Some), Source::Env);
+        webhook_url.layer(cli.webhook_url.clone().map(Some), Source::Cli);
+
+        let mut heartbeat_interval_secs =
+            Resolved::new(default_heartbeat_interval_secs(), Source::Builtin);
+        heartbeat_interval_secs.layer(defaults.heartbeat_interval_secs, Source::Defaults);
+        heartbeat_interval_secs.layer(
+            executor.and_then(|e| e.heartbeat_interval_secs),
+            Source::Executor,
+        );
+
+        // The global value lives under `hooks`, next to the hooks it fires
+        let mut heartbeat_timeout_minutes = Resolved::new(None, Source::Builtin);
+        heartbeat_timeout_minutes.layer(
+            config.hooks.heartbeat_timeout_minutes.map(Some),
+            Source::Defaults,
+        );
+        heartbeat_timeout_minutes.layer(
+            executor.and_then(|e| e.heartbeat_timeout_minutes).map(Some),
+            Source::Executor,
+        );
+
+        let mut min_free_disk_mb = Resolved::new(None, Source::Builtin);
+        min_free_disk_mb.layer(defaults.min_free_disk_mb.map(Some), Source::Defaults);
+        min_free_disk_mb.layer(
+            executor.and_then(|e| e.min_free_disk_mb).map(Some),
+            Source::Executor,
+        );
+
+        let mut max_load = Resolved::new(None, Source::Builtin);
+        max_load.layer(defaults.max_load.map(Some), Source::Defaults);
+        max_load.layer(executor.and_then(|e| e.max_load).map(Some), Source::Executor);
+
+        Self {
+            max_turns,
+            claude_path,
+            webhook_url,
+            heartbeat_interval_secs,
+            heartbeat_timeout_minutes,
+            min_free_disk_mb,
+            max_load,
+        }
+    }
+
+    /// Rows of (field, value, source) for display.
+    pub fn rows(&self) -> Vec<(&'static str, String, Source)> {
+        vec![
+            (
+                "max_turns",
+                self.max_turns.value.to_string(),
+                self.max_turns.source,
+            ),
+            (
+                "claude_path",
+                self.claude_path.value.clone(),
+                self.claude_path.source,
+            ),
+            (
+                "webhook_url",
+                self.webhook_url.value.clone().unwrap_or_else(|| "-".into()),
+                self.webhook_url.source,
+            ),
+            (
+                "heartbeat_interval_secs",
+                self.heartbeat_interval_secs.value.to_string(),
+                self.heartbeat_interval_secs.source,
+            ),
+            (
+                "heartbeat_timeout_minutes",
+                self.heartbeat_timeout_minutes
+                    .value
+                    .map_or_else(|| "-".into(), |v| v.to_string()),
+                self.heartbeat_timeout_minutes.source,
+            ),
+            (
+                "min_free_disk_mb",
+                self.min_free_disk_mb
+                    .value
+                    .map_or_else(|| "-".into(), |v| v.to_string()),
+                self.min_free_disk_mb.source,
+            ),
+            (
+                "max_load",
+                self.max_load
+                    .value
+                    .map_or_else(|| "-".into(), |v| v.to_string()),
+                self.max_load.source,
+            ),
+        ]
+    }
+}
@@ -0,0 +1,27 @@
+//! JSON Schemas for the machine-readable outputs, generated from the types
+//! that produce them, so dashboards can validate against (and generate code
+//! from) the same contract the CLI writes.
+
+use crate::completion::CompletionRecord;
+use crate::events::Event;
+use crate::metadata::{Dashboard, TaskMetadata};
+
+/// Outputs with a schema, as named on the command line.
+pub const KINDS: [&str; 4] = ["dashboard", "metadata", "completion", "events"];
+
+/// The JSON Schema for one of [`KINDS`]:
+/// - `dashboard`: the `dashboard` snapshot (its `tasks` entries are what
+///   `dashboard --stream` and `list --output jsonl` print)
+/// - `metadata`: a task's `.meta.json`
+/// - `completion`: a completion record file and webhook body
+/// - `events`: one line of the event stream
+pub fn schema(kind: &str) -> Option<serde_json::Value> {
+    let schema = match kind {
+        "dashboard" => schemars::schema_for!(Dashboard),
+        "metadata" => schemars::schema_for!(TaskMetadata),
+        "completion" => schemars::schema_for!(CompletionRecord),
+        "events" => schemars::schema_for!(Event),
+        _ => return None,
+    };
+    Some(schema.to_value())
+}
@@ -0,0 +1,79 @@
+use crate::log_cache;
+use crate::metadata::TaskMetadata;
+use crate::task::TaskId;
+use serde::Serialize;
+
+/// Characters of context shown on each side of a match.
+const CONTEXT: usize = 40;
+
+/// One place a search term was found.
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    pub task_id: TaskId,
+    pub executor: String,
+    /// `prompt`, `error` or `log`.
+    pub field: &'static str,
+    pub snippet: String,
+}
+
+/// Case-insensitive search over task prompts, errors and locally cached logs.
+/// Returns at most one hit per field per task.
+pub fn search(tasks: &[TaskMetadata], term: &str) -> Vec<SearchHit> {
+    let needle = fold(term);
+    let mut hits = Vec::new();
+
+    for task in tasks {
+        let hit = |field, snippet| SearchHit {
+            task_id: task.task_id.clone(),
+            executor: task.executor_name.clone(),
+            field,
+            snippet,
+        };
+
+        if let Some(snippet) = find(&task.prompt, &needle) {
+            hits.push(hit("prompt", snippet));
+        }
+        if let Some(snippet) = task.error.as_deref().and_then(|e| find(e, &needle)) {
+            hits.push(hit("error", snippet));
+        }
+        if let Some(log) = log_cache::read_cached_log(&task.task_id) {
+            if let Some(snippet) = log.iter().find_map(|line| find(line, &needle)) {
+                hits.push(hit("log", snippet));
+            }
+        }
+    }
+
+    hits
+}
+
+/// `text` lowercased a character at a time, so each character stays at its
+/// index in `text` (`to_lowercase` turns some, like `İ`, into two).
+fn fold(text: &str) -> Vec<char> {
+    text.chars()
+        .map(|c| c.to_lowercase().next().unwrap_or(c))
+        .collect()
+}
+
+/// The first match of `needle` (already [`fold`]ed) in `text`, with
+/// surrounding context.
+fn find(text: &str, needle: &[char]) -> Option<String> {
+    let chars: Vec<char> = text.chars().collect();
+    let lower = fold(text);
+    if needle.is_empty() {
+        return None;
+    }
+
+    let pos = lower.windows(needle.len()).position(|w| w == needle)?;
+    let start = pos.saturating_sub(CONTEXT);
+    let end = (pos + needle.len() + CONTEXT).min(chars.len());
+
+    let mut snippet: String = chars[start..end].iter().collect();
+    snippet = snippet.replace(['\n', '\r', '\t'], " ");
+    if start > 0 {
+        snippet.insert(0, '…');
+    }
+    if end < chars.len() {
+        snippet.push('…');
+    }
+    Some(snippet)
+}
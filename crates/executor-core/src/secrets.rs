@@ -0,0 +1,162 @@
+//! Secret references in executor `env` values, resolved only when an executor
+//! hands the environment to a task, so tokens never sit in plaintext config
+//! or task metadata:
+//!
+//! - `keyring:<service>` or `keyring:<service>/<account>`: the OS keyring
+//!   (`secret-tool` on Linux, `security` on macOS)
+//! - `vault:<path>#<field>`: HashiCorp Vault, via `vault kv get`
+//! - `sops:<file>#<key>`: a sops-encrypted file; nested keys are dot-separated
+//!
+//! Any other value is used as-is. Each reference is looked up once per process.
+
+use crate::error::ExecutorError;
+use std::collections::HashMap;
+use std::process::Command;
+use std::sync::{Mutex, OnceLock};
+
+/// A parsed secret reference.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SecretRef {
+    Keyring {
+        service: String,
+        account: Option<String>,
+    },
+    Vault {
+        path: String,
+        field: String,
+    },
+    Sops {
+        file: String,
+        key: String,
+    },
+}
+
+impl SecretRef {
+    /// Parse a value as a secret reference. `Ok(None)` means a plain value.
+    pub fn parse(value: &str) -> Result<Option<Self>, String> {
+        let Some((scheme, rest)) = value.split_once(':') else {
+            return Ok(None);
+        };
+        let secret = match scheme {
+            "keyring" => match rest.split_once('/') {
+                Some((service, account)) => Self::Keyring {
+                    service: service.to_string(),
+                    account: Some(account.to_string()),
+                },
+                None => Self::Keyring {
+                    service: rest.to_string(),
+                    account: None,
+                },
+            },
+            "vault" => {
+                let (path, field) = split_key(value, rest)?;
+                Self::Vault { path, field }
+            }
+            "sops" => {
+                let (file, key) = split_key(value, rest)?;
+                Self::Sops { file, key }
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(secret))
+    }
+
+    /// Look the secret up in its backend.
+    fn fetch(&self) -> Result<String, String> {
+        let mut command = match self {
+            Self::Keyring { service, account } if cfg!(target_os = "macos") => {
+                let mut command = Command::new("security");
+                command.args(["find-generic-password", "-w", "-s", service]);
+                if let Some(account) = account {
+                    command.args(["-a", account]);
+                }
+                command
+            }
+            Self::Keyring { service, account } => {
+                let mut command = Command::new("secret-tool");
+                command.args(["lookup", "service", service]);
+                if let Some(account) = account {
+                    command.args(["account", account]);
+                }
+                command
+            }
+            Self::Vault { path, field } => {
+                let mut command = Command::new("vault");
+                command.args(["kv", "get", &format!("-field={}", field), path]);
+                command
+            }
+            Self::Sops { file, key } => {
+                let extract: String = key.split('.').map(|k| format!("[\"{}\"]", k)).collect();
+                let mut command = Command::new("sops");
+                command.args(["--decrypt", "--extract", &extract, file]);
+                command
+            }
+        };
+
+        let program = command.get_program().to_string_lossy().into_owned();
+        let output = command
+            .output()
+            .map_err(|e| format!("failed to run {}: {}", program, e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "{} failed: {}",
+                program,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        let value = String::from_utf8_lossy(&output.stdout)
+            .trim_end_matches(['\r', '\n'])
+            .to_string();
+        if value.is_empty() {
+            return Err(format!("{} returned an empty secret", program));
+        }
+        Ok(value)
+    }
+}
+
+fn split_key(value: &str, rest: &str) -> Result<(String, String), String> {
+    match rest.split_once('#') {
+        Some((path, key)) if !path.is_empty() && !key.is_empty() => {
+            Ok((path.to_string(), key.to_string()))
+        }
+        _ => Err(format!("'{}' must look like <path>#<key>", value)),
+    }
+}
+
+fn cache() -> &'static Mutex<HashMap<String, String>> {
+    static CACHE: OnceLock<Mutex<HashMap<String, String>>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// A value with any secret reference resolved.
+pub fn resolve(value: &str) -> Result<String, ExecutorError> {
+    lookup(value).map_err(ExecutorError::Secret)
+}
+
+pub(crate) fn lookup(value: &str) -> Result<String, String> {
+    let Some(secret) = SecretRef::parse(value)? else {
+        return Ok(value.to_string());
+    };
+    if let Some(cached) = cache().lock().unwrap_or_else(|e| e.into_inner()).get(value) {
+        return Ok(cached.clone());
+    }
+    let resolved = secret.fetch().map_err(|e| format!("{}: {}", value, e))?;
+    cache()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(value.to_string(), resolved.clone());
+    Ok(resolved)
+}
+
+/// An executor `env` map with every secret reference resolved.
+pub fn resolve_env(
+    env: &HashMap<String, String>,
+) -> Result<HashMap<String, String>, ExecutorError> {
+    env.iter()
+        .map(|(key, value)| {
+            lookup(value)
+                .map(|value| (key.clone(), value))
+                .map_err(|e| ExecutorError::Secret(format!("env {}: {}", key, e)))
+        })
+        .collect()
+}
@@ -0,0 +1,113 @@
+use crate::task::{ServiceSpec, TaskId, TaskPayload, TaskRequest};
+use serde::{Deserialize, Serialize};
+
+/// A task described in a file (batch or pipeline) rather than on the command line.
+/// Exactly one of `prompt` (Claude Code) or `command` (shell) must be set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskSpec {
+    pub executor: String,
+    #[serde(default)]
+    pub prompt: Option<String>,
+    #[serde(default)]
+    pub command: Option<String>,
+    #[serde(default)]
+    pub workspace: Option<String>,
+    #[serde(default)]
+    pub max_turns: Option<u32>,
+    #[serde(default, alias = "tools")]
+    pub allowed_tools: Vec<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Sidecar containers for the task (container executors only).
+    #[serde(default)]
+    pub services: Vec<ServiceSpec>,
+    /// Nix shell to run the task in, overriding the executor's.
+    #[serde(default)]
+    pub nix_shell: Option<String>,
+}
+
+impl TaskSpec {
+    /// Build the payload for this spec.
+    pub fn payload(&self) -> anyhow::Result<TaskPayload> {
+        match (&self.prompt, &self.command) {
+            (Some(prompt), None) => Ok(TaskPayload::ClaudeCode {
+                prompt: prompt.clone(),
+                max_turns: self.max_turns,
+                allowed_tools: self.allowed_tools.clone(),
+            }),
+            (None, Some(command)) => Ok(TaskPayload::ShellCommand {
+                command: command.clone(),
+            }),
+            _ => anyhow::bail!("exactly one of 'prompt' or 'command' must be set"),
+        }
+    }
+
+    /// Build a task request for this spec.
+    pub fn to_request(&self, parent_ids: Vec<TaskId>) -> anyhow::Result<TaskRequest> {
+        Ok(TaskRequest {
+            parent_ids,
+            tags: self.tags.clone(),
+            services: self.services.clone(),
+            nix_shell: self.nix_shell.clone(),
+            ..TaskRequest::new(self.payload()?, self.workspace.clone())
+        })
+    }
+}
+
+/// Options a prompt file's YAML front-matter may set. Anything given on the
+/// command line takes precedence.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PromptOptions {
+    #[serde(default)]
+    pub executor: Option<String>,
+    #[serde(default)]
+    pub workspace: Option<String>,
+    #[serde(default)]
+    pub max_turns: Option<u32>,
+    #[serde(default, alias = "tools")]
+    pub allowed_tools: Vec<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub services: Vec<ServiceSpec>,
+    #[serde(default)]
+    pub nix_shell: Option<String>,
+}
+
+/// A Claude Code task kept in a Markdown file (`start --prompt-file`): the
+/// prompt, optionally preceded by `---`-fenced YAML front-matter.
+#[derive(Debug, Clone)]
+pub struct PromptFile {
+    pub options: PromptOptions,
+    pub prompt: String,
+}
+
+impl PromptFile {
+    pub fn parse(text: &str) -> anyhow::Result<Self> {
+        let (options, body) = match split_front_matter(text) {
+            Some((yaml, body)) => (serde_yaml::from_str(yaml)?, body),
+            None => (PromptOptions::default(), text),
+        };
+        let prompt = body.trim().to_string();
+        if prompt.is_empty() {
+            anyhow::bail!("the prompt is empty");
+        }
+        Ok(Self { options, prompt })
+    }
+}
+
+/// Split `---\n<yaml>\n---\n<body>` into its YAML and body.
+fn split_front_matter(text: &str) -> Option<(&str, &str)> {
+    let rest = text
+        .strip_prefix("---\n")
+        .or_else(|| text.strip_prefix("---\r\n"))?;
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            return Some((&rest[..offset], &rest[offset + line.len()..]));
+        }
+        offset += line.len();
+    }
+    None
+}
@@ -0,0 +1,197 @@
+use crate::metadata::{Annotation, TaskMetadata};
+use crate::notify;
+use crate::task::{TaskId, TaskStatus};
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Longest error excerpt kept for recent failures.
+const EXCERPT_LEN: usize = 100;
+
+/// Longest window `parse_window` accepts: a century.
+const MAX_WINDOW_DAYS: i64 = 36_500;
+
+/// Parse a look-back window such as `30s`, `90m`, `24h`, `7d` or `2w`. The
+/// count is unsigned, so a window never reaches into the future, and windows
+/// are capped at `MAX_WINDOW_DAYS` so subtracting one from now can't overflow.
+pub fn parse_window(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let split = s.char_indices().last().map_or(0, |(i, _)| i);
+    let (num, unit) = s.split_at(split);
+    let n: u32 = num
+        .parse()
+        .map_err(|_| format!("invalid window '{}' (expected e.g. 24h, 7d)", s))?;
+    let unit_secs: i64 = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86_400,
+        "w" => 604_800,
+        _ => return Err(format!("invalid window unit in '{}' (use s, m, h, d or w)", s)),
+    };
+    let window = Duration::seconds(i64::from(n) * unit_secs);
+    if window > Duration::days(MAX_WINDOW_DAYS) {
+        return Err(format!("window '{}' is too long (at most {}d)", s, MAX_WINDOW_DAYS));
+    }
+    Ok(window)
+}
+
+/// Run-time percentiles for one executor, in seconds.
+#[derive(Debug, Clone, Serialize)]
+pub struct ExecutorDurations {
+    pub executor: String,
+    pub tasks: usize,
+    pub p50_secs: i64,
+    pub p90_secs: i64,
+    pub p99_secs: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FailureRate {
+    pub task_type: String,
+    pub tasks: usize,
+    pub failed: usize,
+    pub rate: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RecentFailure {
+    pub task_id: TaskId,
+    pub executor: String,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub error: String,
+}
+
+/// A note left on a finished task (see `annotate`).
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskNote {
+    pub task_id: TaskId,
+    pub executor: String,
+    #[serde(flatten)]
+    pub annotation: Annotation,
+}
+
+/// Aggregate statistics over finished tasks.
+#[derive(Debug, Clone, Serialize)]
+pub struct HistoryReport {
+    pub since: DateTime<Utc>,
+    pub total: usize,
+    pub by_status: BTreeMap<String, usize>,
+    pub durations: Vec<ExecutorDurations>,
+    pub failure_rates: Vec<FailureRate>,
+    pub recent_failures: Vec<RecentFailure>,
+    /// Notes on the tasks, newest first.
+    pub notes: Vec<TaskNote>,
+}
+
+impl HistoryReport {
+    /// Summarize tasks that finished at or after `since`. Unfinished tasks are ignored.
+    pub fn build(tasks: &[TaskMetadata], since: DateTime<Utc>, max_failures: usize) -> Self {
+        let mut finished: Vec<&TaskMetadata> = tasks
+            .iter()
+            .filter(|t| t.status.is_terminal())
+            .filter(|t| t.finished_at.unwrap_or(t.updated_at) >= since)
+            .collect();
+        finished.sort_by_key(|t| std::cmp::Reverse(t.finished_at.unwrap_or(t.updated_at)));
+
+        let mut by_status = BTreeMap::new();
+        let mut run_times: BTreeMap<&str, Vec<i64>> = BTreeMap::new();
+        let mut by_type: BTreeMap<&str, (usize, usize)> = BTreeMap::new();
+
+        for task in &finished {
+            *by_status.entry(task.status.to_string()).or_insert(0) += 1;
+            if let Some(d) = task.duration() {
+                run_times
+                    .entry(&task.executor_name)
+                    .or_default()
+                    .push(d.num_seconds());
+            }
+            let entry = by_type.entry(&task.task_type).or_insert((0, 0));
+            entry.0 += 1;
+            if task.status == TaskStatus::Failed {
+                entry.1 += 1;
+            }
+        }
+
+        let durations = run_times
+            .into_iter()
+            .map(|(executor, mut secs)| {
+                secs.sort_unstable();
+                ExecutorDurations {
+                    executor: executor.to_string(),
+                    tasks: secs.len(),
+                    p50_secs: percentile(&secs, 50),
+                    p90_secs: percentile(&secs, 90),
+                    p99_secs: percentile(&secs, 99),
+                }
+            })
+            .collect();
+
+        let failure_rates = by_type
+            .into_iter()
+            .map(|(task_type, (tasks, failed))| FailureRate {
+                task_type: task_type.to_string(),
+                tasks,
+                failed,
+                rate: failed as f64 / tasks as f64,
+            })
+            .collect();
+
+        let recent_failures = finished
+            .iter()
+            .filter(|t| t.status == TaskStatus::Failed)
+            .take(max_failures)
+            .map(|t| RecentFailure {
+                task_id: t.task_id.clone(),
+                executor: t.executor_name.clone(),
+                finished_at: t.finished_at,
+                error: excerpt(t),
+            })
+            .collect();
+
+        let mut notes: Vec<TaskNote> = finished
+            .iter()
+            .flat_map(|t| {
+                t.annotations.iter().map(|annotation| TaskNote {
+                    task_id: t.task_id.clone(),
+                    executor: t.executor_name.clone(),
+                    annotation: annotation.clone(),
+                })
+            })
+            .collect();
+        notes.sort_by_key(|n| std::cmp::Reverse(n.annotation.at));
+
+        Self {
+            since,
+            total: finished.len(),
+            by_status,
+            durations,
+            failure_rates,
+            recent_failures,
+            notes,
+        }
+    }
+}
+
+/// Nearest-rank percentile of a sorted, non-empty slice.
+fn percentile(sorted: &[i64], p: usize) -> i64 {
+    let rank = (p * sorted.len()).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+fn excerpt(meta: &TaskMetadata) -> String {
+    match (&meta.error, meta.exit_code) {
+        (Some(err), _) => notify::excerpt(err, EXCERPT_LEN),
+        (None, Some(code)) => format!("exit code {}", code),
+        (None, None) => "no error recorded".to_string(),
+    }
+}
+
+/// Compact human duration: `45s`, `3m05s`, `2h10m`.
+pub fn format_duration(secs: i64) -> String {
+    match secs {
+        s if s < 60 => format!("{}s", s),
+        s if s < 3600 => format!("{}m{:02}s", s / 60, s % 60),
+        s => format!("{}h{:02}m", s / 3600, (s % 3600) / 60),
+    }
+}
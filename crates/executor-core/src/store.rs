@@ -0,0 +1,192 @@
+use crate::error::ExecutorError;
+use crate::metadata::{metadata_dir, TaskMetadata};
+use crate::task::{TaskId, TaskStatus};
+use chrono::{DateTime, Utc};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Most matches an ambiguity error lists.
+const MAX_LISTED_MATCHES: usize = 5;
+
+/// Filter for `MetadataStore::query`. Unset fields match everything.
+#[derive(Debug, Clone, Default)]
+pub struct MetadataQuery {
+    pub status: Option<TaskStatus>,
+    pub executor: Option<String>,
+    pub group_id: Option<String>,
+    pub schedule_name: Option<String>,
+    /// Only tasks carrying this tag.
+    pub tag: Option<String>,
+    /// Only tasks started at or after this time.
+    pub started_since: Option<DateTime<Utc>>,
+}
+
+impl MetadataQuery {
+    pub fn matches(&self, meta: &TaskMetadata) -> bool {
+        self.status.is_none_or(|s| meta.status == s)
+            && self
+                .executor
+                .as_ref()
+                .is_none_or(|e| meta.executor_name == *e)
+            && self
+                .group_id
+                .as_ref()
+                .is_none_or(|g| meta.group_id.as_ref() == Some(g))
+            && self
+                .schedule_name
+                .as_ref()
+                .is_none_or(|s| meta.schedule_name.as_ref() == Some(s))
+            && self.tag.as_ref().is_none_or(|t| meta.tags.contains(t))
+            && self.started_since.is_none_or(|since| meta.started_at >= since)
+    }
+}
+
+/// Storage backend for task metadata. Executors and the CLI only go through
+/// this trait, so the file store can be swapped for sqlite, redis, or a
+/// remote API without touching executor code.
+pub trait MetadataStore: Send + Sync {
+    /// Fetch a task's metadata, or `None` if the store has no record of it.
+    fn get(&self, task_id: &TaskId) -> Result<Option<TaskMetadata>, ExecutorError>;
+
+    /// Insert or replace a task's metadata.
+    fn put(&self, meta: &TaskMetadata) -> Result<(), ExecutorError>;
+
+    /// All tasks, newest first.
+    fn list(&self) -> Result<Vec<TaskMetadata>, ExecutorError>;
+
+    /// Remove a task's metadata. Deleting an unknown task is not an error.
+    fn delete(&self, task_id: &TaskId) -> Result<(), ExecutorError>;
+
+    /// Tasks matching a filter, newest first. Backends with an index should override this.
+    fn query(&self, query: &MetadataQuery) -> Result<Vec<TaskMetadata>, ExecutorError> {
+        Ok(self
+            .list()?
+            .into_iter()
+            .filter(|m| query.matches(m))
+            .collect())
+    }
+
+    /// Like `get`, but a missing task is `ExecutorError::TaskNotFound`.
+    fn load(&self, task_id: &TaskId) -> Result<TaskMetadata, ExecutorError> {
+        self.get(task_id)?
+            .ok_or_else(|| ExecutorError::TaskNotFound(task_id.to_string()))
+    }
+
+    /// The task a command-line reference points at: its full ID, its name
+    /// (`start --name`), or any prefix of its ID, like docker's (IDs are
+    /// matched case-insensitively). A name or prefix matching several tasks
+    /// is `ExecutorError::AmbiguousTask`.
+    fn resolve(&self, reference: &str) -> Result<TaskId, ExecutorError> {
+        let task_id = TaskId::from_string(reference.to_string());
+        if self.get(&task_id)?.is_some() {
+            return Ok(task_id);
+        }
+
+        let tasks = self.list()?;
+        let mut matches: Vec<&TaskMetadata> = tasks
+            .iter()
+            .filter(|t| t.name.as_deref() == Some(reference))
+            .collect();
+        if matches.is_empty() && !reference.is_empty() {
+            let prefix = reference.to_ascii_lowercase();
+            matches = tasks
+                .iter()
+                .filter(|t| t.task_id.0.starts_with(&prefix))
+                .collect();
+        }
+
+        match matches.as_slice() {
+            [] => Err(ExecutorError::TaskNotFound(reference.to_string())),
+            [task] => Ok(task.task_id.clone()),
+            _ => {
+                let listed: Vec<String> = matches
+                    .iter()
+                    .take(MAX_LISTED_MATCHES)
+                    .map(|t| match t.name {
+                        Some(ref name) => format!("{} ({})", t.task_id, name),
+                        None => t.task_id.to_string(),
+                    })
+                    .collect();
+                let more = matches.len().saturating_sub(MAX_LISTED_MATCHES);
+                Err(ExecutorError::AmbiguousTask(format!(
+                    "'{}' matches {} tasks: {}{}; use the full task ID",
+                    reference,
+                    matches.len(),
+                    listed.join(", "),
+                    if more > 0 { format!(" and {} more", more) } else { String::new() }
+                )))
+            }
+        }
+    }
+}
+
+/// The default store: one `<task-id>.meta.json` file per task in a directory.
+pub struct FileMetadataStore {
+    dir: PathBuf,
+}
+
+impl FileMetadataStore {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    fn path(&self, task_id: &TaskId) -> PathBuf {
+        self.dir.join(format!("{}.meta.json", task_id))
+    }
+}
+
+impl Default for FileMetadataStore {
+    fn default() -> Self {
+        Self::new(metadata_dir())
+    }
+}
+
+impl MetadataStore for FileMetadataStore {
+    fn get(&self, task_id: &TaskId) -> Result<Option<TaskMetadata>, ExecutorError> {
+        let path = self.path(task_id);
+        if path.exists() {
+            Ok(Some(TaskMetadata::read_from_file(&path)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn put(&self, meta: &TaskMetadata) -> Result<(), ExecutorError> {
+        std::fs::create_dir_all(&self.dir)?;
+        meta.write_to_dir(&self.dir)?;
+        Ok(())
+    }
+
+    fn list(&self) -> Result<Vec<TaskMetadata>, ExecutorError> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut results = Vec::new();
+        for entry in std::fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path
+                .file_name()
+                .is_some_and(|n| n.to_string_lossy().ends_with(".meta.json"))
+            {
+                if let Ok(meta) = TaskMetadata::read_from_file(&path) {
+                    results.push(meta);
+                }
+            }
+        }
+        results.sort_by_key(|m| std::cmp::Reverse(m.started_at));
+        Ok(results)
+    }
+
+    fn delete(&self, task_id: &TaskId) -> Result<(), ExecutorError> {
+        let path = self.path(task_id);
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+}
+
+/// The store used when nothing else is configured.
+pub fn default_store() -> Arc<dyn MetadataStore> {
+    Arc::new(FileMetadataStore::default())
+}
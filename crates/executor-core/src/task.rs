@@ -1,7 +1,10 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fmt;
+use std::path::{Component, Path};
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq, Hash)]
 pub struct TaskId(pub String);
 
 impl TaskId {
@@ -12,6 +15,17 @@ impl TaskId {
     pub fn from_string(s: String) -> Self {
         Self(s)
     }
+
+    /// Whether the ID is safe to name files by: a single plain path
+    /// component, as every ID an executor hands out is. IDs read from
+    /// untrusted input must be checked before they reach a path.
+    pub fn is_file_safe(&self) -> bool {
+        let mut components = Path::new(&self.0).components();
+        matches!(
+            (components.next(), components.next()),
+            (Some(Component::Normal(_)), None)
+        ) && !self.0.contains(['/', '\\', '\0'])
+    }
 }
 
 impl fmt::Display for TaskId {
@@ -26,6 +40,28 @@ impl Default for TaskId {
     }
 }
 
+/// Longest name `start --name` accepts.
+const MAX_NAME_LEN: usize = 64;
+
+/// Check a task name: a letter followed by letters, digits, `-`, `_` or `.`,
+/// so it can be typed anywhere a task ID can without quoting.
+pub fn validate_name(name: &str) -> Result<(), String> {
+    let valid = name.len() <= MAX_NAME_LEN
+        && name.starts_with(|c: char| c.is_ascii_alphabetic())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "Invalid task name '{}': use up to {} letters, digits, '-', '_' or '.', \
+             starting with a letter",
+            name, MAX_NAME_LEN
+        ))
+    }
+}
+
 /// Payload type: either a Claude Code prompt or an arbitrary shell command.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -71,9 +107,92 @@ impl TaskPayload {
 pub struct TaskRequest {
     pub payload: TaskPayload,
     pub workspace: Option<String>,
+    /// Name to refer to the task by instead of its ID (`start --name`).
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Tasks that had to complete successfully before this one was launched.
+    #[serde(default)]
+    pub parent_ids: Vec<TaskId>,
+    /// Shared ID for tasks launched together by a fan-out.
+    #[serde(default)]
+    pub group_id: Option<String>,
+    /// Name of the schedule that launched this task, if any.
+    #[serde(default)]
+    pub schedule_name: Option<String>,
+    /// Completion webhook for this task only, overriding the config.
+    #[serde(default)]
+    pub webhook_url: Option<String>,
+    /// Repository to clone into a fresh per-task workspace before starting.
+    #[serde(default)]
+    pub repo: Option<RepoSpec>,
+    /// Run on a fresh `openclaw/<task-id>` branch and capture the diff at the end.
+    #[serde(default)]
+    pub git_branch: bool,
+    /// Free-form labels for finding the task later (`list --tag`).
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Seconds between status polls while waiting on this task, overriding
+    /// the executor's `heartbeat_interval_secs`.
+    #[serde(default)]
+    pub heartbeat_interval_secs: Option<u64>,
+    /// Sidecar containers to run alongside this task, on top of the
+    /// executor's own `services`. Only container executors start them.
+    #[serde(default)]
+    pub services: Vec<ServiceSpec>,
+    /// Nix shell to run the task in, overriding the executor's `nix_shell`.
+    #[serde(default)]
+    pub nix_shell: Option<String>,
+}
+
+/// An auxiliary container (a database, a cache, ...) started before a
+/// container task on a network shared with it, and removed at cleanup.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct ServiceSpec {
+    /// Host name the task reaches the service at.
+    pub name: String,
+    pub image: String,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Arguments replacing the image's default command.
+    #[serde(default)]
+    pub command: Vec<String>,
+}
+
+/// A repository checkout requested with `start --repo`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct RepoSpec {
+    pub url: String,
+    /// Branch or tag to check out; the remote's default branch if unset.
+    #[serde(default)]
+    pub branch: Option<String>,
+    /// Shallow-clone to this many commits.
+    #[serde(default)]
+    pub depth: Option<u32>,
+}
+
+impl TaskRequest {
+    /// A request with no name, dependencies, group, schedule, webhook, repo,
+    /// tags, services or Nix shell attached.
+    pub fn new(payload: TaskPayload, workspace: Option<String>) -> Self {
+        Self {
+            payload,
+            workspace,
+            name: None,
+            parent_ids: Vec::new(),
+            group_id: None,
+            schedule_name: None,
+            webhook_url: None,
+            repo: None,
+            git_branch: false,
+            tags: Vec::new(),
+            heartbeat_interval_secs: None,
+            services: Vec::new(),
+            nix_shell: None,
+        }
+    }
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum TaskStatus {
     Starting,
@@ -90,6 +209,25 @@ impl TaskStatus {
     }
 }
 
+/// The signal that ended a killed task: SIGTERM if it exited within the
+/// executor's `kill_grace_secs`, SIGKILL if it had to be forced.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub enum KillSignal {
+    #[serde(rename = "SIGTERM")]
+    Term,
+    #[serde(rename = "SIGKILL")]
+    Kill,
+}
+
+impl fmt::Display for KillSignal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KillSignal::Term => write!(f, "SIGTERM"),
+            KillSignal::Kill => write!(f, "SIGKILL"),
+        }
+    }
+}
+
 impl fmt::Display for TaskStatus {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -0,0 +1,339 @@
+//! Rate limits on an executor's backend operations: `max_concurrent_ops` and
+//! `min_op_interval_ms`.
+//!
+//! Every executor call (a status check, a log fetch, an `exec`...) counts as
+//! one operation for as long as it runs. Limits hold across every
+//! `openclaw-agent` process on this machine, so a dashboard polling `status`
+//! in a loop queues behind the daemon instead of piling onto a small host.
+//! They are kept with lock files under ~/.local/share/openclaw/throttle/<executor>/:
+//! `slot-<n>` for each concurrent operation allowed, and `interval` holding
+//! the start time of the last one.
+
+use crate::config::ExecutorConfig;
+use crate::error::ExecutorError;
+use crate::executor::{
+    with_cancel, AgentInfo, CleanupOutcome, Executor, FileRoot, RemoteTask, StartPlan,
+    TaskChanges,
+};
+use crate::host::HostStats;
+use crate::log_filter::LogFilter;
+use crate::metadata::TaskMetadata;
+use crate::progress::TaskProgress;
+use crate::provision::ProvisionReport;
+use crate::task::{TaskId, TaskRequest};
+use crate::usage::ResourceUsage;
+use chrono::Utc;
+use std::fs::{File, TryLockError};
+use std::io::{Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+/// How often a waiting operation retries a busy lock.
+const LOCK_RETRY: Duration = Duration::from_millis(20);
+
+pub fn throttle_dir() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("/tmp"))
+        .join("openclaw")
+        .join("throttle")
+}
+
+/// Wraps an executor so its operations respect the configured limits.
+pub struct ThrottledExecutor {
+    inner: Box<dyn Executor>,
+    max_concurrent: Option<usize>,
+    min_interval: Option<Duration>,
+    dir: PathBuf,
+}
+
+impl ThrottledExecutor {
+    /// `inner` as is if `config` sets no limits.
+    pub fn wrap(inner: Box<dyn Executor>, config: &ExecutorConfig) -> Box<dyn Executor> {
+        let max_concurrent = config.max_concurrent_ops.filter(|&n| n > 0);
+        let min_interval = config
+            .min_op_interval_ms
+            .filter(|&ms| ms > 0)
+            .map(Duration::from_millis);
+        if max_concurrent.is_none() && min_interval.is_none() {
+            return inner;
+        }
+        Box::new(Self {
+            inner,
+            max_concurrent,
+            min_interval,
+            dir: throttle_dir().join(config.file_name()),
+        })
+    }
+
+    /// Wait for a free slot, then for the interval since the last operation
+    /// started. The slot is held until the returned permit is dropped.
+    async fn acquire(&self, cancel: &CancellationToken) -> Result<Option<File>, ExecutorError> {
+        std::fs::create_dir_all(&self.dir)?;
+        let slot = match self.max_concurrent {
+            Some(slots) => Some(with_cancel(cancel, self.take_slot(slots)).await?),
+            None => None,
+        };
+        if let Some(interval) = self.min_interval {
+            with_cancel(cancel, self.wait_interval(interval)).await?;
+        }
+        Ok(slot)
+    }
+
+    async fn take_slot(&self, slots: usize) -> Result<File, ExecutorError> {
+        loop {
+            for n in 0..slots {
+                let file = open(&self.dir.join(format!("slot-{}", n)))?;
+                match file.try_lock() {
+                    Ok(()) => return Ok(file),
+                    Err(TryLockError::WouldBlock) => {}
+                    Err(TryLockError::Error(e)) => return Err(e.into()),
+                }
+            }
+            tokio::time::sleep(LOCK_RETRY).await;
+        }
+    }
+
+    /// Hold the `interval` lock while sleeping, so operations start one at a
+    /// time and at least `interval` apart.
+    async fn wait_interval(&self, interval: Duration) -> Result<(), ExecutorError> {
+        let mut file = open(&self.dir.join("interval"))?;
+        loop {
+            match file.try_lock() {
+                Ok(()) => break,
+                Err(TryLockError::WouldBlock) => tokio::time::sleep(LOCK_RETRY).await,
+                Err(TryLockError::Error(e)) => return Err(e.into()),
+            }
+        }
+        let mut last = String::new();
+        file.read_to_string(&mut last)?;
+        if let Ok(last) = last.trim().parse::<i64>() {
+            let since = (Utc::now().timestamp_millis() - last).max(0) as u64;
+            if let Some(wait) = interval.checked_sub(Duration::from_millis(since)) {
+                tokio::time::sleep(wait).await;
+            }
+        }
+        file.set_len(0)?;
+        file.rewind()?;
+        write!(file, "{}", Utc::now().timestamp_millis())?;
+        Ok(())
+    }
+}
+
+fn open(path: &Path) -> Result<File, ExecutorError> {
+    Ok(std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)?)
+}
+
+#[async_trait::async_trait]
+impl Executor for ThrottledExecutor {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn executor_type(&self) -> &str {
+        self.inner.executor_type()
+    }
+
+    async fn start(
+        &self,
+        request: TaskRequest,
+        cancel: &CancellationToken,
+    ) -> Result<TaskMetadata, ExecutorError> {
+        let _permit = self.acquire(cancel).await?;
+        self.inner.start(request, cancel).await
+    }
+
+    fn plan_start(&self, request: TaskRequest) -> Result<StartPlan, ExecutorError> {
+        self.inner.plan_start(request)
+    }
+
+    async fn status(
+        &self,
+        task_id: &TaskId,
+        cancel: &CancellationToken,
+    ) -> Result<TaskMetadata, ExecutorError> {
+        let _permit = self.acquire(cancel).await?;
+        self.inner.status(task_id, cancel).await
+    }
+
+    async fn status_many(
+        &self,
+        task_ids: &[TaskId],
+        cancel: &CancellationToken,
+    ) -> Result<Vec<TaskMetadata>, ExecutorError> {
+        let _permit = self.acquire(cancel).await?;
+        self.inner.status_many(task_ids, cancel).await
+    }
+
+    async fn logs(
+        &self,
+        task_id: &TaskId,
+        lines: usize,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<String>, ExecutorError> {
+        let _permit = self.acquire(cancel).await?;
+        self.inner.logs(task_id, lines, cancel).await
+    }
+
+    async fn filtered_logs(
+        &self,
+        task_id: &TaskId,
+        lines: usize,
+        filter: &LogFilter,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<String>, ExecutorError> {
+        let _permit = self.acquire(cancel).await?;
+        self.inner.filtered_logs(task_id, lines, filter, cancel).await
+    }
+
+    async fn kill(
+        &self,
+        task_id: &TaskId,
+        cancel: &CancellationToken,
+    ) -> Result<(), ExecutorError> {
+        let _permit = self.acquire(cancel).await?;
+        self.inner.kill(task_id, cancel).await
+    }
+
+    async fn cleanup(
+        &self,
+        task_id: &TaskId,
+        cancel: &CancellationToken,
+    ) -> Result<(), ExecutorError> {
+        let _permit = self.acquire(cancel).await?;
+        self.inner.cleanup(task_id, cancel).await
+    }
+
+    async fn cleanup_many(
+        &self,
+        task_ids: &[TaskId],
+        cancel: &CancellationToken,
+    ) -> Result<Vec<CleanupOutcome>, ExecutorError> {
+        let _permit = self.acquire(cancel).await?;
+        self.inner.cleanup_many(task_ids, cancel).await
+    }
+
+    async fn list_remote(
+        &self,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<RemoteTask>, ExecutorError> {
+        let _permit = self.acquire(cancel).await?;
+        self.inner.list_remote(cancel).await
+    }
+
+    async fn exec(
+        &self,
+        task_id: &TaskId,
+        command: &str,
+        cancel: &CancellationToken,
+    ) -> Result<i32, ExecutorError> {
+        let _permit = self.acquire(cancel).await?;
+        self.inner.exec(task_id, command, cancel).await
+    }
+
+    async fn exec_output(
+        &self,
+        task_id: &TaskId,
+        command: &str,
+        cancel: &CancellationToken,
+    ) -> Result<(i32, String), ExecutorError> {
+        let _permit = self.acquire(cancel).await?;
+        self.inner.exec_output(task_id, command, cancel).await
+    }
+
+    async fn host_stats(
+        &self,
+        dir: Option<&str>,
+        cancel: &CancellationToken,
+    ) -> Result<Option<HostStats>, ExecutorError> {
+        let _permit = self.acquire(cancel).await?;
+        self.inner.host_stats(dir, cancel).await
+    }
+
+    async fn find_claude(
+        &self,
+        cancel: &CancellationToken,
+    ) -> Result<Option<String>, ExecutorError> {
+        let _permit = self.acquire(cancel).await?;
+        self.inner.find_claude(cancel).await
+    }
+
+    async fn provision(
+        &self,
+        install: bool,
+        cancel: &CancellationToken,
+    ) -> Result<ProvisionReport, ExecutorError> {
+        let _permit = self.acquire(cancel).await?;
+        self.inner.provision(install, cancel).await
+    }
+
+    async fn upgrade_agent(&self, cancel: &CancellationToken) -> Result<AgentInfo, ExecutorError> {
+        let _permit = self.acquire(cancel).await?;
+        self.inner.upgrade_agent(cancel).await
+    }
+
+    async fn usage(
+        &self,
+        task_id: &TaskId,
+        cancel: &CancellationToken,
+    ) -> Result<Option<ResourceUsage>, ExecutorError> {
+        let _permit = self.acquire(cancel).await?;
+        self.inner.usage(task_id, cancel).await
+    }
+
+    async fn progress(
+        &self,
+        task_id: &TaskId,
+        cancel: &CancellationToken,
+    ) -> Result<Option<TaskProgress>, ExecutorError> {
+        let _permit = self.acquire(cancel).await?;
+        self.inner.progress(task_id, cancel).await
+    }
+
+    async fn log_size(
+        &self,
+        task_id: &TaskId,
+        cancel: &CancellationToken,
+    ) -> Result<u64, ExecutorError> {
+        let _permit = self.acquire(cancel).await?;
+        self.inner.log_size(task_id, cancel).await
+    }
+
+    async fn changes(
+        &self,
+        task_id: &TaskId,
+        cancel: &CancellationToken,
+    ) -> Result<TaskChanges, ExecutorError> {
+        let _permit = self.acquire(cancel).await?;
+        self.inner.changes(task_id, cancel).await
+    }
+
+    async fn download(
+        &self,
+        task_id: &TaskId,
+        path: &str,
+        root: FileRoot,
+        dest: &Path,
+        cancel: &CancellationToken,
+    ) -> Result<(), ExecutorError> {
+        let _permit = self.acquire(cancel).await?;
+        self.inner.download(task_id, path, root, dest, cancel).await
+    }
+
+    async fn upload(
+        &self,
+        task_id: &TaskId,
+        source: &Path,
+        path: &str,
+        root: FileRoot,
+        cancel: &CancellationToken,
+    ) -> Result<(), ExecutorError> {
+        let _permit = self.acquire(cancel).await?;
+        self.inner.upload(task_id, source, path, root, cancel).await
+    }
+}
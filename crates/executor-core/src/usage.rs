@@ -0,0 +1,105 @@
+//! CPU time and peak memory of a task, for capacity planning across hosts.
+//!
+//! A finished process tree can't be inspected any more, so usage is sampled
+//! on every heartbeat and folded into the task's metadata: CPU time keeps its
+//! latest value and peak memory its highest. What is stored when the task
+//! finishes is therefore as of its last heartbeat.
+
+use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// A `sh` script for containers, printing `usage <cpu secs> <peak KiB>` from
+/// the container's own cgroup (v2, falling back to v1). Peak memory is the
+/// cgroup's high-water mark, or its current usage on kernels that don't
+/// track one.
+pub const CGROUP_SCRIPT: &str = "\
+if [ -r /sys/fs/cgroup/cpu.stat ]; then
+  cpu=$(awk '/^usage_usec/{printf \"%.2f\", $2 / 1000000}' /sys/fs/cgroup/cpu.stat)
+  mem=$(cat /sys/fs/cgroup/memory.peak 2>/dev/null || cat /sys/fs/cgroup/memory.current 2>/dev/null)
+else
+  cpu=$(awk '{printf \"%.2f\", $1 / 1000000000}' /sys/fs/cgroup/cpuacct/cpuacct.usage 2>/dev/null)
+  mem=$(cat /sys/fs/cgroup/memory/memory.max_usage_in_bytes 2>/dev/null)
+fi
+[ -n \"$cpu\" ] && [ -n \"$mem\" ] && echo \"usage $cpu $((mem / 1024))\"
+true";
+
+/// A `sh` script printing `usage <cpu secs> <rss KiB>` for `pid` and all its
+/// descendants, read from `ps` and `/proc/<pid>/stat`. CPU time includes
+/// children the tree has already reaped. Prints nothing once `pid` is gone.
+pub fn tree_script(pid: u32) -> String {
+    format!(
+        "\
+hz=$(getconf CLK_TCK 2>/dev/null || echo 100)
+ps -eo pid=,ppid=,rss= 2>/dev/null | awk -v root={pid} '
+  {{ parent[$1] = $2; rss[$1] = $3 }}
+  END {{
+    if (!(root in parent)) exit
+    tree[root] = 1
+    do {{ n = 0; for (p in parent) if (!(p in tree) && (parent[p] in tree)) {{ tree[p] = 1; n++ }} }} while (n)
+    for (p in tree) print p, rss[p]
+  }}' | while read pid rss; do
+  ticks=$(sed 's/.*) //' /proc/$pid/stat 2>/dev/null | awk '{{print $12 + $13 + $14 + $15}}')
+  echo \"${{ticks:-0}} $rss\"
+done | awk -v hz=$hz '{{ t += $1; r += $2; n++ }} END {{ if (n) printf \"usage %.2f %d\\n\", t / hz, r }}'
+true"
+    )
+}
+
+/// Resource usage of a task's processes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ResourceUsage {
+    /// User plus system CPU time.
+    pub cpu_secs: f64,
+    /// Highest resident memory seen (for containers, the cgroup's peak).
+    pub peak_rss_kb: u64,
+    pub sampled_at: DateTime<Utc>,
+}
+
+impl ResourceUsage {
+    /// Fold a new sample into the usage recorded so far.
+    pub fn merge(previous: Option<&ResourceUsage>, sample: ResourceUsage) -> ResourceUsage {
+        match previous {
+            Some(prev) => ResourceUsage {
+                cpu_secs: sample.cpu_secs.max(prev.cpu_secs),
+                peak_rss_kb: sample.peak_rss_kb.max(prev.peak_rss_kb),
+                sampled_at: sample.sampled_at,
+            },
+            None => sample,
+        }
+    }
+
+    /// e.g. `1m32.4s CPU, 812 MiB peak memory`.
+    pub fn summary(&self) -> String {
+        format!(
+            "{} CPU, {} peak memory",
+            cpu_time(self.cpu_secs),
+            crate::host::kib(self.peak_rss_kb)
+        )
+    }
+}
+
+/// The usage printed by [`tree_script`] or [`CGROUP_SCRIPT`], or `None` if
+/// it printed none (e.g. the process has already exited).
+pub fn parse_usage(output: &str) -> Option<ResourceUsage> {
+    output.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        match fields.as_slice() {
+            ["usage", cpu, rss] => Some(ResourceUsage {
+                cpu_secs: cpu.parse().ok()?,
+                peak_rss_kb: rss.parse().ok()?,
+                sampled_at: Utc::now(),
+            }),
+            _ => None,
+        }
+    })
+}
+
+/// CPU seconds as `4.2s`, `3m05.0s` or `2h10m`.
+fn cpu_time(secs: f64) -> String {
+    match secs {
+        s if s < 60.0 => format!("{:.1}s", s),
+        s if s < 3600.0 => format!("{}m{:04.1}s", (s / 60.0) as u64, s % 60.0),
+        s => crate::stats::format_duration(s as i64),
+    }
+}
@@ -0,0 +1,548 @@
+//! `config --validate`: checks a config file and its includes beyond what
+//! loading does. Loading ignores unknown keys and leaves missing optional
+//! fields unset; validation reports both, along with fields each executor type
+//! requires, schedules that can't run, and local files that don't exist.
+
+use crate::config::{
+    load_merged, parse_file, Config, ContainerRuntime, ExecutorType, FixtureMode, GpuSpec,
+    HostPlatform, NAMED_LISTS,
+};
+use crate::cron::CronExpr;
+use crate::registry;
+use serde_yaml::{Mapping, Value};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Characters a remote shell gives a meaning to, not allowed in an ssh
+/// executor's `task_root`.
+const SHELL_SPECIAL: &str = "'\"`$\\;&|<>()[]{}*?!#";
+
+/// One thing wrong with a config, located as precisely as possible.
+#[derive(Debug, Clone)]
+pub struct Problem {
+    pub file: PathBuf,
+    /// 1-based line, when it could be found.
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}: {}", self.file.display(), line, self.message),
+            None => write!(f, "{}: {}", self.file.display(), self.message),
+        }
+    }
+}
+
+/// A config file as read from disk, for locating problems in it.
+struct Source {
+    path: PathBuf,
+    text: String,
+    value: Value,
+}
+
+/// Check the config at `path`, with `profile` applied if given. An empty
+/// result means it's valid.
+pub fn validate(path: &Path, profile: Option<&str>) -> Vec<Problem> {
+    let mut paths = Vec::new();
+    let merged = match load_merged(path, profile, &mut paths) {
+        Ok(merged) => merged,
+        Err(e) => return vec![parse_problem(path, &format!("{:#}", e))],
+    };
+    let file = paths.last().cloned().unwrap_or_else(|| path.to_path_buf());
+    let sources: Vec<Source> = paths
+        .into_iter()
+        .filter_map(|path| {
+            let text = std::fs::read_to_string(&path).ok()?;
+            let value = parse_file(&path, &text).ok()?;
+            Some(Source { path, text, value })
+        })
+        .collect();
+
+    let mut config: Config = match serde_yaml::from_value(Value::Mapping(merged)) {
+        Ok(config) => config,
+        Err(e) => return vec![parse_problem(path, &e.to_string())],
+    };
+    config.resolve_paths(&file);
+
+    let mut problems = Vec::new();
+    // Serde drops keys it doesn't know, so anything missing from the parsed
+    // config written back out is a typo or an unsupported setting
+    if let Ok(Value::Mapping(reference)) = serde_yaml::to_value(&config) {
+        for source in &sources {
+            if let Value::Mapping(ref layer) = source.value {
+                unknown_keys(source, layer, &reference, &mut Vec::new(), 0, &mut problems);
+            }
+            // The profile is checked like the top level it's merged into
+            if let Some(Value::Mapping(layer)) = source.profile_layer(profile) {
+                let mut path = profile_path(profile);
+                let top = path.len();
+                unknown_keys(source, layer, &reference, &mut path, top, &mut problems);
+            }
+        }
+    }
+    check_executors(&config, &sources, profile, &mut problems);
+    check_schedules(&config, &sources, profile, &mut problems);
+    problems
+}
+
+/// Where the active profile sits in a config file.
+fn profile_path(profile: Option<&str>) -> Vec<Part> {
+    match profile {
+        Some(name) => vec![Part::Key("profiles".into()), Part::Key(name.into())],
+        None => Vec::new(),
+    }
+}
+
+/// A load or parse error, with the line the parser reports if it has one.
+fn parse_problem(path: &Path, message: &str) -> Problem {
+    let line = message
+        .split("line ")
+        .nth(1)
+        .and_then(|rest| rest.split(|c: char| !c.is_ascii_digit()).next())
+        .and_then(|n| n.parse().ok());
+    Problem {
+        file: path.to_path_buf(),
+        line,
+        message: message.to_string(),
+    }
+}
+
+/// One step into a config: a mapping key, or a named list entry.
+#[derive(Debug, Clone)]
+enum Part {
+    Key(String),
+    Named(String),
+}
+
+fn display_path(path: &[Part]) -> String {
+    let mut out = String::new();
+    for part in path {
+        match part {
+            Part::Key(key) if out.is_empty() => out.push_str(key),
+            Part::Key(key) => out.push_str(&format!(".{}", key)),
+            Part::Named(name) => out.push_str(&format!("[{}]", name)),
+        }
+    }
+    out
+}
+
+/// Report keys in `layer` that `reference` (the parsed config) doesn't have.
+/// `top` is the length of `path` at the config's top level.
+fn unknown_keys(
+    source: &Source,
+    layer: &Mapping,
+    reference: &Mapping,
+    path: &mut Vec<Part>,
+    top: usize,
+    problems: &mut Vec<Problem>,
+) {
+    for (key, value) in layer {
+        let Some(key) = key.as_str() else {
+            continue;
+        };
+        // A null leaves an optional field unset, which isn't written back out
+        if (path.len() == top && key == "include") || value.is_null() {
+            continue;
+        }
+        // `tools` is accepted for `allowed_tools`
+        let known = if key == "tools" { "allowed_tools" } else { key };
+        path.push(Part::Key(key.to_string()));
+        match reference.get(known) {
+            None => {
+                problems.push(source.problem(path, format!("unknown key '{}'", display_path(path))))
+            }
+            Some(Value::Mapping(reference)) => {
+                if let Value::Mapping(layer) = value {
+                    unknown_keys(source, layer, reference, path, top, problems);
+                }
+            }
+            Some(Value::Sequence(reference))
+                if path.len() == top + 1 && NAMED_LISTS.contains(&key) =>
+            {
+                if let Value::Sequence(entries) = value {
+                    named_unknown_keys(source, entries, reference, path, problems);
+                }
+            }
+            Some(_) => {}
+        }
+        path.pop();
+    }
+}
+
+/// Match `executors`/`schedules` entries to the parsed ones by name.
+fn named_unknown_keys(
+    source: &Source,
+    entries: &[Value],
+    reference: &[Value],
+    path: &mut Vec<Part>,
+    problems: &mut Vec<Problem>,
+) {
+    for entry in entries {
+        let Some(name) = entry.get("name").and_then(Value::as_str) else {
+            continue;
+        };
+        let parsed = reference
+            .iter()
+            .find(|r| r.get("name").and_then(Value::as_str) == Some(name));
+        if let (Value::Mapping(entry), Some(Value::Mapping(parsed))) = (entry, parsed) {
+            path.push(Part::Named(name.to_string()));
+            let top = path.len();
+            unknown_keys(source, entry, parsed, path, top, problems);
+            path.pop();
+        }
+    }
+}
+
+impl Source {
+    fn problem(&self, path: &[Part], message: String) -> Problem {
+        Problem {
+            file: self.path.clone(),
+            line: locate(&self.text, path),
+            message,
+        }
+    }
+
+    /// This file's section for the active profile, if it has one.
+    fn profile_layer(&self, profile: Option<&str>) -> Option<&Value> {
+        self.value.get("profiles")?.get(profile?)
+    }
+
+    /// Where this file defines the entry named `name` in `list`, preferring
+    /// the active profile's section, which overrides the top level.
+    fn entry_path(&self, list: &str, name: &str, profile: Option<&str>) -> Option<Vec<Part>> {
+        let defines = |layer: &Value| {
+            layer
+                .get(list)
+                .and_then(Value::as_sequence)
+                .is_some_and(|entries| {
+                    entries
+                        .iter()
+                        .any(|e| e.get("name").and_then(Value::as_str) == Some(name))
+                })
+        };
+        let mut path = if self.profile_layer(profile).is_some_and(defines) {
+            profile_path(profile)
+        } else if defines(&self.value) {
+            Vec::new()
+        } else {
+            return None;
+        };
+        path.push(Part::Key(list.to_string()));
+        path.push(Part::Named(name.to_string()));
+        Some(path)
+    }
+}
+
+/// Best-effort 1-based line of `path` in a config file, by scanning for each
+/// key (or `name` of a list entry) in turn. Understands block-style YAML, TOML
+/// and pretty-printed JSON; a step that can't be found is skipped.
+fn locate(text: &str, path: &[Part]) -> Option<usize> {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut from = 0;
+    let mut found = None;
+    for part in path {
+        let line = match part {
+            Part::Key(key) => (from..lines.len())
+                .find(|&i| key_value(lines[i], key).is_some() || is_table(lines[i], key)),
+            Part::Named(name) => (from..lines.len())
+                .find(|&i| key_value(lines[i], "name").is_some_and(|v| unquote(v) == name)),
+        };
+        let Some(line) = line else {
+            continue;
+        };
+        found = Some(line);
+        from = match part {
+            // A list entry's keys may come before its `name`
+            Part::Named(_) => (0..=line)
+                .rev()
+                .find(|&i| {
+                    let line = lines[i].trim_start();
+                    line.starts_with("- ") || line.starts_with("[[") || line.starts_with('{')
+                })
+                .unwrap_or(line),
+            Part::Key(_) => line,
+        };
+    }
+    found.map(|line| line + 1)
+}
+
+/// The value of `key` if `line` sets it: `key: value` (YAML), `key = value`
+/// (TOML) or `"key": value` (YAML or JSON).
+fn key_value<'a>(line: &'a str, key: &str) -> Option<&'a str> {
+    let line = line.trim_start();
+    let line = line.strip_prefix("- ").unwrap_or(line).trim_start();
+    let rest = line
+        .strip_prefix(&format!("\"{}\"", key))
+        .or_else(|| line.strip_prefix(key))?
+        .trim_start();
+    rest.strip_prefix(':')
+        .or_else(|| rest.strip_prefix('='))
+        .map(str::trim)
+}
+
+/// Whether `line` is a TOML table header (`[key]`, `[[key]]`, `[a.key]`).
+fn is_table(line: &str, key: &str) -> bool {
+    let line = line.trim();
+    line.starts_with('[')
+        && line
+            .trim_matches(|c| c == '[' || c == ']')
+            .rsplit('.')
+            .next()
+            .is_some_and(|last| last.trim() == key)
+}
+
+fn unquote(value: &str) -> &str {
+    value
+        .trim_end_matches(',')
+        .trim_matches(|c| c == '"' || c == '\'')
+}
+
+/// The last file defining an entry, and where, for reporting problems with it.
+fn source_of<'a>(
+    sources: &'a [Source],
+    list: &str,
+    name: &str,
+    profile: Option<&str>,
+) -> Option<(&'a Source, Vec<Part>)> {
+    sources
+        .iter()
+        .rev()
+        .find_map(|s| Some((s, s.entry_path(list, name, profile)?)))
+}
+
+fn check_executors(
+    config: &Config,
+    sources: &[Source],
+    profile: Option<&str>,
+    problems: &mut Vec<Problem>,
+) {
+    let types = registry::registered_types();
+    for executor in &config.executors {
+        let Some((source, entry)) = source_of(sources, "executors", &executor.name, profile) else {
+            continue;
+        };
+        let mut report = |message: String| problems.push(source.problem(&entry, message));
+        let label = format!("executors[{}]", executor.name);
+
+        let required: &[(&str, bool)] = match executor.executor_type {
+            ExecutorType::Ssh => &[
+                ("host", executor.host.is_some()),
+                ("user", executor.user.is_some()),
+            ],
+            ExecutorType::Container => &[(
+                "image",
+                executor.image.is_some() || executor.image_build.is_some(),
+            )],
+            ExecutorType::External => &[("command", executor.command.is_some())],
+            ExecutorType::Local | ExecutorType::Mock | ExecutorType::Custom(_) => &[],
+        };
+        for (field, set) in required {
+            if !set {
+                report(format!(
+                    "{}: type {} requires '{}'",
+                    label, executor.executor_type, field
+                ));
+            }
+        }
+
+        let type_name = executor.executor_type.to_string();
+        if !types.is_empty() && !types.contains(&type_name) {
+            report(format!(
+                "{}: unknown type '{}' (registered: {})",
+                label,
+                type_name,
+                types.join(", ")
+            ));
+        }
+
+        if executor.cpus.is_some_and(|cpus| cpus <= 0.0) {
+            report(format!("{}: cpus must be greater than 0", label));
+        }
+        if let Some(ref memory) = executor.memory {
+            if !is_memory_size(memory) {
+                report(format!(
+                    "{}: invalid memory '{}' (expected e.g. 512m or 4g)",
+                    label, memory
+                ));
+            }
+        }
+
+        match executor.gpus {
+            Some(GpuSpec::All(ref value)) if value != "all" => report(format!(
+                "{}: invalid gpus '{}' (expected 'all' or a list of device IDs)",
+                label, value
+            )),
+            Some(GpuSpec::Devices(ref ids)) if ids.is_empty() => {
+                report(format!("{}: gpus lists no devices", label))
+            }
+            _ => {}
+        }
+
+        if let Some(ref build) = executor.image_build {
+            if !Path::new(&build.context).is_dir() {
+                report(format!(
+                    "{}: image_build context {} is not a directory",
+                    label, build.context
+                ));
+            }
+        }
+
+        if let Some(ref registry) = executor.registry {
+            if registry.password.is_none() && registry.password_env.is_none() {
+                report(format!(
+                    "{}: registry requires 'password' or 'password_env'",
+                    label
+                ));
+            }
+        }
+
+        for host in &executor.extra_hosts {
+            if !host.contains(':') {
+                report(format!(
+                    "{}: invalid extra_hosts entry '{}' (expected host:ip)",
+                    label, host
+                ));
+            }
+        }
+
+        if executor.systemd && executor.runtime != Some(ContainerRuntime::Podman) {
+            report(format!("{}: systemd requires runtime 'podman'", label));
+        }
+
+        if !executor.services.is_empty() {
+            if executor.executor_type != ExecutorType::Container {
+                report(format!("{}: services are only run by container executors", label));
+            } else if executor.network.as_deref() == Some("host") {
+                report(format!("{}: services can't be used with network 'host'", label));
+            }
+        }
+        let mut service_names = Vec::new();
+        for service in &executor.services {
+            if !is_service_name(&service.name) {
+                report(format!(
+                    "{}: invalid service name '{}' (letters, digits, '-' and '_' only)",
+                    label, service.name
+                ));
+            } else if service_names.contains(&&service.name) {
+                report(format!("{}: duplicate service '{}'", label, service.name));
+            }
+            service_names.push(&service.name);
+        }
+
+        if let Some(ref agent_binary) = executor.agent_binary {
+            if executor.executor_type != ExecutorType::Ssh {
+                report(format!("{}: agent_binary is only used by ssh executors", label));
+            } else if !Path::new(agent_binary).is_file() {
+                report(format!("{}: agent_binary {} does not exist", label, agent_binary));
+            } else if executor.platform == Some(HostPlatform::Windows) {
+                report(format!("{}: agent_binary can't be used with a Windows host", label));
+            }
+        }
+
+        if executor.platform.is_some() && executor.executor_type != ExecutorType::Ssh {
+            report(format!("{}: platform is only used by ssh executors", label));
+        }
+        if executor.nix_shell.is_some() && executor.platform == Some(HostPlatform::Windows) {
+            report(format!("{}: nix_shell can't be used with a Windows host", label));
+        }
+        if executor.activate.is_some() && executor.platform == Some(HostPlatform::Windows) {
+            report(format!("{}: activate can't be used with a Windows host", label));
+        }
+        if !executor.env.is_empty() && executor.platform == Some(HostPlatform::Windows) {
+            report(format!("{}: env can't be used with a Windows host", label));
+        }
+        if let Some(ref task_root) = executor.task_root {
+            let home = task_root == "~" || task_root.starts_with("~/");
+            if !matches!(executor.executor_type, ExecutorType::Local | ExecutorType::Ssh) {
+                report(format!("{}: task_root is only used by local and ssh executors", label));
+            } else if executor.platform != Some(HostPlatform::Windows)
+                && !task_root.starts_with('/')
+                && !home
+            {
+                report(format!(
+                    "{}: task_root {} must be an absolute path or start with ~/",
+                    label, task_root
+                ));
+            } else if executor.executor_type == ExecutorType::Ssh
+                && executor.platform != Some(HostPlatform::Windows)
+                && task_root.chars().any(|c| c.is_whitespace() || SHELL_SPECIAL.contains(c))
+            {
+                report(format!(
+                    "{}: task_root {} must not contain whitespace or shell metacharacters",
+                    label, task_root
+                ));
+            }
+        }
+
+        if executor.mock.is_some() && executor.executor_type != ExecutorType::Mock {
+            report(format!("{}: mock is only used by mock executors", label));
+        }
+        if executor.fixture.is_some()
+            && !matches!(executor.executor_type, ExecutorType::Ssh | ExecutorType::Container)
+        {
+            report(format!("{}: fixture is only used by ssh and container executors", label));
+        }
+        if let Some(ref fixture) = executor.fixture {
+            if fixture.mode == FixtureMode::Replay && !Path::new(&fixture.path).is_file() {
+                report(format!("{}: fixture {} does not exist", label, fixture.path));
+            }
+        }
+
+        if let Some(ref key_path) = executor.key_path {
+            if !Path::new(key_path).exists() {
+                let hint = if key_path.starts_with('~') {
+                    " (~ is not expanded; use an absolute path)"
+                } else {
+                    ""
+                };
+                report(format!(
+                    "{}: key_path {} does not exist{}",
+                    label, key_path, hint
+                ));
+            }
+        }
+    }
+}
+
+/// A service name usable as both a container name suffix and a host name.
+fn is_service_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('-')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// A container memory size as docker and podman take it: a number with an
+/// optional `b`, `k`, `m` or `g` suffix.
+fn is_memory_size(s: &str) -> bool {
+    let digits = s.trim_end_matches(['b', 'k', 'm', 'g', 'B', 'K', 'M', 'G']);
+    s.len() - digits.len() <= 1 && !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+}
+
+fn check_schedules(
+    config: &Config,
+    sources: &[Source],
+    profile: Option<&str>,
+    problems: &mut Vec<Problem>,
+) {
+    for schedule in &config.schedules {
+        let Some((source, entry)) = source_of(sources, "schedules", &schedule.name, profile) else {
+            continue;
+        };
+        let label = format!("schedules[{}]", schedule.name);
+
+        if let Err(e) = schedule.cron.parse::<CronExpr>() {
+            problems.push(source.problem(&entry, format!("{}: invalid cron: {}", label, e)));
+        }
+        if config.find_executor(&schedule.spec.executor).is_none() {
+            problems.push(source.problem(
+                &entry,
+                format!("{}: unknown executor '{}'", label, schedule.spec.executor),
+            ));
+        }
+        if let Err(e) = schedule.spec.payload() {
+            problems.push(source.problem(&entry, format!("{}: {}", label, e)));
+        }
+    }
+}
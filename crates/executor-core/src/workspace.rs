@@ -0,0 +1,88 @@
+//! Fresh per-task workspaces made from an executor's `workspace_template`,
+//! so concurrent tasks never share a checkout.
+//!
+//! Like the scripts in [`crate::git`], [`copy_script`] runs on the executor's
+//! own backend.
+
+use crate::error::ExecutorError;
+use crate::git::quote;
+use crate::task::{RepoSpec, TaskRequest};
+
+/// What an executor's `workspace_template` names.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WorkspaceTemplate {
+    /// A git remote, cloned as if the task had been started with `--repo`.
+    Repo(RepoSpec),
+    /// A directory on the executor's host, copied into the task's workspace.
+    Dir(String),
+}
+
+impl WorkspaceTemplate {
+    /// Parse a `workspace_template` setting: anything that looks like a git
+    /// remote URL is cloned, anything else is a directory.
+    pub fn parse(template: &str) -> Self {
+        if is_repo_url(template) {
+            Self::Repo(RepoSpec {
+                url: template.to_string(),
+                branch: None,
+                depth: None,
+            })
+        } else {
+            Self::Dir(template.to_string())
+        }
+    }
+
+    /// The template a request starts from, if any: only requests that named
+    /// neither a `--workspace` nor a `--repo` get one.
+    pub fn for_request(template: Option<&str>, request: &TaskRequest) -> Option<Self> {
+        if request.workspace.is_some() || request.repo.is_some() {
+            return None;
+        }
+        template.map(Self::parse)
+    }
+}
+
+/// Point a request at the executor's `workspace_template`, if it gets one
+/// (see [`WorkspaceTemplate::for_request`]). A repo template becomes the
+/// request's `repo`; a directory template is returned for the executor to
+/// copy with [`copy_script`].
+pub fn apply_template(template: Option<&str>, request: &mut TaskRequest) -> Option<String> {
+    match WorkspaceTemplate::for_request(template, request)? {
+        WorkspaceTemplate::Repo(repo) => {
+            request.repo = Some(repo);
+            None
+        }
+        WorkspaceTemplate::Dir(dir) => Some(dir),
+    }
+}
+
+fn is_repo_url(template: &str) -> bool {
+    template.contains("://") || template.starts_with("git@")
+}
+
+/// Printed by [`copy_script`] once the copy succeeded.
+const COPIED: &str = "openclaw-workspace-copied";
+
+/// A `sh` script that copies the contents of the `template` directory
+/// (including dotfiles such as `.git`) into `dest`. Errors go to stdout and
+/// the script always exits 0; hand its output to [`parse_copy`].
+pub fn copy_script(template: &str, dest: &str) -> String {
+    format!(
+        "(\nset -e\ntest -d {t} || {{ echo \"no such directory: \"{t}; exit 1; }}\nmkdir -p {d}\ncp -a {t}/. {d}\necho {c}\n) 2>&1 || true",
+        t = quote(template),
+        d = quote(dest),
+        c = COPIED
+    )
+}
+
+/// Check the output of a [`copy_script`] run.
+pub fn parse_copy(output: &str) -> Result<(), ExecutorError> {
+    if output.lines().any(|line| line.trim() == COPIED) {
+        Ok(())
+    } else {
+        Err(ExecutorError::Config(format!(
+            "copying workspace template failed: {}",
+            output.trim()
+        )))
+    }
+}
@@ -0,0 +1,313 @@
+use crate::protocol::{Op, Request, Response};
+use executor_core::config::ExecutorConfig;
+use executor_core::error::ExecutorError;
+use executor_core::executor::{with_cancel, RemoteTask};
+use executor_core::metadata::TaskMetadata;
+use executor_core::store::{default_store, MetadataStore};
+use executor_core::task::{TaskId, TaskRequest, TaskStatus};
+use executor_core::workspace;
+use executor_core::{CancellationToken, Executor};
+use std::process::Stdio;
+use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tracing::{debug, info, instrument, warn};
+
+/// External executor: delegates every operation to a plugin binary speaking
+/// JSON over stdin/stdout, so backends can be written in any language.
+pub struct ExternalExecutor {
+    config: ExecutorConfig,
+    store: Arc<dyn MetadataStore>,
+}
+
+impl ExternalExecutor {
+    pub fn new(config: ExecutorConfig) -> Self {
+        Self::with_store(config, default_store())
+    }
+
+    pub fn with_store(config: ExecutorConfig, store: Arc<dyn MetadataStore>) -> Self {
+        Self { config, store }
+    }
+
+    /// Run the plugin once for `op` and return its successful response.
+    async fn call(
+        &self,
+        op: Op<'_>,
+        cancel: &CancellationToken,
+    ) -> Result<Response, ExecutorError> {
+        let command = self.config.command.as_deref().ok_or_else(|| {
+            ExecutorError::Config(format!(
+                "external executor '{}' needs a `command`",
+                self.config.name
+            ))
+        })?;
+        let op_name = op.name();
+        let mut line = serde_json::to_vec(&Request {
+            executor: &self.config.name,
+            options: &self.config.options,
+            op,
+        })?;
+        line.push(b'\n');
+
+        debug!("External exec: {} ({})", command, op_name);
+
+        let env = self.config.resolved_env()?;
+        let output = with_cancel(cancel, async {
+            let mut child = Command::new(command)
+                .args(&self.config.args)
+                .envs(env)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::inherit())
+                .kill_on_drop(true)
+                .spawn()
+                .map_err(|e| {
+                    ExecutorError::Process(format!("Failed to spawn {}: {}", command, e))
+                })?;
+
+            if let Some(mut stdin) = child.stdin.take() {
+                stdin.write_all(&line).await?;
+            }
+            Ok(child.wait_with_output().await?)
+        })
+        .await?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let response: Response = match stdout.lines().find(|l| !l.trim().is_empty()) {
+            Some(reply) => serde_json::from_str(reply).map_err(|e| {
+                ExecutorError::External(format!("invalid {} response: {}", op_name, e))
+            })?,
+            None => {
+                return Err(ExecutorError::External(format!(
+                    "{} exited with {} and no {} response",
+                    command, output.status, op_name
+                )))
+            }
+        };
+
+        if response.ok {
+            return Ok(response);
+        }
+        let error = response
+            .error
+            .unwrap_or_else(|| format!("{} failed", op_name));
+        if response.unsupported {
+            return Err(ExecutorError::Unsupported(error));
+        }
+        Err(ExecutorError::External(error))
+    }
+}
+
+#[async_trait::async_trait]
+impl Executor for ExternalExecutor {
+    fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    fn executor_type(&self) -> &str {
+        "external"
+    }
+
+    #[instrument(
+        skip_all,
+        fields(executor = %self.config.name, payload = request.payload.type_str())
+    )]
+    async fn start(
+        &self,
+        mut request: TaskRequest,
+        cancel: &CancellationToken,
+    ) -> Result<TaskMetadata, ExecutorError> {
+        // A repo template reaches the plugin as the request's repo
+        let template =
+            workspace::apply_template(self.config.workspace_template.as_deref(), &mut request);
+        if template.is_some() {
+            return Err(ExecutorError::Unsupported(
+                "copying a workspace_template directory".into(),
+            ));
+        }
+
+        let task_id = TaskId::new();
+        let response = self
+            .call(
+                Op::Start {
+                    task_id: &task_id.0,
+                    request: &request,
+                },
+                cancel,
+            )
+            .await?;
+
+        info!("Task {} started by external executor", task_id);
+
+        let mut meta = TaskMetadata::new(
+            task_id,
+            self.config.name.clone(),
+            "external".to_string(),
+            request.payload.type_str().to_string(),
+            request.payload.description().to_string(),
+            request.workspace.clone(),
+        );
+        meta.inherit_from(&request);
+        // Not every backend has a process ID, so don't go through mark_running
+        meta.pid = response.pid;
+        meta.commit = response.commit;
+        meta.status = TaskStatus::Running;
+
+        self.store.put(&meta)?;
+
+        Ok(meta)
+    }
+
+    #[instrument(skip_all, fields(executor = %self.config.name, task_id = %task_id))]
+    async fn status(
+        &self,
+        task_id: &TaskId,
+        cancel: &CancellationToken,
+    ) -> Result<TaskMetadata, ExecutorError> {
+        let mut meta = self.store.load(task_id)?;
+        if meta.status.is_terminal() {
+            return Ok(meta);
+        }
+
+        let response = self
+            .call(
+                Op::Status {
+                    task_id: &task_id.0,
+                    pid: meta.pid,
+                },
+                cancel,
+            )
+            .await?;
+        let Some(status) = response.status else {
+            return Err(ExecutorError::External(
+                "status response is missing `status`".into(),
+            ));
+        };
+
+        if status != meta.status {
+            match status {
+                TaskStatus::Completed => meta.mark_completed(response.exit_code.unwrap_or(0)),
+                TaskStatus::Failed => meta.mark_completed(response.exit_code.unwrap_or(1)),
+                TaskStatus::Killed => meta.mark_killed(None),
+                other => {
+                    meta.status = other;
+                    meta.updated_at = chrono::Utc::now();
+                }
+            }
+            meta.error = response.error;
+            self.store.put(&meta)?;
+        }
+
+        Ok(meta)
+    }
+
+    #[instrument(skip_all, fields(executor = %self.config.name, task_id = %task_id))]
+    async fn logs(
+        &self,
+        task_id: &TaskId,
+        lines: usize,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<String>, ExecutorError> {
+        let response = self
+            .call(
+                Op::Logs {
+                    task_id: &task_id.0,
+                    lines,
+                },
+                cancel,
+            )
+            .await?;
+        Ok(response.lines)
+    }
+
+    #[instrument(skip_all, fields(executor = %self.config.name, task_id = %task_id))]
+    async fn kill(
+        &self,
+        task_id: &TaskId,
+        cancel: &CancellationToken,
+    ) -> Result<(), ExecutorError> {
+        let mut meta = self.store.load(task_id)?;
+
+        warn!("Killing external task {}", task_id);
+        self.call(
+            Op::Kill {
+                task_id: &task_id.0,
+            },
+            cancel,
+        )
+        .await?;
+
+        meta.mark_killed(None);
+        self.store.put(&meta)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(executor = %self.config.name, task_id = %task_id))]
+    async fn cleanup(
+        &self,
+        task_id: &TaskId,
+        cancel: &CancellationToken,
+    ) -> Result<(), ExecutorError> {
+        self.call(
+            Op::Cleanup {
+                task_id: &task_id.0,
+            },
+            cancel,
+        )
+        .await?;
+
+        self.store.delete(task_id)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(executor = %self.config.name))]
+    async fn list_remote(
+        &self,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<RemoteTask>, ExecutorError> {
+        let response = self.call(Op::List, cancel).await?;
+        Ok(response
+            .task_ids
+            .into_iter()
+            .map(|id| RemoteTask {
+                task_id: TaskId::from_string(id),
+                metadata: None,
+            })
+            .collect())
+    }
+
+    #[instrument(skip_all, fields(executor = %self.config.name, task_id = %task_id))]
+    async fn exec(
+        &self,
+        task_id: &TaskId,
+        command: &str,
+        cancel: &CancellationToken,
+    ) -> Result<i32, ExecutorError> {
+        // The protocol is one reply per call, so output arrives all at once
+        let (code, output) = self.exec_output(task_id, command, cancel).await?;
+        print!("{}", output);
+        Ok(code)
+    }
+
+    #[instrument(skip_all, fields(executor = %self.config.name, task_id = %task_id))]
+    async fn exec_output(
+        &self,
+        task_id: &TaskId,
+        command: &str,
+        cancel: &CancellationToken,
+    ) -> Result<(i32, String), ExecutorError> {
+        let response = self
+            .call(
+                Op::Exec {
+                    task_id: &task_id.0,
+                    command,
+                },
+                cancel,
+            )
+            .await?;
+        let output = response.lines.iter().map(|l| format!("{}\n", l)).collect();
+        Ok((response.exit_code.unwrap_or(0), output))
+    }
+}
@@ -0,0 +1,4 @@
+mod external_executor;
+mod protocol;
+
+pub use external_executor::ExternalExecutor;
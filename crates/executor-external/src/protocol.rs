@@ -0,0 +1,81 @@
+//! One request per plugin invocation: a single JSON line on stdin, answered by
+//! a single JSON object on stdout. Anything the plugin writes to stderr is
+//! passed through for diagnostics.
+
+use executor_core::task::{TaskRequest, TaskStatus};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Debug, Serialize)]
+pub struct Request<'a> {
+    /// The executor's config name.
+    pub executor: &'a str,
+    /// The executor's free-form `options` from config.
+    pub options: &'a HashMap<String, serde_yaml::Value>,
+    #[serde(flatten)]
+    pub op: Op<'a>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum Op<'a> {
+    Start {
+        task_id: &'a str,
+        request: &'a TaskRequest,
+    },
+    Status {
+        task_id: &'a str,
+        pid: Option<u32>,
+    },
+    Logs {
+        task_id: &'a str,
+        lines: usize,
+    },
+    Kill {
+        task_id: &'a str,
+    },
+    Cleanup {
+        task_id: &'a str,
+    },
+    List,
+    Exec {
+        task_id: &'a str,
+        command: &'a str,
+    },
+}
+
+impl Op<'_> {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Op::Start { .. } => "start",
+            Op::Status { .. } => "status",
+            Op::Logs { .. } => "logs",
+            Op::Kill { .. } => "kill",
+            Op::Cleanup { .. } => "cleanup",
+            Op::List => "list",
+            Op::Exec { .. } => "exec",
+        }
+    }
+}
+
+/// The plugin's answer. Only the fields relevant to the op need be present.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Response {
+    pub ok: bool,
+    pub error: Option<String>,
+    /// `unsupported: true` with `ok: false` marks an op the plugin doesn't implement.
+    pub unsupported: bool,
+    /// start: process ID, if the backend has one.
+    pub pid: Option<u32>,
+    /// start: commit checked out for a `request.repo`.
+    pub commit: Option<String>,
+    /// status: current state of the task.
+    pub status: Option<TaskStatus>,
+    /// status, exec: the command's exit code.
+    pub exit_code: Option<i32>,
+    /// logs: the last lines of output; exec: the command's output.
+    pub lines: Vec<String>,
+    /// list: IDs of tasks the backend knows about.
+    pub task_ids: Vec<String>,
+}
@@ -1,60 +1,182 @@
+use executor_core::activate;
 use executor_core::config::ExecutorConfig;
 use executor_core::error::ExecutorError;
-use executor_core::metadata::{metadata_dir, TaskMetadata};
-use executor_core::task::{TaskId, TaskPayload, TaskRequest, TaskStatus};
-use executor_core::Executor;
-use std::path::PathBuf;
+use executor_core::executor::{
+    resolve_path, with_cancel, CleanupOutcome, FileRoot, RemoteTask, StartPlan, TaskChanges,
+};
+use executor_core::git;
+use executor_core::host::{self, HostStats};
+use executor_core::kill;
+use executor_core::log_filter::LogFilter;
+use executor_core::log_timestamps;
+use executor_core::metadata::TaskMetadata;
+use executor_core::nix;
+use executor_core::progress::{self, TaskProgress};
+use executor_core::provision::{self, ProvisionReport};
+use executor_core::store::{default_store, MetadataStore};
+use executor_core::task::{RepoSpec, TaskId, TaskPayload, TaskRequest, TaskStatus};
+use executor_core::usage::{self, ResourceUsage};
+use executor_core::workspace;
+use executor_core::{CancellationToken, Executor};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use tokio::process::Command;
-use tracing::{debug, info, warn};
+use tracing::{debug, info, instrument, warn};
 
 /// Local executor: runs claude or shell commands directly on the host machine.
 pub struct LocalExecutor {
     config: ExecutorConfig,
+    store: Arc<dyn MetadataStore>,
 }
 
 impl LocalExecutor {
     pub fn new(config: ExecutorConfig) -> Self {
-        Self { config }
+        Self::with_store(config, default_store())
     }
 
-    fn local_meta_dir(&self) -> PathBuf {
-        metadata_dir()
+    pub fn with_store(config: ExecutorConfig, store: Arc<dyn MetadataStore>) -> Self {
+        Self { config, store }
+    }
+
+    /// The configured task root, with a leading `~` expanded.
+    fn task_root(&self) -> PathBuf {
+        let root = self.config.task_root();
+        let home = std::env::var_os("HOME").map(PathBuf::from).unwrap_or_default();
+        match root.strip_prefix('~') {
+            Some("") => home,
+            Some(rest) if rest.starts_with('/') => home.join(&rest[1..]),
+            _ => PathBuf::from(root),
+        }
     }
 
     fn task_dir(&self, task_id: &TaskId) -> PathBuf {
-        PathBuf::from("/tmp/openclaw-tasks").join(task_id.to_string())
+        self.task_root().join(task_id.to_string())
     }
-}
 
-#[async_trait::async_trait]
-impl Executor for LocalExecutor {
-    fn name(&self) -> &str {
-        &self.config.name
+    /// `sh -c command` in a task's workspace.
+    fn workspace_command(&self, task_id: &TaskId, command: &str) -> Result<Command, ExecutorError> {
+        let meta = self.store.load(task_id)?;
+        let workspace = meta.workspace.as_deref().unwrap_or(".");
+        debug!("Local exec in {}: {}", workspace, command);
+
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c")
+            .arg(command)
+            .current_dir(workspace)
+            .envs(self.config.resolved_env()?)
+            .kill_on_drop(true);
+        Ok(cmd)
     }
 
-    fn executor_type(&self) -> &str {
-        "local"
+    /// Run a git script with `sh` and return its stdout.
+    async fn run_git_script(
+        &self,
+        script: &str,
+        cancel: &CancellationToken,
+    ) -> Result<String, ExecutorError> {
+        let env = self.config.resolved_env()?;
+        let output = with_cancel(cancel, async {
+            Command::new("sh")
+                .arg("-c")
+                .arg(script)
+                .envs(env)
+                .kill_on_drop(true)
+                .output()
+                .await
+                .map_err(|e| ExecutorError::Process(format!("Failed to spawn git: {}", e)))
+        })
+        .await?;
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
     }
 
-    async fn start(&self, request: TaskRequest) -> Result<TaskMetadata, ExecutorError> {
-        let task_id = TaskId::new();
-        let task_dir = self.task_dir(&task_id);
-        std::fs::create_dir_all(&task_dir)?;
+    /// Clone a task's repo into `dest`, returning the checked-out commit.
+    async fn clone_repo(
+        &self,
+        repo: &RepoSpec,
+        dest: &Path,
+        cancel: &CancellationToken,
+    ) -> Result<String, ExecutorError> {
+        let script = git::clone_script(repo, &dest.display().to_string(), &self.config.git);
+        info!("Cloning {} into {}", repo.url, dest.display());
+        git::parse_commit(&self.run_git_script(&script, cancel).await?)
+    }
 
-        let log_file = task_dir.join("claude.log");
-        let pid_file = task_dir.join("claude.pid");
+    /// Where a task file `path`, relative to `root`, lives.
+    fn file_path(
+        &self,
+        task_id: &TaskId,
+        path: &str,
+        root: FileRoot,
+    ) -> Result<String, ExecutorError> {
+        let dir = match root {
+            FileRoot::Workspace => {
+                let meta = self.store.load(task_id)?;
+                meta.workspace.unwrap_or_else(|| ".".to_string())
+            }
+            FileRoot::TaskDir => self.task_dir(task_id).display().to_string(),
+        };
+        Ok(resolve_path(path, &dir))
+    }
 
-        let workspace = request.workspace.as_deref().unwrap_or(".");
+    /// Copy a `workspace_template` directory into `dest`.
+    async fn copy_template(
+        &self,
+        template: &str,
+        dest: &Path,
+        cancel: &CancellationToken,
+    ) -> Result<(), ExecutorError> {
+        let script = workspace::copy_script(template, &dest.display().to_string());
+        info!("Copying workspace template {} into {}", template, dest.display());
+        workspace::parse_copy(&self.run_git_script(&script, cancel).await?)
+    }
 
-        // Build env var prefix from config.env (exported before the command)
-        let env_prefix: String = self
-            .config
-            .env
-            .iter()
-            .map(|(k, v)| format!("{}='{}' ", k, v.replace('\'', "'\\''")))
-            .collect();
+    /// Create the task's branch in `workspace`, returning the commit it starts
+    /// from, or `None` if the workspace isn't a git work tree.
+    async fn create_branch(
+        &self,
+        task_id: &TaskId,
+        workspace: &str,
+        task_dir: &Path,
+        cancel: &CancellationToken,
+    ) -> Result<Option<String>, ExecutorError> {
+        let script = format!(
+            "cd {} && {}",
+            shell_escape(workspace),
+            git::branch_script(task_id, &task_dir.display().to_string())
+        );
+        let output = self.run_git_script(&script, cancel).await?;
+        let base = git::parse_sha(&output);
+        match output.trim() {
+            _ if base.is_some() => {}
+            "" => warn!("{} is not a git work tree; no task branch created", workspace),
+            error => warn!("Failed to create a task branch in {}: {}", workspace, error),
+        }
+        Ok(base)
+    }
 
-        let shell_cmd = match &request.payload {
+    /// The `sh` line that launches a task in `workspace_dir` in the
+    /// background, its output going to the task dir's claude.log and its PID
+    /// to claude.pid.
+    fn launch_command(
+        &self,
+        request: &TaskRequest,
+        workspace_dir: &str,
+        task_dir: &Path,
+        env: &HashMap<String, String>,
+    ) -> String {
+        // Build env var prefix from config.env (set via `env`, since nohup
+        // doesn't take assignments). `setsid` puts the task in a process
+        // group of its own, which `kill` signals as a whole.
+        let env_prefix: String = if env.is_empty() {
+            String::new()
+        } else {
+            env.iter()
+                .map(|(k, v)| format!("{}='{}' ", k, v.replace('\'', "'\\''")))
+                .fold("env ".to_string(), |prefix, var| prefix + &var)
+        };
+
+        let inner_cmd = match &request.payload {
             TaskPayload::ClaudeCode {
                 prompt,
                 max_turns,
@@ -62,8 +184,9 @@ impl Executor for LocalExecutor {
             } => {
                 let claude_bin = self.config.claude_binary();
                 let mut claude_args = format!(
-                    "{} --print --output-format json -p {}",
+                    "{} --print {} -p {}",
                     claude_bin,
+                    self.config.claude_output_args(),
                     shell_escape(prompt)
                 );
 
@@ -75,35 +198,109 @@ impl Executor for LocalExecutor {
                     claude_args.push_str(&format!(" --allowedTools {}", shell_escape(tool)));
                 }
 
-                format!(
-                    "cd {} && nohup {}{}> {} 2>&1 & echo $! > {}",
-                    shell_escape(workspace),
-                    env_prefix,
-                    claude_args,
-                    log_file.display(),
-                    pid_file.display(),
-                )
+                claude_args
             }
             TaskPayload::ShellCommand { command } => {
-                format!(
-                    "cd {} && nohup {}sh -c {} > {} 2>&1 & echo $! > {}",
-                    shell_escape(workspace),
-                    env_prefix,
-                    shell_escape(command),
-                    log_file.display(),
-                    pid_file.display(),
-                )
+                format!("sh -c {}", shell_escape(command))
             }
         };
+        let inner_cmd = match self.config.activate.as_deref() {
+            Some(activate) => activate::wrap(activate, &inner_cmd),
+            None => inner_cmd,
+        };
+        let inner_cmd = match nix::for_request(&self.config, request) {
+            Some(shell) => nix::wrap(shell, &inner_cmd),
+            None => inner_cmd,
+        };
+        let inner_cmd = if self.config.log_timestamps {
+            let stamped = format!("{} 2>&1 | {}", inner_cmd, log_timestamps::STAMP_PIPE);
+            format!("sh -c {}", shell_escape(&stamped))
+        } else {
+            inner_cmd
+        };
+
+        format!(
+            "cd {} && setsid nohup {}{} > {} 2>&1 & echo $! > {}",
+            shell_escape(workspace_dir),
+            env_prefix,
+            inner_cmd,
+            task_dir.join("claude.log").display(),
+            task_dir.join("claude.pid").display(),
+        )
+    }
+}
+
+#[async_trait::async_trait]
+impl Executor for LocalExecutor {
+    fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    fn executor_type(&self) -> &str {
+        "local"
+    }
+
+    #[instrument(
+        skip_all,
+        fields(executor = %self.config.name, payload = request.payload.type_str())
+    )]
+    async fn start(
+        &self,
+        mut request: TaskRequest,
+        cancel: &CancellationToken,
+    ) -> Result<TaskMetadata, ExecutorError> {
+        let task_id = TaskId::new();
+        let task_dir = self.task_dir(&task_id);
+        std::fs::create_dir_all(&task_dir)?;
+
+        let pid_file = task_dir.join("claude.pid");
+
+        // A --repo task gets a fresh clone of its own instead of --workspace,
+        // and so does a task started from a workspace_template
+        let template = workspace::apply_template(
+            self.config.workspace_template.as_deref(),
+            &mut request,
+        );
+        let (workspace, commit) = match (&request.repo, template) {
+            (Some(repo), _) => {
+                let dest = task_dir.join("workspace");
+                let commit = self.clone_repo(repo, &dest, cancel).await?;
+                (Some(dest.display().to_string()), Some(commit))
+            }
+            (None, Some(template)) => {
+                let dest = task_dir.join("workspace");
+                self.copy_template(&template, &dest, cancel).await?;
+                (Some(dest.display().to_string()), None)
+            }
+            (None, None) => (request.workspace.clone(), None),
+        };
+        let workspace_dir = workspace.as_deref().unwrap_or(".");
+
+        let base_commit = if request.git_branch {
+            self.create_branch(&task_id, workspace_dir, &task_dir, cancel).await?
+        } else {
+            None
+        };
+
+        let shell_cmd = self.launch_command(
+            &request,
+            workspace_dir,
+            &task_dir,
+            &self.config.resolved_env()?,
+        );
 
         debug!("Local exec: {}", shell_cmd);
 
-        Command::new("sh")
-            .arg("-c")
-            .arg(&shell_cmd)
-            .output()
-            .await
-            .map_err(|e| ExecutorError::Process(format!("Failed to spawn: {}", e)))?;
+        with_cancel(cancel, async {
+            Command::new("sh")
+                .arg("-c")
+                .arg(&shell_cmd)
+                .kill_on_drop(true)
+                .output()
+                .await
+                .map_err(|e| ExecutorError::Process(format!("Failed to spawn: {}", e)))
+        })
+        .await?;
 
         // Read PID
         let pid_str = tokio::fs::read_to_string(&pid_file)
@@ -122,44 +319,114 @@ impl Executor for LocalExecutor {
             "local".to_string(),
             request.payload.type_str().to_string(),
             request.payload.description().to_string(),
-            request.workspace,
+            workspace,
         );
+        meta.inherit_from(&request);
+        meta.commit = commit;
+        if base_commit.is_some() {
+            meta.git_branch = Some(git::branch_name(&task_id));
+            meta.base_commit = base_commit;
+        }
         meta.mark_running(pid);
 
-        let meta_dir = self.local_meta_dir();
-        std::fs::create_dir_all(&meta_dir)?;
-        meta.write_to_dir(&meta_dir)?;
+        self.store.put(&meta)?;
 
         Ok(meta)
     }
 
-    async fn status(&self, task_id: &TaskId) -> Result<TaskMetadata, ExecutorError> {
-        let meta_dir = self.local_meta_dir();
-        let meta_path = meta_dir.join(format!("{}.meta.json", task_id));
+    fn plan_start(&self, mut request: TaskRequest) -> Result<StartPlan, ExecutorError> {
+        let task_id = TaskId::new();
+        let task_dir = self.task_dir(&task_id);
+        let mut steps = vec![format!("Create {}", task_dir.display())];
 
-        let mut meta = if meta_path.exists() {
-            TaskMetadata::read_from_file(&meta_path)?
-        } else {
-            return Err(ExecutorError::TaskNotFound(task_id.to_string()));
+        let template = workspace::apply_template(
+            self.config.workspace_template.as_deref(),
+            &mut request,
+        );
+        let dest = task_dir.join("workspace").display().to_string();
+        let workspace = match (&request.repo, template) {
+            (Some(repo), _) => {
+                steps.push(format!("Clone {} into {}", repo.url, dest));
+                Some(dest)
+            }
+            (None, Some(template)) => {
+                steps.push(format!("Copy workspace template {} into {}", template, dest));
+                Some(dest)
+            }
+            (None, None) => request.workspace.clone(),
         };
+        let workspace_dir = workspace.as_deref().unwrap_or(".");
+        if request.git_branch {
+            steps.push(format!(
+                "Create branch {} in {}",
+                git::branch_name(&task_id),
+                workspace_dir
+            ));
+        }
+
+        // Secret references are shown as configured, not resolved
+        let command = self.launch_command(&request, workspace_dir, &task_dir, &self.config.env);
+
+        let mut metadata = TaskMetadata::new(
+            task_id.clone(),
+            self.config.name.clone(),
+            "local".to_string(),
+            request.payload.type_str().to_string(),
+            request.payload.description().to_string(),
+            workspace,
+        );
+        metadata.inherit_from(&request);
+        if request.git_branch {
+            metadata.git_branch = Some(git::branch_name(&task_id));
+        }
+        Ok(StartPlan {
+            steps,
+            command,
+            metadata,
+        })
+    }
+
+    #[instrument(skip_all, fields(executor = %self.config.name, task_id = %task_id))]
+    async fn status(
+        &self,
+        task_id: &TaskId,
+        cancel: &CancellationToken,
+    ) -> Result<TaskMetadata, ExecutorError> {
+        let mut meta = self.store.load(task_id)?;
+
+        // Never recorded as running: the PID file tells whether it launched
+        if meta.status == TaskStatus::Starting {
+            let pid_file = self.task_dir(task_id).join("claude.pid");
+            match tokio::fs::read_to_string(&pid_file).await {
+                Ok(pid) => match pid.trim().parse() {
+                    Ok(pid) => meta.mark_running(pid),
+                    Err(_) => meta.mark_failed(format!("Invalid PID: '{}'", pid.trim())),
+                },
+                Err(_) => meta.mark_failed("Launch never happened: no PID file".into()),
+            }
+            self.store.put(&meta)?;
+        }
 
         if meta.status == TaskStatus::Running {
             if let Some(pid) = meta.pid {
                 // Check if process is alive
-                let output = Command::new("kill")
-                    .args(["-0", &pid.to_string()])
-                    .output()
-                    .await;
+                let output = with_cancel(cancel, async {
+                    Ok(Command::new("kill")
+                        .args(["-0", &pid.to_string()])
+                        .output()
+                        .await)
+                })
+                .await?;
 
                 match output {
                     Ok(o) if !o.status.success() => {
                         // Process no longer running
                         meta.mark_completed(0);
-                        meta.write_to_dir(&meta_dir)?;
+                        self.store.put(&meta)?;
                     }
                     Err(_) => {
                         meta.mark_completed(1);
-                        meta.write_to_dir(&meta_dir)?;
+                        self.store.put(&meta)?;
                     }
                     _ => {} // still running
                 }
@@ -169,7 +436,13 @@ impl Executor for LocalExecutor {
         Ok(meta)
     }
 
-    async fn logs(&self, task_id: &TaskId, lines: usize) -> Result<Vec<String>, ExecutorError> {
+    #[instrument(skip_all, fields(executor = %self.config.name, task_id = %task_id))]
+    async fn logs(
+        &self,
+        task_id: &TaskId,
+        lines: usize,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<String>, ExecutorError> {
         let task_dir = self.task_dir(task_id);
         let log_file = task_dir.join("claude.log");
 
@@ -177,57 +450,337 @@ impl Executor for LocalExecutor {
             return Ok(Vec::new());
         }
 
-        let output = Command::new("tail")
-            .args(["-n", &lines.to_string()])
-            .arg(&log_file)
-            .output()
-            .await
-            .map_err(|e| ExecutorError::Process(format!("tail failed: {}", e)))?;
+        let output = with_cancel(cancel, async {
+            Command::new("tail")
+                .args(["-n", &lines.to_string()])
+                .arg(&log_file)
+                .kill_on_drop(true)
+                .output()
+                .await
+                .map_err(|e| ExecutorError::Process(format!("tail failed: {}", e)))
+        })
+        .await?;
 
         let text = String::from_utf8_lossy(&output.stdout);
         Ok(text.lines().map(|l| l.to_string()).collect())
     }
 
-    async fn kill(&self, task_id: &TaskId) -> Result<(), ExecutorError> {
-        let meta_dir = self.local_meta_dir();
-        let meta_path = meta_dir.join(format!("{}.meta.json", task_id));
-
-        let mut meta = if meta_path.exists() {
-            TaskMetadata::read_from_file(&meta_path)?
-        } else {
-            return Err(ExecutorError::TaskNotFound(task_id.to_string()));
-        };
+    #[instrument(skip_all, fields(executor = %self.config.name, task_id = %task_id))]
+    async fn filtered_logs(
+        &self,
+        task_id: &TaskId,
+        lines: usize,
+        filter: &LogFilter,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<String>, ExecutorError> {
+        let log_file = self.task_dir(task_id).join("claude.log");
+        if !log_file.exists() {
+            return Ok(Vec::new());
+        }
 
-        if let Some(pid) = meta.pid {
-            warn!("Killing local task {} (PID {})", task_id, pid);
-            let _ = Command::new("kill")
-                .arg(pid.to_string())
+        let script = filter.script(&shell_escape(&log_file.display().to_string()), lines);
+        let output = with_cancel(cancel, async {
+            Command::new("sh")
+                .arg("-c")
+                .arg(&script)
+                .kill_on_drop(true)
                 .output()
-                .await;
+                .await
+                .map_err(|e| ExecutorError::Process(format!("Log filter failed: {}", e)))
+        })
+        .await?;
+
+        let text = String::from_utf8_lossy(&output.stdout);
+        Ok(text.lines().map(|l| l.to_string()).collect())
+    }
 
-            meta.mark_killed();
-            meta.write_to_dir(&meta_dir)?;
+    #[instrument(skip_all, fields(executor = %self.config.name, task_id = %task_id))]
+    async fn kill(&self, task_id: &TaskId, cancel: &CancellationToken) -> Result<(), ExecutorError> {
+        let mut meta = self.store.load(task_id)?;
+
+        if let Some(pid) = meta.pid {
+            let grace_secs = self.config.kill_grace_secs();
+            warn!("Killing local task {} (PID {}, SIGKILL after {}s)", task_id, pid, grace_secs);
+            let output = with_cancel(cancel, async {
+                Ok(Command::new("sh")
+                    .arg("-c")
+                    .arg(kill::terminate_script(pid, grace_secs))
+                    .kill_on_drop(true)
+                    .output()
+                    .await)
+            })
+            .await?;
+            let signal = output
+                .ok()
+                .and_then(|o| kill::parse_signal(&String::from_utf8_lossy(&o.stdout)));
+
+            meta.mark_killed(signal);
+            self.store.put(&meta)?;
         }
 
         Ok(())
     }
 
-    async fn cleanup(&self, task_id: &TaskId) -> Result<(), ExecutorError> {
+    #[instrument(skip_all, fields(executor = %self.config.name, task_id = %task_id))]
+    async fn cleanup(
+        &self,
+        task_id: &TaskId,
+        _cancel: &CancellationToken,
+    ) -> Result<(), ExecutorError> {
         let task_dir = self.task_dir(task_id);
         if task_dir.exists() {
             info!("Cleaning up local task dir: {}", task_dir.display());
             std::fs::remove_dir_all(task_dir)?;
         }
 
-        let meta_path = self
-            .local_meta_dir()
-            .join(format!("{}.meta.json", task_id));
-        if meta_path.exists() {
-            std::fs::remove_file(meta_path)?;
+        self.store.delete(task_id)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(executor = %self.config.name))]
+    async fn cleanup_many(
+        &self,
+        task_ids: &[TaskId],
+        cancel: &CancellationToken,
+    ) -> Result<Vec<CleanupOutcome>, ExecutorError> {
+        let mut outcomes = Vec::new();
+        for task_id in task_ids {
+            let freed_kb = disk_usage(&self.task_dir(task_id)) / 1024;
+            let error = match self.cleanup(task_id, cancel).await {
+                Ok(()) => None,
+                Err(e) => Some(e.to_string()),
+            };
+            outcomes.push(CleanupOutcome {
+                task_id: task_id.clone(),
+                freed_kb: Some(freed_kb),
+                error,
+            });
+        }
+        Ok(outcomes)
+    }
+
+    #[instrument(skip_all, fields(executor = %self.config.name, task_id = %task_id))]
+    async fn changes(
+        &self,
+        task_id: &TaskId,
+        cancel: &CancellationToken,
+    ) -> Result<TaskChanges, ExecutorError> {
+        let meta = self.store.load(task_id)?;
+        if meta.git_branch.is_none() {
+            return Err(ExecutorError::Git(format!("task {} has no branch", task_id)));
         }
+        let task_dir = self.task_dir(task_id);
+        let workspace = meta.workspace.as_deref().unwrap_or(".");
+
+        let script = format!(
+            "cd {} && {}",
+            shell_escape(workspace),
+            git::changes_script(&task_dir.display().to_string())
+        );
+        let output = self.run_git_script(&script, cancel).await?;
 
+        Ok(TaskChanges {
+            files: output.lines().map(String::from).collect(),
+            diff: std::fs::read_to_string(task_dir.join("changes.diff")).unwrap_or_default(),
+        })
+    }
+
+    #[instrument(skip_all, fields(executor = %self.config.name, task_id = %task_id))]
+    async fn download(
+        &self,
+        task_id: &TaskId,
+        path: &str,
+        root: FileRoot,
+        dest: &Path,
+        cancel: &CancellationToken,
+    ) -> Result<(), ExecutorError> {
+        let source = self.file_path(task_id, path, root)?;
+        debug!("Copying {} to {}", source, dest.display());
+        with_cancel(cancel, async {
+            tokio::fs::copy(&source, dest)
+                .await
+                .map_err(|e| ExecutorError::Process(format!("Failed to copy {}: {}", source, e)))
+        })
+        .await?;
         Ok(())
     }
+
+    #[instrument(skip_all, fields(executor = %self.config.name, task_id = %task_id))]
+    async fn upload(
+        &self,
+        task_id: &TaskId,
+        source: &Path,
+        path: &str,
+        root: FileRoot,
+        cancel: &CancellationToken,
+    ) -> Result<(), ExecutorError> {
+        let dest = self.file_path(task_id, path, root)?;
+        debug!("Copying {} to {}", source.display(), dest);
+        with_cancel(cancel, async {
+            if let Some(parent) = Path::new(&dest).parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            tokio::fs::copy(source, &dest)
+                .await
+                .map_err(|e| ExecutorError::Process(format!("Failed to copy to {}: {}", dest, e)))
+        })
+        .await?;
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(executor = %self.config.name))]
+    async fn list_remote(
+        &self,
+        _cancel: &CancellationToken,
+    ) -> Result<Vec<RemoteTask>, ExecutorError> {
+        let root = self.task_root();
+        if !root.exists() {
+            return Ok(Vec::new());
+        }
+
+        // Local task dirs hold no metadata of their own; the store is the only record
+        let mut tasks = Vec::new();
+        for entry in std::fs::read_dir(root)? {
+            let entry = entry?;
+            if entry.file_type()?.is_dir() {
+                tasks.push(RemoteTask {
+                    task_id: TaskId::from_string(entry.file_name().to_string_lossy().into_owned()),
+                    metadata: None,
+                });
+            }
+        }
+
+        Ok(tasks)
+    }
+
+    #[instrument(skip_all, fields(executor = %self.config.name, task_id = %task_id))]
+    async fn exec(
+        &self,
+        task_id: &TaskId,
+        command: &str,
+        cancel: &CancellationToken,
+    ) -> Result<i32, ExecutorError> {
+        let mut cmd = self.workspace_command(task_id, command)?;
+        let status = with_cancel(cancel, async {
+            cmd.status()
+                .await
+                .map_err(|e| ExecutorError::Process(format!("Failed to spawn: {}", e)))
+        })
+        .await?;
+
+        Ok(status.code().unwrap_or(-1))
+    }
+
+    #[instrument(skip_all, fields(executor = %self.config.name, task_id = %task_id))]
+    async fn exec_output(
+        &self,
+        task_id: &TaskId,
+        command: &str,
+        cancel: &CancellationToken,
+    ) -> Result<(i32, String), ExecutorError> {
+        // Merge stderr into stdout inside the shell so the output stays in order
+        let mut cmd = self.workspace_command(task_id, &format!("exec 2>&1\n{}", command))?;
+        let output = with_cancel(cancel, async {
+            cmd.output()
+                .await
+                .map_err(|e| ExecutorError::Process(format!("Failed to spawn: {}", e)))
+        })
+        .await?;
+
+        Ok((
+            output.status.code().unwrap_or(-1),
+            String::from_utf8_lossy(&output.stdout).into_owned(),
+        ))
+    }
+
+    #[instrument(skip_all, fields(executor = %self.config.name))]
+    async fn host_stats(
+        &self,
+        dir: Option<&str>,
+        cancel: &CancellationToken,
+    ) -> Result<Option<HostStats>, ExecutorError> {
+        let root = self.task_root().display().to_string();
+        std::fs::create_dir_all(&root)?;
+        let script = host::stats_script_for(dir.unwrap_or(&root));
+        Ok(host::parse_stats(&self.run_git_script(&script, cancel).await?))
+    }
+
+    #[instrument(skip_all, fields(executor = %self.config.name))]
+    async fn find_claude(
+        &self,
+        cancel: &CancellationToken,
+    ) -> Result<Option<String>, ExecutorError> {
+        let script = host::claude_script(self.config.claude_path.as_deref());
+        Ok(host::parse_claude_path(&self.run_git_script(&script, cancel).await?))
+    }
+
+    #[instrument(skip_all, fields(executor = %self.config.name))]
+    async fn provision(
+        &self,
+        install: bool,
+        cancel: &CancellationToken,
+    ) -> Result<ProvisionReport, ExecutorError> {
+        let root = self.task_root().display().to_string();
+        let script = provision::script(&root, install, self.config.claude_path.as_deref());
+        let output = self.run_git_script(&script, cancel).await?;
+        Ok(provision::parse_report(&output, &root))
+    }
+
+    #[instrument(skip_all, fields(executor = %self.config.name, task_id = %task_id))]
+    async fn usage(
+        &self,
+        task_id: &TaskId,
+        cancel: &CancellationToken,
+    ) -> Result<Option<ResourceUsage>, ExecutorError> {
+        let Some(pid) = self.store.load(task_id)?.pid else {
+            return Ok(None);
+        };
+        let (_, output) = self.exec_output(task_id, &usage::tree_script(pid), cancel).await?;
+        Ok(usage::parse_usage(&output))
+    }
+
+    #[instrument(skip_all, fields(executor = %self.config.name, task_id = %task_id))]
+    async fn progress(
+        &self,
+        task_id: &TaskId,
+        cancel: &CancellationToken,
+    ) -> Result<Option<TaskProgress>, ExecutorError> {
+        let meta = self.store.load(task_id)?;
+        if !self.config.stream_json || meta.task_type != "claude_code" {
+            return Ok(None);
+        }
+        let log_file = self.task_dir(task_id).join("claude.log");
+        let script = progress::script(&shell_escape(&log_file.display().to_string()));
+        let output = self.run_git_script(&script, cancel).await?;
+        Ok(progress::parse_progress(&output, meta.workspace.as_deref()))
+    }
+
+    async fn log_size(
+        &self,
+        task_id: &TaskId,
+        _cancel: &CancellationToken,
+    ) -> Result<u64, ExecutorError> {
+        let log_file = self.task_dir(task_id).join("claude.log");
+        match std::fs::metadata(&log_file) {
+            Ok(metadata) => Ok(metadata.len()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(0),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// Bytes of the files under `path`, not following symlinks; 0 if it is gone.
+fn disk_usage(path: &Path) -> u64 {
+    let Ok(metadata) = std::fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+    std::fs::read_dir(path)
+        .map(|entries| entries.flatten().map(|entry| disk_usage(&entry.path())).sum())
+        .unwrap_or(0)
 }
 
 fn shell_escape(s: &str) -> String {
@@ -0,0 +1,3 @@
+mod mock_executor;
+
+pub use mock_executor::MockExecutor;
@@ -0,0 +1,165 @@
+use chrono::{DateTime, Duration, Utc};
+use executor_core::config::{ExecutorConfig, MockConfig};
+use executor_core::error::ExecutorError;
+use executor_core::log_filter::LogFilter;
+use executor_core::metadata::TaskMetadata;
+use executor_core::store::{default_store, MetadataStore};
+use executor_core::task::{TaskId, TaskRequest, TaskStatus};
+use executor_core::{CancellationToken, Executor};
+use std::sync::Arc;
+use tracing::{info, instrument};
+
+/// Mock executor: simulates tasks without running anything, for developing
+/// and testing integrations (dashboards, webhooks, queues) with no host to
+/// run on. A task runs for `mock.duration_secs`, printing `mock.log_lines`
+/// as it goes, then exits with `mock.exit_code`. Its state follows from its
+/// start time alone, so every command sees the same task wherever it runs.
+pub struct MockExecutor {
+    config: ExecutorConfig,
+    mock: MockConfig,
+    store: Arc<dyn MetadataStore>,
+}
+
+impl MockExecutor {
+    pub fn new(config: ExecutorConfig) -> Self {
+        Self::with_store(config, default_store())
+    }
+
+    pub fn with_store(config: ExecutorConfig, store: Arc<dyn MetadataStore>) -> Self {
+        let mock = config.mock.clone().unwrap_or_default();
+        Self {
+            config,
+            mock,
+            store,
+        }
+    }
+
+    /// How long each task runs, capped so task times stay in range.
+    fn duration(&self) -> Duration {
+        Duration::seconds(self.mock.duration_secs.min(i32::MAX as u64) as i64)
+    }
+
+    /// The lines a task has printed by `now`, or by when it finished: each
+    /// line is printed a fraction of the way through the run.
+    fn printed(&self, meta: &TaskMetadata) -> Vec<String> {
+        let until = meta.finished_at.unwrap_or_else(Utc::now);
+        let count = self.mock.log_lines.len() as i32;
+        self.mock
+            .log_lines
+            .iter()
+            .enumerate()
+            .map(|(i, line)| (meta.started_at + self.duration() * i as i32 / count, line))
+            .take_while(|(at, _)| *at <= until)
+            .map(|(at, line)| self.log_line(at, line))
+            .collect()
+    }
+
+    /// `line` as the log holds it, stamped if the executor has
+    /// `log_timestamps`.
+    fn log_line(&self, at: DateTime<Utc>, line: &str) -> String {
+        if self.config.log_timestamps {
+            format!("{} {}", at.format("%Y-%m-%dT%H:%M:%SZ"), line)
+        } else {
+            line.to_string()
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Executor for MockExecutor {
+    fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    fn executor_type(&self) -> &str {
+        "mock"
+    }
+
+    #[instrument(
+        skip_all,
+        fields(executor = %self.config.name, payload = request.payload.type_str())
+    )]
+    async fn start(
+        &self,
+        request: TaskRequest,
+        _cancel: &CancellationToken,
+    ) -> Result<TaskMetadata, ExecutorError> {
+        let task_id = TaskId::new();
+        let mut meta = TaskMetadata::new(
+            task_id.clone(),
+            self.config.name.clone(),
+            "mock".to_string(),
+            request.payload.type_str().to_string(),
+            request.payload.description().to_string(),
+            request.workspace.clone(),
+        );
+        meta.inherit_from(&request);
+        // Nothing runs, so there's no process ID
+        meta.status = TaskStatus::Running;
+        self.store.put(&meta)?;
+
+        info!(
+            "Mock task {} started; exits {} in {}s",
+            task_id, self.mock.exit_code, self.mock.duration_secs
+        );
+        Ok(meta)
+    }
+
+    #[instrument(skip_all, fields(executor = %self.config.name, task_id = %task_id))]
+    async fn status(
+        &self,
+        task_id: &TaskId,
+        _cancel: &CancellationToken,
+    ) -> Result<TaskMetadata, ExecutorError> {
+        let mut meta = self.store.load(task_id)?;
+        let ends_at = meta.started_at + self.duration();
+        if !meta.status.is_terminal() && Utc::now() >= ends_at {
+            meta.mark_completed(self.mock.exit_code);
+            meta.finished_at = Some(ends_at);
+            self.store.put(&meta)?;
+        }
+        Ok(meta)
+    }
+
+    #[instrument(skip_all, fields(executor = %self.config.name, task_id = %task_id))]
+    async fn logs(
+        &self,
+        task_id: &TaskId,
+        lines: usize,
+        _cancel: &CancellationToken,
+    ) -> Result<Vec<String>, ExecutorError> {
+        let printed = self.printed(&self.store.load(task_id)?);
+        Ok(printed[printed.len().saturating_sub(lines)..].to_vec())
+    }
+
+    async fn filtered_logs(
+        &self,
+        task_id: &TaskId,
+        lines: usize,
+        filter: &LogFilter,
+        _cancel: &CancellationToken,
+    ) -> Result<Vec<String>, ExecutorError> {
+        Ok(filter.apply(self.printed(&self.store.load(task_id)?), lines))
+    }
+
+    #[instrument(skip_all, fields(executor = %self.config.name, task_id = %task_id))]
+    async fn kill(&self, task_id: &TaskId, cancel: &CancellationToken) -> Result<(), ExecutorError> {
+        // A task whose run is over has finished rather than been killed
+        let mut meta = self.status(task_id, cancel).await?;
+        if !meta.status.is_terminal() {
+            meta.mark_killed(None);
+            self.store.put(&meta)?;
+        }
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(executor = %self.config.name, task_id = %task_id))]
+    async fn cleanup(
+        &self,
+        task_id: &TaskId,
+        _cancel: &CancellationToken,
+    ) -> Result<(), ExecutorError> {
+        self.store.delete(task_id)?;
+        Ok(())
+    }
+}
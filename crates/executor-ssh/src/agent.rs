@@ -0,0 +1,91 @@
+//! The remote agent (`agent_binary`): pushed to the host once per build,
+//! then run on one SSH channel that later calls on the executor reuse.
+
+use executor_agent::protocol::{self, Request, Response};
+use executor_core::error::ExecutorError;
+use sha2::{Digest, Sha256};
+use ssh2::{Channel, Session};
+use std::time::{Duration, Instant};
+
+/// How long a channel may sit unused before it is checked with a heartbeat.
+const IDLE_CHECK: Duration = Duration::from_secs(30);
+
+/// A running agent and the channel it speaks on.
+pub struct AgentConnection {
+    sess: Session,
+    channel: Channel,
+    last_used: Instant,
+    /// Where the agent runs from on the host.
+    pub path: String,
+    /// The agent's own version and protocol, as it said hello with.
+    pub version: String,
+    pub protocol: u32,
+}
+
+impl AgentConnection {
+    /// Run the agent at `path` on `sess` and ask which protocol it speaks.
+    /// Whether that is the one this build speaks is for the caller to check.
+    pub fn open(sess: Session, path: &str) -> Result<Self, ExecutorError> {
+        let mut channel = sess
+            .channel_session()
+            .map_err(|e| ExecutorError::SshCommand(format!("Channel: {}", e)))?;
+        channel
+            .exec(path)
+            .map_err(|e| ExecutorError::SshCommand(format!("Exec '{}': {}", path, e)))?;
+        let mut agent = Self {
+            sess,
+            channel,
+            last_used: Instant::now(),
+            path: path.to_string(),
+            version: String::new(),
+            protocol: 0,
+        };
+        match agent.call(&Request::Hello)? {
+            Response::Hello { protocol, version } => {
+                agent.protocol = protocol;
+                agent.version = version;
+                Ok(agent)
+            }
+            other => Err(unexpected(&other)),
+        }
+    }
+
+    /// The session the agent runs on, for plain commands alongside it.
+    pub fn session(&self) -> &Session {
+        &self.sess
+    }
+
+    /// Whether the agent still answers. Only asked after the channel has
+    /// been idle a while.
+    pub fn alive(&mut self) -> bool {
+        self.last_used.elapsed() < IDLE_CHECK
+            || matches!(self.call(&Request::Heartbeat), Ok(Response::Heartbeat))
+    }
+
+    /// Send `request` and read the agent's response. A failure the agent
+    /// reports is a [`ExecutorError::Process`]; any other error means the
+    /// channel is unusable.
+    pub fn call(&mut self, request: &Request) -> Result<Response, ExecutorError> {
+        protocol::write_frame(&mut self.channel, request)
+            .map_err(|e| ExecutorError::SshCommand(format!("Send to remote agent: {}", e)))?;
+        let frame = protocol::read_frame(&mut self.channel)
+            .map_err(|e| ExecutorError::SshCommand(format!("Read from remote agent: {}", e)))?
+            .ok_or_else(|| ExecutorError::SshCommand("Remote agent exited".into()))?;
+        self.last_used = Instant::now();
+        match serde_json::from_slice(&frame)? {
+            Response::Error { message } => Err(ExecutorError::Process(message)),
+            response => Ok(response),
+        }
+    }
+}
+
+pub fn unexpected(response: &Response) -> ExecutorError {
+    ExecutorError::SshCommand(format!("Unexpected reply from remote agent: {:?}", response))
+}
+
+/// File name for `binary` on the host: by content hash, so a new build
+/// never replaces the file a running agent was started from.
+pub fn file_name(binary: &[u8]) -> String {
+    let hash = hex::encode(Sha256::digest(binary));
+    format!("openclaw-remote-agent-{}", &hash[..16])
+}
@@ -1,3 +1,5 @@
+mod agent;
 mod ssh_executor;
+mod windows;
 
 pub use ssh_executor::SshExecutor;
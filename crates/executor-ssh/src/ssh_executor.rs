@@ -1,27 +1,101 @@
-use executor_core::config::ExecutorConfig;
+use crate::agent::{self, AgentConnection};
+use crate::windows;
+use executor_agent::protocol::{
+    Request, Response, StartRequest, TaskQuery, TaskState, PROTOCOL_VERSION,
+};
+use executor_core::activate;
+use executor_core::config::{ExecutorConfig, HostPlatform};
 use executor_core::error::ExecutorError;
-use executor_core::metadata::{metadata_dir, TaskMetadata};
+use executor_core::fixture::Fixture;
+use executor_core::git;
+use executor_core::host::{self, HostStats};
+use executor_core::kill;
+use executor_core::log_filter::LogFilter;
+use executor_core::log_timestamps;
+use executor_core::metadata::TaskMetadata;
+use executor_core::nix;
+use executor_core::progress::{self, TaskProgress};
+use executor_core::provision::{self, ProvisionReport};
+use executor_core::store::{default_store, MetadataStore};
 use executor_core::task::{TaskId, TaskPayload, TaskRequest, TaskStatus};
-use executor_core::Executor;
-use ssh2::Session;
-use std::io::Read;
+use executor_core::usage::{self, ResourceUsage};
+use executor_core::executor::{
+    resolve_path, AgentInfo, CleanupOutcome, FileRoot, RemoteTask, StartPlan, TaskChanges,
+};
+use executor_core::workspace;
+use executor_core::{CancellationToken, Executor};
+use serde::Deserialize;
+use ssh2::{OpenFlags, OpenType, Session};
+use std::collections::HashMap;
+use std::io::{Read, Write};
 use std::net::TcpStream;
-use std::path::PathBuf;
-use tracing::{debug, info, warn};
+use std::path::Path;
+use std::sync::{Arc, Mutex, OnceLock};
+use tracing::{debug, info, instrument, warn};
 
 /// SSH executor: connects to a remote host, runs claude or shell commands
-/// via nohup, tracks PID, and tails logs.
+/// via nohup, tracks PID, and tails logs. With `agent_binary`, launches,
+/// status checks, log tails and kills go through the remote agent instead.
+/// A Windows host is driven with PowerShell (see [`windows`]).
 pub struct SshExecutor {
     config: ExecutorConfig,
+    store: Arc<dyn MetadataStore>,
+    /// The remote agent, once started.
+    agent: Mutex<Option<AgentConnection>>,
+    /// Task root on a Windows host, once asked for.
+    windows_root: OnceLock<String>,
+    /// The user's home directory on a Unix host, once asked for (only when
+    /// `task_root` starts with `~`).
+    home: OnceLock<String>,
+    /// Where remote commands are recorded to or replayed from, if anywhere.
+    fixture: Option<Fixture>,
 }
 
 impl SshExecutor {
     pub fn new(config: ExecutorConfig) -> Self {
-        Self { config }
+        Self::with_store(config, default_store())
     }
 
-    /// Establish an SSH session to the configured host.
-    fn connect(&self) -> Result<Session, ExecutorError> {
+    pub fn with_store(config: ExecutorConfig, store: Arc<dyn MetadataStore>) -> Self {
+        Self {
+            fixture: config
+                .fixture
+                .clone()
+                .map(|fixture| Fixture::new(fixture, &config)),
+            config,
+            store,
+            agent: Mutex::new(None),
+            windows_root: OnceLock::new(),
+            home: OnceLock::new(),
+        }
+    }
+
+    /// Establish an SSH session to the configured host. Replaying a
+    /// fixture, the session is never connected: no command reaches it.
+    #[instrument(skip_all, fields(executor = %self.config.name))]
+    fn connect(&self, cancel: &CancellationToken) -> Result<Session, ExecutorError> {
+        if cancel.is_cancelled() {
+            return Err(ExecutorError::Cancelled);
+        }
+        let sess = match self.fixture {
+            Some(ref fixture) if fixture.is_replay() => Session::new()
+                .map_err(|e| ExecutorError::SshConnection(format!("Session::new: {}", e)))?,
+            _ => self.open_session()?,
+        };
+        if self.config.task_root().starts_with('~')
+            && self.home.get().is_none()
+            && !self.is_windows(&sess)
+        {
+            let home = self.exec_remote(&sess, "printf %s \"$HOME\"", cancel)?;
+            if !home.trim().is_empty() {
+                let _ = self.home.set(home.trim().trim_end_matches('/').to_string());
+            }
+        }
+        Ok(sess)
+    }
+
+    /// Connect and authenticate to the configured host.
+    fn open_session(&self) -> Result<Session, ExecutorError> {
         let host = self
             .config
             .host
@@ -35,8 +109,13 @@ impl SshExecutor {
         let port = self.config.ssh_port();
 
         debug!("Connecting to {}@{}:{}", user, host, port);
-        let tcp = TcpStream::connect(format!("{}:{}", host, port))
-            .map_err(|e| ExecutorError::SshConnection(format!("TCP connect to {}:{}: {}", host, port, e)))?;
+        let tcp = TcpStream::connect(format!("{}:{}", host, port)).map_err(|e| {
+            ExecutorError::SshConnection(format!(
+                "TCP connect to {}:{}: {}; check that the host is up and sshd listens on \
+                 port {} (try `ssh -p {} {}@{}`)",
+                host, port, e, port, port, user, host
+            ))
+        })?;
 
         let mut sess = Session::new()
             .map_err(|e| ExecutorError::SshConnection(format!("Session::new: {}", e)))?;
@@ -44,26 +123,84 @@ impl SshExecutor {
         sess.handshake()
             .map_err(|e| ExecutorError::SshConnection(format!("Handshake: {}", e)))?;
 
-        // Try key-based auth first
-        if let Some(key_path) = &self.config.key_path {
-            sess.userauth_pubkey_file(user, None, std::path::Path::new(key_path), None)
-                .map_err(|e| ExecutorError::SshConnection(format!("Pubkey auth: {}", e)))?;
-        } else {
-            // Try SSH agent
-            sess.userauth_agent(user)
-                .map_err(|e| ExecutorError::SshConnection(format!("Agent auth: {}", e)))?;
-        }
-
-        if !sess.authenticated() {
-            return Err(ExecutorError::SshConnection("Authentication failed".into()));
-        }
+        self.authenticate(&sess, user, host)?;
 
         info!("SSH connected to {}@{}:{}", user, host, port);
         Ok(sess)
     }
 
-    /// Execute a command on the remote host and return stdout.
-    fn exec_remote(&self, sess: &Session, cmd: &str) -> Result<String, ExecutorError> {
+    /// Authenticate as `user` with `key_path` if set, else through ssh-agent.
+    /// A failure says what was tried, what the server accepts and what to fix.
+    fn authenticate(&self, sess: &Session, user: &str, host: &str) -> Result<(), ExecutorError> {
+        let result = match &self.config.key_path {
+            Some(key_path) => sess.userauth_pubkey_file(user, None, Path::new(key_path), None),
+            None => sess.userauth_agent(user),
+        };
+        if result.is_ok() && sess.authenticated() {
+            return Ok(());
+        }
+
+        let reason = match result {
+            Err(e) => e.to_string(),
+            Ok(()) => "not accepted".to_string(),
+        };
+        let tried = match &self.config.key_path {
+            Some(key_path) => format!("key {}", key_path),
+            None => agent_identities(sess),
+        };
+        let accepted = sess
+            .auth_methods(user)
+            .map(str::to_string)
+            .unwrap_or_else(|_| "unknown".to_string());
+        let fix = match &self.config.key_path {
+            Some(key_path) if !Path::new(key_path).exists() => format!(
+                "key_path {} does not exist (~ is not expanded; give the full path)",
+                key_path
+            ),
+            Some(key_path) => format!(
+                "the key must be unencrypted and its public half in ~/.ssh/authorized_keys \
+                 for {} on {} (e.g. `ssh-copy-id -i {}.pub {}@{}`)",
+                user, host, key_path, user, host
+            ),
+            None if std::env::var_os("SSH_AUTH_SOCK").is_none() => {
+                "no ssh-agent is running (SSH_AUTH_SOCK is unset); start one and `ssh-add` \
+                 your key, or set key_path"
+                    .to_string()
+            }
+            None => format!("`ssh-add` a key {}@{} accepts, or set key_path", user, host),
+        };
+        Err(ExecutorError::SshConnection(format!(
+            "Authentication as {}@{} failed: {}; tried {}; the server accepts {}; {}",
+            user, host, reason, tried, accepted, fix
+        )))
+    }
+
+    /// Execute a command on the remote host and return stdout, recorded to
+    /// or replayed from the fixture if there is one. ssh2 calls block, so
+    /// cancellation is checked before each command.
+    #[instrument(skip_all)]
+    fn exec_remote(
+        &self,
+        sess: &Session,
+        cmd: &str,
+        cancel: &CancellationToken,
+    ) -> Result<String, ExecutorError> {
+        if cancel.is_cancelled() {
+            return Err(ExecutorError::Cancelled);
+        }
+        let Some(ref fixture) = self.fixture else {
+            return self.exec_channel(sess, cmd);
+        };
+        if let Some(replayed) = fixture.replay(cmd) {
+            return replayed;
+        }
+        let result = self.exec_channel(sess, cmd);
+        fixture.record(cmd, &result);
+        result
+    }
+
+    /// Run a command on a channel of its own and return stdout.
+    fn exec_channel(&self, sess: &Session, cmd: &str) -> Result<String, ExecutorError> {
         debug!("Remote exec: {}", cmd);
         let mut channel = sess
             .channel_session()
@@ -93,41 +230,370 @@ impl SshExecutor {
         Ok(output)
     }
 
+    /// Run `command` in a task's workspace, copying its combined
+    /// stdout/stderr to `out` as it arrives. Returns the exit code.
+    fn exec_in_workspace(
+        &self,
+        task_id: &TaskId,
+        command: &str,
+        out: &mut dyn Write,
+        cancel: &CancellationToken,
+    ) -> Result<i32, ExecutorError> {
+        let meta = self.store.load(task_id)?;
+        let workspace = meta.workspace.as_deref().unwrap_or("~");
+        let sess = self.connect(cancel)?;
+
+        let mut channel = sess
+            .channel_session()
+            .map_err(|e| ExecutorError::SshCommand(format!("Channel: {}", e)))?;
+        // Interleave stderr with stdout so output arrives in order
+        channel
+            .handle_extended_data(ssh2::ExtendedData::Merge)
+            .map_err(|e| ExecutorError::SshCommand(format!("Merge stderr: {}", e)))?;
+
+        let full_cmd = match self.windows_root(&sess, cancel)? {
+            Some(_) => windows::command(&windows::exec_script(workspace, command)),
+            None if self.config.env.is_empty() => {
+                format!("cd {} && sh -c {}", shell_path(workspace), shell_escape(command))
+            }
+            None => {
+                // A task started before `env` was set has no env file
+                let env_file = shell_path(&env_file(&self.remote_task_dir(task_id)));
+                format!(
+                    "cd {} && {{ [ ! -r {} ] || . {}; }} && sh -c {}",
+                    shell_path(workspace),
+                    env_file,
+                    env_file,
+                    shell_escape(command)
+                )
+            }
+        };
+        debug!("Remote exec: {}", full_cmd);
+        channel
+            .exec(&full_cmd)
+            .map_err(|e| ExecutorError::SshCommand(format!("Exec '{}': {}", full_cmd, e)))?;
+
+        // ssh2 reads block, so cancellation is checked between chunks
+        let mut buf = [0u8; 8192];
+        loop {
+            if cancel.is_cancelled() {
+                channel.close().ok();
+                return Err(ExecutorError::Cancelled);
+            }
+            let n = channel
+                .read(&mut buf)
+                .map_err(|e| ExecutorError::SshCommand(format!("Read output: {}", e)))?;
+            if n == 0 {
+                break;
+            }
+            out.write_all(&buf[..n])?;
+            out.flush()?;
+        }
+
+        channel.wait_close().ok();
+        Ok(channel.exit_status().unwrap_or(-1))
+    }
+
+    /// Send `request` to the remote agent. The channel from an earlier call
+    /// is reused while the agent answers on it; otherwise the agent is
+    /// started afresh.
+    fn agent_call(
+        &self,
+        request: &Request,
+        cancel: &CancellationToken,
+    ) -> Result<Response, ExecutorError> {
+        let mut agent = self.agent.lock().unwrap_or_else(|e| e.into_inner());
+        if agent.as_mut().is_some_and(|conn| !conn.alive()) {
+            debug!("Remote agent on {} stopped answering; restarting it", self.name());
+            *agent = None;
+        }
+        let conn = match agent.as_mut() {
+            Some(conn) => conn,
+            None => agent.insert(self.start_agent(false, cancel)?),
+        };
+        if cancel.is_cancelled() {
+            return Err(ExecutorError::Cancelled);
+        }
+        let result = conn.call(request);
+        if matches!(result, Err(ref e) if !matches!(e, ExecutorError::Process(_))) {
+            *agent = None;
+        }
+        result
+    }
+
+    /// Run the remote agent on a new connection, pushing this build of it to
+    /// the host first if it isn't there yet (or always, with `repush`). An
+    /// agent that won't start or speaks another protocol is pushed again once
+    /// before giving up.
+    fn start_agent(
+        &self,
+        repush: bool,
+        cancel: &CancellationToken,
+    ) -> Result<AgentConnection, ExecutorError> {
+        let local = self.config.agent_binary.as_deref().unwrap_or_default();
+        let binary = std::fs::read(local)
+            .map_err(|e| ExecutorError::Config(format!("agent_binary {}: {}", local, e)))?;
+
+        let sess = self.connect(cancel)?;
+        if self.windows_root(&sess, cancel)?.is_some() {
+            return Err(ExecutorError::Config(format!(
+                "agent_binary can't be used with {}, a Windows host",
+                self.name()
+            )));
+        }
+        let path = format!("{}/{}", self.agent_dir(), agent::file_name(&binary));
+        let present = self.exec_remote(&sess, &format!("test -x {} && echo yes", shell_path(&path)), cancel)?;
+        let mut push = repush || present.trim() != "yes";
+        loop {
+            if push {
+                self.push_agent(&sess, &binary, &path, cancel)?;
+            }
+            let problem = match AgentConnection::open(sess.clone(), &path) {
+                Ok(conn) if conn.protocol == PROTOCOL_VERSION => {
+                    if conn.version != env!("CARGO_PKG_VERSION") {
+                        warn!(
+                            "Remote agent on {} is version {}, openclaw-agent is {}; \
+                             rebuild agent_binary and run `openclaw-agent agent upgrade`",
+                            self.name(),
+                            conn.version,
+                            env!("CARGO_PKG_VERSION")
+                        );
+                    }
+                    return Ok(conn);
+                }
+                Ok(conn) => format!(
+                    "speaks protocol {} (version {}), expected {}",
+                    conn.protocol, conn.version, PROTOCOL_VERSION
+                ),
+                Err(ExecutorError::Cancelled) => return Err(ExecutorError::Cancelled),
+                Err(e) => format!("failed to start: {}", e),
+            };
+            if push {
+                return Err(ExecutorError::Config(format!(
+                    "Remote agent {} on {} {}; is agent_binary ({}) built from this \
+                     openclaw-agent's source for the host's platform?",
+                    path,
+                    self.name(),
+                    problem,
+                    local
+                )));
+            }
+            warn!("Remote agent on {} {}; pushing it again", self.name(), problem);
+            push = true;
+        }
+    }
+
+    /// Copy `binary` to `path` on the host, written aside and renamed so a
+    /// half-pushed agent is never run.
+    fn push_agent(
+        &self,
+        sess: &Session,
+        binary: &[u8],
+        path: &str,
+        cancel: &CancellationToken,
+    ) -> Result<(), ExecutorError> {
+        info!("Pushing remote agent to {} on {}", path, self.name());
+        self.exec_remote(sess, &format!("mkdir -p {}", shell_path(&self.agent_dir())), cancel)?;
+        let partial = format!("{}.{}.partial", path, std::process::id());
+        self.sftp_write(sess, &partial, binary, 0o644)?;
+        let (partial_arg, path_arg) = (shell_path(&partial), shell_path(path));
+        self.exec_remote(
+            sess,
+            &format!("chmod 755 {} && mv {} {}", partial_arg, partial_arg, path_arg),
+            cancel,
+        )?;
+        Ok(())
+    }
+
+    /// Write `contents` to the file `path` on the host over SFTP, creating it
+    /// with permissions `mode`.
+    fn sftp_write(
+        &self,
+        sess: &Session,
+        path: &str,
+        contents: &[u8],
+        mode: i32,
+    ) -> Result<(), ExecutorError> {
+        let sftp = sess
+            .sftp()
+            .map_err(|e| ExecutorError::SshCommand(format!("SFTP: {}", e)))?;
+        let flags = OpenFlags::WRITE | OpenFlags::CREATE | OpenFlags::TRUNCATE;
+        let mut remote = sftp
+            .open_mode(Path::new(path), flags, mode, OpenType::File)
+            .map_err(|e| ExecutorError::SshCommand(format!("Create {}: {}", path, e)))?;
+        remote
+            .write_all(contents)
+            .map_err(|e| ExecutorError::SshCommand(format!("Write {}: {}", path, e)))
+    }
+
+    /// Each task's state on the host, given its ID and recorded PID.
+    fn check_tasks(
+        &self,
+        checks: Vec<(&TaskId, Option<u32>)>,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<TaskState>, ExecutorError> {
+        if self.config.agent_binary.is_some() {
+            let tasks = checks
+                .into_iter()
+                .map(|(task_id, pid)| TaskQuery {
+                    task_dir: self.remote_task_dir(task_id),
+                    pid,
+                })
+                .collect();
+            return match self.agent_call(&Request::Status { tasks }, cancel)? {
+                Response::Status { tasks } => Ok(tasks),
+                other => Err(agent::unexpected(&other)),
+            };
+        }
+        let sess = self.connect(cancel)?;
+        let mut remote: Vec<RemoteStatus> = Vec::new();
+        match self.windows_root(&sess, cancel)? {
+            Some(root) => {
+                let checks: Vec<(String, Option<u32>)> = checks
+                    .into_iter()
+                    .map(|(task_id, pid)| (windows_task_dir(&root, task_id), pid))
+                    .collect();
+                for batch in checks.chunks(windows::STATUS_BATCH) {
+                    let script = windows::command(&windows::status_script(batch));
+                    let output = self.exec_remote(&sess, &script, cancel)?;
+                    remote.extend(serde_json::from_str::<Vec<RemoteStatus>>(output.trim())?);
+                }
+            }
+            None => {
+                let checks: Vec<(String, Option<u32>)> = checks
+                    .into_iter()
+                    .map(|(task_id, pid)| (self.remote_task_dir(task_id), pid))
+                    .collect();
+                let output = self.exec_remote(&sess, &status_script(&checks), cancel)?;
+                remote = serde_json::from_str(output.trim())?;
+            }
+        }
+        Ok(remote.into_iter().map(RemoteStatus::into_state).collect())
+    }
+
+    /// The task root on a Unix host, with a leading `~` expanded once
+    /// `connect` has asked for the home directory (and left for the remote
+    /// shell to expand before then).
+    fn remote_root(&self) -> String {
+        let root = self.config.task_root();
+        match (root.strip_prefix('~'), self.home.get()) {
+            (Some(rest), Some(home)) => format!("{}{}", home, rest),
+            _ => root.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Where agent builds are kept on the host; hidden from `list_remote`.
+    fn agent_dir(&self) -> String {
+        format!("{}/.agent", self.remote_root())
+    }
+
     /// Remote directory for task metadata/logs.
     fn remote_task_dir(&self, task_id: &TaskId) -> String {
-        format!("/tmp/openclaw-tasks/{}", task_id)
+        format!("{}/{}", self.remote_root(), task_id)
     }
 
-    /// Local metadata directory for this task.
-    fn local_meta_dir(&self) -> PathBuf {
-        metadata_dir()
+    /// Whether the host runs Windows, as `platform` says or else the SSH
+    /// server's banner.
+    fn is_windows(&self, sess: &Session) -> bool {
+        match self.config.platform {
+            Some(platform) => platform == HostPlatform::Windows,
+            None => sess.banner().is_some_and(windows::banner_is_windows),
+        }
     }
-}
 
-#[async_trait::async_trait]
-impl Executor for SshExecutor {
-    fn name(&self) -> &str {
-        &self.config.name
+    /// The task root if the host runs Windows: `task_root`, or else
+    /// `openclaw-tasks` in the user's temp dir.
+    fn windows_root(
+        &self,
+        sess: &Session,
+        cancel: &CancellationToken,
+    ) -> Result<Option<String>, ExecutorError> {
+        if !self.is_windows(sess) {
+            return Ok(None);
+        }
+        if let Some(root) = self.windows_root.get() {
+            return Ok(Some(root.clone()));
+        }
+        let script = windows::root_script(self.config.task_root.as_deref());
+        let output = self.exec_remote(sess, &windows::command(&script), cancel)?;
+        let root = output.trim().trim_end_matches('\\');
+        if root.is_empty() {
+            return Err(ExecutorError::SshCommand(format!(
+                "{} is a Windows host but PowerShell didn't report its task root",
+                self.name()
+            )));
+        }
+        let root = self.windows_root.get_or_init(|| root.to_string());
+        Ok(Some(root.clone()))
     }
 
-    fn executor_type(&self) -> &str {
-        "ssh"
-    }
+    /// `start` on a Windows host. The task's run.ps1 (see
+    /// [`windows::run_script`]) is written over SFTP and launched from its
+    /// dir; clones, workspace templates and task branches aren't supported.
+    fn start_windows(
+        &self,
+        sess: &Session,
+        root: &str,
+        task_id: TaskId,
+        mut request: TaskRequest,
+        cancel: &CancellationToken,
+    ) -> Result<TaskMetadata, ExecutorError> {
+        let template =
+            workspace::apply_template(self.config.workspace_template.as_deref(), &mut request);
+        if request.repo.is_some() || template.is_some() || request.git_branch {
+            return Err(ExecutorError::Unsupported(
+                "--repo, workspace_template and --git-branch on a Windows host".into(),
+            ));
+        }
+        if nix::for_request(&self.config, &request).is_some() {
+            return Err(ExecutorError::Unsupported("nix_shell on a Windows host".into()));
+        }
+        if self.config.activate.is_some() {
+            return Err(ExecutorError::Unsupported("activate on a Windows host".into()));
+        }
+        if !self.config.env.is_empty() {
+            return Err(ExecutorError::Unsupported("env on a Windows host".into()));
+        }
+        let task_dir = windows_task_dir(root, &task_id);
+        let workspace_dir = request.workspace.as_deref().unwrap_or("~");
+        self.exec_remote(sess, &windows::command(&windows::mkdir_script(&task_dir)), cancel)?;
 
-    async fn start(&self, request: TaskRequest) -> Result<TaskMetadata, ExecutorError> {
-        let task_id = TaskId::new();
-        let sess = self.connect()?;
+        let statement = windows::payload_statement(&self.config, &request.payload);
+        let run = windows::run_script(&task_dir, &statement, self.config.log_timestamps);
+        let run_path = windows::sftp_path(&format!("{}\\run.ps1", task_dir));
+        self.sftp_write(sess, &run_path, run.as_bytes(), 0o644)?;
 
-        let task_dir = self.remote_task_dir(&task_id);
-        self.exec_remote(&sess, &format!("mkdir -p {}", task_dir))?;
+        info!("Starting task {} on {} (Windows)", task_id, self.name());
+        let launch = windows::command(&windows::launch_script(&task_dir, workspace_dir));
+        let output = self.exec_remote(sess, &launch, cancel)?;
+        let pid = output
+            .trim()
+            .parse()
+            .map_err(|_| ExecutorError::Process(format!("Invalid PID: '{}'", output.trim())))?;
+        info!("Task {} started with PID {} on {}", task_id, pid, self.name());
 
-        let workspace = request.workspace.as_deref().unwrap_or("~");
-        let log_file = format!("{}/claude.log", task_dir);
-        let pid_file = format!("{}/claude.pid", task_dir);
-        let exit_file = format!("{}/claude.exitcode", task_dir);
+        let mut meta = TaskMetadata::new(
+            task_id.clone(),
+            self.config.name.clone(),
+            "ssh".to_string(),
+            request.payload.type_str().to_string(),
+            request.payload.description().to_string(),
+            request.workspace.clone(),
+        );
+        meta.inherit_from(&request);
+        meta.mark_running(pid);
+        self.store.put(&meta)?;
 
-        // Build the inner command based on payload type, then wrap in a subshell
-        // that writes exit code: ( cd <dir> && <cmd> > log 2>&1; echo $? > exitcode ) & echo $! > pid
+        let meta_json = serde_json::to_string_pretty(&meta)
+            .map_err(|e| ExecutorError::SshCommand(format!("Serialize meta: {}", e)))?;
+        let meta_path = windows::sftp_path(&format!("{}\\{}.meta.json", task_dir, task_id));
+        self.sftp_write(sess, &meta_path, meta_json.as_bytes(), 0o644)?;
+        Ok(meta)
+    }
+
+    /// The command a task's payload runs, inside its `activate` environment
+    /// and Nix shell, with its `env` file in `task_dir` sourced first.
+    fn task_command(&self, request: &TaskRequest, task_dir: &str) -> String {
         let inner_cmd = match &request.payload {
             TaskPayload::ClaudeCode {
                 prompt,
@@ -136,8 +602,9 @@ impl Executor for SshExecutor {
             } => {
                 let claude_bin = self.config.claude_binary();
                 let mut claude_args = format!(
-                    "{} --print --output-format json -p {}",
+                    "{} --print {} -p {}",
                     claude_bin,
+                    self.config.claude_output_args(),
                     shell_escape(prompt)
                 );
 
@@ -155,23 +622,179 @@ impl Executor for SshExecutor {
                 format!("sh -c {}", shell_escape(command))
             }
         };
+        let inner_cmd = match self.config.activate.as_deref() {
+            Some(activate) => activate::wrap(activate, &inner_cmd),
+            None => inner_cmd,
+        };
+        let inner_cmd = match nix::for_request(&self.config, request) {
+            Some(shell) => nix::wrap(shell, &inner_cmd),
+            None => inner_cmd,
+        };
+        if self.config.env.is_empty() {
+            return inner_cmd;
+        }
+        format!(". {} && {}", shell_path(&env_file(task_dir)), inner_cmd)
+    }
+
+    /// The remote `sh` line that launches `inner_cmd` in the background,
+    /// writing its log, exit code and PID to `task_dir`. The command is
+    /// wrapped in a shell that writes the exit code, in a process group of
+    /// its own for `kill`:
+    /// setsid sh -c 'cd <dir> && <cmd> > log 2>&1; echo $? > exitcode' & echo $! > pid
+    /// With log_timestamps the output goes through the stamper, and the exit
+    /// code is written inside the pipeline so it's the task's own:
+    /// setsid sh -c 'cd <dir> && { <cmd> 2>&1; echo $? > exitcode; } | stamp > log' & ...
+    fn launch_command(&self, inner_cmd: &str, workspace_dir: &str, task_dir: &str) -> String {
+        let log_file = shell_path(&format!("{}/claude.log", task_dir));
+        let pid_file = shell_path(&format!("{}/claude.pid", task_dir));
+        let exit_file = shell_path(&format!("{}/claude.exitcode", task_dir));
+        let workspace_dir = shell_path(workspace_dir);
+        let wrapped = if self.config.log_timestamps {
+            format!(
+                "cd {} && {{ {} 2>&1; echo $? > {}; }} | {} > {}",
+                workspace_dir,
+                inner_cmd,
+                exit_file,
+                log_timestamps::STAMP_PIPE,
+                log_file
+            )
+        } else {
+            format!(
+                "cd {} && {} > {} 2>&1; echo $? > {}",
+                workspace_dir, inner_cmd, log_file, exit_file
+            )
+        };
+        format!("setsid sh -c {} & echo $! > {}", shell_escape(&wrapped), pid_file)
+    }
+
+    /// Where a task file `path`, relative to `root`, lives, in a form both
+    /// SFTP and the remote shell understand: SFTP doesn't expand `~`, but
+    /// both resolve relative paths against the home directory.
+    fn file_path(
+        &self,
+        task_id: &TaskId,
+        path: &str,
+        root: FileRoot,
+    ) -> Result<String, ExecutorError> {
+        let dir = match root {
+            FileRoot::Workspace => {
+                let meta = self.store.load(task_id)?;
+                meta.workspace.unwrap_or_else(|| "~".to_string())
+            }
+            FileRoot::TaskDir => self.remote_task_dir(task_id),
+        };
+        let resolved = resolve_path(path, &dir);
+        Ok(resolved.strip_prefix("~/").unwrap_or(&resolved).to_string())
+    }
+}
 
-        let full_cmd = format!(
-            "( cd {} && {} > {} 2>&1; echo $? > {} ) & echo $! > {}",
-            workspace, inner_cmd, log_file, exit_file, pid_file
-        );
+#[async_trait::async_trait]
+impl Executor for SshExecutor {
+    fn name(&self) -> &str {
+        &self.config.name
+    }
 
-        info!("Starting task {} on {}: {}", task_id, self.name(), full_cmd);
-        self.exec_remote(&sess, &full_cmd)?;
+    fn executor_type(&self) -> &str {
+        "ssh"
+    }
 
-        // Read the PID
-        let pid_str = self
-            .exec_remote(&sess, &format!("cat {}", pid_file))?
-            .trim()
-            .to_string();
-        let pid: u32 = pid_str
-            .parse()
-            .map_err(|_| ExecutorError::Process(format!("Invalid PID: '{}'", pid_str)))?;
+    #[instrument(
+        skip_all,
+        fields(executor = %self.config.name, payload = request.payload.type_str())
+    )]
+    async fn start(
+        &self,
+        mut request: TaskRequest,
+        cancel: &CancellationToken,
+    ) -> Result<TaskMetadata, ExecutorError> {
+        let task_id = TaskId::new();
+        let sess = self.connect(cancel)?;
+        if let Some(root) = self.windows_root(&sess, cancel)? {
+            return self.start_windows(&sess, &root, task_id, request, cancel);
+        }
+        let env = self.config.resolved_env()?;
+
+        let task_dir = self.remote_task_dir(&task_id);
+        self.exec_remote(&sess, &format!("mkdir -p {}", shell_path(&task_dir)), cancel)?;
+
+        // Values go in a file only the user can read, never on a remote
+        // command line where other users could see them
+        if !env.is_empty() {
+            let env_file = env_file(&task_dir);
+            let sftp_path = env_file.strip_prefix("~/").unwrap_or(&env_file);
+            self.sftp_write(&sess, sftp_path, env_script(&env).as_bytes(), 0o600)?;
+        }
+
+        // A --repo task gets a fresh clone of its own instead of --workspace,
+        // and so does a task started from a workspace_template
+        let template =
+            workspace::apply_template(self.config.workspace_template.as_deref(), &mut request);
+        let (workspace, commit) = match (&request.repo, template) {
+            (Some(repo), _) => {
+                let dest = format!("{}/workspace", task_dir);
+                info!("Cloning {} into {} on {}", repo.url, dest, self.name());
+                let script = git::clone_script(repo, &dest, &self.config.git);
+                let output = self.exec_remote(&sess, &script, cancel)?;
+                (Some(dest), Some(git::parse_commit(&output)?))
+            }
+            (None, Some(template)) => {
+                let dest = format!("{}/workspace", task_dir);
+                info!("Copying workspace template {} into {} on {}", template, dest, self.name());
+                let script = workspace::copy_script(&template, &dest);
+                workspace::parse_copy(&self.exec_remote(&sess, &script, cancel)?)?;
+                (Some(dest), None)
+            }
+            (None, None) => (request.workspace.clone(), None),
+        };
+        let workspace_dir = workspace.as_deref().unwrap_or("~");
+
+        // Give the task a branch of its own so its changes can be diffed at the end
+        let base_commit = if request.git_branch {
+            let script = format!(
+                "cd {} && {}",
+                shell_path(workspace_dir),
+                git::branch_script(&task_id, &task_dir)
+            );
+            let output = self.exec_remote(&sess, &script, cancel)?;
+            let base = git::parse_sha(&output);
+            match output.trim() {
+                _ if base.is_some() => {}
+                "" => warn!("{} is not a git work tree; no task branch created", workspace_dir),
+                error => warn!("Failed to create a task branch in {}: {}", workspace_dir, error),
+            }
+            base
+        } else {
+            None
+        };
+        let inner_cmd = self.task_command(&request, &task_dir);
+
+        // The remote agent does the same wrapping itself
+        let pid = if self.config.agent_binary.is_some() {
+            let request = Request::Start(StartRequest {
+                task_dir: task_dir.clone(),
+                workspace: workspace_dir.to_string(),
+                command: inner_cmd,
+                timestamps: self.config.log_timestamps,
+            });
+            info!("Starting task {} on {} through the remote agent", task_id, self.name());
+            match self.agent_call(&request, cancel)? {
+                Response::Started { pid } => pid,
+                other => return Err(agent::unexpected(&other)),
+            }
+        } else {
+            let full_cmd = self.launch_command(&inner_cmd, workspace_dir, &task_dir);
+            info!("Starting task {} on {}: {}", task_id, self.name(), full_cmd);
+            self.exec_remote(&sess, &full_cmd, cancel)?;
+
+            // Read the PID
+            let pid_str = self
+                .exec_remote(&sess, &format!("cat {}", shell_path(&format!("{}/claude.pid", task_dir))), cancel)?
+                .trim()
+                .to_string();
+            pid_str
+                .parse()
+                .map_err(|_| ExecutorError::Process(format!("Invalid PID: '{}'", pid_str)))?
+        };
 
         info!("Task {} started with PID {} on {}", task_id, pid, self.name());
 
@@ -182,14 +805,18 @@ impl Executor for SshExecutor {
             "ssh".to_string(),
             request.payload.type_str().to_string(),
             request.payload.description().to_string(),
-            request.workspace,
+            workspace,
         );
+        meta.inherit_from(&request);
+        meta.commit = commit;
+        if base_commit.is_some() {
+            meta.git_branch = Some(git::branch_name(&task_id));
+            meta.base_commit = base_commit;
+        }
         meta.mark_running(pid);
 
-        // Write .meta.json locally
-        let local_dir = self.local_meta_dir();
-        std::fs::create_dir_all(&local_dir)?;
-        meta.write_to_dir(&local_dir)?;
+        // Record metadata locally
+        self.store.put(&meta)?;
 
         // Write .meta.json on remote too
         let meta_json = serde_json::to_string_pretty(&meta)
@@ -197,44 +824,147 @@ impl Executor for SshExecutor {
         self.exec_remote(
             &sess,
             &format!(
-                "cat > {}/{}.meta.json << 'METAEOF'\n{}\nMETAEOF",
-                task_dir, task_id, meta_json
+                "cat > {} << 'METAEOF'\n{}\nMETAEOF",
+                shell_path(&format!("{}/{}.meta.json", task_dir, task_id)),
+                meta_json
             ),
+            cancel,
         )?;
 
         Ok(meta)
     }
 
-    async fn status(&self, task_id: &TaskId) -> Result<TaskMetadata, ExecutorError> {
-        // Try reading local metadata first
-        let local_dir = self.local_meta_dir();
-        let local_path = local_dir.join(format!("{}.meta.json", task_id));
+    fn plan_start(&self, mut request: TaskRequest) -> Result<StartPlan, ExecutorError> {
+        if self.config.platform == Some(HostPlatform::Windows) {
+            return Err(ExecutorError::Unsupported("start --dry-run on a Windows host".into()));
+        }
+        // Nothing is asked of the host, so a `~` task root is left unexpanded
+        let task_id = TaskId::new();
+        let task_dir = self.remote_task_dir(&task_id);
+        let mut steps = vec![format!("Create {} on {}", task_dir, self.name())];
+
+        let template =
+            workspace::apply_template(self.config.workspace_template.as_deref(), &mut request);
+        let dest = format!("{}/workspace", task_dir);
+        let workspace = match (&request.repo, template) {
+            (Some(repo), _) => {
+                steps.push(format!("Clone {} into {}", repo.url, dest));
+                Some(dest)
+            }
+            (None, Some(template)) => {
+                steps.push(format!("Copy workspace template {} into {}", template, dest));
+                Some(dest)
+            }
+            (None, None) => request.workspace.clone(),
+        };
+        let workspace_dir = workspace.as_deref().unwrap_or("~");
+        if request.git_branch {
+            steps.push(format!(
+                "Create branch {} in {}",
+                git::branch_name(&task_id),
+                workspace_dir
+            ));
+        }
 
-        let mut meta = if local_path.exists() {
-            TaskMetadata::read_from_file(&local_path)?
+        if !self.config.env.is_empty() {
+            steps.push(format!("Write env to {}, readable only by the user", env_file(&task_dir)));
+        }
+        let inner_cmd = self.task_command(&request, &task_dir);
+        let command = if self.config.agent_binary.is_some() {
+            steps.push("Launch through the remote agent, which wraps the command itself".into());
+            serde_json::to_string_pretty(&Request::Start(StartRequest {
+                task_dir: task_dir.clone(),
+                workspace: workspace_dir.to_string(),
+                command: inner_cmd,
+                timestamps: self.config.log_timestamps,
+            }))?
         } else {
-            return Err(ExecutorError::TaskNotFound(task_id.to_string()));
+            self.launch_command(&inner_cmd, workspace_dir, &task_dir)
         };
 
+        let mut metadata = TaskMetadata::new(
+            task_id.clone(),
+            self.config.name.clone(),
+            "ssh".to_string(),
+            request.payload.type_str().to_string(),
+            request.payload.description().to_string(),
+            workspace,
+        );
+        metadata.inherit_from(&request);
+        if request.git_branch {
+            metadata.git_branch = Some(git::branch_name(&task_id));
+        }
+        Ok(StartPlan {
+            steps,
+            command,
+            metadata,
+        })
+    }
+
+    #[instrument(skip_all, fields(executor = %self.config.name, task_id = %task_id))]
+    async fn status(
+        &self,
+        task_id: &TaskId,
+        cancel: &CancellationToken,
+    ) -> Result<TaskMetadata, ExecutorError> {
+        // Try reading local metadata first
+        let mut meta = self.store.load(task_id)?;
+
+        // The agent checks PID file, process and exit code in one request
+        if self.config.agent_binary.is_some() {
+            let mut tasks = self.status_many(std::slice::from_ref(task_id), cancel).await?;
+            return tasks.pop().ok_or_else(|| ExecutorError::TaskNotFound(task_id.to_string()));
+        }
+
+        let unsettled = match meta.status {
+            TaskStatus::Starting => true,
+            TaskStatus::Running => meta.pid.is_some(),
+            _ => false,
+        };
+        if !unsettled {
+            return Ok(meta);
+        }
+        let sess = self.connect(cancel)?;
+
+        // A Windows host gets the one PowerShell check status_many uses too
+        if self.windows_root(&sess, cancel)?.is_some() {
+            let mut tasks = self.status_many(std::slice::from_ref(task_id), cancel).await?;
+            return tasks.pop().ok_or_else(|| ExecutorError::TaskNotFound(task_id.to_string()));
+        }
+
+        // Never recorded as running: the remote PID file tells whether it launched
+        if meta.status == TaskStatus::Starting {
+            let pid_file = format!("{}/claude.pid", self.remote_task_dir(task_id));
+            let pid = self.exec_remote(&sess, &format!("cat {} 2>/dev/null", shell_path(&pid_file)), cancel)?;
+            match pid.trim().parse() {
+                Ok(pid) => meta.mark_running(pid),
+                Err(_) => meta.mark_failed("Launch never happened: no PID file".into()),
+            }
+            self.store.put(&meta)?;
+        }
+
         // Check if the process is still running on remote
         if meta.status == TaskStatus::Running {
             if let Some(pid) = meta.pid {
-                let sess = self.connect()?;
-                let check = self.exec_remote(&sess, &format!("kill -0 {} 2>/dev/null && echo running || echo stopped", pid))?;
+                let check = self.exec_remote(&sess, &format!("kill -0 {} 2>/dev/null && echo running || echo stopped", pid), cancel)?;
                 let check = check.trim();
 
                 if check == "stopped" {
                     // Process finished — read exit code from file written by the subshell wrapper
                     let task_dir = self.remote_task_dir(task_id);
                     let exit_file = format!("{}/claude.exitcode", task_dir);
-                    let exit_output = self
-                        .exec_remote(&sess, &format!("cat {} 2>/dev/null || echo 0", exit_file))
-                        .unwrap_or_else(|_| "0".to_string());
-                    let exit_code: i32 = exit_output.trim().parse().unwrap_or(0);
-                    meta.mark_completed(exit_code);
+                    let exit_output = self.exec_remote(
+                        &sess,
+                        &format!("cat {} 2>/dev/null", shell_path(&exit_file)),
+                        cancel,
+                    )?;
+                    match exit_output.trim().parse() {
+                        Ok(exit_code) => meta.mark_completed(exit_code),
+                        Err(_) => meta.mark_failed(NO_EXIT_CODE.into()),
+                    }
 
                     // Update local metadata
-                    meta.write_to_dir(&local_dir)?;
+                    self.store.put(&meta)?;
                 }
             }
         }
@@ -242,58 +972,631 @@ impl Executor for SshExecutor {
         Ok(meta)
     }
 
-    async fn logs(&self, task_id: &TaskId, lines: usize) -> Result<Vec<String>, ExecutorError> {
-        let sess = self.connect()?;
+    #[instrument(skip_all, fields(executor = %self.config.name, tasks = task_ids.len()))]
+    async fn status_many(
+        &self,
+        task_ids: &[TaskId],
+        cancel: &CancellationToken,
+    ) -> Result<Vec<TaskMetadata>, ExecutorError> {
+        let mut tasks = Vec::new();
+        for task_id in task_ids {
+            tasks.extend(self.store.get(task_id)?);
+        }
+
+        // Only tasks `status` would ask the host about; one round-trip for all
+        let unsettled: Vec<usize> = (0..tasks.len())
+            .filter(|&i| match tasks[i].status {
+                TaskStatus::Starting => true,
+                TaskStatus::Running => tasks[i].pid.is_some(),
+                _ => false,
+            })
+            .collect();
+        if unsettled.is_empty() {
+            return Ok(tasks);
+        }
+        let checks: Vec<(&TaskId, Option<u32>)> = unsettled
+            .iter()
+            .map(|&i| (&tasks[i].task_id, tasks[i].pid))
+            .collect();
+        let remote = self.check_tasks(checks, cancel)?;
+        if remote.len() != unsettled.len() {
+            return Err(ExecutorError::SshCommand(format!(
+                "Status check returned {} results for {} tasks",
+                remote.len(),
+                unsettled.len()
+            )));
+        }
+
+        // Same transitions as `status`
+        for (&i, remote) in unsettled.iter().zip(remote) {
+            let meta = &mut tasks[i];
+            let recorded = meta.status;
+            if meta.status == TaskStatus::Starting {
+                match remote.pid {
+                    Some(pid) => meta.mark_running(pid),
+                    None => meta.mark_failed("Launch never happened: no PID file".into()),
+                }
+            }
+            if meta.status == TaskStatus::Running && !remote.alive {
+                match remote.exit_code {
+                    Some(exit_code) => meta.mark_completed(exit_code),
+                    None => meta.mark_failed(NO_EXIT_CODE.into()),
+                }
+            }
+            if meta.status != recorded {
+                self.store.put(meta)?;
+            }
+        }
+
+        Ok(tasks)
+    }
+
+    #[instrument(skip_all, fields(executor = %self.config.name, task_id = %task_id))]
+    async fn logs(
+        &self,
+        task_id: &TaskId,
+        lines: usize,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<String>, ExecutorError> {
         let task_dir = self.remote_task_dir(task_id);
         let log_file = format!("{}/claude.log", task_dir);
 
-        let output = self.exec_remote(&sess, &format!("tail -n {} {}", lines, log_file))?;
+        if self.config.agent_binary.is_some() {
+            let request = Request::Tail {
+                file: log_file,
+                lines,
+            };
+            return match self.agent_call(&request, cancel)? {
+                Response::Lines { lines } => Ok(lines),
+                other => Err(agent::unexpected(&other)),
+            };
+        }
+
+        let sess = self.connect(cancel)?;
+        let script = match self.windows_root(&sess, cancel)? {
+            Some(root) => {
+                let log_file = format!("{}\\claude.log", windows_task_dir(&root, task_id));
+                windows::command(&windows::tail_script(&log_file, lines))
+            }
+            None => format!("tail -n {} {}", lines, shell_path(&log_file)),
+        };
+        let output = self.exec_remote(&sess, &script, cancel)?;
 
         Ok(output.lines().map(|l| l.to_string()).collect())
     }
 
-    async fn kill(&self, task_id: &TaskId) -> Result<(), ExecutorError> {
-        let local_dir = self.local_meta_dir();
-        let local_path = local_dir.join(format!("{}.meta.json", task_id));
+    #[instrument(skip_all, fields(executor = %self.config.name, task_id = %task_id))]
+    async fn filtered_logs(
+        &self,
+        task_id: &TaskId,
+        lines: usize,
+        filter: &LogFilter,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<String>, ExecutorError> {
+        let sess = self.connect(cancel)?;
+        if self.windows_root(&sess, cancel)?.is_some() {
+            return Err(ExecutorError::Unsupported("log filters on a Windows host".into()));
+        }
+        let log_file = format!("{}/claude.log", self.remote_task_dir(task_id));
+        let script = filter.script(&shell_path(&log_file), lines);
+        let output = self.exec_remote(&sess, &script, cancel)?;
+
+        Ok(output.lines().map(|l| l.to_string()).collect())
+    }
 
-        let mut meta = if local_path.exists() {
-            TaskMetadata::read_from_file(&local_path)?
-        } else {
-            return Err(ExecutorError::TaskNotFound(task_id.to_string()));
-        };
+    #[instrument(skip_all, fields(executor = %self.config.name, task_id = %task_id))]
+    async fn kill(&self, task_id: &TaskId, cancel: &CancellationToken) -> Result<(), ExecutorError> {
+        let mut meta = self.store.load(task_id)?;
 
         if let Some(pid) = meta.pid {
-            let sess = self.connect()?;
-            warn!("Killing task {} (PID {}) on {}", task_id, pid, self.name());
-            self.exec_remote(&sess, &format!("kill {} 2>/dev/null || true", pid))?;
+            let grace_secs = self.config.kill_grace_secs();
+            warn!(
+                "Killing task {} (PID {}, SIGKILL after {}s) on {}",
+                task_id,
+                pid,
+                grace_secs,
+                self.name()
+            );
+            let output = if self.config.agent_binary.is_some() {
+                match self.agent_call(&Request::Kill { pid, grace_secs }, cancel)? {
+                    Response::Killed { signal } => signal.unwrap_or_default(),
+                    other => return Err(agent::unexpected(&other)),
+                }
+            } else {
+                let sess = self.connect(cancel)?;
+                let script = match self.windows_root(&sess, cancel)? {
+                    Some(_) => windows::command(&windows::terminate_script(pid, grace_secs)),
+                    None => kill::terminate_script(pid, grace_secs),
+                };
+                self.exec_remote(&sess, &script, cancel)?
+            };
 
-            meta.mark_killed();
-            meta.write_to_dir(&local_dir)?;
+            meta.mark_killed(kill::parse_signal(&output));
+            self.store.put(&meta)?;
         }
 
         Ok(())
     }
 
-    async fn cleanup(&self, task_id: &TaskId) -> Result<(), ExecutorError> {
-        let sess = self.connect()?;
-        let task_dir = self.remote_task_dir(task_id);
+    #[instrument(skip_all, fields(executor = %self.config.name, task_id = %task_id))]
+    async fn cleanup(
+        &self,
+        task_id: &TaskId,
+        cancel: &CancellationToken,
+    ) -> Result<(), ExecutorError> {
+        let sess = self.connect(cancel)?;
+        let script = match self.windows_root(&sess, cancel)? {
+            Some(root) => {
+                windows::command(&windows::remove_script(&windows_task_dir(&root, task_id)))
+            }
+            None => format!("rm -rf {}", shell_path(&self.remote_task_dir(task_id))),
+        };
 
         info!("Cleaning up task {} on {}", task_id, self.name());
-        self.exec_remote(&sess, &format!("rm -rf {}", task_dir))?;
+        self.exec_remote(&sess, &script, cancel)?;
 
         // Remove local metadata
-        let local_path = self
-            .local_meta_dir()
-            .join(format!("{}.meta.json", task_id));
-        if local_path.exists() {
-            std::fs::remove_file(local_path)?;
+        self.store.delete(task_id)?;
+
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(executor = %self.config.name))]
+    async fn cleanup_many(
+        &self,
+        task_ids: &[TaskId],
+        cancel: &CancellationToken,
+    ) -> Result<Vec<CleanupOutcome>, ExecutorError> {
+        let sess = self.connect(cancel)?;
+        info!("Cleaning up {} task(s) on {}", task_ids.len(), self.name());
+        let mut lines = Vec::new();
+        match self.windows_root(&sess, cancel)? {
+            Some(root) => {
+                for chunk in task_ids.chunks(windows::CLEANUP_BATCH) {
+                    let dirs: Vec<String> =
+                        chunk.iter().map(|task_id| windows_task_dir(&root, task_id)).collect();
+                    let script = windows::command(&windows::cleanup_script(&dirs));
+                    let output = self.exec_remote(&sess, &script, cancel)?;
+                    let mut chunk_lines: Vec<String> =
+                        output.lines().map(|line| line.trim().to_string()).collect();
+                    chunk_lines.resize(chunk.len(), String::new());
+                    lines.extend(chunk_lines);
+                }
+            }
+            None => {
+                let dirs: Vec<String> = task_ids.iter().map(|id| self.remote_task_dir(id)).collect();
+                let output = self.exec_remote(&sess, &cleanup_script(&dirs), cancel)?;
+                lines.extend(output.lines().map(|line| line.trim().to_string()));
+            }
+        }
+
+        let mut outcomes = Vec::new();
+        for (i, task_id) in task_ids.iter().enumerate() {
+            let line = lines.get(i).map(String::as_str).unwrap_or_default();
+            let outcome = match line.strip_prefix("ok ") {
+                Some(kb) => {
+                    self.store.delete(task_id)?;
+                    CleanupOutcome {
+                        task_id: task_id.clone(),
+                        freed_kb: kb.trim().parse().ok(),
+                        error: None,
+                    }
+                }
+                None => CleanupOutcome {
+                    task_id: task_id.clone(),
+                    freed_kb: None,
+                    error: Some(format!("couldn't remove its task dir on {}", self.name())),
+                },
+            };
+            outcomes.push(outcome);
+        }
+        Ok(outcomes)
+    }
+
+    #[instrument(skip_all, fields(executor = %self.config.name, task_id = %task_id))]
+    async fn changes(
+        &self,
+        task_id: &TaskId,
+        cancel: &CancellationToken,
+    ) -> Result<TaskChanges, ExecutorError> {
+        let meta = self.store.load(task_id)?;
+        if meta.git_branch.is_none() {
+            return Err(ExecutorError::Git(format!("task {} has no branch", task_id)));
+        }
+        let sess = self.connect(cancel)?;
+        let task_dir = self.remote_task_dir(task_id);
+        let workspace = meta.workspace.as_deref().unwrap_or("~");
+
+        let script = format!("cd {} && {}", shell_path(workspace), git::changes_script(&task_dir));
+        let files = self.exec_remote(&sess, &script, cancel)?;
+        let diff_file = shell_path(&format!("{}/changes.diff", task_dir));
+        let diff = self.exec_remote(&sess, &format!("cat {} 2>/dev/null", diff_file), cancel)?;
+
+        Ok(TaskChanges {
+            files: files.lines().map(String::from).collect(),
+            diff,
+        })
+    }
+
+    #[instrument(skip_all, fields(executor = %self.config.name))]
+    async fn download(
+        &self,
+        task_id: &TaskId,
+        path: &str,
+        root: FileRoot,
+        dest: &Path,
+        cancel: &CancellationToken,
+    ) -> Result<(), ExecutorError> {
+        let source = self.file_path(task_id, path, root)?;
+        let sess = self.connect(cancel)?;
+        if self.windows_root(&sess, cancel)?.is_some() {
+            return Err(ExecutorError::Unsupported("file transfer on a Windows host".into()));
         }
+        let sftp = sess
+            .sftp()
+            .map_err(|e| ExecutorError::SshCommand(format!("SFTP: {}", e)))?;
+        let mut remote = sftp
+            .open(Path::new(&source))
+            .map_err(|e| ExecutorError::SshCommand(format!("Open {}: {}", source, e)))?;
+        debug!("Downloading {} to {}", source, dest.display());
+        let mut local = std::fs::File::create(dest)?;
+        std::io::copy(&mut remote, &mut local)
+            .map_err(|e| ExecutorError::SshCommand(format!("Read {}: {}", source, e)))?;
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(executor = %self.config.name))]
+    async fn upload(
+        &self,
+        task_id: &TaskId,
+        source: &Path,
+        path: &str,
+        root: FileRoot,
+        cancel: &CancellationToken,
+    ) -> Result<(), ExecutorError> {
+        let dest = self.file_path(task_id, path, root)?;
+        let mut local = std::fs::File::open(source)?;
 
+        let sess = self.connect(cancel)?;
+        if self.windows_root(&sess, cancel)?.is_some() {
+            return Err(ExecutorError::Unsupported("file transfer on a Windows host".into()));
+        }
+        if let Some(parent) = Path::new(&dest).parent().filter(|p| !p.as_os_str().is_empty()) {
+            let parent = parent.display().to_string();
+            self.exec_remote(&sess, &format!("mkdir -p {}", shell_escape(&parent)), cancel)?;
+        }
+        let sftp = sess
+            .sftp()
+            .map_err(|e| ExecutorError::SshCommand(format!("SFTP: {}", e)))?;
+        let mut remote = sftp
+            .create(Path::new(&dest))
+            .map_err(|e| ExecutorError::SshCommand(format!("Create {}: {}", dest, e)))?;
+        debug!("Uploading {} to {}", source.display(), dest);
+        std::io::copy(&mut local, &mut remote)
+            .map_err(|e| ExecutorError::SshCommand(format!("Write {}: {}", dest, e)))?;
         Ok(())
     }
+
+    #[instrument(skip_all, fields(executor = %self.config.name))]
+    async fn list_remote(
+        &self,
+        cancel: &CancellationToken,
+    ) -> Result<Vec<RemoteTask>, ExecutorError> {
+        let sess = self.connect(cancel)?;
+
+        // One round-trip: a marker line per task dir followed by its .meta.json (if any)
+        let script = match self.windows_root(&sess, cancel)? {
+            Some(root) => windows::command(&windows::list_script(&root, REMOTE_TASK_MARKER)),
+            None => format!(
+                "for d in {}/*/; do [ -d \"$d\" ] || continue; id=$(basename \"$d\"); \
+                 echo \"{}$id\"; cat \"$d/$id.meta.json\" 2>/dev/null; echo; done",
+                shell_path(&self.remote_root()),
+                REMOTE_TASK_MARKER
+            ),
+        };
+        let output = self.exec_remote(&sess, &script, cancel)?;
+
+        let mut tasks = Vec::new();
+        for chunk in output.split(REMOTE_TASK_MARKER).skip(1) {
+            let (id, meta_json) = chunk.split_once('\n').unwrap_or((chunk, ""));
+            let task_id = TaskId::from_string(id.trim().to_string());
+            // A .meta.json naming another task isn't this one's
+            let metadata = serde_json::from_str::<TaskMetadata>(meta_json.trim())
+                .ok()
+                .filter(|meta| meta.task_id == task_id)
+                .map(|mut meta| {
+                    meta.executor_name = self.config.name.clone();
+                    meta
+                });
+            tasks.push(RemoteTask { task_id, metadata });
+        }
+
+        Ok(tasks)
+    }
+
+    #[instrument(skip_all, fields(executor = %self.config.name, task_id = %task_id))]
+    async fn exec(
+        &self,
+        task_id: &TaskId,
+        command: &str,
+        cancel: &CancellationToken,
+    ) -> Result<i32, ExecutorError> {
+        self.exec_in_workspace(task_id, command, &mut std::io::stdout(), cancel)
+    }
+
+    #[instrument(skip_all, fields(executor = %self.config.name, task_id = %task_id))]
+    async fn exec_output(
+        &self,
+        task_id: &TaskId,
+        command: &str,
+        cancel: &CancellationToken,
+    ) -> Result<(i32, String), ExecutorError> {
+        let mut output = Vec::new();
+        let code = self.exec_in_workspace(task_id, command, &mut output, cancel)?;
+        Ok((code, String::from_utf8_lossy(&output).into_owned()))
+    }
+
+    #[instrument(skip_all, fields(executor = %self.config.name))]
+    async fn host_stats(
+        &self,
+        dir: Option<&str>,
+        cancel: &CancellationToken,
+    ) -> Result<Option<HostStats>, ExecutorError> {
+        let sess = self.connect(cancel)?;
+        if self.windows_root(&sess, cancel)?.is_some() {
+            return Ok(None);
+        }
+        let root = self.remote_root();
+        let script = format!(
+            "mkdir -p {}\n{}",
+            shell_path(&root),
+            host::stats_script_for(dir.unwrap_or(&root))
+        );
+        Ok(host::parse_stats(&self.exec_remote(&sess, &script, cancel)?))
+    }
+
+    #[instrument(skip_all, fields(executor = %self.config.name))]
+    async fn find_claude(
+        &self,
+        cancel: &CancellationToken,
+    ) -> Result<Option<String>, ExecutorError> {
+        let sess = self.connect(cancel)?;
+        let configured = self.config.claude_path.as_deref();
+        let script = match self.windows_root(&sess, cancel)? {
+            Some(_) => windows::command(&windows::claude_script(configured)),
+            None => host::claude_script(configured),
+        };
+        Ok(host::parse_claude_path(&self.exec_remote(&sess, &script, cancel)?))
+    }
+
+    #[instrument(skip_all, fields(executor = %self.config.name))]
+    async fn provision(
+        &self,
+        install: bool,
+        cancel: &CancellationToken,
+    ) -> Result<ProvisionReport, ExecutorError> {
+        let sess = self.connect(cancel)?;
+        if self.windows_root(&sess, cancel)?.is_some() {
+            return Err(ExecutorError::Unsupported("provision on a Windows host".into()));
+        }
+        let root = self.remote_root();
+        let script = provision::script(&root, install, self.config.claude_path.as_deref());
+        let output = self.exec_remote(&sess, &script, cancel)?;
+        Ok(provision::parse_report(&output, &root))
+    }
+
+    #[instrument(skip_all, fields(executor = %self.config.name))]
+    async fn upgrade_agent(&self, cancel: &CancellationToken) -> Result<AgentInfo, ExecutorError> {
+        if self.config.agent_binary.is_none() {
+            return Err(ExecutorError::Config(format!("{} has no agent_binary", self.name())));
+        }
+        let mut agent = self.agent.lock().unwrap_or_else(|e| e.into_inner());
+        *agent = None;
+        let conn = agent.insert(self.start_agent(true, cancel)?);
+
+        // Agents still running from an older build keep their (deleted) file
+        let name = Path::new(&conn.path).file_name().unwrap_or_default().to_string_lossy();
+        let prune = format!(
+            "find {} -maxdepth 1 -name 'openclaw-remote-agent-*' ! -name {} \
+             -print -exec rm -f {{}} + | wc -l",
+            shell_path(&self.agent_dir()),
+            shell_escape(&name)
+        );
+        let removed = self.exec_remote(conn.session(), &prune, cancel)?;
+        Ok(AgentInfo {
+            path: conn.path.clone(),
+            version: conn.version.clone(),
+            protocol: conn.protocol,
+            removed: removed.trim().parse().unwrap_or(0),
+        })
+    }
+
+    #[instrument(skip_all, fields(executor = %self.config.name, task_id = %task_id))]
+    async fn usage(
+        &self,
+        task_id: &TaskId,
+        cancel: &CancellationToken,
+    ) -> Result<Option<ResourceUsage>, ExecutorError> {
+        let Some(pid) = self.store.load(task_id)?.pid else {
+            return Ok(None);
+        };
+        let sess = self.connect(cancel)?;
+        if self.windows_root(&sess, cancel)?.is_some() {
+            return Ok(None);
+        }
+        let output = self.exec_remote(&sess, &usage::tree_script(pid), cancel)?;
+        Ok(usage::parse_usage(&output))
+    }
+
+    #[instrument(skip_all, fields(executor = %self.config.name, task_id = %task_id))]
+    async fn progress(
+        &self,
+        task_id: &TaskId,
+        cancel: &CancellationToken,
+    ) -> Result<Option<TaskProgress>, ExecutorError> {
+        let meta = self.store.load(task_id)?;
+        if !self.config.stream_json || meta.task_type != "claude_code" {
+            return Ok(None);
+        }
+        let sess = self.connect(cancel)?;
+        let script = match self.windows_root(&sess, cancel)? {
+            Some(root) => {
+                let log_file = format!("{}\\claude.log", windows_task_dir(&root, task_id));
+                windows::command(&windows::progress_script(&log_file))
+            }
+            None => {
+                let log_file = format!("{}/claude.log", self.remote_task_dir(task_id));
+                progress::script(&shell_path(&log_file))
+            }
+        };
+        let output = self.exec_remote(&sess, &script, cancel)?;
+        Ok(progress::parse_progress(&output, meta.workspace.as_deref()))
+    }
+
+    #[instrument(skip_all, fields(executor = %self.config.name, task_id = %task_id))]
+    async fn log_size(
+        &self,
+        task_id: &TaskId,
+        cancel: &CancellationToken,
+    ) -> Result<u64, ExecutorError> {
+        let sess = self.connect(cancel)?;
+        let script = match self.windows_root(&sess, cancel)? {
+            Some(root) => {
+                let log_file = format!("{}\\claude.log", windows_task_dir(&root, task_id));
+                windows::command(&windows::size_script(&log_file))
+            }
+            None => {
+                let log_file = format!("{}/claude.log", self.remote_task_dir(task_id));
+                format!("wc -c < {} 2>/dev/null", shell_path(&log_file))
+            }
+        };
+        let output = self.exec_remote(&sess, &script, cancel)?;
+        Ok(output.trim().parse().unwrap_or(0))
+    }
+}
+
+/// A task's dir under a Windows host's task root.
+fn windows_task_dir(root: &str, task_id: &TaskId) -> String {
+    format!("{}\\{}", root, task_id)
+}
+
+
+/// Where a task's resolved `env` is kept in its dir on the host.
+fn env_file(task_dir: &str) -> String {
+    format!("{}/env", task_dir)
+}
+
+/// A `sh` script exporting each of `env`, for a task to source.
+fn env_script(env: &HashMap<String, String>) -> String {
+    let mut keys: Vec<&String> = env.keys().collect();
+    keys.sort_unstable();
+    keys.into_iter()
+        .map(|key| format!("export {}={}\n", key, shell_escape(&env[key])))
+        .collect()
+}
+
+/// Why a task whose process is gone without writing an exit code failed:
+/// its wrapper was killed, or the host rebooted, before the task finished.
+const NO_EXIT_CODE: &str = "Task process exited without recording an exit code";
+
+/// Separates task entries in the `list_remote` output.
+const REMOTE_TASK_MARKER: &str = "==OPENCLAW-TASK==";
+
+/// One task's entry in the output of [`status_script`].
+#[derive(Deserialize)]
+struct RemoteStatus {
+    /// The recorded PID, or the PID file's contents; empty if neither.
+    pid: String,
+    alive: bool,
+    /// Contents of the exit code file; empty until the task exits.
+    exit_code: String,
+}
+
+impl RemoteStatus {
+    /// As the remote agent would report it.
+    fn into_state(self) -> TaskState {
+        TaskState {
+            pid: self.pid.parse().ok(),
+            alive: self.alive,
+            exit_code: self.exit_code.parse().ok(),
+        }
+    }
+}
+
+/// A `sh` script checking several tasks in one go, given each task's dir and
+/// recorded PID (read from the dir's PID file if there is none). Prints a
+/// JSON array with a [`RemoteStatus`] per task, in the order given.
+fn status_script(tasks: &[(String, Option<u32>)]) -> String {
+    let args: Vec<String> = tasks
+        .iter()
+        .map(|(dir, pid)| {
+            let pid = pid.map(|p| p.to_string()).unwrap_or_default();
+            format!("{} {}", shell_path(dir), shell_escape(&pid))
+        })
+        .collect();
+    format!(
+        "\
+set -- {}
+printf '['; sep=
+while [ $# -gt 1 ]; do
+  d=$1; pid=$2; shift 2
+  [ -n \"$pid\" ] || pid=$(cat \"$d/claude.pid\" 2>/dev/null | tr -cd 0-9)
+  alive=false; [ -n \"$pid\" ] && kill -0 \"$pid\" 2>/dev/null && alive=true
+  code=$(cat \"$d/claude.exitcode\" 2>/dev/null | tr -cd 0-9-)
+  printf '%s{{\"pid\":\"%s\",\"alive\":%s,\"exit_code\":\"%s\"}}' \"$sep\" \"$pid\" \"$alive\" \"$code\"
+  sep=,
+done
+echo ']'",
+        args.join(" ")
+    )
+}
+
+/// A `sh` script removing each of `dirs`, printing `ok <KiB it took>` or
+/// `fail` per dir in the order given.
+fn cleanup_script(dirs: &[String]) -> String {
+    let dirs: Vec<String> = dirs.iter().map(|dir| shell_path(dir)).collect();
+    format!(
+        "\
+for d in {}; do
+  k=$(du -sk \"$d\" 2>/dev/null | cut -f1)
+  if rm -rf \"$d\"; then echo \"ok ${{k:-0}}\"; else echo fail; fi
+done",
+        dirs.join(" ")
+    )
+}
+
+/// The keys ssh-agent offers, for authentication errors: `ssh-agent (2 keys:
+/// a@laptop, b@laptop)`, or why there were none.
+fn agent_identities(sess: &Session) -> String {
+    let keys = sess.agent().and_then(|mut agent| {
+        agent.connect()?;
+        agent.list_identities()?;
+        agent.identities()
+    });
+    match keys {
+        Ok(keys) if keys.is_empty() => "ssh-agent (no keys loaded)".to_string(),
+        Ok(keys) => {
+            let names: Vec<&str> = keys.iter().map(|key| key.comment()).collect();
+            format!("ssh-agent ({} key(s): {})", keys.len(), names.join(", "))
+        }
+        Err(e) => format!("ssh-agent (unreachable: {})", e),
+    }
 }
 
 /// Shell-escape a string for safe use in remote commands.
 fn shell_escape(s: &str) -> String {
     format!("'{}'", s.replace('\'', "'\\''"))
 }
+
+/// Shell-escape a remote path, leaving a leading `~` outside the quotes so
+/// the remote shell still expands it (a `~` task root before `connect` has
+/// asked for the home directory, or a `~/...` workspace).
+fn shell_path(path: &str) -> String {
+    if path == "~" {
+        return path.to_string();
+    }
+    match path.strip_prefix("~/") {
+        Some(rest) => format!("~/{}", shell_escape(rest)),
+        None => shell_escape(path),
+    }
+}
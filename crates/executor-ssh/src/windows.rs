@@ -0,0 +1,312 @@
+//! Windows hosts (`platform: windows`, or an OpenSSH for Windows server):
+//! there is no `sh`, so tasks are launched, checked, tailed and killed with
+//! PowerShell scripts. Each is sent as one `-EncodedCommand`, which reaches
+//! PowerShell intact whichever shell sshd starts it from.
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use executor_core::config::ExecutorConfig;
+use executor_core::task::TaskPayload;
+
+/// Whether a server with SSH `banner` (e.g. `SSH-2.0-OpenSSH_for_Windows_8.1`)
+/// runs Windows.
+pub fn banner_is_windows(banner: &str) -> bool {
+    banner.to_ascii_lowercase().contains("windows")
+}
+
+const UTF8_OUTPUT: &str = "[Console]::OutputEncoding = [Text.UTF8Encoding]::new($false)";
+
+/// A command line running `script` in Windows PowerShell, with output in
+/// UTF-8 so logs and paths come back intact. cmd.exe limits a command line
+/// to 8191 characters, so anything long (a prompt) goes in a file instead.
+pub fn command(script: &str) -> String {
+    let script = format!("{}\n{}", UTF8_OUTPUT, script);
+    let utf16: Vec<u8> = script.encode_utf16().flat_map(u16::to_le_bytes).collect();
+    format!(
+        "powershell -NoProfile -NonInteractive -EncodedCommand {}",
+        STANDARD.encode(utf16)
+    )
+}
+
+/// `s` as a PowerShell single-quoted string, which expands nothing. The
+/// typographic quotes PowerShell also accepts there are doubled too.
+pub fn quote(s: &str) -> String {
+    let mut quoted = String::from("'");
+    for c in s.chars() {
+        if matches!(c, '\'' | '\u{2018}' | '\u{2019}' | '\u{201a}' | '\u{201b}') {
+            quoted.push(c);
+        }
+        quoted.push(c);
+    }
+    quoted.push('\'');
+    quoted
+}
+
+/// A PowerShell expression for the directory `workspace`, with a leading
+/// `~` meaning the user's profile directory.
+pub fn location(workspace: &str) -> String {
+    match workspace.strip_prefix('~') {
+        Some("") => "$HOME".to_string(),
+        Some(rest) if rest.starts_with(['/', '\\']) => {
+            format!("(Join-Path $HOME {})", quote(&rest[1..]))
+        }
+        _ => quote(workspace),
+    }
+}
+
+/// Where SFTP finds `path` (`C:\Users\...` is `/C:/Users/...` to it).
+pub fn sftp_path(path: &str) -> String {
+    format!("/{}", path.replace('\\', "/"))
+}
+
+/// Prints the task root: `configured` (a `task_root`), or else
+/// `openclaw-tasks` in the user's temp dir.
+pub fn root_script(configured: Option<&str>) -> String {
+    match configured {
+        Some(root) => format!("[string]{}", location(root)),
+        None => "Join-Path ([IO.Path]::GetTempPath()) 'openclaw-tasks'".to_string(),
+    }
+}
+
+pub fn mkdir_script(dir: &str) -> String {
+    format!("New-Item -ItemType Directory -Force -Path {} | Out-Null", quote(dir))
+}
+
+pub fn remove_script(dir: &str) -> String {
+    format!("Remove-Item -LiteralPath {} -Recurse -Force -ErrorAction SilentlyContinue", quote(dir))
+}
+
+/// The statement that runs a task's payload, its output going down the
+/// pipeline. The prompt is piped to claude on stdin rather than passed as an
+/// argument, which Windows PowerShell would mangle if it held a `"`. A shell
+/// command is PowerShell.
+pub fn payload_statement(config: &ExecutorConfig, payload: &TaskPayload) -> String {
+    match payload {
+        TaskPayload::ClaudeCode {
+            prompt,
+            max_turns,
+            allowed_tools,
+        } => {
+            let mut claude = format!(
+                "{} | & {} --print {}",
+                quote(prompt),
+                quote(config.claude_binary()),
+                config.claude_output_args()
+            );
+            if let Some(turns) = max_turns {
+                claude.push_str(&format!(" --max-turns {}", turns));
+            }
+            for tool in allowed_tools {
+                claude.push_str(&format!(" --allowedTools {}", quote(tool)));
+            }
+            claude
+        }
+        TaskPayload::ShellCommand { command } => {
+            format!("& ([scriptblock]::Create({}))", quote(command))
+        }
+    }
+}
+
+/// run.ps1, which runs a task from its dir: `statement`'s output (stderr
+/// included) goes to claude.log, with `timestamps` each line prefixed with
+/// the UTC time, and its exit code to claude.exitcode once it is done (1 if
+/// PowerShell itself reported an error and no program set one).
+/// Written with a BOM, without which Windows PowerShell reads it as ANSI.
+pub fn run_script(dir: &str, statement: &str, timestamps: bool) -> String {
+    let line = if timestamps {
+        "(Get-Date).ToUniversalTime().ToString(\"yyyy-MM-dd'T'HH:mm:ss'Z '\") + $_"
+    } else {
+        "\"$_\""
+    };
+    format!(
+        "\u{feff}\
+[Console]::OutputEncoding = [Text.UTF8Encoding]::new($false)
+$OutputEncoding = [Text.UTF8Encoding]::new($false)
+$dir = {dir}
+$log = [IO.StreamWriter]::new((Join-Path $dir 'claude.log'), $false, $OutputEncoding)
+$log.AutoFlush = $true
+$code = 0
+$failed = $false
+try {{
+    & {{ {statement} }} 2>&1 | ForEach-Object {{
+        # A program's stderr arrives as RemoteException records; any other
+        # error record is PowerShell's own (e.g. claude not found)
+        if ($_ -is [Management.Automation.ErrorRecord] -and
+            $_.Exception -isnot [Management.Automation.RemoteException]) {{ $failed = $true }}
+        $log.WriteLine({line})
+    }}
+    if ($LASTEXITCODE) {{ $code = $LASTEXITCODE }} elseif ($failed) {{ $code = 1 }}
+}} catch {{
+    $log.WriteLine(\"$_\")
+    $code = 1
+}}
+$log.Close()
+Set-Content -LiteralPath (Join-Path $dir 'claude.exitcode') -Value $code
+",
+        dir = quote(dir),
+        statement = statement,
+        line = line,
+    )
+}
+
+/// Starts `dir`'s run.ps1 in `workspace`, writes its PID to claude.pid and
+/// prints it. It is created through WMI rather than `Start-Process`, since
+/// OpenSSH for Windows ends every process a session started when the
+/// session closes.
+pub fn launch_script(dir: &str, workspace: &str) -> String {
+    format!(
+        "\
+$dir = {dir}
+$run = 'powershell.exe -NoProfile -NonInteractive -ExecutionPolicy Bypass -File \"' + (Join-Path $dir 'run.ps1') + '\"'
+$p = Invoke-CimMethod -ClassName Win32_Process -MethodName Create -Arguments @{{
+    CommandLine = $run; CurrentDirectory = [string]{workspace}
+}}
+if ($p.ReturnValue -ne 0) {{ \"Win32_Process.Create failed with $($p.ReturnValue)\"; exit }}
+Set-Content -LiteralPath (Join-Path $dir 'claude.pid') -Value $p.ProcessId
+$p.ProcessId",
+        dir = quote(dir),
+        workspace = location(workspace),
+    )
+}
+
+/// Tasks per status check, keeping its command line under cmd.exe's limit.
+pub const STATUS_BATCH: usize = 16;
+
+/// The Windows counterpart of the `sh` status script: prints the same JSON
+/// array, one entry per (task dir, recorded PID) in the order given.
+pub fn status_script(tasks: &[(String, Option<u32>)]) -> String {
+    let entries: Vec<String> = tasks
+        .iter()
+        .map(|(dir, pid)| {
+            let pid = pid.map(|p| p.to_string()).unwrap_or_default();
+            format!("@{{ d = {}; p = '{}' }}", quote(dir), pid)
+        })
+        .collect();
+    format!(
+        "\
+$out = foreach ($t in @({})) {{
+    $id = $t.p
+    if (-not $id) {{
+        $id = \"$(Get-Content -LiteralPath (Join-Path $t.d 'claude.pid') -ErrorAction SilentlyContinue)\".Trim()
+    }}
+    $code = \"$(Get-Content -LiteralPath (Join-Path $t.d 'claude.exitcode') -ErrorAction SilentlyContinue)\".Trim()
+    $alive = [bool]($id -and (Get-Process -Id $id -ErrorAction SilentlyContinue))
+    [pscustomobject]@{{ pid = $id; alive = $alive; exit_code = $code }}
+}}
+ConvertTo-Json -Compress -InputObject @($out)",
+        entries.join(", ")
+    )
+}
+
+/// Task dirs per bulk cleanup, for the same reason as [`STATUS_BATCH`].
+pub const CLEANUP_BATCH: usize = 16;
+
+/// The Windows counterpart of the `sh` bulk cleanup script: removes each
+/// dir, printing `ok <KiB it took>` or `fail` per dir in the order given.
+pub fn cleanup_script(dirs: &[String]) -> String {
+    let dirs: Vec<String> = dirs.iter().map(|dir| quote(dir)).collect();
+    format!(
+        "\
+foreach ($d in @({})) {{
+    $files = Get-ChildItem -LiteralPath $d -Recurse -Force -File -ErrorAction SilentlyContinue
+    $kb = [long](($files | Measure-Object Length -Sum).Sum / 1024)
+    Remove-Item -LiteralPath $d -Recurse -Force -ErrorAction SilentlyContinue
+    if (Test-Path -LiteralPath $d) {{ 'fail' }} else {{ \"ok $kb\" }}
+}}",
+        dirs.join(", ")
+    )
+}
+
+/// `marker` and a task's ID, then its .meta.json (if any), for each task
+/// dir under `root`.
+pub fn list_script(root: &str, marker: &str) -> String {
+    format!(
+        "\
+Get-ChildItem -LiteralPath {} -Directory -ErrorAction SilentlyContinue | ForEach-Object {{
+    '{}' + $_.Name
+    Get-Content -Raw -LiteralPath (Join-Path $_.FullName \"$($_.Name).meta.json\") -ErrorAction SilentlyContinue
+}}",
+        quote(root),
+        marker
+    )
+}
+
+/// The last `lines` lines of `file`; nothing if there is no such file yet.
+pub fn tail_script(file: &str, lines: usize) -> String {
+    format!(
+        "Get-Content -LiteralPath {} -Tail {} -Encoding UTF8 -ErrorAction SilentlyContinue",
+        quote(file),
+        lines
+    )
+}
+
+pub fn size_script(file: &str) -> String {
+    format!("(Get-Item -LiteralPath {} -ErrorAction SilentlyContinue).Length", quote(file))
+}
+
+/// Ends `pid` and the processes it started: `taskkill /T` asks them to
+/// close, then `/F` forces them after `grace_secs`. Console programs (claude
+/// among them) can only be forced, so expect SIGKILL. Prints which it took,
+/// like `kill::terminate_script`, and nothing if `pid` was already gone.
+pub fn terminate_script(pid: u32, grace_secs: u64) -> String {
+    format!(
+        "\
+$id = {pid}
+if (-not (Get-Process -Id $id -ErrorAction SilentlyContinue)) {{ exit }}
+taskkill /PID $id /T 2>&1 | Out-Null
+$deadline = (Get-Date).AddSeconds({grace})
+while ((Get-Date) -lt $deadline) {{
+    if (-not (Get-Process -Id $id -ErrorAction SilentlyContinue)) {{ 'SIGTERM'; exit }}
+    Start-Sleep -Milliseconds 250
+}}
+taskkill /PID $id /T /F 2>&1 | Out-Null
+'SIGKILL'",
+        pid = pid,
+        grace = grace_secs,
+    )
+}
+
+/// Runs `command` (PowerShell) in `workspace`, exiting with its exit code.
+pub fn exec_script(workspace: &str, command: &str) -> String {
+    format!(
+        "\
+Set-Location -LiteralPath {}
+& ([scriptblock]::Create({})) 2>&1 | ForEach-Object {{ \"$_\" }}
+exit $LASTEXITCODE",
+        location(workspace),
+        quote(command)
+    )
+}
+
+/// The Windows counterpart of `host::claude_script`: the full path of the
+/// configured claude, or else the one on `PATH`.
+pub fn claude_script(configured: Option<&str>) -> String {
+    let mut candidates = Vec::new();
+    if let Some(path) = configured {
+        candidates.push(location(path));
+    }
+    candidates.push("'claude'".to_string());
+    format!(
+        "\
+foreach ($c in @({})) {{
+    $p = Get-Command $c -CommandType Application -ErrorAction SilentlyContinue | Select-Object -First 1
+    if ($p) {{ $p.Source; exit }}
+}}",
+        candidates.join(", ")
+    )
+}
+
+/// The Windows counterpart of `progress::script`, printing the same lines.
+pub fn progress_script(log_file: &str) -> String {
+    format!(
+        "\
+$f = {}
+if (-not (Test-Path -LiteralPath $f)) {{ exit }}
+$events = @(Select-String -LiteralPath $f -SimpleMatch '\"type\":\"assistant\"' | ForEach-Object {{ $_.Line }})
+$ids = $events | ForEach-Object {{ [regex]::Match($_, '\"id\":\"msg_[^\"]*\"').Value }} | Where-Object {{ $_ }}
+\"turns $(@($ids | Sort-Object -Unique).Count)\"
+$last = $events | Where-Object {{ $_.Contains('\"type\":\"tool_use\"') }} | Select-Object -Last 1
+if ($last) {{ \"last $last\" }}",
+        quote(log_file)
+    )
+}